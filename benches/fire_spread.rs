@@ -0,0 +1,87 @@
+//! Measures how long the fire takes to spread across the map. Fire spread is
+//! either serial or parallel depending on whether the `parallel` feature is
+//! enabled, so compare them by saving a baseline from one build and checking
+//! the other against it:
+//!
+//! ```sh
+//! cargo bench --bench fire_spread -- --save-baseline serial
+//! cargo bench --bench fire_spread --features parallel -- --baseline serial
+//! ```
+
+use bevy::prelude::*;
+use criterion::{BatchSize, BenchmarkId, Criterion, criterion_group, criterion_main};
+use spellfire::wildfire::{
+    Biome, DifficultyLevel, FireStats, GOOD_SEEDS, GameMap, MapSize, MoistureMap, WeatherEvent,
+    WindDirection,
+};
+
+/// The number of map updates run in each sample, so the fire front has time
+/// to grow
+const UPDATES_PER_SAMPLE: usize = 20;
+
+/// The width and height of the square of fire lit in the middle of the map
+const STARTING_FIRE_SIZE: i32 = 8;
+
+/// Creates a map with a square of fire burning in the middle
+fn burning_map(size: MapSize) -> (GameMap, MoistureMap) {
+    let size_cells = UVec2::from(size);
+    let (size_x, size_y) = (size_cells.x as usize, size_cells.y as usize);
+    let mut map = GameMap::new(
+        GOOD_SEEDS[0],
+        size.sprite_size(),
+        size_x,
+        size_y,
+        Biome::default(),
+        &[],
+    );
+
+    let centre = size_cells.as_ivec2() / 2;
+    for y in 0..STARTING_FIRE_SIZE {
+        for x in 0..STARTING_FIRE_SIZE {
+            let loc = centre + IVec2::new(x, y) - STARTING_FIRE_SIZE / 2;
+            if let Some(cell) = map.get_mut(loc) {
+                cell.ignite();
+                map.mark_on_fire(loc);
+            }
+        }
+    }
+
+    (map, MoistureMap::new(size_x, size_y))
+}
+
+fn fire_spread(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fire_spread");
+    let wind = WindDirection::default();
+
+    for size in [MapSize::Small, MapSize::Medium, MapSize::Large] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{size:?}")),
+            &size,
+            |b, size| {
+                b.iter_batched(
+                    || burning_map(*size),
+                    |(mut map, mut moisture)| {
+                        let mut stats = FireStats::default();
+                        for _ in 0..UPDATES_PER_SAMPLE {
+                            map.update(
+                                &wind,
+                                None,
+                                WeatherEvent::default(),
+                                DifficultyLevel::default(),
+                                &mut moisture,
+                                &mut stats,
+                            );
+                        }
+                        map
+                    },
+                    BatchSize::LargeInput,
+                );
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, fire_spread);
+criterion_main!(benches);
@@ -0,0 +1,165 @@
+//! Runs the fire simulation without a window or renderer, driven by JSON
+//! commands on stdin, so that fire spread can be tested from scripts and CI.
+//! Build with the `headless` feature and send one command per line, e.g.
+//! `cargo run --features headless --bin headless`:
+//!
+//! - `{"cmd": "set_terrain", "x": 10, "y": 10, "type": "Fire"}`
+//! - `{"cmd": "tick", "n": 100}` runs 100 map updates
+//! - `{"cmd": "query_terrain", "x": 10, "y": 10}`
+//!
+//! Each command writes a single line of JSON to stdout. The app exits when
+//! stdin is closed.
+
+use bevy::prelude::*;
+use serde::Deserialize;
+use serde_json::json;
+
+use spellfire::wildfire::{
+    Biome, CurrentWeather, DifficultySettings, FireStats, GOOD_SEEDS, GameMap, MapSize,
+    MoistureMap, TerrainType, WindDirection,
+};
+
+fn main() -> AppExit {
+    App::new()
+        .add_plugins(MinimalPlugins)
+        .add_systems(Startup, spawn_headless_map)
+        .add_systems(Update, headless_command_reader)
+        .run()
+}
+
+/// A command read from stdin
+#[derive(Debug, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+enum HeadlessCommand {
+    /// Changes the terrain of a cell. Setting a cell to `Fire` ignites it
+    SetTerrain {
+        x: i32,
+        y: i32,
+        #[serde(rename = "type")]
+        terrain: String,
+    },
+    /// Runs the given number of map updates
+    Tick { n: u32 },
+    /// Reports the state of a cell
+    QueryTerrain { x: i32, y: i32 },
+}
+
+fn spawn_headless_map(mut commands: Commands) {
+    let map_size = MapSize::default();
+    let size = UVec2::from(map_size);
+
+    commands.insert_resource(GameMap::new(
+        GOOD_SEEDS[0],
+        map_size.sprite_size(),
+        size.x as usize,
+        size.y as usize,
+        Biome::default(),
+        &[],
+    ));
+    commands.insert_resource(MoistureMap::new(size.x as usize, size.y as usize));
+    commands.init_resource::<FireStats>();
+    commands.init_resource::<WindDirection>();
+    commands.init_resource::<CurrentWeather>();
+    commands.init_resource::<DifficultySettings>();
+}
+
+/// Reads and applies one command from stdin each frame. Blocks until a
+/// command arrives so the simulation only advances when told to
+fn headless_command_reader(
+    mut exit: EventWriter<AppExit>,
+    mut map: ResMut<GameMap>,
+    mut moisture: ResMut<MoistureMap>,
+    mut stats: ResMut<FireStats>,
+    wind: Res<WindDirection>,
+    weather: Res<CurrentWeather>,
+    difficulty: Res<DifficultySettings>,
+) {
+    let Some(Ok(line)) = std::io::stdin().lines().next() else {
+        exit.write(AppExit::Success);
+        return;
+    };
+
+    if line.trim().is_empty() {
+        return;
+    }
+
+    let response = match serde_json::from_str::<HeadlessCommand>(&line) {
+        Ok(HeadlessCommand::SetTerrain { x, y, terrain }) => {
+            set_terrain(&mut map, IVec2::new(x, y), &terrain)
+        }
+        Ok(HeadlessCommand::Tick { n }) => {
+            let mut rng = rand::thread_rng();
+            for _ in 0..n {
+                map.update(
+                    &wind,
+                    None,
+                    weather.0,
+                    difficulty.0,
+                    &mut moisture,
+                    &mut stats,
+                );
+
+                // the game shows embers in flight before they land, here they
+                // land straight away so spot fires start in the same tick
+                for (origin, ember_wind) in map.take_thrown_embers() {
+                    map.spot_ember(origin, ember_wind, &mut rng);
+                }
+            }
+            json!({ "ok": true, "fires": map.fire_count() })
+        }
+        Ok(HeadlessCommand::QueryTerrain { x, y }) => match map.get(IVec2::new(x, y)) {
+            Some(cell) => json!({
+                "x": x,
+                "y": y,
+                "type": format!("{:?}", cell.terrain),
+                "moisture": cell.moisture,
+                "fuel_load": cell.fuel_load,
+                "fire_intensity": cell.fire_intensity,
+            }),
+            None => json!({ "error": format!("Invalid map coordinates {}", IVec2::new(x, y)) }),
+        },
+        Err(e) => json!({ "error": e.to_string() }),
+    };
+
+    println!("{response}");
+}
+
+/// Changes the terrain of the cell at the given location, returning the
+/// response to write to stdout
+fn set_terrain(map: &mut GameMap, loc: IVec2, name: &str) -> serde_json::Value {
+    let Some(terrain) = parse_terrain(name) else {
+        return json!({ "error": format!("Unknown terrain type {name}") });
+    };
+    let Some(cell) = map.get_mut(loc) else {
+        return json!({ "error": format!("Invalid map coordinates {loc}") });
+    };
+
+    if terrain == TerrainType::Fire {
+        cell.ignite();
+        map.mark_on_fire(loc);
+    } else {
+        cell.terrain = terrain;
+        cell.mark_dirty();
+        if terrain == TerrainType::Smoldering {
+            map.mark_smoldering(loc);
+        }
+    }
+
+    json!({ "ok": true })
+}
+
+/// Gets the terrain type with the given name, e.g. `Fire`
+fn parse_terrain(name: &str) -> Option<TerrainType> {
+    [
+        TerrainType::Dirt,
+        TerrainType::Building,
+        TerrainType::Grassland,
+        TerrainType::Tree,
+        TerrainType::Stone,
+        TerrainType::Fire,
+        TerrainType::Smoldering,
+        TerrainType::Water,
+    ]
+    .into_iter()
+    .find(|terrain| format!("{terrain:?}").eq_ignore_ascii_case(name))
+}
@@ -7,10 +7,13 @@ use crate::{
     asset_tracking::LoadResource,
     audio::music,
     screens::{
-        BuildingMode, EndlessMode, NextStoryLevel, PlayerResources, RequiresCityHall, Screen,
-        get_level_data,
+        BuildingMode, EditorTestLevel, EndlessMode, NextStoryLevel, PlayerResources,
+        RequiresCityHall, ResearchState, Screen, get_level_data,
+    },
+    wildfire::{
+        Biome, DifficultySettings, GameMap, MapRegions, MapSize, MoistureMap, OnSpawnMap,
+        SelectedMapSize, SpawnedMap, WindDirection,
     },
-    wildfire::{GameMap, OnSpawnMap, SpawnedMap},
 };
 
 pub(super) fn plugin(app: &mut App) {
@@ -40,8 +43,12 @@ impl FromWorld for LevelAssets {
 pub fn spawn_level(
     mut commands: Commands,
     endless_mode: Option<Res<EndlessMode>>,
+    test_level: Option<Res<EditorTestLevel>>,
     next_story_level: Res<NextStoryLevel>,
+    selected_size: Res<SelectedMapSize>,
     level_assets: Res<LevelAssets>,
+    difficulty: Res<DifficultySettings>,
+    mut wind: ResMut<WindDirection>,
     mut mode: ResMut<BuildingMode>,
     mut next_screen: ResMut<NextState<Screen>>,
 ) {
@@ -51,22 +58,41 @@ pub fn spawn_level(
     if endless_mode {
         info!("Spawning random level ixn endless mode");
 
-        let seed = rand::thread_rng().r#gen();
-        commands.trigger(OnSpawnMap::new(seed));
+        let mut rng = rand::thread_rng();
+        let seed = rng.r#gen();
+        commands
+            .trigger(OnSpawnMap::new(seed, selected_size.0).with_biome(Biome::random(&mut rng)));
 
+        wind.reset_strength(difficulty.0.wind_multiplier());
         *mode = BuildingMode::PlaceCityHall;
         commands.init_resource::<RequiresCityHall>();
+        commands.insert_resource(ResearchState::default());
     } else {
-        let Some(level_data) = get_level_data(next_story_level.0) else {
+        // levels being tested from the editor take the place of the story
+        let level_data = match test_level {
+            Some(test_level) => Some(test_level.0.clone()),
+            None => get_level_data(next_story_level.0),
+        };
+
+        let Some(level_data) = level_data else {
             warn!("No level exists, aborting");
             next_screen.set(Screen::Title);
             return;
         };
 
-        commands.trigger(OnSpawnMap::new(level_data.map_seed));
+        commands.trigger(
+            // story levels are laid out for a medium map
+            OnSpawnMap::new(level_data.map_seed, MapSize::Medium)
+                .with_biome(level_data.biome)
+                .with_starting_location(level_data.starting_location)
+                .with_fog_of_war(),
+        );
         commands.queue(level_data.clone());
         commands.insert_resource(level_data);
         commands.remove_resource::<RequiresCityHall>();
+
+        // story levels are too short to wait for research
+        commands.insert_resource(ResearchState::all_unlocked());
     }
 
     commands.spawn((
@@ -85,5 +111,7 @@ fn despawn_maps(mut commands: Commands, maps: Query<Entity, With<SpawnedMap>>) {
     }
 
     commands.remove_resource::<GameMap>();
+    commands.remove_resource::<MapRegions>();
+    commands.remove_resource::<MoistureMap>();
     commands.remove_resource::<PlayerResources>();
 }
@@ -1,10 +1,26 @@
 use bevy::{input::mouse::MouseWheel, prelude::*};
 
-use crate::{MainCamera, Pause, input::MousePosition};
+use crate::{
+    MainCamera, Pause,
+    input::MousePosition,
+    screens::painting_terrain,
+    wildfire::{GameMap, MapSize, OnSpawnMap},
+};
+
+/// How fast (in world units per second, at a camera scale of 1) the arrow
+/// keys move the camera
+const CAMERA_KEYBOARD_SPEED: f32 = 300.0;
+
+/// The zoom limits for a medium map, other map sizes scale these
+const BASE_MIN_SCALE: f32 = 0.2;
+const BASE_MAX_SCALE: f32 = 3.0;
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<MouseWheelGestures>();
     app.register_type::<MousePanGestures>();
+    app.register_type::<CameraPanLimit>();
+
+    app.init_resource::<CameraPanLimit>();
 
     app.insert_resource(MousePanGestures {
         sensitivity: 1.0,
@@ -14,10 +30,12 @@ pub(super) fn plugin(app: &mut App) {
     app.insert_resource(MouseWheelGestures {
         sensitivity: 0.1,
         delta: 0.0,
-        min_scale: 0.2,
-        max_scale: 3.0,
+        min_scale: BASE_MIN_SCALE,
+        max_scale: BASE_MAX_SCALE,
     });
 
+    app.add_observer(scale_zoom_to_map);
+
     app.add_systems(
         Update,
         (update_pan_gestures, update_zoom_gestures).distributive_run_if(in_state(Pause(false))),
@@ -25,7 +43,12 @@ pub(super) fn plugin(app: &mut App) {
 
     app.add_systems(
         Update,
-        (handle_camera_pan_gestures, handle_camera_zoom_gestures)
+        (
+            // dragging and scrolling control the brush when painting terrain
+            handle_camera_pan_gestures.run_if(not(painting_terrain)),
+            handle_keyboard_camera,
+            handle_camera_zoom_gestures.run_if(not(painting_terrain)),
+        )
             .distributive_run_if(in_state(Pause(false))),
     );
 }
@@ -59,8 +82,23 @@ fn handle_camera_zoom_gestures(
     }
 }
 
+/// Scales the zoom limits to the map being spawned. The closest zoom follows
+/// the sprite size so cells look the same size up close, and the furthest zoom
+/// follows the overall size of the map so that all of it can be seen.
+fn scale_zoom_to_map(trigger: Trigger<OnSpawnMap>, mut gestures: ResMut<MouseWheelGestures>) {
+    let data = trigger.event();
+    let base_size = MapSize::Medium;
+    let base_extent = UVec2::from(base_size).max_element() as f32 * base_size.sprite_size();
+    let extent = data.size.max_element() as f32 * data.sprite_size;
+
+    gestures.min_scale = BASE_MIN_SCALE * data.sprite_size / base_size.sprite_size();
+    gestures.max_scale = BASE_MAX_SCALE * extent / base_extent;
+}
+
 fn handle_camera_pan_gestures(
     pan_gestures: Res<MousePanGestures>,
+    limit: Res<CameraPanLimit>,
+    maybe_map: Option<Res<GameMap>>,
     mut camera: Single<(&mut Transform, &Projection), With<MainCamera>>,
 ) {
     if let GestureType::Pan { unscaled_delta } = pan_gestures.current() {
@@ -70,6 +108,80 @@ fn handle_camera_pan_gestures(
         };
 
         tx.translation += (unscaled_delta * proj.scale).extend(0.0);
+        limit.clamp(&mut tx.translation, maybe_map.as_deref());
+    }
+}
+
+/// Moves the camera with the arrow keys. WASD isn't used as W and D are
+/// already bound to the wind overlay and demolish hotkeys.
+fn handle_keyboard_camera(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    limit: Res<CameraPanLimit>,
+    maybe_map: Option<Res<GameMap>>,
+    mut camera: Single<(&mut Transform, &Projection), With<MainCamera>>,
+) {
+    let mut direction = Vec2::ZERO;
+    if keys.pressed(KeyCode::ArrowUp) {
+        direction.y += 1.0;
+    }
+    if keys.pressed(KeyCode::ArrowDown) {
+        direction.y -= 1.0;
+    }
+    if keys.pressed(KeyCode::ArrowLeft) {
+        direction.x -= 1.0;
+    }
+    if keys.pressed(KeyCode::ArrowRight) {
+        direction.x += 1.0;
+    }
+
+    if direction == Vec2::ZERO {
+        return;
+    }
+
+    let (ref mut tx, Projection::Orthographic(proj)) = *camera else {
+        warn!("Unable to find orthographic projection for camera in keyboard_camera. Aborting");
+        return;
+    };
+
+    tx.translation +=
+        (direction.normalize() * CAMERA_KEYBOARD_SPEED * proj.scale * time.delta_secs())
+            .extend(0.0);
+    limit.clamp(&mut tx.translation, maybe_map.as_deref());
+}
+
+/// Limits how far the camera can be panned away from the map
+#[derive(Debug, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct CameraPanLimit {
+    /// How far the camera can move, as a multiple of the map size
+    pub map_bounds_factor: f32,
+}
+
+impl Default for CameraPanLimit {
+    fn default() -> Self {
+        Self {
+            map_bounds_factor: 1.5,
+        }
+    }
+}
+
+impl CameraPanLimit {
+    /// Keeps the camera translation within the limit. The map is centred on
+    /// the origin, so this is symmetric around zero.
+    fn clamp(&self, translation: &mut Vec3, maybe_map: Option<&GameMap>) {
+        let Some(map) = maybe_map else {
+            return;
+        };
+
+        let half_extents = Vec2::new(
+            map.size_x as f32 * map.sprite_size,
+            map.size_y as f32 * map.sprite_size,
+        ) * 0.5
+            * self.map_bounds_factor;
+
+        translation.x = translation.x.clamp(-half_extents.x, half_extents.x);
+        translation.y = translation.y.clamp(-half_extents.y, half_extents.y);
     }
 }
 
@@ -0,0 +1,124 @@
+//! The challenge menu, where handicaps can be chosen before starting story mode.
+
+use bevy::{
+    ecs::spawn::SpawnWith, input::common_conditions::input_just_pressed, prelude::*, ui::Val::*,
+};
+
+use crate::{
+    asset_tracking::ResourceHandles,
+    menus::Menu,
+    screens::{CHALLENGES, ChallengeCompletions, ChallengeModifier, ChallengeModifiers, Screen},
+    theme::widget,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ChallengesMenuRoot>();
+
+    app.add_systems(OnEnter(Menu::Challenges), spawn_challenges_menu);
+    app.add_systems(
+        Update,
+        (
+            spawn_challenges_menu.run_if(resource_changed::<ChallengeModifiers>),
+            go_back.run_if(input_just_pressed(KeyCode::Escape)),
+        )
+            .run_if(in_state(Menu::Challenges)),
+    );
+}
+
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+struct ChallengesMenuRoot;
+
+fn spawn_challenges_menu(
+    mut commands: Commands,
+    modifiers: Res<ChallengeModifiers>,
+    completions: Res<ChallengeCompletions>,
+    previous_menus: Query<Entity, With<ChallengesMenuRoot>>,
+) {
+    for previous in &previous_menus {
+        commands.entity(previous).despawn();
+    }
+
+    let completed = completions
+        .completed
+        .iter()
+        .map(|completion| {
+            let names = completion
+                .modifiers
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            format!("Level {}: {}", completion.level, names.join(", "))
+        })
+        .collect::<Vec<_>>();
+
+    commands
+        .spawn((
+            widget::ui_root("Challenges Menu"),
+            ChallengesMenuRoot,
+            GlobalZIndex(2),
+            StateScoped(Menu::Challenges),
+            children![widget::header("Challenges")],
+        ))
+        .with_children(|parent| {
+            for modifier in CHALLENGES {
+                parent.spawn(challenge_row(modifier, modifiers.has(modifier)));
+            }
+
+            parent.spawn(widget::label("Completed"));
+            if completed.is_empty() {
+                parent.spawn(widget::label("No challenges completed yet"));
+            } else {
+                for line in completed {
+                    parent.spawn(widget::label(line));
+                }
+            }
+
+            parent.spawn(widget::button("Start Story Mode", start_story_mode));
+            parent.spawn(widget::button("Back", go_back_on_click));
+        });
+}
+
+fn challenge_row(modifier: ChallengeModifier, active: bool) -> impl Bundle {
+    (
+        Name::new(format!("Challenge {modifier}")),
+        Node {
+            align_items: AlignItems::Center,
+            column_gap: Px(10.0),
+            ..default()
+        },
+        Children::spawn(SpawnWith(move |parent: &mut ChildSpawner| {
+            parent.spawn(widget::button_small(
+                if active { "X" } else { "" },
+                move |_: Trigger<Pointer<Click>>, mut modifiers: ResMut<ChallengeModifiers>| {
+                    modifiers.toggle(modifier);
+                },
+            ));
+
+            parent.spawn(widget::label(format!(
+                "{modifier} - {}",
+                modifier.description()
+            )));
+        })),
+    )
+}
+
+fn start_story_mode(
+    _: Trigger<Pointer<Click>>,
+    resource_handles: Res<ResourceHandles>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    if resource_handles.is_all_done() {
+        next_screen.set(Screen::Gameplay);
+    } else {
+        next_screen.set(Screen::Loading);
+    }
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
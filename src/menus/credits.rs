@@ -1,9 +1,9 @@
 //! The credits menu.
 
-use bevy::{
-    ecs::spawn::SpawnIter, input::common_conditions::input_just_pressed, prelude::*, ui::Val::*,
-};
+use bevy::{input::common_conditions::input_just_pressed, prelude::*};
 
+#[cfg(not(target_family = "wasm"))]
+use crate::screens::PlayerProfile;
 use crate::{
     asset_tracking::LoadResource, audio::music, menus::Menu, screens::Screen, theme::prelude::*,
 };
@@ -20,8 +20,11 @@ pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::Title), start_credits_music);
 }
 
-fn spawn_credits_menu(mut commands: Commands) {
-    commands.spawn((
+fn spawn_credits_menu(
+    mut commands: Commands,
+    #[cfg(not(target_family = "wasm"))] profile: Res<PlayerProfile>,
+) {
+    let mut menu = commands.spawn((
         widget::ui_root("Credits Menu"),
         GlobalZIndex(2),
         StateScoped(Menu::Credits),
@@ -30,48 +33,56 @@ fn spawn_credits_menu(mut commands: Commands) {
             created_by(),
             widget::header("Assets"),
             assets(),
-            widget::button("Back", go_back_on_click),
         ],
     ));
+
+    #[cfg(not(target_family = "wasm"))]
+    menu.with_children(|parent| {
+        parent.spawn(widget::header("Profile"));
+        parent.spawn(profile_stats(&profile));
+    });
+
+    menu.with_child(widget::button("Back", go_back_on_click));
 }
 
 fn created_by() -> impl Bundle {
-    grid(vec![["Will Hart", "Created for bevy jam 6"]])
+    widget::grid(vec![["Will Hart", "Created for bevy jam 6"]])
 }
 
 fn assets() -> impl Bundle {
-    grid(vec![[
+    widget::grid(vec![[
         "Bevy logo",
         "All rights reserved by the Bevy Foundation, permission granted for splash screen use when unmodified",
     ]])
 }
 
-fn grid(content: Vec<[&'static str; 2]>) -> impl Bundle {
-    (
-        Name::new("Grid"),
-        Node {
-            display: Display::Grid,
-            row_gap: Px(10.0),
-            column_gap: Px(30.0),
-            grid_template_columns: RepeatedGridTrack::px(2, 400.0),
-            ..default()
-        },
-        Children::spawn(SpawnIter(content.into_iter().flatten().enumerate().map(
-            |(i, text)| {
-                (
-                    widget::label(text),
-                    Node {
-                        justify_self: if i % 2 == 0 {
-                            JustifySelf::End
-                        } else {
-                            JustifySelf::Start
-                        },
-                        ..default()
-                    },
-                )
-            },
-        ))),
-    )
+#[cfg(not(target_family = "wasm"))]
+fn profile_stats(profile: &PlayerProfile) -> impl Bundle {
+    let best_time = profile.endless_best_time as u32;
+
+    widget::grid(vec![
+        ["Games played".to_string(), profile.total_games.to_string()],
+        [
+            "Cells saved".to_string(),
+            profile.total_cells_saved.to_string(),
+        ],
+        [
+            "Buildings placed".to_string(),
+            profile.total_buildings_placed.to_string(),
+        ],
+        [
+            "Fires put out".to_string(),
+            profile.total_fires_extinguished.to_string(),
+        ],
+        [
+            "Story levels completed".to_string(),
+            profile.story_levels_completed.len().to_string(),
+        ],
+        [
+            "Best endless time".to_string(),
+            format!("{}:{:02}", best_time / 60, best_time % 60),
+        ],
+    ])
 }
 
 fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
@@ -0,0 +1,61 @@
+//! The high score menu, showing the best endless mode runs.
+
+use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+
+use crate::{menus::Menu, screens::HighScores, theme::widget};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::HighScores), spawn_high_scores_menu);
+    app.add_systems(
+        Update,
+        go_back.run_if(in_state(Menu::HighScores).and(input_just_pressed(KeyCode::Escape))),
+    );
+}
+
+fn spawn_high_scores_menu(mut commands: Commands, scores: Res<HighScores>) {
+    let rows = scores
+        .0
+        .entries
+        .iter()
+        .enumerate()
+        .map(|(idx, entry)| {
+            let survived = entry.survival_time as u32;
+            [
+                format!("{}. {} points", idx + 1, entry.score),
+                format!(
+                    "Survived {}:{:02}, built {} - {} (seed {})",
+                    survived / 60,
+                    survived % 60,
+                    entry.buildings_built,
+                    entry.date,
+                    entry.map_seed
+                ),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    commands
+        .spawn((
+            widget::ui_root("High Scores Menu"),
+            GlobalZIndex(2),
+            StateScoped(Menu::HighScores),
+            children![widget::header("High Scores")],
+        ))
+        .with_children(|parent| {
+            if rows.is_empty() {
+                parent.spawn(widget::label("No endless runs recorded yet"));
+            } else {
+                parent.spawn(widget::grid(rows));
+            }
+
+            parent.spawn(widget::button("Back", go_back_on_click));
+        });
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
@@ -0,0 +1,131 @@
+//! The import map menu, opened from the pause menu. Map codes are pasted in
+//! with <ctrl+v> as they are far too long to type.
+
+use bevy::{
+    input::{
+        ButtonState,
+        common_conditions::input_just_pressed,
+        keyboard::{Key, KeyboardInput},
+    },
+    prelude::*,
+};
+
+use crate::{menus::Menu, screens::ImportMap, theme::widget};
+
+/// The number of characters of the map code shown in the menu
+const MAP_CODE_PREVIEW_LENGTH: usize = 24;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<MapCode>();
+    app.register_type::<MapCodeLabel>();
+
+    app.add_systems(OnEnter(Menu::ImportMap), spawn_import_map_menu);
+    app.add_systems(OnExit(Menu::ImportMap), clear_map_code);
+    app.add_systems(
+        Update,
+        (
+            go_back.run_if(input_just_pressed(KeyCode::Escape)),
+            import_map.run_if(input_just_pressed(KeyCode::Enter)),
+            (edit_map_code, update_map_code_label).chain(),
+        )
+            .run_if(in_state(Menu::ImportMap)),
+    );
+}
+
+/// The map code that has been pasted in so far
+#[derive(Resource, Reflect, Debug, Default)]
+#[reflect(Resource)]
+struct MapCode(String);
+
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+struct MapCodeLabel;
+
+fn spawn_import_map_menu(mut commands: Commands) {
+    commands.init_resource::<MapCode>();
+    commands.spawn((
+        widget::ui_root("Import Map Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::ImportMap),
+        children![
+            widget::header("Import Map"),
+            widget::label("Paste a map code with Ctrl+V"),
+            (MapCodeLabel, widget::label(map_code_text(""))),
+            widget::button("Import", import_map_on_click),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+}
+
+fn clear_map_code(mut commands: Commands) {
+    commands.remove_resource::<MapCode>();
+}
+
+fn map_code_text(code: &str) -> String {
+    if code.is_empty() {
+        return "No map code".into();
+    }
+
+    let preview = code
+        .chars()
+        .take(MAP_CODE_PREVIEW_LENGTH)
+        .collect::<String>();
+    format!("{preview}... ({} characters)", code.len())
+}
+
+/// <ctrl+v> replaces the code with the clipboard contents and <backspace>
+/// clears it
+fn edit_map_code(
+    mut keys: EventReader<KeyboardInput>,
+    held: Res<ButtonInput<KeyCode>>,
+    mut code: ResMut<MapCode>,
+) {
+    let ctrl = held.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+
+    for key in keys.read() {
+        if key.state != ButtonState::Pressed {
+            continue;
+        }
+
+        match &key.logical_key {
+            Key::Character(c) if ctrl && c.eq_ignore_ascii_case("v") => {
+                match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text()) {
+                    Ok(text) => code.0 = text.trim().to_string(),
+                    Err(e) => warn!("Unable to read map code from clipboard: {e}"),
+                }
+            }
+            Key::Backspace => code.0.clear(),
+            _ => {}
+        }
+    }
+}
+
+fn update_map_code_label(code: Res<MapCode>, mut labels: Query<&mut Text, With<MapCodeLabel>>) {
+    if !code.is_changed() {
+        return;
+    }
+
+    for mut label in &mut labels {
+        label.0 = map_code_text(&code.0);
+    }
+}
+
+fn import_map_on_click(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.run_system_cached(import_map);
+}
+
+fn import_map(mut commands: Commands, code: Res<MapCode>) {
+    if code.0.is_empty() {
+        return;
+    }
+
+    commands.trigger(ImportMap(code.0.clone()));
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Pause);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Pause);
+}
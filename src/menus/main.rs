@@ -1,10 +1,13 @@
 //! The main menu (seen on the title screen).
 
 use crate::{
-    asset_tracking::ResourceHandles,
     menus::Menu,
-    screens::{EndlessMode, NextStoryLevel, Screen, StoryModeLevel},
+    screens::{
+        ChallengeLumberLimit, ChallengeModifiers, EditorTestLevel, EndlessMode, NextStoryLevel,
+        Screen, StoryModeLevel,
+    },
     theme::{node_builder::NodeBuilder, widget},
+    wildfire::DifficultySettings,
 };
 use bevy::{
     audio::Volume,
@@ -14,18 +17,37 @@ use bevy::{
 };
 
 pub(super) fn plugin(app: &mut App) {
+    app.register_type::<DifficultyLabel>();
+
     app.add_systems(OnEnter(Menu::Main), spawn_main_menu);
     app.add_systems(Startup, lower_volume_you_psychos);
+    app.add_systems(
+        Update,
+        update_difficulty_label
+            .run_if(in_state(Menu::Main).and(resource_changed::<DifficultySettings>)),
+    );
 }
 
+/// Shows the currently selected difficulty on the main menu
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+struct DifficultyLabel;
+
 fn lower_volume_you_psychos(mut vol: ResMut<GlobalVolume>) {
     *vol = GlobalVolume::from(Volume::Linear(0.5));
 }
 
-fn spawn_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn spawn_main_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    difficulty: Res<DifficultySettings>,
+) {
     commands.remove_resource::<EndlessMode>();
     commands.remove_resource::<StoryModeLevel>();
+    commands.remove_resource::<EditorTestLevel>();
     commands.insert_resource(NextStoryLevel::default());
+    commands.insert_resource(ChallengeModifiers::default());
+    commands.remove_resource::<ChallengeLumberLimit>();
 
     commands.spawn((
         Name::new("Main Menu Hints"),
@@ -99,6 +121,32 @@ fn spawn_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
         ],
     ));
 
+    commands.spawn((
+        Name::new("Difficulty Selector"),
+        NodeBuilder::new()
+            .width(Val::Percent(100.0))
+            .position(PositionType::Absolute)
+            .bottom(140.0)
+            .center_content()
+            .build(),
+        GlobalZIndex(2),
+        StateScoped(Menu::Main),
+        children![
+            widget::button_small("<", easier_difficulty),
+            (
+                Name::new("Current Difficulty"),
+                NodeBuilder::new()
+                    .padding(UiRect::horizontal(Val::Px(10.0)))
+                    .build(),
+                children![(
+                    widget::label(format!("Difficulty: {}", difficulty.0)),
+                    DifficultyLabel
+                )],
+            ),
+            widget::button_small(">", harder_difficulty),
+        ],
+    ));
+
     commands.spawn((
         Name::new("Main Menu Buttons"),
         NodeBuilder::new()
@@ -114,47 +162,76 @@ fn spawn_main_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
         StateScoped(Menu::Main),
         #[cfg(not(target_family = "wasm"))]
         children![
-            widget::button_menu("Story Mode", enter_loading_or_gameplay_screen),
-            widget::button_menu("Endless Mode", enter_loading_or_gameplay_screen_endless),
+            widget::button_menu("Campaign", enter_campaign_map_screen),
+            widget::button_menu("Level Select", enter_level_select_screen),
+            widget::button_menu("Endless Mode", open_map_size_menu),
+            widget::button_menu("Challenges", open_challenges_menu),
+            widget::button_menu("High Scores", open_high_scores_menu),
+            widget::button_menu("Level Editor", enter_level_editor_screen),
             widget::button_menu("Settings", open_settings_menu),
             widget::button_menu("Credits", open_credits_menu),
             widget::button_menu("Exit", exit_app),
         ],
         #[cfg(target_family = "wasm")]
         children![
-            widget::button_menu("Story Mode", enter_loading_or_gameplay_screen),
-            widget::button_menu("Endless Mode", enter_loading_or_gameplay_screen_endless),
+            widget::button_menu("Campaign", enter_campaign_map_screen),
+            widget::button_menu("Level Select", enter_level_select_screen),
+            widget::button_menu("Endless Mode", open_map_size_menu),
+            widget::button_menu("Challenges", open_challenges_menu),
+            widget::button_menu("Level Editor", enter_level_editor_screen),
             widget::button_menu("Settings", open_settings_menu),
             widget::button_menu("Credits", open_credits_menu),
         ],
     ));
 }
 
-fn enter_loading_or_gameplay_screen(
+fn easier_difficulty(_: Trigger<Pointer<Click>>, mut difficulty: ResMut<DifficultySettings>) {
+    difficulty.0 = difficulty.0.easier();
+}
+
+fn harder_difficulty(_: Trigger<Pointer<Click>>, mut difficulty: ResMut<DifficultySettings>) {
+    difficulty.0 = difficulty.0.harder();
+}
+
+fn update_difficulty_label(
+    difficulty: Res<DifficultySettings>,
+    mut label: Single<&mut Text, With<DifficultyLabel>>,
+) {
+    label.0 = format!("Difficulty: {}", difficulty.0);
+}
+
+fn enter_campaign_map_screen(
     _: Trigger<Pointer<Click>>,
-    resource_handles: Res<ResourceHandles>,
     mut next_screen: ResMut<NextState<Screen>>,
 ) {
-    if resource_handles.is_all_done() {
-        next_screen.set(Screen::Gameplay);
-    } else {
-        next_screen.set(Screen::Loading);
-    }
+    next_screen.set(Screen::CampaignMap);
 }
 
-fn enter_loading_or_gameplay_screen_endless(
+fn enter_level_select_screen(
     _: Trigger<Pointer<Click>>,
-    mut commands: Commands,
-    resource_handles: Res<ResourceHandles>,
     mut next_screen: ResMut<NextState<Screen>>,
 ) {
-    commands.init_resource::<EndlessMode>();
+    next_screen.set(Screen::LevelSelect);
+}
 
-    if resource_handles.is_all_done() {
-        next_screen.set(Screen::Gameplay);
-    } else {
-        next_screen.set(Screen::Loading);
-    }
+fn open_map_size_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::MapSize);
+}
+
+fn enter_level_editor_screen(
+    _: Trigger<Pointer<Click>>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    next_screen.set(Screen::LevelEditor);
+}
+
+fn open_challenges_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Challenges);
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn open_high_scores_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::HighScores);
 }
 
 fn open_settings_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
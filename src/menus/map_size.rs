@@ -0,0 +1,70 @@
+//! The map size menu, shown before starting endless mode.
+
+use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+
+use crate::{
+    asset_tracking::ResourceHandles,
+    menus::Menu,
+    screens::{EndlessMode, Screen},
+    theme::widget,
+    wildfire::{MapSize, SelectedMapSize},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::MapSize), spawn_map_size_menu);
+    app.add_systems(
+        Update,
+        go_back.run_if(in_state(Menu::MapSize).and(input_just_pressed(KeyCode::Escape))),
+    );
+}
+
+fn spawn_map_size_menu(mut commands: Commands) {
+    commands.spawn((
+        widget::ui_root("Map Size Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::MapSize),
+        children![
+            widget::header("Map Size"),
+            map_size_button(MapSize::Small),
+            map_size_button(MapSize::Medium),
+            map_size_button(MapSize::Large),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+}
+
+fn map_size_button(size: MapSize) -> impl Bundle {
+    widget::button(
+        size.to_string(),
+        move |_: Trigger<Pointer<Click>>,
+              mut commands: Commands,
+              resource_handles: Res<ResourceHandles>,
+              mut next_screen: ResMut<NextState<Screen>>| {
+            start_endless_mode(size, &mut commands, &resource_handles, &mut next_screen);
+        },
+    )
+}
+
+fn start_endless_mode(
+    size: MapSize,
+    commands: &mut Commands,
+    resource_handles: &ResourceHandles,
+    next_screen: &mut NextState<Screen>,
+) {
+    commands.insert_resource(SelectedMapSize(size));
+    commands.init_resource::<EndlessMode>();
+
+    if resource_handles.is_all_done() {
+        next_screen.set(Screen::Gameplay);
+    } else {
+        next_screen.set(Screen::Loading);
+    }
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
@@ -1,8 +1,16 @@
 //! The game's menus and transitions between them.
 
+mod challenges;
 mod credits;
+#[cfg(not(target_family = "wasm"))]
+mod high_scores;
+#[cfg(not(target_family = "wasm"))]
+mod import_map;
 mod main;
+mod map_size;
 mod pause;
+#[cfg(not(target_family = "wasm"))]
+mod save_slots;
 mod settings;
 
 use bevy::prelude::*;
@@ -11,11 +19,15 @@ pub(super) fn plugin(app: &mut App) {
     app.init_state::<Menu>();
 
     app.add_plugins((
+        challenges::plugin,
         credits::plugin,
         main::plugin,
+        map_size::plugin,
         settings::plugin,
         pause::plugin,
     ));
+    #[cfg(not(target_family = "wasm"))]
+    app.add_plugins((high_scores::plugin, import_map::plugin, save_slots::plugin));
 }
 
 #[derive(States, Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
@@ -24,7 +36,12 @@ pub enum Menu {
     #[default]
     None,
     Main,
+    Challenges,
+    MapSize,
     Credits,
     Settings,
     Pause,
+    SaveSlots,
+    HighScores,
+    ImportMap,
 }
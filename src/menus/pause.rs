@@ -2,6 +2,8 @@
 
 use bevy::{input::common_conditions::input_just_pressed, prelude::*};
 
+#[cfg(not(target_family = "wasm"))]
+use crate::screens::{EndlessMode, ExportMap};
 use crate::{menus::Menu, screens::Screen, theme::widget};
 
 pub(super) fn plugin(app: &mut App) {
@@ -12,18 +14,52 @@ pub(super) fn plugin(app: &mut App) {
     );
 }
 
-fn spawn_pause_menu(mut commands: Commands) {
-    commands.spawn((
-        widget::ui_root("Pause Menu"),
-        GlobalZIndex(2),
-        StateScoped(Menu::Pause),
-        children![
-            widget::header("Game paused"),
-            widget::button("Continue", close_menu),
-            widget::button("Settings", open_settings_menu),
-            widget::button("Quit to title", quit_to_title),
-        ],
-    ));
+fn spawn_pause_menu(
+    mut commands: Commands,
+    #[cfg(not(target_family = "wasm"))] endless_mode: Option<Res<EndlessMode>>,
+) {
+    commands
+        .spawn((
+            widget::ui_root("Pause Menu"),
+            GlobalZIndex(2),
+            StateScoped(Menu::Pause),
+            children![
+                widget::header("Game paused"),
+                widget::button("Continue", close_menu),
+            ],
+        ))
+        .with_children(|parent| {
+            // games are saved to disk, and maps are shared through the
+            // clipboard, neither of which are available on the web
+            #[cfg(not(target_family = "wasm"))]
+            {
+                parent.spawn(widget::button("Save / Load", open_save_slots_menu));
+                parent.spawn(widget::button("Export Map", export_map));
+
+                // imported maps start a new endless game
+                if endless_mode.is_some() {
+                    parent.spawn(widget::button("Import Map", open_import_map_menu));
+                }
+            }
+
+            parent.spawn(widget::button("Settings", open_settings_menu));
+            parent.spawn(widget::button("Quit to title", quit_to_title));
+        });
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn export_map(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(ExportMap);
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn open_import_map_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::ImportMap);
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn open_save_slots_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::SaveSlots);
 }
 
 fn open_settings_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
@@ -0,0 +1,84 @@
+//! The save slot menu, opened from the pause menu.
+
+use bevy::{
+    ecs::spawn::SpawnWith, input::common_conditions::input_just_pressed, prelude::*, ui::Val::*,
+};
+
+use crate::{
+    menus::Menu,
+    screens::{LoadGame, SAVE_SLOTS, SaveGame, save_exists},
+    theme::widget,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::SaveSlots), spawn_save_slots_menu);
+    app.add_systems(
+        Update,
+        go_back.run_if(in_state(Menu::SaveSlots).and(input_just_pressed(KeyCode::Escape))),
+    );
+}
+
+fn spawn_save_slots_menu(mut commands: Commands) {
+    commands
+        .spawn((
+            widget::ui_root("Save Slots Menu"),
+            GlobalZIndex(2),
+            StateScoped(Menu::SaveSlots),
+            children![widget::header("Save / Load")],
+        ))
+        .with_children(|parent| {
+            for slot in 1..=SAVE_SLOTS {
+                parent.spawn(save_slot_row(slot));
+            }
+
+            parent.spawn(widget::button("Back", go_back_on_click));
+        });
+}
+
+fn save_slot_row(slot: usize) -> impl Bundle {
+    let exists = save_exists(slot);
+
+    (
+        Name::new(format!("Save Slot {slot}")),
+        Node {
+            align_items: AlignItems::Center,
+            column_gap: Px(10.0),
+            ..default()
+        },
+        Children::spawn(SpawnWith(move |parent: &mut ChildSpawner| {
+            parent.spawn(widget::label(format!(
+                "Slot {slot}{}",
+                if exists { "" } else { " (empty)" }
+            )));
+
+            parent.spawn(widget::button_menu(
+                "Save",
+                move |_: Trigger<Pointer<Click>>,
+                      mut commands: Commands,
+                      mut next_menu: ResMut<NextState<Menu>>| {
+                    commands.trigger(SaveGame(slot));
+                    next_menu.set(Menu::Pause);
+                },
+            ));
+
+            if exists {
+                parent.spawn(widget::button_menu(
+                    "Load",
+                    move |_: Trigger<Pointer<Click>>, mut commands: Commands| {
+                        commands.trigger(LoadGame(slot));
+                    },
+                ));
+            } else {
+                parent.spawn(widget::disabled_button("Load"));
+            }
+        })),
+    )
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Pause);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Pause);
+}
@@ -2,9 +2,17 @@
 //!
 //! Additional settings and accessibility options should go here.
 
-use bevy::{audio::Volume, input::common_conditions::input_just_pressed, prelude::*, ui::Val::*};
+use bevy::{
+    audio::Volume, ecs::system::IntoObserverSystem, input::common_conditions::input_just_pressed,
+    prelude::*, ui::Val::*,
+};
 
-use crate::{menus::Menu, screens::Screen, theme::prelude::*};
+use crate::{
+    menus::Menu,
+    screens::{HasSeenTutorial, Screen},
+    theme::prelude::*,
+    wildfire::{ColourBlindMode, ShowTerrainPatterns, SpreadRateAutoPause},
+};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Menu::Settings), spawn_settings_menu);
@@ -14,9 +22,18 @@ pub(super) fn plugin(app: &mut App) {
     );
 
     app.register_type::<GlobalVolumeLabel>();
+    app.register_type::<AutoPauseLabel>();
+    app.register_type::<ColourBlindModeLabel>();
+    app.register_type::<TerrainPatternsLabel>();
     app.add_systems(
         Update,
-        update_global_volume_label.run_if(in_state(Menu::Settings)),
+        (
+            update_global_volume_label,
+            update_auto_pause_label,
+            update_colour_blind_mode_label,
+            update_terrain_patterns_label,
+        )
+            .run_if(in_state(Menu::Settings)),
     );
 }
 
@@ -52,6 +69,52 @@ fn settings_grid() -> impl Bundle {
                 }
             ),
             global_volume_widget(),
+            (
+                widget::label("Tutorial"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            (
+                Node {
+                    justify_self: JustifySelf::Start,
+                    ..default()
+                },
+                children![widget::button_small("Replay", replay_tutorial)],
+            ),
+            (
+                widget::label("Pause When Fire Surges"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            auto_pause_widget(),
+            (
+                widget::label("Colour Blind Mode"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            toggle_widget(
+                "Colour Blind Mode Widget",
+                ColourBlindModeLabel,
+                cycle_colour_blind_mode
+            ),
+            (
+                widget::label("Terrain Patterns"),
+                Node {
+                    justify_self: JustifySelf::End,
+                    ..default()
+                }
+            ),
+            toggle_widget(
+                "Terrain Patterns Widget",
+                TerrainPatternsLabel,
+                toggle_terrain_patterns
+            ),
         ],
     )
 }
@@ -79,9 +142,70 @@ fn global_volume_widget() -> impl Bundle {
     )
 }
 
+fn auto_pause_widget() -> impl Bundle {
+    (
+        Name::new("Auto Pause Widget"),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            widget::button_small("-", lower_auto_pause_threshold),
+            (
+                Name::new("Current Auto Pause Threshold"),
+                Node {
+                    padding: UiRect::horizontal(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), AutoPauseLabel)],
+            ),
+            widget::button_small("+", raise_auto_pause_threshold),
+        ],
+    )
+}
+
+/// A label showing the current value of a setting, followed by a button to
+/// change it
+fn toggle_widget<E, B, M, I>(
+    name: &'static str,
+    label_marker: impl Component,
+    action: I,
+) -> impl Bundle
+where
+    E: Event,
+    B: Bundle,
+    I: IntoObserverSystem<E, B, M>,
+{
+    (
+        Name::new(name),
+        Node {
+            justify_self: JustifySelf::Start,
+            ..default()
+        },
+        children![
+            (
+                Name::new("Current Value"),
+                Node {
+                    padding: UiRect::right(Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![(widget::label(""), label_marker)],
+            ),
+            widget::button_small(">", action),
+        ],
+    )
+}
+
 const MIN_VOLUME: f32 = 0.0;
 const MAX_VOLUME: f32 = 3.0;
 
+fn replay_tutorial(_: Trigger<Pointer<Click>>, mut seen: ResMut<HasSeenTutorial>) {
+    info!("Tutorial will be shown again next game");
+    seen.reset();
+}
+
 fn lower_global_volume(_: Trigger<Pointer<Click>>, mut global_volume: ResMut<GlobalVolume>) {
     let linear = (global_volume.volume.to_linear() - 0.1).max(MIN_VOLUME);
     global_volume.volume = Volume::Linear(linear);
@@ -96,6 +220,89 @@ fn raise_global_volume(_: Trigger<Pointer<Click>>, mut global_volume: ResMut<Glo
 #[reflect(Component)]
 struct GlobalVolumeLabel;
 
+/// The amount the auto pause threshold changes by with each click, in cells
+/// per second. Lowering it to zero turns auto pause off
+const AUTO_PAUSE_STEP: f32 = 5.0;
+const MAX_AUTO_PAUSE_THRESHOLD: f32 = 100.0;
+
+fn lower_auto_pause_threshold(
+    _: Trigger<Pointer<Click>>,
+    mut auto_pause: ResMut<SpreadRateAutoPause>,
+) {
+    auto_pause.0 = auto_pause
+        .0
+        .map(|threshold| threshold - AUTO_PAUSE_STEP)
+        .filter(|threshold| *threshold > 0.0);
+}
+
+fn raise_auto_pause_threshold(
+    _: Trigger<Pointer<Click>>,
+    mut auto_pause: ResMut<SpreadRateAutoPause>,
+) {
+    let threshold = auto_pause.0.unwrap_or(0.0) + AUTO_PAUSE_STEP;
+    auto_pause.0 = Some(threshold.min(MAX_AUTO_PAUSE_THRESHOLD));
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct AutoPauseLabel;
+
+fn update_auto_pause_label(
+    auto_pause: Res<SpreadRateAutoPause>,
+    mut label: Single<&mut Text, With<AutoPauseLabel>>,
+) {
+    label.0 = match auto_pause.0 {
+        Some(threshold) => format!("{threshold:.0} cells/sec"),
+        None => "Off".into(),
+    };
+}
+
+fn cycle_colour_blind_mode(_: Trigger<Pointer<Click>>, mut mode: ResMut<ColourBlindMode>) {
+    *mode = mode.next();
+    info!("Setting colour blind mode to {}", *mode);
+}
+
+fn toggle_terrain_patterns(
+    _: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    maybe_patterns: Option<Res<ShowTerrainPatterns>>,
+) {
+    if maybe_patterns.is_some() {
+        info!("Hiding terrain patterns");
+        commands.remove_resource::<ShowTerrainPatterns>();
+    } else {
+        info!("Showing terrain patterns");
+        commands.init_resource::<ShowTerrainPatterns>();
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ColourBlindModeLabel;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct TerrainPatternsLabel;
+
+fn update_colour_blind_mode_label(
+    mode: Res<ColourBlindMode>,
+    mut label: Single<&mut Text, With<ColourBlindModeLabel>>,
+) {
+    label.0 = mode.to_string();
+}
+
+fn update_terrain_patterns_label(
+    maybe_patterns: Option<Res<ShowTerrainPatterns>>,
+    mut label: Single<&mut Text, With<TerrainPatternsLabel>>,
+) {
+    label.0 = if maybe_patterns.is_some() {
+        "On"
+    } else {
+        "Off"
+    }
+    .into();
+}
+
 fn update_global_volume_label(
     global_volume: Res<GlobalVolume>,
     mut label: Single<&mut Text, With<GlobalVolumeLabel>>,
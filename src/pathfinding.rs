@@ -0,0 +1,187 @@
+//! A* pathfinding through the map grid, used by units that walk between cells
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::wildfire::{GameMap, TerrainType};
+
+/// The maximum number of cells to expand before giving up, so that searching
+/// for a distant or unreachable target doesn't freeze the game
+const MAX_SEARCH_NODES: usize = 500;
+
+/// The cells a unit can step to from any cell
+const NEIGHBOURS: [IVec2; 4] = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y];
+
+/// Finds the shortest path from `start` to `goal`, moving between adjacent cells
+/// and avoiding fires, buildings and water that isn't crossed by a river ferry.
+/// The returned path excludes `start` and ends
+/// at `goal`. The start and goal cells are always considered passable, as units
+/// leave from buildings and walk to fires.
+pub fn astar(start: IVec2, goal: IVec2, map: &GameMap) -> Option<Vec<IVec2>> {
+    if !map.is_valid_coords(start) || !map.is_valid_coords(goal) {
+        return None;
+    }
+
+    // glam vectors don't implement `Ord`, so the open set stores plain arrays
+    let mut open = BinaryHeap::new();
+    let mut came_from = HashMap::<IVec2, IVec2>::default();
+    let mut cost = HashMap::<IVec2, i32>::default();
+
+    open.push((Reverse(manhattan(start, goal)), start.to_array()));
+    cost.insert(start, 0);
+
+    let mut expanded = 0;
+    while let Some((_, current)) = open.pop() {
+        let current = IVec2::from_array(current);
+        if current == goal {
+            return Some(reconstruct_path(&came_from, start, goal));
+        }
+
+        expanded += 1;
+        if expanded > MAX_SEARCH_NODES {
+            break;
+        }
+
+        let next_cost = cost[&current] + 1;
+        for offset in NEIGHBOURS {
+            let next = current + offset;
+            if next != goal && !is_passable(map, next) {
+                continue;
+            }
+
+            if cost
+                .get(&next)
+                .is_some_and(|existing| *existing <= next_cost)
+            {
+                continue;
+            }
+
+            cost.insert(next, next_cost);
+            came_from.insert(next, current);
+            open.push((Reverse(next_cost + manhattan(next, goal)), next.to_array()));
+        }
+    }
+
+    None
+}
+
+/// Whether a unit can walk through the given cell
+fn is_passable(map: &GameMap, loc: IVec2) -> bool {
+    map.get(loc).is_some_and(|cell| match cell.terrain {
+        TerrainType::Fire | TerrainType::Building => false,
+        TerrainType::Water => map.is_crossable(loc),
+        TerrainType::Dirt
+        | TerrainType::Grassland
+        | TerrainType::Tree
+        | TerrainType::Stone
+        | TerrainType::Smoldering => true,
+    })
+}
+
+fn manhattan(from: IVec2, to: IVec2) -> i32 {
+    let delta = (to - from).abs();
+    delta.x + delta.y
+}
+
+/// Walks back from the goal to the start to build the path
+fn reconstruct_path(came_from: &HashMap<IVec2, IVec2>, start: IVec2, goal: IVec2) -> Vec<IVec2> {
+    let mut path = vec![goal];
+    let mut current = goal;
+
+    while let Some(previous) = came_from.get(&current) {
+        if *previous == start {
+            break;
+        }
+
+        path.push(*previous);
+        current = *previous;
+    }
+
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wildfire::{Biome, TerrainCellState};
+
+    /// The width and height of the maps used in the tests
+    const MAP_SIZE: usize = 10;
+
+    /// Creates a map covered in grass, with the given cells changed to the
+    /// given terrain
+    fn grass_map(terrain: TerrainType, cells: impl IntoIterator<Item = IVec2>) -> GameMap {
+        let grass = TerrainCellState {
+            terrain: TerrainType::Grassland,
+            ..default()
+        };
+        let mut data = vec![vec![grass; MAP_SIZE]; MAP_SIZE];
+        for loc in cells {
+            data[loc.y as usize][loc.x as usize].terrain = terrain;
+        }
+
+        GameMap::from_data(0, 16.0, data, Biome::default())
+    }
+
+    /// Checks that each step of the path moves to an adjacent passable cell
+    fn assert_walkable(map: &GameMap, start: IVec2, path: &[IVec2]) {
+        let mut previous = start;
+        for step in path {
+            assert_eq!(
+                manhattan(previous, *step),
+                1,
+                "{previous} to {step} is not one step"
+            );
+            assert!(is_passable(map, *step), "{step} is not passable");
+            previous = *step;
+        }
+    }
+
+    #[test]
+    fn finds_straight_path() {
+        let map = grass_map(TerrainType::Grassland, []);
+        let start = IVec2::new(1, 5);
+        let goal = IVec2::new(6, 5);
+
+        let path = astar(start, goal, &map).expect("the goal is reachable");
+
+        assert_eq!(path, (2..=6).map(|x| IVec2::new(x, 5)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn walks_around_obstacles() {
+        // a wall of buildings down the map with a gap at the bottom
+        let wall = (0..8).map(|y| IVec2::new(4, y));
+        let map = grass_map(TerrainType::Building, wall);
+        let start = IVec2::new(1, 1);
+        let goal = IVec2::new(7, 1);
+
+        let path = astar(start, goal, &map).expect("the goal is reachable");
+
+        assert_eq!(path.last(), Some(&goal));
+        assert_walkable(&map, start, &path);
+        // down to the gap at y = 8 and back up again
+        assert_eq!(path.len(), 20);
+    }
+
+    #[test]
+    fn unreachable_goal_returns_none() {
+        let goal = IVec2::new(5, 5);
+        let moat = (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| goal + IVec2::new(dx, dy)))
+            .filter(|loc| *loc != goal);
+        let map = grass_map(TerrainType::Water, moat);
+
+        assert_eq!(astar(IVec2::new(1, 1), goal, &map), None);
+    }
+
+    #[test]
+    fn goal_off_the_map_returns_none() {
+        let map = grass_map(TerrainType::Grassland, []);
+
+        assert_eq!(astar(IVec2::new(1, 1), IVec2::new(-1, 3), &map), None);
+    }
+}
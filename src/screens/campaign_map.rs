@@ -0,0 +1,340 @@
+//! A map of the story campaign, showing each level as a node on a hand drawn
+//! map with a trail between them. Levels unlock once the level before them is
+//! completed.
+
+use bevy::{
+    color::palettes::tailwind::{AMBER_400, SLATE_400, SLATE_700, SLATE_800, STONE_700},
+    image::{ImageLoaderSettings, ImageSampler},
+    input::common_conditions::input_just_pressed,
+    platform::collections::HashSet,
+    prelude::*,
+};
+
+#[cfg(not(target_family = "wasm"))]
+use crate::screens::{LevelRecords, PlayerProfile};
+use crate::{
+    asset_tracking::ResourceHandles,
+    screens::{Medal, NextStoryLevel, Screen, get_level_data},
+    theme::{node_builder::NodeBuilder, widget},
+};
+
+use super::victory::medal_color;
+
+/// The size the campaign map is drawn at, in pixels. The image itself is a
+/// small parchment vignette that is smoothly stretched to this size
+const CAMPAIGN_MAP_SIZE: Vec2 = Vec2::new(640.0, 400.0);
+
+/// Where each level sits on the campaign map, in pixels from the top left of
+/// the image. Level 1 is the first entry
+const LEVEL_NODE_POSITIONS: [Vec2; 4] = [
+    Vec2::new(90.0, 310.0),
+    Vec2::new(230.0, 170.0),
+    Vec2::new(400.0, 260.0),
+    Vec2::new(545.0, 95.0),
+];
+
+/// The width and height of each level node, in pixels
+const LEVEL_NODE_SIZE: f32 = 56.0;
+
+/// The distance between the dots on the trail joining two levels, in pixels
+const TRAIL_DOT_SPACING: f32 = 14.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<CampaignLevelNode>();
+
+    app.add_systems(OnEnter(Screen::CampaignMap), spawn_campaign_map);
+    app.add_systems(
+        Update,
+        go_back.run_if(in_state(Screen::CampaignMap).and(input_just_pressed(KeyCode::Escape))),
+    );
+}
+
+/// A clickable level on the campaign map
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+struct CampaignLevelNode {
+    level_number: usize,
+    unlocked: bool,
+}
+
+/// How far the player has got with a level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LevelStatus {
+    Locked,
+    Incomplete,
+    Complete(Option<Medal>),
+}
+
+fn spawn_campaign_map(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    #[cfg(not(target_family = "wasm"))] profile: Res<PlayerProfile>,
+    #[cfg(not(target_family = "wasm"))] records: Res<LevelRecords>,
+) {
+    // progress is only saved on native builds, so every level is open on the web
+    #[cfg(not(target_family = "wasm"))]
+    let completed: HashSet<usize> = profile.story_levels_completed.clone();
+    #[cfg(target_family = "wasm")]
+    let completed: HashSet<usize> = HashSet::new();
+
+    let level_status = |level_number: usize| {
+        if completed.contains(&level_number) {
+            #[cfg(not(target_family = "wasm"))]
+            let medal = records
+                .0
+                .best(level_number)
+                .map(|best| Medal::for_completion_time(best.completion_time));
+            #[cfg(target_family = "wasm")]
+            let medal = None;
+
+            LevelStatus::Complete(medal)
+        } else if cfg!(target_family = "wasm")
+            || level_number == 1
+            || completed.contains(&(level_number - 1))
+        {
+            LevelStatus::Incomplete
+        } else {
+            LevelStatus::Locked
+        }
+    };
+
+    let levels = (1..)
+        .map_while(get_level_data)
+        .zip(LEVEL_NODE_POSITIONS)
+        .map(|(level, position)| {
+            (
+                level.level_number,
+                position,
+                level_status(level.level_number),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    commands
+        .spawn((
+            widget::ui_root("Campaign Map"),
+            GlobalZIndex(2),
+            StateScoped(Screen::CampaignMap),
+            children![widget::header("Campaign")],
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Name::new("Campaign Map Image"),
+                    Node {
+                        width: Val::Px(CAMPAIGN_MAP_SIZE.x),
+                        height: Val::Px(CAMPAIGN_MAP_SIZE.y),
+                        ..default()
+                    },
+                    ImageNode::new(asset_server.load_with_settings(
+                        "images/campaign_map.png",
+                        |settings: &mut ImageLoaderSettings| {
+                            settings.sampler = ImageSampler::linear();
+                        },
+                    )),
+                ))
+                .with_children(|map| {
+                    for pair in levels.windows(2) {
+                        let (_, from, _) = pair[0];
+                        let (_, to, to_status) = pair[1];
+                        spawn_trail(map, from, to, to_status != LevelStatus::Locked);
+                    }
+
+                    for (level_number, position, status) in &levels {
+                        spawn_level_node(map, *level_number, *position, *status);
+                    }
+                });
+
+            parent.spawn(widget::button("Back", go_back_on_click));
+        });
+}
+
+/// Draws a dotted trail between two levels, faded if the second level is
+/// still locked
+fn spawn_trail(parent: &mut ChildSpawnerCommands, from: Vec2, to: Vec2, unlocked: bool) {
+    let colour = if unlocked { STONE_700 } else { SLATE_400 };
+    let steps = (from.distance(to) / TRAIL_DOT_SPACING) as usize;
+
+    for step in 1..steps {
+        let dot = from.lerp(to, step as f32 / steps as f32);
+        parent.spawn((
+            Name::new("Campaign Trail"),
+            NodeBuilder::new()
+                .position(PositionType::Absolute)
+                .left(dot.x - 2.0)
+                .top(dot.y - 2.0)
+                .width(Val::Px(4.0))
+                .height(Val::Px(4.0))
+                .background(colour)
+                .build(),
+            BorderRadius::MAX,
+            Pickable::IGNORE,
+        ));
+    }
+}
+
+fn spawn_level_node(
+    parent: &mut ChildSpawnerCommands,
+    level_number: usize,
+    position: Vec2,
+    status: LevelStatus,
+) {
+    let unlocked = status != LevelStatus::Locked;
+    let background = if unlocked { SLATE_800 } else { SLATE_700 };
+    let is_gold = status == LevelStatus::Complete(Some(Medal::Gold));
+    let status_text = match status {
+        LevelStatus::Locked => "Locked",
+        LevelStatus::Incomplete => "Incomplete",
+        LevelStatus::Complete(Some(Medal::Gold)) => "Gold",
+        LevelStatus::Complete(_) => "Complete",
+    };
+
+    parent
+        .spawn((
+            Name::new(format!("Level {level_number} Node")),
+            CampaignLevelNode {
+                level_number,
+                unlocked,
+            },
+            Button,
+            NodeBuilder::new()
+                .position(PositionType::Absolute)
+                .left(position.x - LEVEL_NODE_SIZE / 2.0)
+                .top(position.y - LEVEL_NODE_SIZE / 2.0)
+                .width(Val::Px(LEVEL_NODE_SIZE))
+                .height(Val::Px(LEVEL_NODE_SIZE))
+                .flex_direction(FlexDirection::Column)
+                .center_content()
+                .background(background)
+                .build(),
+            BorderRadius::MAX,
+        ))
+        .with_children(|node| {
+            node.spawn((
+                Text::new(level_number.to_string()),
+                TextFont::from_font_size(24.0),
+                Pickable::IGNORE,
+            ));
+
+            if !unlocked {
+                node.spawn(lock_icon());
+            }
+
+            if let LevelStatus::Complete(Some(medal)) = status {
+                node.spawn(medal_icon(medal));
+            }
+
+            // the status hangs below the node so it doesn't crowd the number
+            node.spawn((
+                Text::new(status_text),
+                TextFont::from_font_size(14.0),
+                TextColor(if is_gold {
+                    AMBER_400.into()
+                } else {
+                    Color::WHITE
+                }),
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(LEVEL_NODE_SIZE + 2.0),
+                    ..default()
+                },
+                Pickable::IGNORE,
+            ));
+        })
+        .observe(start_campaign_level);
+}
+
+/// A small padlock made of a shackle and a body
+fn lock_icon() -> impl Bundle {
+    (
+        Name::new("Lock Icon"),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(-8.0),
+            right: Val::Px(-4.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        Pickable::IGNORE,
+        children![
+            (
+                Node {
+                    width: Val::Px(10.0),
+                    height: Val::Px(8.0),
+                    border: UiRect {
+                        left: Val::Px(2.0),
+                        right: Val::Px(2.0),
+                        top: Val::Px(2.0),
+                        bottom: Val::Px(0.0),
+                    },
+                    ..default()
+                },
+                BorderColor(AMBER_400.into()),
+                BorderRadius::top(Val::Px(5.0)),
+                Pickable::IGNORE,
+            ),
+            (
+                NodeBuilder::new()
+                    .width(Val::Px(16.0))
+                    .height(Val::Px(12.0))
+                    .background(AMBER_400)
+                    .build(),
+                BorderRadius::all(Val::Px(2.0)),
+                Pickable::IGNORE,
+            ),
+        ],
+    )
+}
+
+/// A medal coloured circle shown on completed levels
+fn medal_icon(medal: Medal) -> impl Bundle {
+    (
+        Name::new("Medal Icon"),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(-6.0),
+            right: Val::Px(-6.0),
+            width: Val::Px(18.0),
+            height: Val::Px(18.0),
+            ..default()
+        },
+        BackgroundColor(medal_color(medal)),
+        BorderRadius::MAX,
+        Pickable::IGNORE,
+    )
+}
+
+fn start_campaign_level(
+    trigger: Trigger<Pointer<Click>>,
+    nodes: Query<&CampaignLevelNode>,
+    resource_handles: Res<ResourceHandles>,
+    mut next_level: ResMut<NextStoryLevel>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    let Ok(node) = nodes.get(trigger.target()) else {
+        return;
+    };
+
+    if !node.unlocked {
+        info!("Level {} is locked", node.level_number);
+        return;
+    }
+
+    // entering gameplay spawns the map for the next story level
+    info!("Starting level {} from the campaign map", node.level_number);
+    next_level.0 = node.level_number;
+    if resource_handles.is_all_done() {
+        next_screen.set(Screen::Gameplay);
+    } else {
+        next_screen.set(Screen::Loading);
+    }
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
+
+fn go_back(mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
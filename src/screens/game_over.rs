@@ -4,8 +4,9 @@ use bevy::prelude::*;
 
 use crate::asset_tracking::LoadResource;
 use crate::audio::sound_effect;
-use crate::screens::{EndlessMode, NextStoryLevel, Screen, get_level_data};
+use crate::screens::{EndlessMode, MedalCollection, NextStoryLevel, Screen, get_level_data};
 use crate::theme::widget;
+use crate::wildfire::{FireStats, fire_stats_panel};
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<GameOverAssets>();
@@ -19,6 +20,8 @@ fn spawn_game_over_screen(
     game_over_assets: Res<GameOverAssets>,
     maybe_endless: Option<Res<EndlessMode>>,
     maybe_next_story: Option<Res<NextStoryLevel>>,
+    medals: Res<MedalCollection>,
+    stats: Res<FireStats>,
 ) {
     let show_try_again = maybe_endless.is_some()
         || match maybe_next_story {
@@ -41,6 +44,20 @@ fn spawn_game_over_screen(
             ],
         ))
         .with_children(|parent| {
+            parent.spawn(fire_stats_panel(&stats));
+
+            if maybe_endless.is_some() && !medals.0.is_empty() {
+                parent.spawn(widget::label(format!(
+                    "Medals earned: {}",
+                    medals
+                        .0
+                        .iter()
+                        .map(|medal| medal.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )));
+            }
+
             if show_try_again {
                 parent.spawn((widget::button(
                     "Try Again?",
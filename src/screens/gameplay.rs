@@ -13,23 +13,82 @@ use crate::{
     screens::{
         Screen,
         gameplay::building::{
-            BuildingAssets, MageRotation, ManaLine, SpawnCityHall, SpawnLumberMill, SpawnManaForge,
-            SpawnMinotaur, SpawnStormMage, SpawnWaterGolem, TrackParentBuildingWhilePlacing,
+            BUILDING_FOOTPRINT_OFFSETS, DemolishBuilding, MageRotation, ManaLine, SpawnArborist,
+            SpawnBeaconTower, SpawnCityHall, SpawnEmberGuard, SpawnFireFighter, SpawnLumberMill,
+            SpawnManaBattery, SpawnManaForge, SpawnMinotaur, SpawnRelayTower, SpawnResearchHall,
+            SpawnRiverFerry, SpawnRiverGolem, SpawnSprinkler, SpawnStoneWall, SpawnStormMage,
+            SpawnTreasury, SpawnWaterGolem, TrackParentBuildingWhilePlacing, UndoFootprint,
+            WallStart,
         },
+        gameplay::context_menu::ContextMenu,
+        gameplay::hotkeys::HotkeyOverlay,
+    },
+    wildfire::{
+        GameMap, OnControlledBurn, OnMeteorStrike, OnRainCloud, RAIN_CLOUD_RADIUS, TerrainType,
+        fire_spread_surged,
     },
-    wildfire::{GameMap, OnMeteorStrike},
 };
 
 mod building;
+mod building_panel;
+mod building_stats;
+mod challenge;
+mod context_menu;
+mod event_log;
+mod fire_health_bar;
+#[cfg(not(target_family = "wasm"))]
+mod high_scores;
+mod hotkeys;
+#[cfg(not(target_family = "wasm"))]
+mod map_sharing;
+mod milestones;
+#[cfg(not(target_family = "wasm"))]
+mod profile;
+mod resource_breakdown;
+#[cfg(not(target_family = "wasm"))]
+mod save;
+mod screen_shake;
 pub mod story_mode;
+mod terrain_paint;
 mod toolbar;
+mod tutorial;
 mod victory;
 
 pub use building::{
-    BuildingType, CityHall, LUMBER_MILL_COST_LUMBER, MANA_FORGE_COST_LUMBER, MINOTAUR_COST_MANA,
-    RequiresCityHall, STORM_MAGE_COST_MANA, WATER_GOLEM_COST_MANA,
+    ARBORIST_COST_LUMBER, BEACON_TOWER_COST_MANA, BEACON_TOWER_MAX_REDIRECTS, BEACON_TOWER_RANGE,
+    BeaconTower, BuildingAssets, BuildingLocation, BuildingType, CityHall, EMBER_GUARD_COST_MANA,
+    FIRE_FIGHTER_COST_MANA, LUMBER_MILL_COST_LUMBER, MANA_BATTERY_COST_LUMBER,
+    MANA_FORGE_COST_LUMBER, MINOTAUR_COST_MANA, RELAY_TOWER_COST_LUMBER, RESEARCH_HALL_COST_LUMBER,
+    RIVER_FERRY_COST_LUMBER, RIVER_GOLEM_COST_MANA, RequiresCityHall, ResearchState,
+    SPRINKLER_COST_MANA, STONE_WALL_COST_LUMBER, STORM_MAGE_COST_MANA, STORM_MAGE_ROTATE_COST_MANA,
+    TREASURY_COST_LUMBER, WATER_GOLEM_COST_MANA, WearOutBeaconTower,
+};
+
+/// The mana cap before any mana batteries are built
+pub const BASE_MAX_MANA: i32 = 200;
+/// The lumber cap before any treasuries are built
+pub const BASE_MAX_LUMBER: i32 = 200;
+pub use challenge::{
+    CHALLENGES, ChallengeCompletions, ChallengeLumberLimit, ChallengeModifier, ChallengeModifiers,
 };
+pub use event_log::{EventLog, log_event};
+#[cfg(not(target_family = "wasm"))]
+pub use high_scores::{HighScores, LevelCompletionResult, LevelRecords};
+#[cfg(not(target_family = "wasm"))]
+pub use map_sharing::{ExportMap, ImportMap};
+pub use milestones::{Medal, MedalCollection};
+#[cfg(not(target_family = "wasm"))]
+pub use profile::PlayerProfile;
+#[cfg(not(target_family = "wasm"))]
+pub use save::{LoadGame, ResumeAutosave, SAVE_SLOTS, SaveGame, autosave_exists, save_exists};
+pub use screen_shake::ShakeScreen;
+pub use terrain_paint::painting_terrain;
 pub use toolbar::OnRedrawToolbar;
+pub use tutorial::{HasSeenTutorial, TutorialState};
+
+/// The folder that saved games, high scores and other records are written to
+#[cfg(not(target_family = "wasm"))]
+const SAVE_DIRECTORY: &str = "saves";
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<BuildingMode>();
@@ -39,16 +98,39 @@ pub(super) fn plugin(app: &mut App) {
     app.register_type::<BuildTextHint>();
     app.register_type::<BuildTextMarker>();
     app.register_type::<EndlessMode>();
+    app.register_type::<GameSpeed>();
 
     app.init_resource::<BuildingMode>();
     app.init_resource::<BuildTextHint>();
+    app.init_resource::<GameSpeed>();
 
     app.add_plugins((
         building::plugin,
+        building_panel::plugin,
+        building_stats::plugin,
+        challenge::plugin,
+        context_menu::plugin,
+        event_log::plugin,
+        fire_health_bar::plugin,
+        hotkeys::plugin,
+    ));
+    app.add_plugins((
+        milestones::plugin,
+        resource_breakdown::plugin,
+        screen_shake::plugin,
         story_mode::plugin,
+        terrain_paint::plugin,
         toolbar::plugin,
+        tutorial::plugin,
         victory::plugin,
     ));
+    #[cfg(not(target_family = "wasm"))]
+    app.add_plugins((
+        high_scores::plugin,
+        map_sharing::plugin,
+        profile::plugin,
+        save::plugin,
+    ));
 
     // Toggle pause on key press.
     app.add_systems(
@@ -57,6 +139,7 @@ pub(super) fn plugin(app: &mut App) {
             (pause, spawn_pause_overlay, open_pause_menu).run_if(
                 in_state(Screen::Gameplay)
                     .and(in_state(Menu::None))
+                    .and(not(any_with_component::<HotkeyOverlay>))
                     .and(input_just_pressed(KeyCode::KeyP).or(input_just_pressed(KeyCode::Escape))),
             ),
             close_menu.run_if(
@@ -66,7 +149,27 @@ pub(super) fn plugin(app: &mut App) {
             ),
         ),
     );
+    // Pause automatically if the fire suddenly starts spreading quickly
+    app.add_systems(
+        Update,
+        (pause, spawn_pause_overlay, open_pause_menu).run_if(
+            in_state(Screen::Gameplay)
+                .and(in_state(Menu::None))
+                .and(in_state(Pause(false)))
+                .and(fire_spread_surged),
+        ),
+    );
     app.add_systems(OnExit(Screen::Gameplay), (close_menu, unpause));
+    app.add_systems(OnEnter(Screen::Gameplay), reset_game_speed);
+
+    app.add_systems(
+        Update,
+        (
+            slow_down_game.run_if(input_just_pressed(KeyCode::BracketLeft)),
+            speed_up_game.run_if(input_just_pressed(KeyCode::BracketRight)),
+        )
+            .run_if(in_state(Screen::Gameplay).and(in_state(Pause(false)))),
+    );
     app.add_systems(
         OnEnter(Menu::None),
         unpause.run_if(in_state(Screen::Gameplay)),
@@ -74,17 +177,24 @@ pub(super) fn plugin(app: &mut App) {
 
     app.add_systems(
         Update,
-        cancel_cursor_mode
-            .run_if(in_state(Screen::Gameplay).and(
-                input_just_pressed(KeyCode::Space).or(input_just_pressed(MouseButton::Right)),
-            )),
+        cancel_cursor_mode.run_if(
+            in_state(Screen::Gameplay)
+                .and(input_just_pressed(KeyCode::Space).or(
+                    // right click paints earth when painting terrain
+                    input_just_pressed(MouseButton::Right).and(not(painting_terrain)),
+                ))
+                // right clicking a building opens its context menu instead
+                .and(not(any_with_component::<ContextMenu>)),
+        ),
     );
 
     app.add_systems(
         Update,
         (
             cursor_mode_follower,
-            handle_mouse_click_input.run_if(input_just_pressed(MouseButton::Left)),
+            handle_mouse_click_input.run_if(
+                input_just_pressed(MouseButton::Left).and(not(any_with_component::<HotkeyOverlay>)),
+            ),
             handle_build_mode_changing
                 .run_if(resource_changed::<BuildingMode>)
                 .after(cancel_cursor_mode),
@@ -106,7 +216,7 @@ pub(super) fn plugin(app: &mut App) {
 
 /// Shhhhhhh
 fn cheat(mut resources: ResMut<PlayerResources>) {
-    resources.mana += 100;
+    resources.add_mana(100);
     resources.lumber += 100;
 }
 
@@ -114,6 +224,56 @@ fn cheat(mut resources: ResMut<PlayerResources>) {
 #[reflect(Resource, Default)]
 pub struct EndlessMode;
 
+/// The speeds that the game can be played at
+const GAME_SPEEDS: [f32; 4] = [0.5, 1.0, 2.0, 3.0];
+
+/// A multiplier for how quickly the game simulation runs
+#[derive(Resource, Reflect, Debug, Clone, Copy, PartialEq)]
+#[reflect(Resource)]
+pub struct GameSpeed(pub f32);
+
+impl Default for GameSpeed {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl std::fmt::Display for GameSpeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x", self.0)
+    }
+}
+
+impl GameSpeed {
+    /// Moves to the next fastest speed, if there is one
+    pub fn faster(&mut self) {
+        if let Some(speed) = GAME_SPEEDS.iter().find(|speed| **speed > self.0) {
+            self.0 = *speed;
+        }
+    }
+
+    /// Moves to the next slowest speed, if there is one
+    pub fn slower(&mut self) {
+        if let Some(speed) = GAME_SPEEDS.iter().rev().find(|speed| **speed < self.0) {
+            self.0 = *speed;
+        }
+    }
+}
+
+fn reset_game_speed(mut game_speed: ResMut<GameSpeed>) {
+    *game_speed = GameSpeed::default();
+}
+
+fn slow_down_game(mut game_speed: ResMut<GameSpeed>) {
+    game_speed.slower();
+    info!("Game speed set to {}", *game_speed);
+}
+
+fn speed_up_game(mut game_speed: ResMut<GameSpeed>) {
+    game_speed.faster();
+    info!("Game speed set to {}", *game_speed);
+}
+
 #[derive(Resource, Reflect, Debug, Clone)]
 #[reflect(Resource)]
 pub struct PlayerResources {
@@ -123,6 +283,14 @@ pub struct PlayerResources {
     pub mana_drain: i32,
     /// The amount of lumber in the bank
     pub lumber: i32,
+    /// The most mana that forges can fill the bank to. Not saved as it is
+    /// recalculated from the number of mana batteries
+    #[reflect(skip_serializing)]
+    pub max_mana: i32,
+    /// The most lumber that mills can fill the bank to. Not saved as it is
+    /// recalculated from the number of treasuries
+    #[reflect(skip_serializing)]
+    pub max_lumber: i32,
 }
 
 impl Default for PlayerResources {
@@ -131,6 +299,18 @@ impl Default for PlayerResources {
             mana: 0,
             mana_drain: 0,
             lumber: 80,
+            max_mana: BASE_MAX_MANA,
+            max_lumber: BASE_MAX_LUMBER,
+        }
+    }
+}
+
+impl PlayerResources {
+    /// Adds mana to the bank, without filling it past the mana cap
+    pub fn add_mana(&mut self, amount: i32) {
+        // mana over the cap (e.g. after losing a battery) isn't taken away
+        if self.mana < self.max_mana {
+            self.mana = (self.mana + amount).min(self.max_mana);
         }
     }
 }
@@ -145,12 +325,93 @@ pub enum BuildingMode {
     #[default]
     None,
     Meteor,
+    ControlledBurn,
+    RainCloud,
     PlaceCityHall,
     PlaceLumberMill,
     PlaceManaForge,
     PlaceMinotaur,
     PlaceStormMage,
     PlaceWaterGolem,
+    PlaceEmberGuard,
+    PlaceStoneWall,
+    PlaceArborist,
+    PlaceBeaconTower,
+    PlaceResearchHall,
+    PlaceRelayTower,
+    PlaceManaBattery,
+    PlaceFireFighter,
+    PlaceSprinkler,
+    PlaceTreasury,
+    PlaceRiverGolem,
+    PlaceRiverFerry,
+    Demolish,
+    /// Paints the terrain type onto the map, only available in sandbox mode
+    TerrainPaint(TerrainType),
+}
+
+impl BuildingMode {
+    /// Whether this mode places a single building that can be undone. City
+    /// halls can't be undone and stone walls aren't buildings
+    pub fn places_undoable_building(&self) -> bool {
+        match self {
+            BuildingMode::PlaceLumberMill
+            | BuildingMode::PlaceManaForge
+            | BuildingMode::PlaceMinotaur
+            | BuildingMode::PlaceStormMage
+            | BuildingMode::PlaceWaterGolem
+            | BuildingMode::PlaceEmberGuard
+            | BuildingMode::PlaceArborist
+            | BuildingMode::PlaceBeaconTower
+            | BuildingMode::PlaceResearchHall
+            | BuildingMode::PlaceRelayTower
+            | BuildingMode::PlaceManaBattery
+            | BuildingMode::PlaceFireFighter
+            | BuildingMode::PlaceSprinkler
+            | BuildingMode::PlaceTreasury
+            | BuildingMode::PlaceRiverGolem
+            | BuildingMode::PlaceRiverFerry => true,
+            BuildingMode::None
+            | BuildingMode::Meteor
+            | BuildingMode::ControlledBurn
+            | BuildingMode::RainCloud
+            | BuildingMode::PlaceCityHall
+            | BuildingMode::PlaceStoneWall
+            | BuildingMode::Demolish
+            | BuildingMode::TerrainPaint(_) => false,
+        }
+    }
+
+    /// The building placed in this mode, if it places one. Stone walls are
+    /// drawn as lines of terrain rather than placed as buildings
+    pub fn placed_building(&self) -> Option<BuildingType> {
+        match self {
+            BuildingMode::PlaceCityHall => Some(BuildingType::CityHall),
+            BuildingMode::PlaceLumberMill => Some(BuildingType::LumberMill),
+            BuildingMode::PlaceManaForge => Some(BuildingType::ManaForge),
+            BuildingMode::PlaceMinotaur => Some(BuildingType::Minotaur),
+            BuildingMode::PlaceStormMage => Some(BuildingType::StormMage),
+            BuildingMode::PlaceWaterGolem => Some(BuildingType::WaterGolem),
+            BuildingMode::PlaceEmberGuard => Some(BuildingType::EmberGuard),
+            BuildingMode::PlaceArborist => Some(BuildingType::Arborist),
+            BuildingMode::PlaceBeaconTower => Some(BuildingType::BeaconTower),
+            BuildingMode::PlaceResearchHall => Some(BuildingType::ResearchHall),
+            BuildingMode::PlaceRelayTower => Some(BuildingType::RelayTower),
+            BuildingMode::PlaceManaBattery => Some(BuildingType::ManaBattery),
+            BuildingMode::PlaceFireFighter => Some(BuildingType::FireFighter),
+            BuildingMode::PlaceSprinkler => Some(BuildingType::Sprinkler),
+            BuildingMode::PlaceTreasury => Some(BuildingType::Treasury),
+            BuildingMode::PlaceRiverGolem => Some(BuildingType::RiverGolem),
+            BuildingMode::PlaceRiverFerry => Some(BuildingType::RiverFerry),
+            BuildingMode::None
+            | BuildingMode::Meteor
+            | BuildingMode::ControlledBurn
+            | BuildingMode::RainCloud
+            | BuildingMode::PlaceStoneWall
+            | BuildingMode::Demolish
+            | BuildingMode::TerrainPaint(_) => None,
+        }
+    }
 }
 
 impl From<BuildingMode> for BuildingType {
@@ -159,11 +420,27 @@ impl From<BuildingMode> for BuildingType {
             BuildingMode::PlaceMinotaur => BuildingType::Minotaur,
             BuildingMode::PlaceWaterGolem => BuildingType::WaterGolem,
             BuildingMode::PlaceStormMage => BuildingType::StormMage,
+            BuildingMode::PlaceEmberGuard => BuildingType::EmberGuard,
+            BuildingMode::PlaceBeaconTower => BuildingType::BeaconTower,
+            BuildingMode::PlaceResearchHall => BuildingType::ResearchHall,
+            BuildingMode::PlaceRelayTower => BuildingType::RelayTower,
+            BuildingMode::PlaceManaBattery => BuildingType::ManaBattery,
+            BuildingMode::PlaceFireFighter => BuildingType::FireFighter,
+            BuildingMode::PlaceRiverGolem => BuildingType::RiverGolem,
             BuildingMode::None
             | BuildingMode::Meteor
+            | BuildingMode::ControlledBurn
+            | BuildingMode::RainCloud
             | BuildingMode::PlaceCityHall
             | BuildingMode::PlaceLumberMill
-            | BuildingMode::PlaceManaForge => unreachable!(),
+            | BuildingMode::PlaceManaForge
+            | BuildingMode::PlaceStoneWall
+            | BuildingMode::PlaceArborist
+            | BuildingMode::PlaceSprinkler
+            | BuildingMode::PlaceTreasury
+            | BuildingMode::PlaceRiverFerry
+            | BuildingMode::Demolish
+            | BuildingMode::TerrainPaint(_) => unreachable!(),
         }
     }
 }
@@ -181,8 +458,11 @@ fn handle_mouse_click_input(
     mut mode: ResMut<BuildingMode>,
     mouse: Res<MousePosition>,
     maybe_mage_rotation: Option<Res<StormMagePlacementRotation>>,
+    maybe_wall_start: Option<Res<WallStart>>,
     maybe_requires_city_hall: Option<Res<RequiresCityHall>>,
+    maybe_tutorial: Option<Res<TutorialState>>,
     maybe_map: Option<Res<GameMap>>,
+    buildings: Query<(Entity, &BuildingLocation), With<BuildingType>>,
 ) {
     if maybe_requires_city_hall.is_some() && !matches!(*mode, BuildingMode::PlaceCityHall) {
         warn!(
@@ -192,14 +472,45 @@ fn handle_mouse_click_input(
         return;
     }
 
+    if let Some(tutorial) = maybe_tutorial {
+        if !tutorial.allows(*mode) {
+            warn!("Cannot handle - {mode:?}. Not available during tutorial step {tutorial:?}");
+            return;
+        }
+    }
+
+    // remember what was under the building before it is placed, so that
+    // placing it can be undone
+    if mode.places_undoable_building() {
+        if let Some(map) = &maybe_map {
+            commands.insert_resource(UndoFootprint::capture(
+                map,
+                map.tile_coords(mouse.world_pos),
+            ));
+        }
+    }
+
     match *mode {
-        BuildingMode::None => {}
+        // terrain is painted while the mouse is held, rather than on click
+        BuildingMode::None | BuildingMode::TerrainPaint(_) => {}
         BuildingMode::PlaceCityHall => {
             commands.queue(SpawnCityHall(mouse.world_pos));
         }
         BuildingMode::PlaceLumberMill => {
             commands.queue(SpawnLumberMill(mouse.world_pos));
         }
+        BuildingMode::PlaceArborist => {
+            commands.queue(SpawnArborist(mouse.world_pos));
+        }
+        BuildingMode::PlaceSprinkler => {
+            commands.queue(SpawnSprinkler(mouse.world_pos));
+        }
+        BuildingMode::PlaceTreasury => {
+            commands.queue(SpawnTreasury(mouse.world_pos));
+        }
+        BuildingMode::PlaceRiverFerry => {
+            commands.queue(SpawnRiverFerry(mouse.world_pos));
+        }
         BuildingMode::Meteor => {
             if let Some(map) = maybe_map {
                 let coords = map.tile_coords(mouse.world_pos);
@@ -208,6 +519,22 @@ fn handle_mouse_click_input(
                 warn!("Skipping meteor strike input as there is no map yet");
             }
         }
+        BuildingMode::ControlledBurn => {
+            if let Some(map) = maybe_map {
+                let coords = map.tile_coords(mouse.world_pos);
+                commands.trigger(OnControlledBurn(coords));
+            } else {
+                warn!("Skipping controlled burn input as there is no map yet");
+            }
+        }
+        BuildingMode::RainCloud => {
+            if let Some(map) = maybe_map {
+                let coords = map.tile_coords(mouse.world_pos);
+                commands.trigger(OnRainCloud(coords, RAIN_CLOUD_RADIUS));
+            } else {
+                warn!("Skipping rain cloud input as there is no map yet");
+            }
+        }
         BuildingMode::PlaceManaForge => {
             commands.queue(SpawnManaForge(mouse.world_pos));
         }
@@ -223,6 +550,50 @@ fn handle_mouse_click_input(
         BuildingMode::PlaceWaterGolem => {
             commands.queue(SpawnWaterGolem(mouse.world_pos));
         }
+        BuildingMode::PlaceRiverGolem => {
+            commands.queue(SpawnRiverGolem(mouse.world_pos));
+        }
+        BuildingMode::PlaceEmberGuard => {
+            commands.queue(SpawnEmberGuard(mouse.world_pos));
+        }
+        BuildingMode::PlaceBeaconTower => {
+            commands.queue(SpawnBeaconTower(mouse.world_pos));
+        }
+        BuildingMode::PlaceResearchHall => {
+            commands.queue(SpawnResearchHall(mouse.world_pos));
+        }
+        BuildingMode::PlaceRelayTower => {
+            commands.queue(SpawnRelayTower(mouse.world_pos));
+        }
+        BuildingMode::PlaceManaBattery => {
+            commands.queue(SpawnManaBattery(mouse.world_pos));
+        }
+        BuildingMode::PlaceFireFighter => {
+            commands.queue(SpawnFireFighter(mouse.world_pos));
+        }
+        BuildingMode::PlaceStoneWall => {
+            // the first click starts the wall, the second click finishes it
+            if let Some(wall_start) = maybe_wall_start {
+                commands.queue(SpawnStoneWall(wall_start.0, mouse.world_pos));
+            } else {
+                commands.insert_resource(WallStart(mouse.world_pos));
+            }
+        }
+        BuildingMode::Demolish => {
+            let Some(map) = maybe_map else {
+                warn!("Skipping demolish input as there is no map yet");
+                return;
+            };
+
+            let coords = map.tile_coords(mouse.world_pos);
+            if let Some((entity, _)) = buildings.iter().find(|(_, loc)| {
+                BUILDING_FOOTPRINT_OFFSETS
+                    .iter()
+                    .any(|offset| loc.0 + *offset == coords)
+            }) {
+                commands.trigger(DemolishBuilding(entity));
+            }
+        }
     }
 }
 
@@ -293,6 +664,7 @@ fn cancel_cursor_mode(
 ) {
     info!("Resetting cursor mode");
     *mode = BuildingMode::None;
+    commands.remove_resource::<WallStart>();
 
     for parent in forge_placements {
         commands.entity(parent).despawn();
@@ -333,7 +705,12 @@ fn handle_build_mode_changing(
             hint.clear();
             commands.remove_resource::<StormMagePlacementRotation>();
         }
-        BuildingMode::Meteor => {}
+        BuildingMode::Meteor
+        | BuildingMode::ControlledBurn
+        | BuildingMode::RainCloud
+        | BuildingMode::PlaceStoneWall
+        | BuildingMode::Demolish
+        | BuildingMode::TerrainPaint(_) => {}
         BuildingMode::PlaceCityHall => {
             commands.spawn((
                 CursorModeFollower,
@@ -355,6 +732,46 @@ fn handle_build_mode_changing(
                 },
             ));
         }
+        BuildingMode::PlaceArborist => {
+            commands.spawn((
+                CursorModeFollower,
+                CursorModeItem,
+                Sprite {
+                    image: building_assets.arborist.clone(),
+                    ..default()
+                },
+            ));
+        }
+        BuildingMode::PlaceSprinkler => {
+            commands.spawn((
+                CursorModeFollower,
+                CursorModeItem,
+                Sprite {
+                    image: building_assets.sprinkler.clone(),
+                    ..default()
+                },
+            ));
+        }
+        BuildingMode::PlaceTreasury => {
+            commands.spawn((
+                CursorModeFollower,
+                CursorModeItem,
+                Sprite {
+                    image: building_assets.treasury.clone(),
+                    ..default()
+                },
+            ));
+        }
+        BuildingMode::PlaceRiverFerry => {
+            commands.spawn((
+                CursorModeFollower,
+                CursorModeItem,
+                Sprite {
+                    image: building_assets.river_ferry.clone(),
+                    ..default()
+                },
+            ));
+        }
 
         BuildingMode::PlaceManaForge => {
             info!("Spawning building mode items for mana forge placement");
@@ -398,7 +815,15 @@ fn handle_build_mode_changing(
                     },
                 ));
         }
-        next_mode @ BuildingMode::PlaceMinotaur | next_mode @ BuildingMode::PlaceWaterGolem => {
+        next_mode @ BuildingMode::PlaceMinotaur
+        | next_mode @ BuildingMode::PlaceWaterGolem
+        | next_mode @ BuildingMode::PlaceEmberGuard
+        | next_mode @ BuildingMode::PlaceBeaconTower
+        | next_mode @ BuildingMode::PlaceResearchHall
+        | next_mode @ BuildingMode::PlaceRelayTower
+        | next_mode @ BuildingMode::PlaceManaBattery
+        | next_mode @ BuildingMode::PlaceFireFighter
+        | next_mode @ BuildingMode::PlaceRiverGolem => {
             info!("Spawning building mode items for {mode:?} placement");
             commands.spawn((
                 TrackParentBuildingWhilePlacing::new(next_mode.into()),
@@ -410,12 +835,28 @@ fn handle_build_mode_changing(
                     image: match next_mode {
                         BuildingMode::PlaceMinotaur => building_assets.minotaur.clone(),
                         BuildingMode::PlaceWaterGolem => building_assets.water_golem.clone(),
+                        BuildingMode::PlaceEmberGuard => building_assets.ember_guard.clone(),
+                        BuildingMode::PlaceBeaconTower => building_assets.beacon_tower.clone(),
+                        BuildingMode::PlaceResearchHall => building_assets.research_hall.clone(),
+                        BuildingMode::PlaceRelayTower => building_assets.relay_tower.clone(),
+                        BuildingMode::PlaceManaBattery => building_assets.mana_battery.clone(),
+                        BuildingMode::PlaceFireFighter => building_assets.fire_fighter.clone(),
+                        BuildingMode::PlaceRiverGolem => building_assets.river_golem.clone(),
                         BuildingMode::None
                         | BuildingMode::Meteor
+                        | BuildingMode::ControlledBurn
+                        | BuildingMode::RainCloud
                         | BuildingMode::PlaceCityHall
                         | BuildingMode::PlaceLumberMill
                         | BuildingMode::PlaceStormMage
-                        | BuildingMode::PlaceManaForge => {
+                        | BuildingMode::PlaceManaForge
+                        | BuildingMode::PlaceStoneWall
+                        | BuildingMode::PlaceArborist
+                        | BuildingMode::PlaceSprinkler
+                        | BuildingMode::PlaceTreasury
+                        | BuildingMode::PlaceRiverFerry
+                        | BuildingMode::Demolish
+                        | BuildingMode::TerrainPaint(_) => {
                             unreachable!();
                         }
                     },
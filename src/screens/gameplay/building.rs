@@ -11,29 +11,77 @@ use crate::{
     asset_tracking::LoadResource,
     input::MousePosition,
     screens::{
-        PlayerResources, Screen,
+        GameSpeed, PlayerResources, Screen,
         gameplay::{
-            CursorModeFollower, StormMagePlacementRotation, building::mana_forge::ManaForge,
+            CursorModeFollower, StormMagePlacementRotation, building::relay_tower::RelayTower,
         },
     },
-    wildfire::GameMap,
+    wildfire::{GameMap, TerrainType},
 };
 
+mod arborist;
+mod beacon_tower;
 mod city_hall;
+mod demolish;
 mod destroy;
+mod ember_guard;
+mod explosion;
+mod fire_alarm;
+mod fire_fighter;
+mod ley_line;
 mod lumber_mill;
+mod mana_battery;
 mod mana_forge;
 mod mana_line;
 mod minotaur;
+mod placement_preview;
+mod relay_tower;
+mod research_hall;
+#[cfg(not(target_family = "wasm"))]
+mod restore;
+mod river_ferry;
+mod river_golem;
+mod sprinkler;
+mod stone_wall;
 mod storm_mage;
+mod treasury;
+mod undo;
+mod upgrade;
 mod water_golem;
 
+pub use arborist::SpawnArborist;
+pub use beacon_tower::{
+    BEACON_TOWER_MAX_REDIRECTS, BEACON_TOWER_RANGE, BeaconTower, SpawnBeaconTower,
+    WearOutBeaconTower,
+};
 pub use city_hall::{CityHall, RequiresCityHall, SpawnCityHall};
-pub use lumber_mill::SpawnLumberMill;
-pub use mana_forge::SpawnManaForge;
-pub use minotaur::SpawnMinotaur;
-pub use storm_mage::{MageRotation, SpawnStormMage};
-pub use water_golem::SpawnWaterGolem;
+pub use demolish::DemolishBuilding;
+pub use ember_guard::SpawnEmberGuard;
+pub use fire_alarm::FireAlarm;
+pub use fire_fighter::SpawnFireFighter;
+pub use lumber_mill::{LumberMill, SpawnLumberMill};
+pub use mana_battery::{MANA_BATTERY_CAPACITY, ManaBattery, ManaStorage, SpawnManaBattery};
+pub use mana_forge::{ManaForge, SpawnManaForge};
+pub use minotaur::{Minotaur, MinotaurPair, SpawnMinotaur, ToggleMinotaurMode};
+pub use relay_tower::SpawnRelayTower;
+pub use research_hall::{OnBuildingUnlocked, ResearchState, SpawnResearchHall, building_unlocked};
+#[cfg(not(target_family = "wasm"))]
+pub use restore::{RestoreBuildings, SavedBuilding, saved_buildings};
+pub use river_ferry::SpawnRiverFerry;
+pub use river_golem::{RIVER_GOLEM_RANGE, RiverGolem, SpawnRiverGolem};
+pub use sprinkler::SpawnSprinkler;
+pub use stone_wall::{SpawnStoneWall, WallStart};
+pub use storm_mage::{
+    MageRotation, RotateStormMage, SetStormMageIntensity, SpawnStormMage, StormMage,
+};
+pub use treasury::{SpawnTreasury, TREASURY_CAPACITY};
+pub use undo::UndoFootprint;
+pub use upgrade::{
+    BuildingTier, MAX_BUILDING_TIER, UpgradeBuilding, upgrade_cost_lumber, upgrade_description,
+};
+pub use water_golem::{
+    SpawnWaterGolem, WATER_GOLEM_MANA_CONSUMPTION, WATER_GOLEM_PRODUCTION_TIME, WaterGolem,
+};
 
 pub const BUILDING_FOOTPRINT_OFFSETS: [IVec2; 4] = [
     IVec2::ZERO,
@@ -42,17 +90,36 @@ pub const BUILDING_FOOTPRINT_OFFSETS: [IVec2; 4] = [
     IVec2::new(0, 1),
 ];
 
+pub const ARBORIST_COST_LUMBER: i32 = 20;
+pub const BEACON_TOWER_COST_MANA: i32 = 25;
+pub const EMBER_GUARD_COST_MANA: i32 = 25;
+pub const FIRE_FIGHTER_COST_MANA: i32 = 20;
 pub const LUMBER_MILL_COST_LUMBER: i32 = 30;
+pub const MANA_BATTERY_COST_LUMBER: i32 = 60;
 pub const MANA_FORGE_COST_LUMBER: i32 = 40;
 pub const MINOTAUR_COST_MANA: i32 = 30;
+pub const RELAY_TOWER_COST_LUMBER: i32 = 15;
+pub const RESEARCH_HALL_COST_LUMBER: i32 = 50;
+pub const RIVER_FERRY_COST_LUMBER: i32 = 20;
+pub const RIVER_GOLEM_COST_MANA: i32 = 25;
+pub const SPRINKLER_COST_MANA: i32 = 10;
+/// The lumber cost for each cell of a stone wall
+pub const STONE_WALL_COST_LUMBER: i32 = 5;
 pub const STORM_MAGE_COST_MANA: i32 = 50;
+/// The mana cost to turn a placed storm mage to face a new direction
+pub const STORM_MAGE_ROTATE_COST_MANA: i32 = 5;
+pub const TREASURY_COST_LUMBER: i32 = 30;
 pub const WATER_GOLEM_COST_MANA: i32 = 20;
 
+/// The buildings that can supply mana to buildings placed nearby
+pub type ManaSource = Or<(With<ManaForge>, With<RelayTower>)>;
+
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<BuildingAssets>();
     app.register_type::<ResourceAssets>();
     app.register_type::<BuildingType>();
     app.register_type::<BuildingLocation>();
+    app.register_type::<BuildingAge>();
     app.register_type::<ManaLine>();
     app.register_type::<ManaLineBalls>();
     app.register_type::<ManaEntityLink>();
@@ -62,15 +129,39 @@ pub(super) fn plugin(app: &mut App) {
     app.load_resource::<ResourceAssets>();
 
     app.add_plugins((
+        arborist::plugin,
+        beacon_tower::plugin,
         city_hall::plugin,
+        demolish::plugin,
         destroy::plugin,
+        ember_guard::plugin,
+        explosion::plugin,
+        fire_alarm::plugin,
+        fire_fighter::plugin,
+        ley_line::plugin,
         lumber_mill::plugin,
+        mana_battery::plugin,
         mana_forge::plugin,
+    ));
+    app.add_plugins((
         mana_line::plugin,
         minotaur::plugin,
+        placement_preview::plugin,
+        relay_tower::plugin,
+        research_hall::plugin,
+        river_ferry::plugin,
+        river_golem::plugin,
+        sprinkler::plugin,
+        stone_wall::plugin,
         storm_mage::plugin,
+        treasury::plugin,
+        undo::plugin,
+        upgrade::plugin,
         water_golem::plugin,
     ));
+    // buildings are only restored when loading a saved game
+    #[cfg(not(target_family = "wasm"))]
+    app.add_plugins(restore::plugin);
 
     app.add_systems(
         Update,
@@ -80,6 +171,8 @@ pub(super) fn plugin(app: &mut App) {
                 input_just_pressed(KeyCode::KeyR)
                     .and(resource_exists::<StormMagePlacementRotation>),
             ),
+            reveal_fog_around_buildings.run_if(resource_exists::<GameMap>),
+            (add_building_age, age_buildings).chain(),
         )
             .run_if(
                 in_state(Pause(false))
@@ -89,7 +182,7 @@ pub(super) fn plugin(app: &mut App) {
     );
 }
 
-#[derive(Component, Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Component, Reflect, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[reflect(Component)]
 pub enum BuildingType {
     CityHall,
@@ -98,6 +191,68 @@ pub enum BuildingType {
     LumberMill,
     StormMage,
     WaterGolem,
+    EmberGuard,
+    Arborist,
+    BeaconTower,
+    ResearchHall,
+    RelayTower,
+    ManaBattery,
+    FireFighter,
+    Sprinkler,
+    Treasury,
+    RiverGolem,
+    RiverFerry,
+}
+
+impl std::fmt::Display for BuildingType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                BuildingType::CityHall => "City Hall",
+                BuildingType::ManaForge => "Mana Forge",
+                BuildingType::Minotaur => "Minotaur Hutch",
+                BuildingType::LumberMill => "Lumber Mill",
+                BuildingType::StormMage => "Storm Mage",
+                BuildingType::WaterGolem => "Water Golem",
+                BuildingType::EmberGuard => "Ember Guard",
+                BuildingType::Arborist => "Arborist",
+                BuildingType::BeaconTower => "Beacon Tower",
+                BuildingType::ResearchHall => "Research Hall",
+                BuildingType::RelayTower => "Relay Tower",
+                BuildingType::ManaBattery => "Mana Battery",
+                BuildingType::FireFighter => "Fire Fighter Hutch",
+                BuildingType::Sprinkler => "Sprinkler",
+                BuildingType::Treasury => "Treasury",
+                BuildingType::RiverGolem => "River Golem",
+                BuildingType::RiverFerry => "River Ferry",
+            }
+        )
+    }
+}
+
+impl BuildingType {
+    /// The radius (in cells) of fog of war that this building reveals
+    pub fn reveal_radius(&self) -> i32 {
+        match self {
+            BuildingType::CityHall => 20,
+            BuildingType::ManaForge => 15,
+            BuildingType::StormMage | BuildingType::EmberGuard | BuildingType::BeaconTower => 12,
+            BuildingType::LumberMill
+            | BuildingType::Arborist
+            | BuildingType::ResearchHall
+            | BuildingType::ManaBattery
+            | BuildingType::Treasury => 10,
+            BuildingType::Minotaur
+            | BuildingType::WaterGolem
+            | BuildingType::RelayTower
+            | BuildingType::FireFighter
+            | BuildingType::Sprinkler
+            | BuildingType::RiverGolem
+            | BuildingType::RiverFerry => 8,
+        }
+    }
 }
 
 #[derive(Resource, Asset, Clone, Reflect)]
@@ -131,6 +286,8 @@ pub struct BuildingAssets {
     #[dependency]
     pub meteor: Handle<Image>,
     #[dependency]
+    pub controlled_burn: Handle<Image>,
+    #[dependency]
     pub lumber_mill: Handle<Image>,
     #[dependency]
     pub mana_forge: Handle<Image>,
@@ -143,6 +300,36 @@ pub struct BuildingAssets {
     #[dependency]
     pub water_golem: Handle<Image>,
     #[dependency]
+    pub ember_guard: Handle<Image>,
+    #[dependency]
+    pub arborist: Handle<Image>,
+    #[dependency]
+    pub beacon_tower: Handle<Image>,
+    #[dependency]
+    pub research_hall: Handle<Image>,
+    #[dependency]
+    pub relay_tower: Handle<Image>,
+    #[dependency]
+    pub mana_battery: Handle<Image>,
+    #[dependency]
+    pub fire_fighter: Handle<Image>,
+    #[dependency]
+    pub sprinkler: Handle<Image>,
+    #[dependency]
+    pub treasury: Handle<Image>,
+    #[dependency]
+    pub river_golem: Handle<Image>,
+    #[dependency]
+    pub river_ferry: Handle<Image>,
+    #[dependency]
+    pub goblin: Handle<Image>,
+    #[dependency]
+    pub rain_cloud: Handle<Image>,
+    #[dependency]
+    pub stone_wall: Handle<Image>,
+    #[dependency]
+    pub demolish: Handle<Image>,
+    #[dependency]
     pub building_lost: Handle<AudioSource>,
 }
 
@@ -165,6 +352,13 @@ impl FromWorld for BuildingAssets {
                     settings.sampler = ImageSampler::nearest();
                 },
             ),
+            controlled_burn: assets.load_with_settings(
+                "images/fireball.png",
+                |settings: &mut ImageLoaderSettings| {
+                    // Use `nearest` image sampling to preserve pixel art style.
+                    settings.sampler = ImageSampler::nearest();
+                },
+            ),
             lumber_mill: assets.load_with_settings(
                 "images/lumbermill.png",
                 |settings: &mut ImageLoaderSettings| {
@@ -207,6 +401,111 @@ impl FromWorld for BuildingAssets {
                     settings.sampler = ImageSampler::nearest();
                 },
             ),
+            river_golem: assets.load_with_settings(
+                "images/river_golem.png",
+                |settings: &mut ImageLoaderSettings| {
+                    // Use `nearest` image sampling to preserve pixel art style.
+                    settings.sampler = ImageSampler::nearest();
+                },
+            ),
+            ember_guard: assets.load_with_settings(
+                "images/ember_guard.png",
+                |settings: &mut ImageLoaderSettings| {
+                    // Use `nearest` image sampling to preserve pixel art style.
+                    settings.sampler = ImageSampler::nearest();
+                },
+            ),
+            arborist: assets.load_with_settings(
+                "images/arborist.png",
+                |settings: &mut ImageLoaderSettings| {
+                    // Use `nearest` image sampling to preserve pixel art style.
+                    settings.sampler = ImageSampler::nearest();
+                },
+            ),
+            beacon_tower: assets.load_with_settings(
+                "images/beacon_tower.png",
+                |settings: &mut ImageLoaderSettings| {
+                    // Use `nearest` image sampling to preserve pixel art style.
+                    settings.sampler = ImageSampler::nearest();
+                },
+            ),
+            research_hall: assets.load_with_settings(
+                "images/research_hall.png",
+                |settings: &mut ImageLoaderSettings| {
+                    // Use `nearest` image sampling to preserve pixel art style.
+                    settings.sampler = ImageSampler::nearest();
+                },
+            ),
+            mana_battery: assets.load_with_settings(
+                "images/mana_battery.png",
+                |settings: &mut ImageLoaderSettings| {
+                    // Use `nearest` image sampling to preserve pixel art style.
+                    settings.sampler = ImageSampler::nearest();
+                },
+            ),
+            fire_fighter: assets.load_with_settings(
+                "images/fire_fighter.png",
+                |settings: &mut ImageLoaderSettings| {
+                    // Use `nearest` image sampling to preserve pixel art style.
+                    settings.sampler = ImageSampler::nearest();
+                },
+            ),
+            sprinkler: assets.load_with_settings(
+                "images/sprinkler.png",
+                |settings: &mut ImageLoaderSettings| {
+                    // Use `nearest` image sampling to preserve pixel art style.
+                    settings.sampler = ImageSampler::nearest();
+                },
+            ),
+            treasury: assets.load_with_settings(
+                "images/treasury.png",
+                |settings: &mut ImageLoaderSettings| {
+                    // Use `nearest` image sampling to preserve pixel art style.
+                    settings.sampler = ImageSampler::nearest();
+                },
+            ),
+            river_ferry: assets.load_with_settings(
+                "images/river_ferry.png",
+                |settings: &mut ImageLoaderSettings| {
+                    // Use `nearest` image sampling to preserve pixel art style.
+                    settings.sampler = ImageSampler::nearest();
+                },
+            ),
+            relay_tower: assets.load_with_settings(
+                "images/relay_tower.png",
+                |settings: &mut ImageLoaderSettings| {
+                    // Use `nearest` image sampling to preserve pixel art style.
+                    settings.sampler = ImageSampler::nearest();
+                },
+            ),
+            goblin: assets.load_with_settings(
+                "images/goblin.png",
+                |settings: &mut ImageLoaderSettings| {
+                    // Use `nearest` image sampling to preserve pixel art style.
+                    settings.sampler = ImageSampler::nearest();
+                },
+            ),
+            rain_cloud: assets.load_with_settings(
+                "images/rain_cloud.png",
+                |settings: &mut ImageLoaderSettings| {
+                    // Use `nearest` image sampling to preserve pixel art style.
+                    settings.sampler = ImageSampler::nearest();
+                },
+            ),
+            stone_wall: assets.load_with_settings(
+                "images/stone_wall.png",
+                |settings: &mut ImageLoaderSettings| {
+                    // Use `nearest` image sampling to preserve pixel art style.
+                    settings.sampler = ImageSampler::nearest();
+                },
+            ),
+            demolish: assets.load_with_settings(
+                "images/demolish.png",
+                |settings: &mut ImageLoaderSettings| {
+                    // Use `nearest` image sampling to preserve pixel art style.
+                    settings.sampler = ImageSampler::nearest();
+                },
+            ),
             building_lost: assets.load("audio/sound_effects/building_lost.ogg"),
         }
     }
@@ -216,6 +515,131 @@ impl FromWorld for BuildingAssets {
 #[reflect(Component)]
 pub struct BuildingLocation(pub IVec2);
 
+/// Why a building can't be placed at a location
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlacementError {
+    /// Part of the footprint is already covered by another building
+    Overlapping,
+    /// Part of the footprint is on water that isn't beside a river ferry
+    OnWater,
+}
+
+impl std::fmt::Display for PlacementError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                PlacementError::Overlapping => "Cannot place here: building already present",
+                PlacementError::OnWater => "Cannot place here: build a river ferry to cross water",
+            }
+        )
+    }
+}
+
+/// Checks that the footprint of a building placed at `coords` doesn't cover
+/// any existing buildings, or water that hasn't been crossed by a river ferry
+pub fn check_placement<'a>(
+    coords: IVec2,
+    map: &GameMap,
+    existing: impl IntoIterator<Item = &'a BuildingLocation>,
+) -> Result<(), PlacementError> {
+    check_overlap(coords, map, existing)?;
+
+    let on_water = BUILDING_FOOTPRINT_OFFSETS.iter().any(|offset| {
+        let loc = coords + *offset;
+        map.get(loc)
+            .is_some_and(|cell| cell.terrain == TerrainType::Water)
+            && !map.is_crossable(loc)
+    });
+
+    if on_water {
+        Err(PlacementError::OnWater)
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks that the footprint of a building placed at `coords` doesn't cover
+/// any existing buildings
+fn check_overlap<'a>(
+    coords: IVec2,
+    map: &GameMap,
+    existing: impl IntoIterator<Item = &'a BuildingLocation>,
+) -> Result<(), PlacementError> {
+    let footprint = BUILDING_FOOTPRINT_OFFSETS.map(|offset| coords + offset);
+
+    // lumber mills don't mark their footprint on the map, so the building
+    // locations are checked as well as the terrain
+    let covers_building = footprint.iter().any(|loc| {
+        map.get(*loc)
+            .is_some_and(|cell| cell.terrain == TerrainType::Building)
+    });
+    let overlaps_location = existing.into_iter().any(|other| {
+        BUILDING_FOOTPRINT_OFFSETS
+            .iter()
+            .any(|offset| footprint.contains(&(other.0 + *offset)))
+    });
+
+    if covers_building || overlaps_location {
+        Err(PlacementError::Overlapping)
+    } else {
+        Ok(())
+    }
+}
+
+/// Whether a building of the given type could be placed at `coords` right
+/// now, using the same checks as the spawn commands. Buildings powered by
+/// mana also need a parent in range, which is tracked while placing
+pub fn is_placement_valid<'a>(
+    building_type: BuildingType,
+    coords: IVec2,
+    map: &GameMap,
+    resources: &PlayerResources,
+    existing: impl IntoIterator<Item = &'a BuildingLocation>,
+    parent: Option<&TrackParentBuildingWhilePlacing>,
+) -> bool {
+    if !map.is_valid_coords(coords) {
+        return false;
+    }
+
+    let (cost_lumber, cost_mana) = demolish::build_cost(building_type);
+    if resources.lumber < cost_lumber || resources.mana < cost_mana {
+        return false;
+    }
+
+    if parent.is_some_and(|parent| parent.entity.is_none()) {
+        return false;
+    }
+
+    if building_type == BuildingType::CityHall
+        && !map
+            .get(coords)
+            .is_some_and(|cell| city_hall::city_hall_terrain_valid(cell.terrain))
+    {
+        return false;
+    }
+
+    if building_type == BuildingType::RiverGolem
+        && !river_golem::river_golem_location_valid(coords, map)
+    {
+        return false;
+    }
+
+    // ferries are the only buildings that go on open water
+    if building_type == BuildingType::RiverFerry {
+        return river_ferry::river_ferry_location_valid(coords, map)
+            && check_overlap(coords, map, existing).is_ok();
+    }
+
+    check_placement(coords, map, existing).is_ok()
+}
+
+/// How long a building has been standing, in game seconds
+#[derive(Component, Reflect, Debug, Default, Copy, Clone)]
+#[reflect(Component)]
+pub struct BuildingAge(pub f32);
+
 #[derive(Component, Reflect, Debug, Copy, Clone)]
 #[reflect(Component)]
 pub struct TrackParentBuildingWhilePlacing {
@@ -268,6 +692,45 @@ pub struct ManaLineBalls {
     pub mana_dot_distance: f32,
 }
 
+/// Clears the fog of war around newly placed buildings
+fn reveal_fog_around_buildings(
+    mut map: ResMut<GameMap>,
+    buildings: Query<(&BuildingLocation, &BuildingType), Added<BuildingType>>,
+) {
+    for (loc, building_type) in &buildings {
+        let cells = map
+            .cells_within_range(loc.0, building_type.reveal_radius())
+            .collect::<Vec<_>>();
+
+        for coord in cells {
+            if let Some(cell) = map.get_mut(coord) {
+                cell.reveal();
+            }
+        }
+    }
+}
+
+fn add_building_age(
+    mut commands: Commands,
+    buildings: Query<Entity, (With<BuildingType>, Without<BuildingAge>)>,
+) {
+    for entity in &buildings {
+        commands.entity(entity).insert(BuildingAge::default());
+    }
+}
+
+fn age_buildings(
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut buildings: Query<&mut BuildingAge>,
+) {
+    let delta = time.delta_secs() * game_speed.0;
+
+    for mut age in &mut buildings {
+        age.0 += delta;
+    }
+}
+
 fn rotate_storm_mage(
     mut mage_rotation: ResMut<StormMagePlacementRotation>,
     mut follower: Query<&mut Transform, With<CursorModeFollower>>,
@@ -285,6 +748,7 @@ fn track_building_parent_while_placing(
     map: Res<GameMap>,
     mut parent_building: Single<(&mut TrackParentBuildingWhilePlacing, &mut ManaLine)>,
     forges: Query<(Entity, &Transform), With<ManaForge>>,
+    relays: Query<(Entity, &Transform, &RelayTower)>,
     hall: Single<(Entity, &Transform), With<CityHall>>,
 ) {
     const MAX_DISTANCE_SQR: f32 = 60.0 * 60.0;
@@ -313,6 +777,19 @@ fn track_building_parent_while_placing(
         if distance_to_hall < MAX_DISTANCE_SQR * map.sprite_size {
             distances.push((hall.0, distance_to_hall, pos));
         }
+    } else {
+        // otherwise relays can pass on mana from a forge. Relays can only be
+        // placed when linked to a forge or another relay, so every relay is
+        // already part of a chain back to a forge.
+        distances.extend(relays.iter().filter_map(|(e, tx, relay)| {
+            let pos = tx.translation.truncate();
+            let distance_to_relay = mouse_pos.distance_squared(pos);
+            if distance_to_relay > relay.reach * relay.reach * map.sprite_size {
+                return None;
+            }
+
+            Some((e, distance_to_relay, pos))
+        }));
     }
 
     // find the closest parent
@@ -0,0 +1,186 @@
+//! Logic + code for placing arborist buildings, which replant degraded land
+
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{
+    Pause,
+    screens::{
+        GameSpeed, PlayerResources, Screen,
+        gameplay::{
+            ARBORIST_COST_LUMBER, BuildTextHint, BuildingMode,
+            building::{
+                BUILDING_FOOTPRINT_OFFSETS, BuildingAssets, BuildingLocation, BuildingType,
+                ManaEntityLink, check_placement,
+            },
+        },
+    },
+    wildfire::{GameMap, TerrainType},
+};
+
+/// The time between each replanting
+const ARBORIST_TICK_TIME: f32 = 2.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Arborist>();
+
+    app.add_systems(
+        Update,
+        produce_from_arborist.run_if(
+            in_state(Pause(false))
+                .and(in_state(Screen::Gameplay))
+                .and(resource_exists::<GameMap>),
+        ),
+    );
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpawnArborist(pub Vec2);
+
+impl Command for SpawnArborist {
+    fn apply(self, world: &mut World) {
+        let _ = world.run_system_cached_with(spawn_arborist, self);
+    }
+}
+
+fn spawn_arborist(
+    In(config): In<SpawnArborist>,
+    mut commands: Commands,
+    mut resources: ResMut<PlayerResources>,
+    mut building_mode: ResMut<BuildingMode>,
+    buildings: Res<BuildingAssets>,
+    mut map: ResMut<GameMap>,
+    existing: Query<&BuildingLocation>,
+    mut hint: ResMut<BuildTextHint>,
+) {
+    if resources.lumber < ARBORIST_COST_LUMBER {
+        warn!("Not enough resources to spawn arborist");
+        return;
+    }
+
+    let coords = map.tile_coords(config.0);
+    if !map.is_valid_coords(coords) {
+        warn!("Invalid map coordinates, aborting arborist placement");
+        return;
+    }
+
+    if let Err(e) = check_placement(coords, &map, &existing) {
+        warn!("{e}, aborting arborist placement");
+        hint.set(e.to_string());
+        return;
+    }
+
+    resources.lumber -= ARBORIST_COST_LUMBER;
+
+    let world_coords = map.world_coords(coords);
+    info!("Spawning arborist at {coords}");
+
+    let mut cmds = commands.spawn((
+        BuildingLocation(coords),
+        BuildingType::Arborist,
+        Arborist::default(),
+        StateScoped(Screen::Gameplay),
+        Transform::from_translation(world_coords.extend(0.1)),
+        Visibility::Visible,
+        Sprite {
+            image: buildings.arborist.clone(),
+            custom_size: Some(Vec2::splat(16.0)),
+            anchor: Anchor::Center,
+            ..default()
+        },
+    ));
+    let id = cmds.id();
+    // same hack as lumber mills so arborists are destroyed by the mana link chain
+    cmds.insert(ManaEntityLink {
+        from_entity: id,
+        destruction_time: None,
+    });
+
+    // update the map underneath to turn to buildings
+    BUILDING_FOOTPRINT_OFFSETS.iter().for_each(|offset| {
+        if let Some(cell) = map.get_mut(coords + *offset) {
+            cell.terrain = TerrainType::Building;
+        }
+    });
+
+    *building_mode = BuildingMode::None;
+}
+
+/// A building that replants nearby degraded land, dirt first and then grass
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct Arborist {
+    /// The range of the arborist (i.e. distance from the building location)
+    pub range: i32,
+    /// The time since the arborist last planted something
+    pub time_since_last_tick: f32,
+}
+
+impl Default for Arborist {
+    fn default() -> Self {
+        Self {
+            range: 6,
+            time_since_last_tick: 0.0,
+        }
+    }
+}
+
+impl Arborist {
+    /// Finds the closest cell of the given terrain within range
+    fn find_nearest(&self, map: &GameMap, center: IVec2, terrain: TerrainType) -> Option<IVec2> {
+        map.cells_within_range(center, self.range)
+            .filter(|coord| {
+                // direct access ok here as we only have valid coords
+                map.data[coord.y as usize][coord.x as usize].terrain == terrain
+            })
+            .min_by_key(|coord| coord.distance_squared(center))
+    }
+}
+
+fn produce_from_arborist(
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut map: ResMut<GameMap>,
+    mut arborists: Query<(&BuildingLocation, &mut Arborist)>,
+) {
+    let delta = time.delta_secs() * game_speed.0;
+
+    for (loc, mut arborist) in &mut arborists {
+        arborist.time_since_last_tick += delta;
+        if arborist.time_since_last_tick < ARBORIST_TICK_TIME {
+            continue;
+        }
+        arborist.time_since_last_tick = 0.0;
+
+        // restore the most degraded land first
+        let target = arborist
+            .find_nearest(&map, loc.0, TerrainType::Dirt)
+            .or_else(|| arborist.find_nearest(&map, loc.0, TerrainType::Grassland));
+        let Some(coord) = target else {
+            continue;
+        };
+
+        let Some(cell) = map.get_mut(coord) else {
+            warn!("Unable to find cell chosen for replanting");
+            continue;
+        };
+
+        match cell.terrain {
+            TerrainType::Dirt => {
+                cell.terrain = TerrainType::Grassland;
+                cell.mark_dirty();
+            }
+            TerrainType::Grassland => {
+                cell.terrain = TerrainType::Tree;
+                cell.mark_dirty();
+            }
+            TerrainType::Building
+            | TerrainType::Tree
+            | TerrainType::Stone
+            | TerrainType::Fire
+            | TerrainType::Smoldering
+            | TerrainType::Water => {
+                // nop
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! Logic + code for placing beacon towers, which draw meteor strikes towards them
+
+use bevy::{color::palettes::tailwind::AMBER_300, prelude::*, sprite::Anchor};
+use bevy_vector_shapes::{prelude::ShapePainter, shapes::DiscPainter};
+
+use crate::{
+    Pause,
+    screens::{
+        GameSpeed, PlayerResources, Screen,
+        gameplay::{
+            BEACON_TOWER_COST_MANA, BuildTextHint, BuildingMode,
+            building::{
+                BUILDING_FOOTPRINT_OFFSETS, BuildingAssets, BuildingLocation, BuildingType,
+                ManaEntityLink, ManaLine, ManaLineBalls, ManaSource,
+                TrackParentBuildingWhilePlacing, check_placement, demolish::Demolished,
+                explosion::DestroyWithAnimation,
+            },
+        },
+    },
+    wildfire::{GameMap, TerrainType},
+};
+
+/// The distance (in cells) from a meteor strike that a beacon will redirect it
+pub const BEACON_TOWER_RANGE: i32 = 40;
+
+/// The time (in seconds) a beacon must wait between redirecting meteors
+pub const BEACON_TOWER_COOLDOWN: f32 = 30.0;
+
+/// The number of meteors a beacon can redirect before it is destroyed
+pub const BEACON_TOWER_MAX_REDIRECTS: u32 = 3;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<BeaconTower>();
+
+    app.add_systems(
+        Update,
+        (
+            tick_beacon_cooldowns.run_if(in_state(Pause(false))),
+            draw_beacon_tower_areas,
+        )
+            .run_if(in_state(Screen::Gameplay).and(resource_exists::<GameMap>)),
+    );
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpawnBeaconTower(pub Vec2);
+
+impl Command for SpawnBeaconTower {
+    fn apply(self, world: &mut World) {
+        let _ = world.run_system_cached_with(spawn_beacon_tower, self);
+    }
+}
+
+fn spawn_beacon_tower(
+    In(config): In<SpawnBeaconTower>,
+    mut commands: Commands,
+    mut resources: ResMut<PlayerResources>,
+    mut building_mode: ResMut<BuildingMode>,
+    buildings: Res<BuildingAssets>,
+    mut map: ResMut<GameMap>,
+    parent_forge: Single<(Entity, &TrackParentBuildingWhilePlacing)>,
+    forges: Query<&Transform, ManaSource>,
+    existing: Query<&BuildingLocation>,
+    mut hint: ResMut<BuildTextHint>,
+) {
+    if resources.mana < BEACON_TOWER_COST_MANA {
+        warn!("Not enough resources to spawn beacon tower");
+        return;
+    }
+
+    let (parent_tracking_entity, parent_forge) = *parent_forge;
+    let Some(parent_forge) = parent_forge.entity else {
+        warn!("No parent mana forge inside tracking, skipping beacon tower placement");
+        return;
+    };
+
+    let coords = map.tile_coords(config.0);
+    if !map.is_valid_coords(coords) {
+        warn!("Invalid map coordinates, aborting beacon tower placement");
+        return;
+    }
+
+    if let Err(e) = check_placement(coords, &map, &existing) {
+        warn!("{e}, aborting beacon tower placement");
+        hint.set(e.to_string());
+        return;
+    }
+
+    let Ok(parent_tx) = forges.get(parent_forge) else {
+        warn!("Unable to find parent mana forge");
+        return;
+    };
+
+    commands.entity(parent_tracking_entity).despawn();
+    resources.mana -= BEACON_TOWER_COST_MANA;
+
+    let world_coords = map.world_coords(coords);
+    info!("Spawning beacon tower at {coords}");
+
+    commands.spawn((
+        BuildingLocation(coords),
+        BuildingType::BeaconTower,
+        BeaconTower::default(),
+        ManaLine::new(
+            parent_tx.translation.truncate().extend(0.05),
+            config.0.extend(0.05),
+        ),
+        ManaLineBalls::default(),
+        ManaEntityLink {
+            from_entity: parent_forge,
+            destruction_time: None,
+        },
+        StateScoped(Screen::Gameplay),
+        Transform::from_xyz(world_coords.x, world_coords.y, 0.1),
+        Visibility::Visible,
+        Sprite {
+            image: buildings.beacon_tower.clone(),
+            custom_size: Some(Vec2::splat(16.0)),
+            anchor: Anchor::Center,
+            ..default()
+        },
+    ));
+
+    // update the map underneath to turn to buildings
+    BUILDING_FOOTPRINT_OFFSETS.iter().for_each(|offset| {
+        if let Some(cell) = map.get_mut(coords + *offset) {
+            cell.terrain = TerrainType::Building;
+        }
+    });
+
+    *building_mode = BuildingMode::None;
+}
+
+/// A building that attracts nearby meteor strikes, drawing them away from
+/// more valuable parts of the map
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct BeaconTower {
+    /// The time (in seconds) since the beacon last redirected a meteor
+    pub last_redirect: f32,
+    /// The number of meteors the beacon has redirected
+    pub redirects: u32,
+}
+
+impl Default for BeaconTower {
+    fn default() -> Self {
+        // start off ready to redirect
+        Self {
+            last_redirect: BEACON_TOWER_COOLDOWN,
+            redirects: 0,
+        }
+    }
+}
+
+impl BeaconTower {
+    /// Returns true if the beacon is able to redirect a meteor
+    pub fn is_ready(&self) -> bool {
+        self.last_redirect >= BEACON_TOWER_COOLDOWN
+    }
+}
+
+/// Removes a beacon tower that has redirected as many meteors as it can. It
+/// collapses like a destroyed building, but isn't counted as lost to fire
+#[derive(Debug, Clone, Copy)]
+pub struct WearOutBeaconTower(pub Entity);
+
+impl Command for WearOutBeaconTower {
+    fn apply(self, world: &mut World) {
+        let _ = world.run_system_cached_with(wear_out_beacon_tower, self);
+    }
+}
+
+fn wear_out_beacon_tower(
+    In(config): In<WearOutBeaconTower>,
+    mut commands: Commands,
+    mut map: ResMut<GameMap>,
+    beacons: Query<&BuildingLocation, With<BeaconTower>>,
+) {
+    let Ok(loc) = beacons.get(config.0) else {
+        warn!("Unable to find beacon tower to wear out");
+        return;
+    };
+
+    info!("Beacon tower at {} has worn out", loc.0);
+
+    // leave the ground free to build on again
+    BUILDING_FOOTPRINT_OFFSETS.iter().for_each(|offset| {
+        if let Some(cell) = map.get_mut(loc.0 + *offset) {
+            cell.terrain = TerrainType::Dirt;
+            cell.mark_dirty();
+        }
+    });
+
+    // stop it redirecting any more meteors while it collapses
+    commands
+        .entity(config.0)
+        .remove::<BeaconTower>()
+        .insert(Demolished);
+    commands.queue(DestroyWithAnimation(config.0));
+}
+
+fn tick_beacon_cooldowns(
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut beacons: Query<&mut BeaconTower>,
+) {
+    let delta = time.delta_secs() * game_speed.0;
+
+    for mut beacon in &mut beacons {
+        if !beacon.is_ready() {
+            beacon.last_redirect += delta;
+        }
+    }
+}
+
+fn draw_beacon_tower_areas(
+    mut painter: ShapePainter,
+    map: Res<GameMap>,
+    beacons: Query<(&Transform, &BeaconTower)>,
+) {
+    let original_tx = painter.transform;
+
+    for (tx, beacon) in &beacons {
+        let mut color = AMBER_300;
+        color.alpha = if beacon.is_ready() { 0.4 } else { 0.1 };
+
+        painter.hollow = true;
+        painter.set_color(color);
+        painter.translate(tx.translation - Vec3::new(0.0, 0.0, 0.05));
+        painter.circle(BEACON_TOWER_RANGE as f32 * map.sprite_size);
+
+        painter.transform = original_tx;
+    }
+}
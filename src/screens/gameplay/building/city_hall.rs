@@ -26,6 +26,19 @@ pub(super) fn plugin(app: &mut App) {
 #[reflect(Resource)]
 pub struct RequiresCityHall;
 
+/// City halls can only be built on grass or forest
+pub fn city_hall_terrain_valid(terrain: TerrainType) -> bool {
+    match terrain {
+        TerrainType::Grassland | TerrainType::Tree => true,
+        TerrainType::Dirt
+        | TerrainType::Stone
+        | TerrainType::Fire
+        | TerrainType::Smoldering
+        | TerrainType::Water
+        | TerrainType::Building => false,
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct SpawnCityHall(pub Vec2);
 
@@ -61,18 +74,9 @@ fn spawn_city_hall(
         return;
     };
 
-    match cell.terrain {
-        TerrainType::Grassland | crate::wildfire::TerrainType::Tree => {
-            // nop
-        }
-        TerrainType::Dirt
-        | TerrainType::Stone
-        | TerrainType::Fire
-        | TerrainType::Smoldering
-        | TerrainType::Building => {
-            warn!("Can't place city hall on invalid terrain. Aborting placement");
-            return;
-        }
+    if !city_hall_terrain_valid(cell.terrain) {
+        warn!("Can't place city hall on invalid terrain. Aborting placement");
+        return;
     }
 
     let clamped_world_coords = map.world_coords(coords);
@@ -0,0 +1,123 @@
+//! Lets the player remove their own buildings for a partial refund
+
+use bevy::prelude::*;
+
+use crate::{
+    screens::{
+        BuildingType, PlayerResources,
+        gameplay::{
+            ARBORIST_COST_LUMBER, BEACON_TOWER_COST_MANA, BuildingMode, EMBER_GUARD_COST_MANA,
+            FIRE_FIGHTER_COST_MANA, LUMBER_MILL_COST_LUMBER, MANA_BATTERY_COST_LUMBER,
+            MANA_FORGE_COST_LUMBER, MINOTAUR_COST_MANA, RELAY_TOWER_COST_LUMBER,
+            RESEARCH_HALL_COST_LUMBER, RIVER_FERRY_COST_LUMBER, RIVER_GOLEM_COST_MANA,
+            SPRINKLER_COST_MANA, STORM_MAGE_COST_MANA, TREASURY_COST_LUMBER, WATER_GOLEM_COST_MANA,
+            building::{
+                BUILDING_FOOTPRINT_OFFSETS, BuildingLocation, ManaEntityLink, ManaLine,
+                destroy::BuildingMarkedForDestruction,
+            },
+        },
+    },
+    wildfire::{GameMap, TerrainType},
+};
+
+/// The fraction of a building's original cost that is returned when it is demolished
+const DEMOLISH_REFUND_FRACTION: f32 = 0.5;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Demolished>();
+
+    app.add_observer(demolish_building);
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DemolishBuilding(pub Entity);
+
+/// Marks a building that was removed by the player or wore out, rather than
+/// lost to fire
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct Demolished;
+
+/// The (lumber, mana) originally spent to place a building
+pub(super) fn build_cost(building_type: BuildingType) -> (i32, i32) {
+    match building_type {
+        BuildingType::CityHall => (0, 0),
+        BuildingType::ManaForge => (MANA_FORGE_COST_LUMBER, 0),
+        BuildingType::LumberMill => (LUMBER_MILL_COST_LUMBER, 0),
+        BuildingType::Arborist => (ARBORIST_COST_LUMBER, 0),
+        BuildingType::ResearchHall => (RESEARCH_HALL_COST_LUMBER, 0),
+        BuildingType::RelayTower => (RELAY_TOWER_COST_LUMBER, 0),
+        BuildingType::ManaBattery => (MANA_BATTERY_COST_LUMBER, 0),
+        BuildingType::Minotaur => (0, MINOTAUR_COST_MANA),
+        BuildingType::StormMage => (0, STORM_MAGE_COST_MANA),
+        BuildingType::WaterGolem => (0, WATER_GOLEM_COST_MANA),
+        BuildingType::EmberGuard => (0, EMBER_GUARD_COST_MANA),
+        BuildingType::BeaconTower => (0, BEACON_TOWER_COST_MANA),
+        BuildingType::FireFighter => (0, FIRE_FIGHTER_COST_MANA),
+        BuildingType::Sprinkler => (0, SPRINKLER_COST_MANA),
+        BuildingType::Treasury => (TREASURY_COST_LUMBER, 0),
+        BuildingType::RiverGolem => (0, RIVER_GOLEM_COST_MANA),
+        BuildingType::RiverFerry => (RIVER_FERRY_COST_LUMBER, 0),
+    }
+}
+
+fn demolish_building(
+    trigger: Trigger<DemolishBuilding>,
+    mut commands: Commands,
+    mut resources: ResMut<PlayerResources>,
+    mut building_mode: ResMut<BuildingMode>,
+    mut map: ResMut<GameMap>,
+    buildings: Query<(&BuildingType, &BuildingLocation)>,
+    mut links: Query<
+        (Entity, &ManaEntityLink, Option<&mut ManaLine>),
+        Without<BuildingMarkedForDestruction>,
+    >,
+) {
+    let target = trigger.event().0;
+    let Ok((building_type, loc)) = buildings.get(target) else {
+        warn!("Unable to find building to demolish");
+        return;
+    };
+
+    if *building_type == BuildingType::CityHall {
+        warn!("The city hall cannot be demolished");
+        return;
+    }
+
+    info!("Demolishing {building_type} at {}", loc.0);
+
+    let (lumber, mana) = build_cost(*building_type);
+    resources.lumber += (lumber as f32 * DEMOLISH_REFUND_FRACTION) as i32;
+    resources.add_mana((mana as f32 * DEMOLISH_REFUND_FRACTION) as i32);
+
+    // ferries float on the water, so leave it behind when they go
+    if *building_type != BuildingType::RiverFerry {
+        BUILDING_FOOTPRINT_OFFSETS.iter().for_each(|offset| {
+            if let Some(cell) = map.get_mut(loc.0 + *offset) {
+                cell.terrain = TerrainType::Dirt;
+                cell.mark_dirty();
+            }
+        });
+    }
+
+    // anything powered through this building loses its mana
+    for (linked_entity, link, maybe_line) in &mut links {
+        if link.from_entity != target || linked_entity == target {
+            continue;
+        }
+
+        info!("Queuing {} for destruction", linked_entity);
+        commands
+            .entity(linked_entity)
+            .insert(BuildingMarkedForDestruction {
+                time_until_boom: 1.0,
+            });
+
+        if let Some(mut line) = maybe_line {
+            line.destroying = true;
+        }
+    }
+
+    commands.entity(target).insert(Demolished).despawn();
+    *building_mode = BuildingMode::None;
+}
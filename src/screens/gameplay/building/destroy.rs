@@ -1,12 +1,9 @@
 //! Code for destroying buildings
 
-use std::time::Duration;
-
 use bevy::{
     ecs::world::OnDespawn,
     platform::collections::{HashMap, HashSet},
     prelude::*,
-    time::common_conditions::on_timer,
 };
 use rand::Rng;
 
@@ -14,13 +11,19 @@ use crate::{
     Pause,
     audio::sound_effect,
     screens::{
-        BuildingType, PlayerResources, Screen,
+        BuildingType, GameSpeed, PlayerResources, Screen, ShakeScreen,
         gameplay::{
-            BuildTextHint,
-            building::{BuildingAssets, BuildingLocation, ManaEntityLink, ManaLine},
+            BuildTextHint, EventLog,
+            building::{
+                BuildingAssets, BuildingLocation, ManaEntityLink, ManaLine,
+                demolish::Demolished,
+                explosion::{Collapsing, DestroyWithAnimation},
+                treasury::TREASURY_CAPACITY,
+            },
+            log_event,
         },
     },
-    wildfire::{Fireball, GameMap, MeteorAssets, TerrainType},
+    wildfire::{FireStats, Fireball, GameMap, MeteorAssets, TerrainType},
 };
 
 pub(super) fn plugin(app: &mut App) {
@@ -28,16 +31,12 @@ pub(super) fn plugin(app: &mut App) {
 
     app.add_systems(
         Update,
-        (
-            burn_buildings.run_if(on_timer(Duration::from_millis(100))),
-            destroy_marked_buildings,
-        )
-            .run_if(
-                in_state(Pause(false))
-                    .and(in_state(Screen::Gameplay))
-                    .and(resource_exists::<PlayerResources>)
-                    .and(resource_exists::<GameMap>),
-            ),
+        (burn_buildings, destroy_marked_buildings).run_if(
+            in_state(Pause(false))
+                .and(in_state(Screen::Gameplay))
+                .and(resource_exists::<PlayerResources>)
+                .and(resource_exists::<GameMap>),
+        ),
     );
 
     app.add_observer(handle_despawned_buildings);
@@ -49,9 +48,15 @@ pub struct BuildingMarkedForDestruction {
     pub time_until_boom: f32,
 }
 
+/// The time between checking whether buildings are on fire at normal game speed
+const BURN_CHECK_INTERVAL: f32 = 0.1;
+
 /// Burns buildings that are consumed by fire
 fn burn_buildings(
     mut commands: Commands,
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut since_last_check: Local<f32>,
     building_assets: Res<BuildingAssets>,
     map: ResMut<GameMap>,
     buildings: Query<
@@ -60,6 +65,12 @@ fn burn_buildings(
     >,
     mut links: Query<(Entity, &ManaEntityLink, Option<&mut ManaLine>)>,
 ) {
+    *since_last_check += time.delta_secs() * game_speed.0;
+    if *since_last_check < BURN_CHECK_INTERVAL {
+        return;
+    }
+    *since_last_check = 0.0;
+
     for (destroyed_entity, loc, building_type) in &buildings {
         // check if there is fire near the building
         if map.check_on_fire(&[
@@ -126,15 +137,40 @@ fn burn_buildings(
 fn destroy_marked_buildings(
     mut commands: Commands,
     time: Res<Time>,
-    mut marked: Query<(Entity, &mut BuildingMarkedForDestruction)>,
+    game_speed: Res<GameSpeed>,
+    mut marked: Query<(Entity, &mut BuildingMarkedForDestruction), Without<Collapsing>>,
+    mut links: Query<
+        (Entity, &ManaEntityLink, Option<&mut ManaLine>),
+        Without<BuildingMarkedForDestruction>,
+    >,
 ) {
     for (marked_entity, mut destruction) in &mut marked {
-        destruction.time_until_boom -= time.delta_secs();
+        destruction.time_until_boom -= time.delta_secs() * game_speed.0;
         if destruction.time_until_boom > 0.0 {
             continue;
         }
 
-        commands.entity(marked_entity).despawn();
+        commands.queue(DestroyWithAnimation(marked_entity));
+
+        // anything still linked to this building (e.g. placed on a relay after
+        // the fire started) loses its mana and goes boom shortly after. Each
+        // of those will do the same when destroyed, so the whole chain goes.
+        for (target_entity, link, maybe_line) in &mut links {
+            if link.from_entity != marked_entity || target_entity == marked_entity {
+                continue;
+            }
+
+            info!("Queuing {} for destruction", target_entity);
+            commands
+                .entity(target_entity)
+                .insert(BuildingMarkedForDestruction {
+                    time_until_boom: 1.0,
+                });
+
+            if let Some(mut line) = maybe_line {
+                line.destroying = true;
+            }
+        }
     }
 }
 
@@ -145,7 +181,10 @@ fn handle_despawned_buildings(
     resources: Option<ResMut<PlayerResources>>,
     map: Option<ResMut<GameMap>>,
     mut hint: ResMut<BuildTextHint>,
-    buildings: Query<(&BuildingType, &BuildingLocation)>,
+    mut stats: ResMut<FireStats>,
+    mut log: ResMut<EventLog>,
+    screen: Res<State<Screen>>,
+    buildings: Query<(&BuildingType, &BuildingLocation, Has<Demolished>)>,
 ) {
     let Some(mut resources) = resources else {
         // probably because we're exiting the game or to menu
@@ -157,11 +196,43 @@ fn handle_despawned_buildings(
     };
 
     let target = trigger.target();
-    let Ok((building_type, loc)) = buildings.get(target) else {
+    let Ok((building_type, loc, demolished)) = buildings.get(target) else {
         warn!("Unable to find building to handle despawn");
         return;
     };
 
+    // buildings are also despawned when leaving the gameplay screen
+    if *screen.get() == Screen::Gameplay && !demolished {
+        // bigger buildings make a bigger bang
+        let shake = match building_type {
+            BuildingType::CityHall => 5.0,
+            BuildingType::ManaForge => 3.0,
+            BuildingType::LumberMill
+            | BuildingType::StormMage
+            | BuildingType::WaterGolem
+            | BuildingType::Minotaur
+            | BuildingType::EmberGuard
+            | BuildingType::Arborist
+            | BuildingType::BeaconTower
+            | BuildingType::ResearchHall
+            | BuildingType::RelayTower
+            | BuildingType::ManaBattery
+            | BuildingType::FireFighter
+            | BuildingType::Sprinkler
+            | BuildingType::Treasury
+            | BuildingType::RiverGolem
+            | BuildingType::RiverFerry => 1.0,
+        };
+        commands.queue(ShakeScreen(shake));
+
+        stats.buildings_lost += 1;
+        log_event(
+            &mut log,
+            stats.duration,
+            format!("Your {building_type} was destroyed!"),
+        );
+    }
+
     match building_type {
         BuildingType::CityHall => {
             hint.set("GAME OVER");
@@ -169,6 +240,11 @@ fn handle_despawned_buildings(
         BuildingType::ManaForge => {
             resources.mana_drain -= 3;
 
+            // a demolished forge is safely shut down rather than exploding
+            if demolished {
+                return;
+            }
+
             // spawn some chain reaction fire balls
             let mut rng = rand::thread_rng();
             let num_fires = rng.gen_range(2..=6);
@@ -190,9 +266,20 @@ fn handle_despawned_buildings(
                     },
                 ));
 
+                // only things that can burn catch fire, so fireballs landing in
+                // rivers or on stone don't leave gaps in the firebreaks
                 if let Some(cell) = map.get_mut(fire_tile_coords) {
-                    cell.terrain = TerrainType::Fire;
-                    cell.mark_dirty();
+                    match cell.terrain {
+                        TerrainType::Grassland | TerrainType::Tree | TerrainType::Building => {
+                            cell.ignite();
+                            map.mark_on_fire(fire_tile_coords);
+                        }
+                        TerrainType::Dirt
+                        | TerrainType::Stone
+                        | TerrainType::Fire
+                        | TerrainType::Smoldering
+                        | TerrainType::Water => {}
+                    }
                 }
             }
         }
@@ -203,9 +290,22 @@ fn handle_despawned_buildings(
         BuildingType::WaterGolem => {
             resources.mana_drain += 2; // 4 mana / 2 seconds
         }
-        BuildingType::Minotaur => {
+        BuildingType::Minotaur | BuildingType::RiverGolem => {
             resources.mana_drain += 1;
         }
-        BuildingType::LumberMill => {}
+        BuildingType::LumberMill
+        | BuildingType::EmberGuard
+        | BuildingType::Arborist
+        | BuildingType::BeaconTower
+        | BuildingType::ResearchHall
+        | BuildingType::RelayTower
+        | BuildingType::ManaBattery
+        | BuildingType::FireFighter
+        | BuildingType::Sprinkler
+        | BuildingType::RiverFerry => {}
+        BuildingType::Treasury => {
+            // lumber over the lower cap is lost the next time a mill harvests
+            resources.max_lumber -= TREASURY_CAPACITY;
+        }
     }
 }
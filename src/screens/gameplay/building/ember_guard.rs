@@ -0,0 +1,204 @@
+//! Logic + code for placing ember guard buildings
+
+use bevy::{
+    color::palettes::tailwind::ORANGE_300, ecs::world::OnDespawn, prelude::*, sprite::Anchor,
+};
+use bevy_vector_shapes::{prelude::ShapePainter, shapes::DiscPainter};
+
+use crate::{
+    Pause,
+    screens::{
+        PlayerResources, Screen,
+        gameplay::{
+            BuildTextHint, BuildingMode, EMBER_GUARD_COST_MANA,
+            building::{
+                BUILDING_FOOTPRINT_OFFSETS, BuildingAssets, BuildingLocation, BuildingType,
+                ManaEntityLink, ManaLine, ManaLineBalls, ManaSource,
+                TrackParentBuildingWhilePlacing, check_placement,
+            },
+        },
+    },
+    wildfire::{GameMap, TerrainType},
+};
+
+/// The amount that embers are less likely to start spot fires near a guard
+pub const EMBER_GUARD_RESISTANCE: f32 = 0.75;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<EmberGuard>();
+    app.add_observer(remove_ember_guard);
+
+    app.add_systems(
+        Update,
+        draw_ember_guard_areas.run_if(
+            in_state(Screen::Gameplay).and(in_state(Pause(false)).and(resource_exists::<GameMap>)),
+        ),
+    );
+}
+
+fn remove_ember_guard(
+    trigger: Trigger<OnDespawn, EmberGuard>,
+    map: Option<ResMut<GameMap>>,
+    guards: Query<(&BuildingLocation, &EmberGuard)>,
+) {
+    let Some(mut map) = map else {
+        warn!("Unable to remove ember guard, no game map exists");
+        return;
+    };
+
+    let Ok((loc, guard)) = guards.get(trigger.target()) else {
+        error!(
+            "Unable to find ember guard being removed, aborting `remove_ember_guard`. The map will be out of date."
+        );
+        return;
+    };
+
+    let cells = map
+        .cells_within_range(loc.0, guard.range)
+        .collect::<Vec<_>>();
+    for coord in cells {
+        if let Some(cell) = map.get_mut(coord) {
+            cell.ember_resistance -= EMBER_GUARD_RESISTANCE;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpawnEmberGuard(pub Vec2);
+
+impl Command for SpawnEmberGuard {
+    fn apply(self, world: &mut World) {
+        let _ = world.run_system_cached_with(spawn_ember_guard, self);
+    }
+}
+
+fn spawn_ember_guard(
+    In(config): In<SpawnEmberGuard>,
+    mut commands: Commands,
+    mut resources: ResMut<PlayerResources>,
+    mut building_mode: ResMut<BuildingMode>,
+    buildings: Res<BuildingAssets>,
+    mut map: ResMut<GameMap>,
+    parent_forge: Single<(Entity, &TrackParentBuildingWhilePlacing)>,
+    forges: Query<&Transform, ManaSource>,
+    existing: Query<&BuildingLocation>,
+    mut hint: ResMut<BuildTextHint>,
+) {
+    if resources.mana < EMBER_GUARD_COST_MANA {
+        warn!("Not enough resources to spawn ember guard");
+        return;
+    }
+
+    let (parent_tracking_entity, parent_forge) = *parent_forge;
+    let Some(parent_forge) = parent_forge.entity else {
+        warn!("No parent mana forge inside tracking, skipping ember guard placement");
+        return;
+    };
+
+    let coords = map.tile_coords(config.0);
+    if !map.is_valid_coords(coords) {
+        warn!("Invalid map coordinates, aborting ember guard placement");
+        return;
+    }
+
+    if let Err(e) = check_placement(coords, &map, &existing) {
+        warn!("{e}, aborting ember guard placement");
+        hint.set(e.to_string());
+        return;
+    }
+
+    let Ok(parent_tx) = forges.get(parent_forge) else {
+        warn!("Unable to find parent mana forge");
+        return;
+    };
+
+    commands.entity(parent_tracking_entity).despawn();
+    resources.mana -= EMBER_GUARD_COST_MANA;
+
+    let world_coords = map.world_coords(coords);
+    info!("Spawning ember guard at {coords}");
+
+    let guard = EmberGuard::default();
+    guard.apply_to_map(coords, &mut map);
+
+    commands.spawn((
+        BuildingLocation(coords),
+        BuildingType::EmberGuard,
+        guard,
+        ManaLine::new(
+            parent_tx.translation.truncate().extend(0.05),
+            config.0.extend(0.05),
+        ),
+        ManaLineBalls::default(),
+        ManaEntityLink {
+            from_entity: parent_forge,
+            destruction_time: None,
+        },
+        StateScoped(Screen::Gameplay),
+        Transform::from_xyz(world_coords.x, world_coords.y, 0.1),
+        Visibility::Visible,
+        Sprite {
+            image: buildings.ember_guard.clone(),
+            custom_size: Some(Vec2::splat(16.0)),
+            anchor: Anchor::Center,
+            ..default()
+        },
+    ));
+
+    // update the map underneath to turn to buildings
+    BUILDING_FOOTPRINT_OFFSETS.iter().for_each(|offset| {
+        if let Some(cell) = map.get_mut(coords + *offset) {
+            cell.terrain = TerrainType::Building;
+        }
+    });
+
+    *building_mode = BuildingMode::None;
+}
+
+/// A building that catches embers before they can start spot fires
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct EmberGuard {
+    /// The range of the guard (i.e. distance from the building location)
+    range: i32,
+}
+
+impl Default for EmberGuard {
+    fn default() -> Self {
+        Self { range: 12 }
+    }
+}
+
+impl EmberGuard {
+    /// Adds ember resistance to the cells around the guard
+    pub fn apply_to_map(&self, guard_cell: IVec2, map: &mut GameMap) {
+        let cells = map
+            .cells_within_range(guard_cell, self.range)
+            .collect::<Vec<_>>();
+        for coord in cells {
+            if let Some(cell) = map.get_mut(coord) {
+                cell.ember_resistance += EMBER_GUARD_RESISTANCE;
+            }
+        }
+    }
+}
+
+fn draw_ember_guard_areas(
+    mut painter: ShapePainter,
+    map: Res<GameMap>,
+    guards: Query<(&Transform, &EmberGuard)>,
+) {
+    let original_tx = painter.transform;
+
+    for (tx, guard) in &guards {
+        let mut color = ORANGE_300;
+        color.alpha = 0.4;
+
+        painter.hollow = true;
+        painter.set_color(color);
+        painter.translate(tx.translation - Vec3::new(0.0, 0.0, 0.05));
+        painter.circle(guard.range as f32 * map.sprite_size);
+
+        painter.transform = original_tx;
+    }
+}
@@ -0,0 +1,179 @@
+//! The animation played when a building is destroyed. A shockwave ring expands
+//! out from the building and debris flies off before the building is finally
+//! despawned.
+
+use std::f32::consts::TAU;
+
+use bevy::{
+    color::palettes::tailwind::{ORANGE_300, STONE_500},
+    prelude::*,
+};
+use bevy_vector_shapes::{prelude::ShapePainter, shapes::DiscPainter};
+use rand::Rng;
+
+use crate::{
+    Pause,
+    screens::{BuildingType, GameSpeed, Screen},
+};
+
+/// How long the building stays on screen while the animation plays
+const COLLAPSE_TIME: f32 = 0.5;
+/// The size (in pixels) the shockwave grows to before disappearing
+const SHOCKWAVE_MAX_RADIUS: f32 = 30.0;
+/// How long the shockwave takes to reach its full size
+const SHOCKWAVE_TIME: f32 = 0.5;
+/// How long debris flies for before it has completely faded
+const DEBRIS_LIFETIME: f32 = 0.8;
+/// How quickly debris slows down, higher is faster
+const DEBRIS_DRAG: f32 = 3.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Collapsing>();
+    app.register_type::<Shockwave>();
+    app.register_type::<Debris>();
+
+    app.add_systems(
+        Update,
+        (despawn_collapsed_buildings, draw_shockwaves, move_debris)
+            .run_if(in_state(Pause(false)).and(in_state(Screen::Gameplay))),
+    );
+}
+
+/// Plays the destruction animation for the given building, then despawns it
+#[derive(Debug, Clone, Copy)]
+pub struct DestroyWithAnimation(pub Entity);
+
+impl Command for DestroyWithAnimation {
+    fn apply(self, world: &mut World) {
+        let _ = world.run_system_cached_with(destroy_with_animation, self);
+    }
+}
+
+/// A building that is playing its destruction animation
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct Collapsing {
+    /// The time until the building is despawned
+    timer: f32,
+}
+
+/// A ring that expands out from a destroyed building
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct Shockwave {
+    pub radius: f32,
+    pub max_radius: f32,
+}
+
+/// A piece of a destroyed building flying away from it
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct Debris {
+    pub velocity: Vec2,
+    pub lifetime: f32,
+}
+
+fn destroy_with_animation(
+    In(config): In<DestroyWithAnimation>,
+    mut commands: Commands,
+    buildings: Query<&Transform, (With<BuildingType>, Without<Collapsing>)>,
+) {
+    let Ok(tx) = buildings.get(config.0) else {
+        warn!("Unable to find building to destroy, skipping animation");
+        return;
+    };
+
+    let pos = tx.translation.truncate();
+    commands.entity(config.0).insert(Collapsing {
+        timer: COLLAPSE_TIME,
+    });
+
+    commands.spawn((
+        Name::new("Shockwave"),
+        StateScoped(Screen::Gameplay),
+        Shockwave {
+            radius: 0.0,
+            max_radius: SHOCKWAVE_MAX_RADIUS,
+        },
+        Transform::from_translation(pos.extend(0.6)),
+    ));
+
+    let mut rng = rand::thread_rng();
+    for _ in 0..rng.gen_range(4..=6) {
+        let angle = rng.gen_range(0.0..TAU);
+        let speed = rng.gen_range(30.0..80.0);
+
+        commands.spawn((
+            Name::new("Debris"),
+            StateScoped(Screen::Gameplay),
+            Debris {
+                velocity: Vec2::from_angle(angle) * speed,
+                lifetime: DEBRIS_LIFETIME,
+            },
+            Transform::from_translation(pos.extend(0.6)),
+            Sprite::from_color(STONE_500, Vec2::splat(rng.gen_range(2.0..4.0))),
+        ));
+    }
+}
+
+/// Despawns buildings once their animation has finished, which runs the usual
+/// despawn handling for the building
+fn despawn_collapsed_buildings(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut collapsing: Query<(Entity, &mut Collapsing)>,
+) {
+    for (entity, mut collapse) in &mut collapsing {
+        collapse.timer -= time.delta_secs() * game_speed.0;
+        if collapse.timer <= 0.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn draw_shockwaves(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut painter: ShapePainter,
+    mut shockwaves: Query<(Entity, &Transform, &mut Shockwave)>,
+) {
+    let original_tx = painter.transform;
+
+    for (entity, tx, mut shockwave) in &mut shockwaves {
+        shockwave.radius += shockwave.max_radius * time.delta_secs() / SHOCKWAVE_TIME;
+        if shockwave.radius >= shockwave.max_radius {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let alpha = 1.0 - shockwave.radius / shockwave.max_radius;
+        painter.hollow = true;
+        painter.thickness = 2.0;
+        painter.set_color(ORANGE_300.with_alpha(alpha));
+        painter.translate(tx.translation);
+        painter.circle(shockwave.radius);
+
+        painter.transform = original_tx;
+    }
+}
+
+fn move_debris(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut debris: Query<(Entity, &mut Transform, &mut Sprite, &mut Debris)>,
+) {
+    let delta = time.delta_secs();
+
+    for (entity, mut tx, mut sprite, mut piece) in &mut debris {
+        piece.lifetime -= delta;
+        if piece.lifetime <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        tx.translation += (piece.velocity * delta).extend(0.0);
+        piece.velocity *= 1.0 - (DEBRIS_DRAG * delta).min(1.0);
+        sprite.color.set_alpha(piece.lifetime / DEBRIS_LIFETIME);
+    }
+}
@@ -0,0 +1,158 @@
+//! Fire alarms pause the game when fire comes close to a building, so the
+//! player has time to react. Every city hall has one, and they can be turned
+//! on and off for any building from its context menu.
+
+use bevy::{color::palettes::tailwind::RED_900, prelude::*};
+
+use crate::{
+    Pause,
+    screens::{
+        GameSpeed, Screen,
+        gameplay::{
+            EventLog,
+            building::{BuildingLocation, BuildingType, CityHall},
+            log_event,
+        },
+    },
+    theme::widget,
+    wildfire::{FireStats, GameMap},
+};
+
+/// How often each alarm checks for fire, in seconds
+const FIRE_ALARM_CHECK_INTERVAL: f32 = 0.5;
+/// How close fire has to be (in cells) to set off an alarm
+const FIRE_ALARM_DEFAULT_RADIUS: i32 = 6;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<FireAlarm>();
+    app.register_type::<OnFireAlarm>();
+
+    app.add_observer(add_city_hall_alarm);
+    app.add_observer(auto_pause_on_alarm);
+
+    app.add_systems(
+        Update,
+        check_fire_alarms.run_if(
+            in_state(Screen::Gameplay)
+                .and(in_state(Pause(false)))
+                .and(resource_exists::<GameMap>),
+        ),
+    );
+}
+
+/// Pauses the game when fire comes within `trigger_radius` of the building
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct FireAlarm {
+    pub trigger_radius: i32,
+    /// The time since the alarm last checked for fire
+    pub last_checked: f32,
+    pub enabled: bool,
+    /// Whether there was fire in range at the last check. The alarm only
+    /// goes off when fire first comes in range, rather than every check.
+    sounding: bool,
+}
+
+impl FireAlarm {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            trigger_radius: FIRE_ALARM_DEFAULT_RADIUS,
+            last_checked: 0.0,
+            enabled,
+            sounding: false,
+        }
+    }
+}
+
+/// Triggered when fire comes in range of a building with an enabled alarm
+#[derive(Event, Reflect, Debug, Clone, Copy)]
+pub struct OnFireAlarm {
+    pub building_type: BuildingType,
+    /// How far the closest fire is from the building, in cells
+    pub distance: f32,
+}
+
+fn add_city_hall_alarm(trigger: Trigger<OnAdd, CityHall>, mut commands: Commands) {
+    commands
+        .entity(trigger.target())
+        .insert(FireAlarm::new(true));
+}
+
+fn check_fire_alarms(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    map: Res<GameMap>,
+    mut alarms: Query<(&BuildingType, &BuildingLocation, &mut FireAlarm)>,
+) {
+    let delta = time.delta_secs() * game_speed.0;
+    // only one alarm is shown at a time, the rest go off after resuming
+    let mut alarm_raised = false;
+
+    for (building_type, loc, mut alarm) in &mut alarms {
+        alarm.last_checked += delta;
+        if !alarm.enabled || alarm.last_checked < FIRE_ALARM_CHECK_INTERVAL || alarm_raised {
+            continue;
+        }
+        alarm.last_checked = 0.0;
+
+        let cells = map
+            .cells_within_range(loc.0, alarm.trigger_radius)
+            .collect::<Vec<_>>();
+        let on_fire = map.check_on_fire(&cells);
+        let newly_on_fire = on_fire && !alarm.sounding;
+        alarm.sounding = on_fire;
+
+        if !newly_on_fire {
+            continue;
+        }
+
+        let distance = cells
+            .iter()
+            .filter(|cell| map.check_on_fire(&[**cell]))
+            .map(|cell| cell.as_vec2().distance(loc.0.as_vec2()))
+            .fold(f32::MAX, f32::min);
+
+        info!("Fire alarm at {building_type} {loc:?}, fire is {distance:.0} cells away");
+        commands.trigger(OnFireAlarm {
+            building_type: *building_type,
+            distance,
+        });
+        alarm_raised = true;
+    }
+}
+
+fn auto_pause_on_alarm(
+    trigger: Trigger<OnFireAlarm>,
+    mut commands: Commands,
+    mut next_pause: ResMut<NextState<Pause>>,
+    mut log: ResMut<EventLog>,
+    stats: Res<FireStats>,
+) {
+    let alarm = trigger.event();
+    log_event(
+        &mut log,
+        stats.duration,
+        format!("Fire alarm at your {}!", alarm.building_type),
+    );
+
+    next_pause.set(Pause(true));
+    commands.spawn((
+        widget::ui_root("Fire Alert"),
+        GlobalZIndex(2),
+        BackgroundColor(RED_900.with_alpha(0.6).into()),
+        StateScoped(Pause(true)),
+        children![
+            widget::header(format!(
+                "FIRE ALERT: {} is under threat!",
+                alarm.building_type
+            )),
+            widget::label(format!("Fire is {:.0} cells away", alarm.distance)),
+            widget::button("Resume", resume_after_alarm),
+        ],
+    ));
+}
+
+fn resume_after_alarm(_: Trigger<Pointer<Click>>, mut next_pause: ResMut<NextState<Pause>>) {
+    next_pause.set(Pause(false));
+}
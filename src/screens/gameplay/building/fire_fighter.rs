@@ -0,0 +1,353 @@
+//! Logic + code for placing fire fighter hutches. The fire fighter leaves the
+//! hutch to put out the closest fire, then heads to the next one.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+};
+
+use bevy::{
+    color::palettes::tailwind::RED_600, platform::collections::HashSet, prelude::*, sprite::Anchor,
+};
+use bevy_vector_shapes::{prelude::ShapePainter, shapes::DiscPainter};
+
+use crate::{
+    Pause,
+    pathfinding::astar,
+    screens::{
+        GameSpeed, PlayerResources, Screen,
+        gameplay::{
+            BuildTextHint, BuildingMode, FIRE_FIGHTER_COST_MANA,
+            building::{
+                BUILDING_FOOTPRINT_OFFSETS, BuildingAssets, BuildingLocation, BuildingType,
+                ManaEntityLink, ManaLine, ManaLineBalls, ManaSource,
+                TrackParentBuildingWhilePlacing, check_placement,
+            },
+        },
+    },
+    wildfire::{FireStats, GameMap, TerrainType},
+};
+
+/// How far (in cells) from the hutch the fire fighter will go to put out fires
+const FIRE_FIGHTER_RANGE: i32 = 8;
+/// The time it takes the fire fighter to move one cell
+const FIRE_FIGHTER_MOVE_TIME: f32 = 0.25;
+/// How quickly the fire fighter sprite catches up with its cell, higher is faster
+const FIRE_FIGHTER_SPRITE_SPEED: f32 = 10.0;
+/// How often (in seconds) the fire fighter recomputes its path to the fire
+const FIRE_FIGHTER_REPATH_TIME: f32 = 5.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<FireFighter>();
+    app.register_type::<FireFighterUnit>();
+
+    app.add_systems(
+        Update,
+        (produce_from_fire_fighter, animate_fire_fighter_units)
+            .chain()
+            .run_if(
+                in_state(Pause(false))
+                    .and(in_state(Screen::Gameplay))
+                    .and(resource_exists::<PlayerResources>)
+                    .and(resource_exists::<GameMap>),
+            ),
+    );
+
+    app.add_systems(
+        Update,
+        draw_fire_fighter_areas.run_if(
+            in_state(Screen::Gameplay).and(in_state(Pause(false)).and(resource_exists::<GameMap>)),
+        ),
+    );
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpawnFireFighter(pub Vec2);
+
+impl Command for SpawnFireFighter {
+    fn apply(self, world: &mut World) {
+        let _ = world.run_system_cached_with(spawn_fire_fighter, self);
+    }
+}
+
+fn spawn_fire_fighter(
+    In(config): In<SpawnFireFighter>,
+    mut commands: Commands,
+    mut resources: ResMut<PlayerResources>,
+    mut building_mode: ResMut<BuildingMode>,
+    buildings: Res<BuildingAssets>,
+    mut map: ResMut<GameMap>,
+    parent_forge: Single<(Entity, &TrackParentBuildingWhilePlacing)>,
+    forges: Query<&Transform, ManaSource>,
+    existing: Query<&BuildingLocation>,
+    mut hint: ResMut<BuildTextHint>,
+) {
+    if resources.mana < FIRE_FIGHTER_COST_MANA {
+        warn!("Not enough resources to spawn fire fighter");
+        return;
+    }
+
+    let (parent_tracking_entity, parent_forge) = *parent_forge;
+    let Some(parent_forge) = parent_forge.entity else {
+        warn!("No parent mana forge inside tracking, skipping fire fighter placement");
+        return;
+    };
+
+    let coords = map.tile_coords(config.0);
+    if !map.is_valid_coords(coords) {
+        warn!("Invalid map coordinates, aborting fire fighter placement");
+        return;
+    }
+
+    if let Err(e) = check_placement(coords, &map, &existing) {
+        warn!("{e}, aborting fire fighter placement");
+        hint.set(e.to_string());
+        return;
+    }
+
+    let Ok(parent_tx) = forges.get(parent_forge) else {
+        warn!("Unable to find parent mana forge");
+        return;
+    };
+
+    commands.entity(parent_tracking_entity).despawn();
+    resources.mana -= FIRE_FIGHTER_COST_MANA;
+
+    let world_coords = map.world_coords(coords);
+    info!("Spawning fire fighter at {coords}");
+
+    commands.spawn((
+        BuildingLocation(coords),
+        BuildingType::FireFighter,
+        FireFighter::new(coords),
+        ManaLine::new(
+            parent_tx.translation.truncate().extend(0.05),
+            config.0.extend(0.05),
+        ),
+        ManaLineBalls::default(),
+        ManaEntityLink {
+            from_entity: parent_forge,
+            destruction_time: None,
+        },
+        StateScoped(Screen::Gameplay),
+        Transform::from_xyz(world_coords.x, world_coords.y, 0.1),
+        Visibility::Visible,
+        Sprite {
+            image: buildings.fire_fighter.clone(),
+            custom_size: Some(Vec2::splat(16.0)),
+            anchor: Anchor::Center,
+            ..default()
+        },
+        children![fire_fighter_unit(&buildings)],
+    ));
+
+    // update the map underneath to turn to buildings
+    BUILDING_FOOTPRINT_OFFSETS.iter().for_each(|offset| {
+        if let Some(cell) = map.get_mut(coords + *offset) {
+            cell.terrain = TerrainType::Building;
+        }
+    });
+
+    *building_mode = BuildingMode::None;
+}
+
+/// The sprite for the fire fighter that walks out from the hutch
+pub(super) fn fire_fighter_unit(buildings: &BuildingAssets) -> impl Bundle {
+    (
+        Name::new("Fire Fighter"),
+        FireFighterUnit,
+        Transform::from_xyz(0.0, 0.0, 0.1),
+        Sprite {
+            image: buildings.fire_fighter.clone(),
+            custom_size: Some(Vec2::splat(8.0)),
+            anchor: Anchor::Center,
+            ..default()
+        },
+    )
+}
+
+/// A hutch for a fire fighter, who puts out the nearest fire in range
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct FireFighter {
+    /// The cell the fire fighter is currently standing on
+    location: IVec2,
+    /// The range of the fire fighter (i.e. distance from the building location)
+    range: i32,
+    /// The time since the fire fighter last moved
+    timer: f32,
+    /// The fire the fire fighter is heading towards
+    target: Option<IVec2>,
+    /// The cells the fire fighter will walk through to reach the target
+    path: VecDeque<IVec2>,
+    /// The time since the path was last computed
+    repath_timer: f32,
+}
+
+impl FireFighter {
+    pub fn new(location: IVec2) -> Self {
+        Self {
+            location,
+            range: FIRE_FIGHTER_RANGE,
+            timer: 0.0,
+            target: None,
+            path: VecDeque::new(),
+            repath_timer: 0.0,
+        }
+    }
+}
+
+/// Marks the sprite of the fire fighter, which is a child of the hutch
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct FireFighterUnit;
+
+/// Finds the closest burning cell to the hutch by searching outwards from it,
+/// always expanding the closest unvisited cell first
+fn find_nearest_fire(map: &GameMap, center: IVec2, range: i32) -> Option<IVec2> {
+    let mut visited = HashSet::new();
+    let mut queue = BinaryHeap::new();
+
+    visited.insert(center);
+    queue.push(Reverse((0, center.x, center.y)));
+
+    while let Some(Reverse((distance, x, y))) = queue.pop() {
+        let loc = IVec2::new(x, y);
+        if map
+            .get(loc)
+            .is_some_and(|cell| matches!(cell.terrain, TerrainType::Fire))
+        {
+            return Some(loc);
+        }
+
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let next = loc + IVec2::new(dx, dy);
+                if next.distance_squared(center) > range * range
+                    || !map.is_valid_coords(next)
+                    || !visited.insert(next)
+                {
+                    continue;
+                }
+
+                queue.push(Reverse((distance + 1, next.x, next.y)));
+            }
+        }
+    }
+
+    None
+}
+
+fn produce_from_fire_fighter(
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut map: ResMut<GameMap>,
+    mut stats: ResMut<FireStats>,
+    mut fighters: Query<(&BuildingLocation, &mut FireFighter)>,
+) {
+    let delta = time.delta_secs() * game_speed.0;
+
+    for (loc, mut fighter) in &mut fighters {
+        // a fire at the hutch sends the fire fighter running home
+        let hutch_on_fire = BUILDING_FOOTPRINT_OFFSETS.iter().any(|offset| {
+            map.get(loc.0 + *offset)
+                .is_some_and(|cell| matches!(cell.terrain, TerrainType::Fire))
+        });
+        if hutch_on_fire {
+            fighter.location = loc.0;
+            fighter.target = None;
+            fighter.path.clear();
+        }
+
+        fighter.repath_timer += delta;
+        fighter.timer += delta;
+        if fighter.timer < FIRE_FIGHTER_MOVE_TIME {
+            continue;
+        }
+        fighter.timer -= FIRE_FIGHTER_MOVE_TIME;
+
+        // the target may have burned out or been put out by something else
+        let target_still_burning = fighter.target.is_some_and(|target| {
+            map.get(target)
+                .is_some_and(|cell| matches!(cell.terrain, TerrainType::Fire))
+        });
+        if !target_still_burning {
+            fighter.target = find_nearest_fire(&map, loc.0, fighter.range);
+            fighter.path.clear();
+        }
+
+        let Some(target) = fighter.target else {
+            continue;
+        };
+
+        if fighter.location != target {
+            // fires spread and burn out, so the path is refreshed every so often
+            if fighter.path.is_empty() || fighter.repath_timer >= FIRE_FIGHTER_REPATH_TIME {
+                fighter.repath_timer = 0.0;
+                let Some(path) = astar(fighter.location, target, &map) else {
+                    warn!("Fire fighter unable to find a path to the fire at {target}");
+                    fighter.target = None;
+                    fighter.path.clear();
+                    continue;
+                };
+                fighter.path = path.into();
+            }
+
+            if let Some(next) = fighter.path.pop_front() {
+                fighter.location = next;
+            }
+            continue;
+        }
+
+        if let Some(cell) = map.get_mut(target) {
+            cell.terrain = TerrainType::Smoldering;
+            cell.recovery_timer = 0.0;
+            cell.mark_dirty();
+            stats.fires_extinguished += 1;
+        }
+        fighter.target = None;
+        fighter.path.clear();
+    }
+}
+
+/// Smoothly moves each fire fighter sprite towards the cell it is standing on
+fn animate_fire_fighter_units(
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    map: Res<GameMap>,
+    fighters: Query<(&BuildingLocation, &FireFighter, &Children)>,
+    mut units: Query<&mut Transform, With<FireFighterUnit>>,
+) {
+    let t = (time.delta_secs() * game_speed.0 * FIRE_FIGHTER_SPRITE_SPEED).min(1.0);
+
+    for (loc, fighter, children) in &fighters {
+        let offset = map.world_coords(fighter.location) - map.world_coords(loc.0);
+
+        for child in children.iter() {
+            if let Ok(mut tx) = units.get_mut(child) {
+                let next = tx.translation.truncate().lerp(offset, t);
+                tx.translation = next.extend(tx.translation.z);
+            }
+        }
+    }
+}
+
+fn draw_fire_fighter_areas(
+    mut painter: ShapePainter,
+    map: Res<GameMap>,
+    fighters: Query<(&Transform, &FireFighter)>,
+) {
+    let original_tx = painter.transform;
+
+    for (tx, fighter) in &fighters {
+        let mut color = RED_600;
+        color.alpha = 0.4;
+
+        painter.hollow = true;
+        painter.set_color(color);
+        painter.translate(tx.translation - Vec3::new(0.0, 0.0, 0.05));
+        painter.circle(fighter.range as f32 * map.sprite_size);
+
+        painter.transform = original_tx;
+    }
+}
@@ -0,0 +1,116 @@
+//! Ley lines form between mana forges that are close together, boosting the
+//! mana produced by both forges
+
+use bevy::prelude::*;
+
+use crate::{
+    screens::{
+        PlayerResources, Screen,
+        gameplay::building::{BuildingLocation, ManaLine, mana_forge::ManaForge},
+    },
+    wildfire::GameMap,
+};
+
+/// How close (in tiles) two forges must be for a ley line to form
+const LEY_LINE_RANGE: i32 = 15;
+/// The most ley lines that can connect to a single forge
+const MAX_LEY_LINES_PER_FORGE: i32 = 3;
+/// The extra mana per second each ley line gives to both of its forges
+pub const LEY_LINE_MANA_BONUS: i32 = 1;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<LeyLine>();
+
+    app.add_systems(
+        Update,
+        update_ley_lines.run_if(
+            in_state(Screen::Gameplay)
+                .and(resource_exists::<PlayerResources>)
+                .and(resource_exists::<GameMap>),
+        ),
+    );
+}
+
+/// A ley line connecting two mana forges
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct LeyLine {
+    pub forges: [Entity; 2],
+}
+
+/// Removes ley lines from destroyed forges and forms new ley lines between
+/// nearby forges, then updates the production bonus of each forge
+fn update_ley_lines(
+    mut commands: Commands,
+    mut resources: ResMut<PlayerResources>,
+    mut removed_forges: RemovedComponents<ManaForge>,
+    added_forges: Query<(), Added<ManaForge>>,
+    mut forges: Query<(Entity, &BuildingLocation, &Transform, &mut ManaForge)>,
+    ley_lines: Query<(Entity, &LeyLine)>,
+) {
+    // only check when forges are placed or destroyed
+    if removed_forges.read().count() == 0 && added_forges.is_empty() {
+        return;
+    }
+
+    let mut connected = Vec::new();
+    for (entity, line) in &ley_lines {
+        if line.forges.iter().all(|forge| forges.contains(*forge)) {
+            connected.push(line.forges);
+        } else {
+            info!("Removing ley line from destroyed forge");
+            commands.entity(entity).despawn();
+        }
+    }
+
+    let mut candidates = forges
+        .iter()
+        .map(|(entity, loc, tx, _)| (entity, loc.0, tx.translation))
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|(entity, _, _)| *entity);
+
+    let count = |connected: &[[Entity; 2]], forge: Entity| {
+        connected
+            .iter()
+            .filter(|line| line.contains(&forge))
+            .count() as i32
+    };
+
+    for (idx, (a, a_loc, a_translation)) in candidates.iter().enumerate() {
+        for (b, b_loc, b_translation) in &candidates[idx + 1..] {
+            if a_loc.distance_squared(*b_loc) > LEY_LINE_RANGE * LEY_LINE_RANGE
+                || connected
+                    .iter()
+                    .any(|line| line.contains(a) && line.contains(b))
+                || count(&connected, *a) >= MAX_LEY_LINES_PER_FORGE
+                || count(&connected, *b) >= MAX_LEY_LINES_PER_FORGE
+            {
+                continue;
+            }
+
+            info!("Forming ley line between forges at {a_loc} and {b_loc}");
+            connected.push([*a, *b]);
+            commands.spawn((
+                Name::new("Ley Line"),
+                LeyLine { forges: [*a, *b] },
+                ManaLine::new(
+                    a_translation.truncate().extend(0.05),
+                    b_translation.truncate().extend(0.05),
+                ),
+                StateScoped(Screen::Gameplay),
+            ));
+        }
+    }
+
+    for (entity, _, _, mut forge) in &mut forges {
+        let ley_lines = count(&connected, entity);
+        if ley_lines == forge.ley_lines {
+            continue;
+        }
+
+        let bonus_change = (ley_lines - forge.ley_lines) * LEY_LINE_MANA_BONUS;
+        forge.ley_lines = ley_lines;
+        forge.mana_per_second += bonus_change;
+        resources.mana_drain += bonus_change;
+    }
+}
@@ -1,22 +1,31 @@
 //! Logic + code for placing lumber mill buildings
 
-use bevy::{prelude::*, sprite::Anchor};
+use bevy::{color::palettes::tailwind::GREEN_600, prelude::*, sprite::Anchor};
+use bevy_vector_shapes::{prelude::ShapePainter, shapes::DiscPainter};
 use rand::Rng;
 
 use crate::{
     Pause,
+    input::MousePosition,
     screens::{
-        PlayerResources, Screen,
+        GameSpeed, PlayerResources, Screen,
         gameplay::{
-            BuildingMode, LUMBER_MILL_COST_LUMBER,
-            building::{BuildingAssets, BuildingLocation, BuildingType, ManaEntityLink},
+            BASE_MAX_LUMBER, BuildTextHint, BuildingMode, EventLog, LUMBER_MILL_COST_LUMBER,
+            building::{
+                BuildingAssets, BuildingLocation, BuildingTier, BuildingType, ManaEntityLink,
+                check_placement,
+                treasury::{TREASURY_CAPACITY, Treasury},
+            },
+            challenge::ChallengeLumberLimit,
+            log_event,
         },
     },
-    wildfire::{GameMap, TerrainType},
+    wildfire::{FireStats, GameMap, TerrainType},
 };
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<LumberMill>();
+    app.register_type::<HarvestMode>();
 
     app.add_systems(
         Update,
@@ -27,6 +36,13 @@ pub(super) fn plugin(app: &mut App) {
                 .and(resource_exists::<GameMap>),
         ),
     );
+
+    app.add_systems(
+        Update,
+        draw_lumber_mill_areas.run_if(
+            in_state(Screen::Gameplay).and(in_state(Pause(false)).and(resource_exists::<GameMap>)),
+        ),
+    );
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -45,6 +61,8 @@ fn spawn_lumber_mill(
     mut building_mode: ResMut<BuildingMode>,
     buildings: Res<BuildingAssets>,
     map: Res<GameMap>,
+    existing: Query<&BuildingLocation>,
+    mut hint: ResMut<BuildTextHint>,
 ) {
     if resources.lumber < LUMBER_MILL_COST_LUMBER {
         warn!("Not enough resources to spawn lumber mill");
@@ -57,6 +75,12 @@ fn spawn_lumber_mill(
         return;
     }
 
+    if let Err(e) = check_placement(coords, &map, &existing) {
+        warn!("{e}, aborting lumber mill placement");
+        hint.set(e.to_string());
+        return;
+    }
+
     resources.lumber -= 30;
 
     let world_coords = map.world_coords(coords);
@@ -66,6 +90,7 @@ fn spawn_lumber_mill(
         BuildingLocation(coords),
         BuildingType::LumberMill,
         LumberMill::default(),
+        BuildingTier::default(),
         StateScoped(Screen::Gameplay),
         Transform::from_translation(world_coords.extend(0.1)),
         Visibility::Visible,
@@ -94,18 +119,96 @@ pub struct LumberMill {
     time_since_last_tick: f32,
     /// The range of the minotaur (i.e. distance from the building location)
     range: i32,
+    /// The time between each harvest
+    tick_time: f32,
+    /// Whether the mill has run out of trees, so it is only reported once
+    out_of_trees: bool,
+    /// The number of trees harvested since the mill was placed
+    pub total_harvested: u32,
+    /// How the mill chooses which tree to harvest next
+    pub harvest_mode: HarvestMode,
+}
+
+/// How a lumber mill picks the next tree to harvest
+#[derive(Reflect, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HarvestMode {
+    /// Any tree in range, which is best for gathering lumber
+    #[default]
+    Random,
+    /// Trees next to dirt where possible, extending existing clearings into
+    /// firebreaks
+    Chained,
+}
+
+impl HarvestMode {
+    /// The other mode, for toggling from the context menu
+    pub fn toggled(self) -> Self {
+        match self {
+            HarvestMode::Random => HarvestMode::Chained,
+            HarvestMode::Chained => HarvestMode::Random,
+        }
+    }
+}
+
+impl std::fmt::Display for HarvestMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mode = match self {
+            HarvestMode::Random => "Random",
+            HarvestMode::Chained => "Chained",
+        };
+        write!(f, "{mode}")
+    }
 }
 
 impl Default for LumberMill {
     fn default() -> Self {
         Self {
             time_since_last_tick: 0.0,
-            range: 5,
+            range: Self::range_for_tier(1),
+            tick_time: Self::tick_time_for_tier(1),
+            out_of_trees: false,
+            total_harvested: 0,
+            harvest_mode: HarvestMode::default(),
         }
     }
 }
 
 impl LumberMill {
+    /// The harvesting range of a mill of the given tier
+    pub fn range_for_tier(tier: u8) -> i32 {
+        match tier {
+            0 | 1 => 5,
+            2 => 7,
+            _ => 9,
+        }
+    }
+
+    /// The time between harvests for a mill of the given tier
+    pub fn tick_time_for_tier(tier: u8) -> f32 {
+        match tier {
+            0 | 1 => 1.0,
+            2 => 0.75,
+            _ => 0.5,
+        }
+    }
+
+    /// The distance (in tiles) the mill harvests from
+    pub fn range(&self) -> i32 {
+        self.range
+    }
+
+    /// Updates the mill range and speed to match the given tier
+    pub fn set_tier(&mut self, tier: u8) {
+        self.range = Self::range_for_tier(tier);
+        self.tick_time = Self::tick_time_for_tier(tier);
+    }
+
+    /// The average lumber harvested each second, assuming there are always
+    /// trees in range
+    pub fn lumber_per_second(&self) -> f32 {
+        LUMBER_PER_HARVEST as f32 * (1.0 - CHANCE_LUMBER_MILL_PLANTS_TREE as f32) / self.tick_time
+    }
+
     /// Find the next tree for the lumber mill to harvest
     fn find_next_target(
         &mut self,
@@ -114,7 +217,7 @@ impl LumberMill {
         target_terrain: TerrainType,
     ) -> Option<IVec2> {
         // first find all the available cells that are trees
-        let coords = map
+        let mut coords = map
             .cells_within_range(center, self.range)
             .filter(
                 // limit to trees and grass
@@ -129,6 +232,20 @@ impl LumberMill {
             return None;
         }
 
+        // chained mills only fall back to any tree once the clearings
+        // can't be extended
+        if self.harvest_mode == HarvestMode::Chained {
+            let next_to_dirt = coords
+                .iter()
+                .copied()
+                .filter(|coord| map.has_neighbour(*coord, TerrainType::Dirt))
+                .collect::<Vec<_>>();
+
+            if !next_to_dirt.is_empty() {
+                coords = next_to_dirt;
+            }
+        }
+
         // now pick one and move there
         let mut rng = rand::thread_rng();
         let idx = rng.gen_range(0..coords.len());
@@ -136,17 +253,62 @@ impl LumberMill {
     }
 }
 
+/// Shows the harvesting range of the lumber mill under the mouse, or of the
+/// mill being placed
+fn draw_lumber_mill_areas(
+    mut painter: ShapePainter,
+    map: Res<GameMap>,
+    mouse: Res<MousePosition>,
+    mode: Res<BuildingMode>,
+    mills: Query<(&Transform, &LumberMill)>,
+) {
+    let original_tx = painter.transform;
+    let mut color = GREEN_600;
+    color.alpha = 0.15;
+
+    painter.hollow = false;
+    painter.set_color(color);
+
+    // lumber mill sprites are 16px across
+    for (tx, mill) in &mills {
+        if tx.translation.truncate().distance(mouse.world_pos) > 8.0 {
+            continue;
+        }
+
+        painter.translate(tx.translation - Vec3::new(0.0, 0.0, 0.05));
+        painter.circle(mill.range as f32 * map.sprite_size);
+        painter.transform = original_tx;
+    }
+
+    if *mode == BuildingMode::PlaceLumberMill {
+        let center = map.world_coords(map.tile_coords(mouse.world_pos));
+        painter.translate(center.extend(0.05));
+        painter.circle(LumberMill::default().range as f32 * map.sprite_size);
+        painter.transform = original_tx;
+    }
+}
+
 const CHANCE_LUMBER_MILL_PLANTS_TREE: f64 = 0.15;
+const LUMBER_PER_HARVEST: i32 = 2;
 
 fn produce_from_lumber_mill(
     time: Res<Time>,
+    game_speed: Res<GameSpeed>,
     mut map: ResMut<GameMap>,
     mut resources: ResMut<PlayerResources>,
+    mut maybe_lumber_limit: Option<ResMut<ChallengeLumberLimit>>,
+    mut log: ResMut<EventLog>,
+    stats: Res<FireStats>,
     mut mills: Query<(&BuildingLocation, &mut LumberMill)>,
+    treasuries: Query<(), With<Treasury>>,
 ) {
-    let delta = time.delta_secs();
+    let delta = time.delta_secs() * game_speed.0;
     let mut rng = rand::thread_rng();
 
+    // each treasury raises the lumber cap
+    let treasury_count = treasuries.iter().count() as i32;
+    resources.max_lumber = BASE_MAX_LUMBER + treasury_count * TREASURY_CAPACITY;
+
     for (loc, mut mill) in &mut mills {
         let (target_terrain, new_terrain) = if rng.gen_bool(CHANCE_LUMBER_MILL_PLANTS_TREE) {
             (TerrainType::Grassland, TerrainType::Tree)
@@ -154,7 +316,7 @@ fn produce_from_lumber_mill(
             (TerrainType::Tree, TerrainType::Grassland)
         };
 
-        if mill.time_since_last_tick + delta <= 1.0 {
+        if mill.time_since_last_tick + delta <= mill.tick_time {
             mill.time_since_last_tick += delta;
             continue;
         }
@@ -163,27 +325,57 @@ fn produce_from_lumber_mill(
 
         // reduce the current cell
         let Some(coord) = mill.find_next_target(&mut map, loc.0, target_terrain) else {
+            if target_terrain == TerrainType::Tree && !mill.out_of_trees {
+                mill.out_of_trees = true;
+                log_event(
+                    &mut log,
+                    stats.duration,
+                    format!("Lumber mill at {} has no trees to harvest", loc.0),
+                );
+            }
             return;
         };
 
+        if target_terrain == TerrainType::Tree {
+            mill.out_of_trees = false;
+        }
+
         let Some(current) = map.get_mut(coord) else {
             warn!("Unable to find cell chosen for harvesting");
             return;
         };
         match current.terrain {
             TerrainType::Tree | TerrainType::Grassland => {
+                let harvesting = new_terrain == TerrainType::Grassland;
+
+                // challenge levels can cap the total lumber that is harvested
+                if harvesting
+                    && maybe_lumber_limit
+                        .as_ref()
+                        .is_some_and(|limit| limit.remaining <= 0)
+                {
+                    continue;
+                }
+
                 current.terrain = new_terrain;
                 current.mark_dirty();
 
-                if new_terrain == TerrainType::Grassland {
-                    resources.lumber += 2;
+                if harvesting {
+                    resources.lumber =
+                        (resources.lumber + LUMBER_PER_HARVEST).min(resources.max_lumber);
+                    mill.total_harvested += 1;
+
+                    if let Some(limit) = maybe_lumber_limit.as_mut() {
+                        limit.remaining -= LUMBER_PER_HARVEST;
+                    }
                 }
             }
             TerrainType::Dirt
             | TerrainType::Building
             | TerrainType::Stone
             | TerrainType::Fire
-            | TerrainType::Smoldering => {
+            | TerrainType::Smoldering
+            | TerrainType::Water => {
                 // nop
             }
         }
@@ -0,0 +1,156 @@
+//! Logic + code for placing mana batteries, which bank surplus mana and
+//! release it again when the player runs dry
+
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{
+    screens::{
+        PlayerResources, Screen,
+        gameplay::{
+            BuildTextHint, BuildingMode, MANA_BATTERY_COST_LUMBER,
+            building::{
+                BUILDING_FOOTPRINT_OFFSETS, BuildingAssets, BuildingLocation, BuildingType,
+                ManaEntityLink, ManaLine, ManaLineBalls, ManaSource,
+                TrackParentBuildingWhilePlacing, check_placement,
+            },
+        },
+    },
+    wildfire::{GameMap, TerrainType},
+};
+
+/// The amount of mana each battery can hold
+pub const MANA_BATTERY_CAPACITY: i32 = 200;
+/// The amount of mana released from storage each second once the player has run out
+pub const MANA_BATTERY_DISCHARGE_RATE: i32 = 5;
+/// Mana above this many seconds of income is considered surplus and is stored
+pub const MANA_SURPLUS_SECONDS: i32 = 10;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ManaBattery>();
+    app.register_type::<ManaStorage>();
+
+    app.init_resource::<ManaStorage>();
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpawnManaBattery(pub Vec2);
+
+impl Command for SpawnManaBattery {
+    fn apply(self, world: &mut World) {
+        let _ = world.run_system_cached_with(spawn_mana_battery, self);
+    }
+}
+
+fn spawn_mana_battery(
+    In(config): In<SpawnManaBattery>,
+    mut commands: Commands,
+    mut resources: ResMut<PlayerResources>,
+    mut building_mode: ResMut<BuildingMode>,
+    buildings: Res<BuildingAssets>,
+    mut map: ResMut<GameMap>,
+    parent_forge: Single<(Entity, &TrackParentBuildingWhilePlacing)>,
+    forges: Query<&Transform, ManaSource>,
+    existing: Query<&BuildingLocation>,
+    mut hint: ResMut<BuildTextHint>,
+) {
+    if resources.lumber < MANA_BATTERY_COST_LUMBER {
+        warn!("Not enough resources to spawn mana battery");
+        return;
+    }
+
+    let (parent_tracking_entity, parent_forge) = *parent_forge;
+    let Some(parent_forge) = parent_forge.entity else {
+        warn!("No parent mana forge inside tracking, skipping mana battery placement");
+        return;
+    };
+
+    let coords = map.tile_coords(config.0);
+    if !map.is_valid_coords(coords) {
+        warn!("Invalid map coordinates, aborting mana battery placement");
+        return;
+    }
+
+    if let Err(e) = check_placement(coords, &map, &existing) {
+        warn!("{e}, aborting mana battery placement");
+        hint.set(e.to_string());
+        return;
+    }
+
+    let Ok(parent_tx) = forges.get(parent_forge) else {
+        warn!("Unable to find parent mana forge");
+        return;
+    };
+
+    commands.entity(parent_tracking_entity).despawn();
+    resources.lumber -= MANA_BATTERY_COST_LUMBER;
+
+    let world_coords = map.world_coords(coords);
+    info!("Spawning mana battery at {coords}");
+
+    commands.spawn((
+        BuildingLocation(coords),
+        BuildingType::ManaBattery,
+        ManaBattery,
+        ManaLine::new(
+            parent_tx.translation.truncate().extend(0.05),
+            config.0.extend(0.05),
+        ),
+        ManaLineBalls::default(),
+        ManaEntityLink {
+            from_entity: parent_forge,
+            destruction_time: None,
+        },
+        StateScoped(Screen::Gameplay),
+        Transform::from_xyz(world_coords.x, world_coords.y, 0.1),
+        Visibility::Visible,
+        Sprite {
+            image: buildings.mana_battery.clone(),
+            custom_size: Some(Vec2::splat(16.0)),
+            anchor: Anchor::Center,
+            ..default()
+        },
+    ));
+
+    // update the map underneath to turn to buildings
+    BUILDING_FOOTPRINT_OFFSETS.iter().for_each(|offset| {
+        if let Some(cell) = map.get_mut(coords + *offset) {
+            cell.terrain = TerrainType::Building;
+        }
+    });
+
+    *building_mode = BuildingMode::None;
+}
+
+/// A building that adds [MANA_BATTERY_CAPACITY] to the player's [ManaStorage]
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct ManaBattery;
+
+/// The mana banked across all of the player's mana batteries
+#[derive(Resource, Reflect, Debug, Clone, Copy, Default)]
+#[reflect(Resource)]
+pub struct ManaStorage {
+    pub capacity: i32,
+    pub stored: i32,
+}
+
+impl ManaStorage {
+    /// Moves mana between the player and storage. Surplus mana is banked while
+    /// income exceeds drain, and is paid back out once the player runs dry.
+    /// This should be called once per second.
+    pub fn balance(&mut self, resources: &mut PlayerResources) {
+        // any mana held by a lost battery is lost with it
+        self.stored = self.stored.clamp(0, self.capacity);
+
+        let surplus_threshold = resources.mana_drain * MANA_SURPLUS_SECONDS;
+        if resources.mana_drain > 0 && resources.mana > surplus_threshold {
+            let banked = (resources.mana - surplus_threshold).min(self.capacity - self.stored);
+            self.stored += banked;
+            resources.mana -= banked;
+        } else if resources.mana <= 0 && self.stored > 0 {
+            let released = self.stored.min(MANA_BATTERY_DISCHARGE_RATE);
+            self.stored -= released;
+            resources.add_mana(released);
+        }
+    }
+}
@@ -1,17 +1,22 @@
 //! Logic + code for placing mana forge buildings
 
-use bevy::{prelude::*, sprite::Anchor};
+use bevy::{color::palettes::tailwind::SKY_500, prelude::*, sprite::Anchor};
+use bevy_vector_shapes::prelude::*;
 
 use crate::{
     Pause,
     screens::{
-        PlayerResources, Screen,
+        GameSpeed, PlayerResources, Screen,
         gameplay::{
-            BuildingMode, MANA_FORGE_COST_LUMBER,
+            BASE_MAX_MANA, BuildTextHint, BuildingMode, MANA_FORGE_COST_LUMBER,
             building::{
-                BUILDING_FOOTPRINT_OFFSETS, BuildingAssets, BuildingLocation, BuildingType,
-                ManaEntityLink, ManaLine, TrackParentBuildingWhilePlacing,
+                BUILDING_FOOTPRINT_OFFSETS, BuildingAssets, BuildingLocation, BuildingTier,
+                BuildingType, ManaEntityLink, ManaLine, TrackParentBuildingWhilePlacing,
+                check_placement,
+                ley_line::LEY_LINE_MANA_BONUS,
+                mana_battery::{MANA_BATTERY_CAPACITY, ManaBattery, ManaStorage},
             },
+            challenge::{ChallengeModifier, ChallengeModifiers},
         },
     },
     wildfire::{GameMap, TerrainType},
@@ -19,18 +24,27 @@ use crate::{
 
 /// The amount of mana a mana forge produces
 pub const MANA_FORGE_MANA_DRAIN: i32 = 5;
+/// The radius the production pulse ring grows to before disappearing, in
+/// pixels. The ring takes one production tick to reach it
+const FORGE_PULSE_RADIUS: f32 = 16.0;
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<ManaForge>();
+    app.register_type::<ManaForgePulse>();
 
     app.add_systems(
         Update,
-        (produce_from_mana_forge,).run_if(
+        (add_forge_pulse, produce_from_mana_forge).chain().run_if(
             in_state(Pause(false))
                 .and(in_state(Screen::Gameplay))
                 .and(resource_exists::<PlayerResources>),
         ),
     );
+
+    app.add_systems(
+        Update,
+        draw_forge_pulse.run_if(in_state(Screen::Gameplay).and(in_state(Pause(false)))),
+    );
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -51,7 +65,15 @@ fn spawn_mana_forge(
     mut map: ResMut<GameMap>,
     parent_forge: Single<(Entity, &TrackParentBuildingWhilePlacing)>,
     parents: Query<&Transform, With<BuildingType>>,
+    challenge: Res<ChallengeModifiers>,
+    existing: Query<&BuildingLocation>,
+    mut hint: ResMut<BuildTextHint>,
 ) {
+    if challenge.has(ChallengeModifier::NoManaForge) {
+        warn!("Mana forges are not allowed in this challenge!");
+        return;
+    }
+
     if resources.lumber < MANA_FORGE_COST_LUMBER {
         warn!("Not enough lumber to place mana forge!");
         return;
@@ -73,6 +95,12 @@ fn spawn_mana_forge(
         return;
     }
 
+    if let Err(e) = check_placement(coords, &map, &existing) {
+        warn!("{e}, aborting mana forge placement");
+        hint.set(e.to_string());
+        return;
+    }
+
     let clamped_world_coords = map.world_coords(coords);
 
     info!("Spawning mana forge at {coords}");
@@ -83,6 +111,7 @@ fn spawn_mana_forge(
         BuildingLocation(coords),
         BuildingType::ManaForge,
         ManaForge::default(),
+        BuildingTier::default(),
         ManaLine::new(parent_tx.translation, clamped_world_coords.extend(0.05)),
         ManaEntityLink {
             from_entity: parent_entity,
@@ -114,6 +143,10 @@ fn spawn_mana_forge(
 #[reflect(Component)]
 pub struct ManaForge {
     pub mana_per_second: i32,
+    /// The number of ley lines connecting this forge to other forges
+    pub ley_lines: i32,
+    /// The total mana produced since the forge was placed
+    pub total_mana: i64,
     time_since_last_tick: f32,
 }
 
@@ -121,25 +154,115 @@ impl Default for ManaForge {
     fn default() -> Self {
         Self {
             mana_per_second: MANA_FORGE_MANA_DRAIN,
+            ley_lines: 0,
+            total_mana: 0,
             time_since_last_tick: 0.,
         }
     }
 }
 
+/// A ring that expands out from a forge each time it produces mana
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct ManaForgePulse {
+    /// The current radius of the ring, in pixels
+    pub radius: f32,
+}
+
+impl ManaForge {
+    /// The amount of mana produced each second by a forge of the given tier.
+    ///
+    /// Tiers were first planned as 3, 5 and 8 mana per second, but a tier 1
+    /// forge already produces [MANA_FORGE_MANA_DRAIN] (5), so that would make
+    /// the tier 2 upgrade worthless. Each tier instead adds a bit more than
+    /// the last: 5, 8 and then 12
+    pub fn mana_per_second_for_tier(tier: u8) -> i32 {
+        match tier {
+            0 | 1 => MANA_FORGE_MANA_DRAIN,
+            2 => 8,
+            _ => 12,
+        }
+    }
+
+    /// Updates the forge production to match the given tier, keeping any
+    /// bonus from ley lines
+    pub fn set_tier(&mut self, tier: u8) {
+        self.mana_per_second =
+            Self::mana_per_second_for_tier(tier) + self.ley_lines * LEY_LINE_MANA_BONUS;
+    }
+}
+
 fn produce_from_mana_forge(
     time: Res<Time>,
+    game_speed: Res<GameSpeed>,
     mut player: ResMut<PlayerResources>,
-    mut forges: Query<&mut ManaForge>,
+    mut storage: ResMut<ManaStorage>,
+    mut since_last_battery_tick: Local<f32>,
+    mut forges: Query<(&mut ManaForge, &mut ManaForgePulse)>,
+    batteries: Query<(), With<ManaBattery>>,
 ) {
-    let delta = time.delta_secs();
+    let delta = time.delta_secs() * game_speed.0;
 
-    for mut forge in &mut forges {
+    // each battery raises the mana cap as well as banking surplus mana
+    let battery_count = batteries.iter().count() as i32;
+    player.max_mana = BASE_MAX_MANA + battery_count * MANA_BATTERY_CAPACITY;
+
+    for (mut forge, mut pulse) in &mut forges {
         if forge.time_since_last_tick + delta <= 1.0 {
             forge.time_since_last_tick += delta;
             continue;
         }
 
         forge.time_since_last_tick = 0.0;
-        player.mana += forge.mana_per_second;
+        pulse.radius = 0.0;
+        forge.total_mana += forge.mana_per_second as i64;
+        player.add_mana(forge.mana_per_second);
+    }
+
+    // batteries bank surplus mana and release it when the player runs out
+    storage.capacity = battery_count * MANA_BATTERY_CAPACITY;
+
+    *since_last_battery_tick += delta;
+    if *since_last_battery_tick >= 1.0 {
+        *since_last_battery_tick = 0.0;
+        storage.balance(&mut player);
+    }
+}
+
+fn add_forge_pulse(mut commands: Commands, forges: Query<Entity, Added<ManaForge>>) {
+    for forge in &forges {
+        commands.entity(forge).insert(ManaForgePulse::default());
+    }
+}
+
+/// Draws the production pulse ring, which grows in step with the forge's
+/// production tick so that it restarts each time mana is produced
+fn draw_forge_pulse(
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut painter: ShapePainter,
+    mut forges: Query<(&Transform, &mut ManaForgePulse)>,
+) {
+    let original_tx = painter.transform;
+    let delta = time.delta_secs() * game_speed.0;
+
+    for (tx, mut pulse) in &mut forges {
+        if pulse.radius >= FORGE_PULSE_RADIUS {
+            continue;
+        }
+
+        pulse.radius = (pulse.radius + delta * FORGE_PULSE_RADIUS).min(FORGE_PULSE_RADIUS);
+
+        // fade the ring out as it grows
+        let mut color = SKY_500;
+        color.alpha = 1.0 - pulse.radius / FORGE_PULSE_RADIUS;
+
+        painter.hollow = true;
+        painter.thickness = 1.0;
+        painter.set_color(color);
+        painter.translate(tx.translation - Vec3::new(0.0, 0.0, 0.05));
+        painter.circle(pulse.radius);
+
+        painter.transform = original_tx;
     }
 }
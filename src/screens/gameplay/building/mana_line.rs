@@ -1,7 +1,12 @@
-//! Code for drawing mana lines
+//! Code for drawing mana lines, and the network overlay (toggled with <j>)
+//! that shows which buildings each mana forge supplies
 
 use bevy::{
-    color::palettes::tailwind::{RED_600, SKY_500},
+    color::palettes::tailwind::{
+        AMBER_400, AMBER_500, EMERALD_500, PINK_500, RED_600, SKY_500, TEAL_500, VIOLET_500,
+    },
+    input::common_conditions::input_just_pressed,
+    platform::collections::HashMap,
     prelude::*,
 };
 // use bevy_simple_subsecond_system::hot;
@@ -11,26 +16,51 @@ use crate::{
     Pause,
     screens::{
         Screen,
-        gameplay::building::{ManaLine, ManaLineBalls},
+        gameplay::building::{
+            ManaEntityLink, ManaForge, ManaLine, ManaLineBalls, ManaSource, ley_line::LeyLine,
+        },
     },
 };
 
+/// The colours used for each island of buildings in the network overlay. If
+/// there are more forges than colours the colours are reused
+const NETWORK_ISLAND_COLOURS: [Srgba; 5] = [AMBER_500, EMERALD_500, VIOLET_500, PINK_500, TEAL_500];
+/// The maximum number of links followed when looking for the forge that
+/// supplies a building, in case the links ever form a loop
+const MAX_NETWORK_DEPTH: usize = 16;
+
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(
         Update,
         (draw_mana_lines, draw_mana_balls)
             .run_if(in_state(Screen::Gameplay).and(in_state(Pause(false)))),
     );
+
+    app.add_systems(
+        Update,
+        (
+            toggle_network_overlay.run_if(input_just_pressed(KeyCode::KeyJ)),
+            draw_network_overlay.run_if(resource_exists::<ShowNetworkOverlay>),
+        )
+            .run_if(in_state(Screen::Gameplay).and(in_state(Pause(false)))),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), remove_network_overlay);
 }
 
 // #[cfg_attr(target_os = "macos", hot)]
-fn draw_mana_lines(time: Res<Time>, mut painter: ShapePainter, lines: Query<&ManaLine>) {
-    for line in &lines {
+fn draw_mana_lines(
+    time: Res<Time>,
+    mut painter: ShapePainter,
+    lines: Query<(&ManaLine, Has<LeyLine>)>,
+) {
+    for (line, is_ley_line) in &lines {
         if line.disabled || line.destroying {
             continue;
         }
 
-        let mana_colour = SKY_500.lighter((3. * time.elapsed_secs()).cos() / 12.0);
+        // ley lines between forges glow gold rather than blue
+        let base_colour = if is_ley_line { AMBER_400 } else { SKY_500 };
+        let mana_colour = base_colour.lighter((3. * time.elapsed_secs()).cos() / 12.0);
         painter.thickness = 2.0;
         painter.set_color(mana_colour);
         painter.cap = Cap::Round;
@@ -78,3 +108,110 @@ fn draw_mana_balls(
         }
     }
 }
+
+/// When this resource exists the mana network is drawn over the map, with
+/// each building coloured by the mana forge that supplies it. Toggled with <j>.
+#[derive(Resource, Debug, Default)]
+pub struct ShowNetworkOverlay;
+
+fn toggle_network_overlay(mut commands: Commands, maybe_overlay: Option<Res<ShowNetworkOverlay>>) {
+    if maybe_overlay.is_some() {
+        info!("Hiding network overlay");
+        commands.remove_resource::<ShowNetworkOverlay>();
+    } else {
+        info!("Showing network overlay");
+        commands.init_resource::<ShowNetworkOverlay>();
+    }
+}
+
+fn remove_network_overlay(mut commands: Commands) {
+    commands.remove_resource::<ShowNetworkOverlay>();
+}
+
+fn draw_network_overlay(
+    time: Res<Time>,
+    mut painter: ShapePainter,
+    mana_forges: Query<(Entity, &Transform), With<ManaForge>>,
+    forges: Query<(&Transform, Option<&ManaEntityLink>), ManaSource>,
+    buildings: Query<(Entity, &Transform, &ManaEntityLink, Option<&ManaLine>), Without<ManaForge>>,
+    existing: Query<(), With<Transform>>,
+) {
+    let original_tx = painter.transform;
+
+    // give each forge a colour, sorted so the colours don't change each frame
+    let mut roots = mana_forges.iter().collect::<Vec<_>>();
+    roots.sort_by_key(|(entity, _)| *entity);
+    let island_colours = roots
+        .iter()
+        .enumerate()
+        .map(|(idx, (entity, _))| {
+            (
+                *entity,
+                NETWORK_ISLAND_COLOURS[idx % NETWORK_ISLAND_COLOURS.len()],
+            )
+        })
+        .collect::<HashMap<_, _>>();
+
+    // follows the links back through any relay towers to the supplying forge
+    let island_colour = |mut entity: Entity| {
+        for _ in 0..MAX_NETWORK_DEPTH {
+            if let Some(colour) = island_colours.get(&entity) {
+                return Some(*colour);
+            }
+            entity = forges.get(entity).ok()?.1?.from_entity;
+        }
+        None
+    };
+
+    for (entity, tx, link, maybe_line) in &buildings {
+        // lumber mills link to themselves and aren't part of the network
+        if link.from_entity == entity {
+            continue;
+        }
+
+        let to = tx.translation.truncate().extend(0.5);
+
+        let (from, line_colour, colour) = match forges.get(link.from_entity) {
+            Ok((from_tx, _)) => (
+                Some(from_tx.translation.truncate()),
+                SKY_500,
+                island_colour(link.from_entity).unwrap_or(SKY_500),
+            ),
+            // linked to something that doesn't supply mana
+            Err(_) if existing.contains(link.from_entity) => continue,
+            // the supplying building has gone, so point back to where it was
+            Err(_) => (
+                maybe_line.map(|line| line.from.truncate()),
+                RED_600,
+                RED_600,
+            ),
+        };
+
+        if let Some(from) = from {
+            painter.thickness = 4.0;
+            painter.cap = Cap::Round;
+            painter.set_color(line_colour);
+            painter.line(from.extend(0.5), to);
+        }
+
+        painter.hollow = false;
+        painter.set_color(colour);
+        painter.translate(to);
+        painter.circle(4.0);
+        painter.transform = original_tx;
+    }
+
+    // forges are drawn larger, with a slowly pulsing glow
+    let glow = 0.3 + (2.0 * time.elapsed_secs()).sin().abs() * 0.3;
+    for (entity, tx) in &roots {
+        let colour = island_colours.get(entity).copied().unwrap_or(SKY_500);
+
+        painter.hollow = false;
+        painter.translate(tx.translation.truncate().extend(0.5));
+        painter.set_color(colour.with_alpha(glow));
+        painter.circle(14.0);
+        painter.set_color(colour);
+        painter.circle(8.0);
+        painter.transform = original_tx;
+    }
+}
@@ -1,20 +1,23 @@
 //! Logic + code for placing minotaur hutch buildings
 
-use bevy::{color::palettes::tailwind::LIME_600, prelude::*, sprite::Anchor};
+use bevy::{
+    color::palettes::tailwind::LIME_600, ecs::world::OnDespawn, prelude::*, sprite::Anchor,
+};
 use bevy_vector_shapes::{prelude::ShapePainter, shapes::DiscPainter};
 use rand::Rng;
 
 use crate::{
     Pause,
     screens::{
-        PlayerResources, Screen,
+        GameSpeed, PlayerResources, Screen,
         gameplay::{
-            BuildingMode, MINOTAUR_COST_MANA,
+            BuildTextHint, BuildingMode, MINOTAUR_COST_MANA,
             building::{
                 BUILDING_FOOTPRINT_OFFSETS, BuildingAssets, BuildingLocation, BuildingType,
-                ManaEntityLink, ManaLine, ManaLineBalls, TrackParentBuildingWhilePlacing,
-                mana_forge::ManaForge,
+                ManaEntityLink, ManaLine, ManaLineBalls, ManaSource,
+                TrackParentBuildingWhilePlacing, check_placement,
             },
+            building_panel::SelectedBuilding,
         },
     },
     wildfire::{GameMap, TerrainType},
@@ -22,6 +25,20 @@ use crate::{
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<Minotaur>();
+    app.register_type::<MinotaurMode>();
+    app.register_type::<MinotaurPair>();
+
+    app.add_observer(unpair_despawned_minotaur);
+
+    app.add_systems(
+        Update,
+        detect_minotaur_pairs.run_if(
+            in_state(Pause(false))
+                .and(in_state(Screen::Gameplay))
+                .and(resource_exists::<GameMap>)
+                .and(resource_changed::<GameMap>),
+        ),
+    );
 
     app.add_systems(
         Update,
@@ -58,7 +75,9 @@ fn spawn_minotaur(
     buildings: Res<BuildingAssets>,
     mut map: ResMut<GameMap>,
     parent_forge: Single<(Entity, &TrackParentBuildingWhilePlacing)>,
-    forges: Query<&Transform, With<ManaForge>>,
+    forges: Query<&Transform, ManaSource>,
+    existing: Query<&BuildingLocation>,
+    mut hint: ResMut<BuildTextHint>,
 ) {
     if resources.mana < MINOTAUR_COST_MANA {
         warn!("Not enough resources to spawn minotaur");
@@ -77,6 +96,12 @@ fn spawn_minotaur(
         return;
     }
 
+    if let Err(e) = check_placement(coords, &map, &existing) {
+        warn!("{e}, aborting minotaur placement");
+        hint.set(e.to_string());
+        return;
+    }
+
     commands.entity(parent_forge_entity).despawn();
     resources.mana -= 30;
     resources.mana_drain -= 1;
@@ -123,6 +148,66 @@ fn spawn_minotaur(
     *building_mode = BuildingMode::None;
 }
 
+/// Switches a minotaur between clearing towards the fire and away from it
+#[derive(Debug, Clone, Copy)]
+pub struct ToggleMinotaurMode(pub Entity);
+
+impl Command for ToggleMinotaurMode {
+    fn apply(self, world: &mut World) {
+        let _ = world.run_system_cached_with(toggle_minotaur_mode, self);
+    }
+}
+
+fn toggle_minotaur_mode(
+    In(config): In<ToggleMinotaurMode>,
+    mut selected: ResMut<SelectedBuilding>,
+    mut minotaurs: Query<(&BuildingLocation, &mut Minotaur)>,
+) {
+    let Ok((loc, mut minotaur)) = minotaurs.get_mut(config.0) else {
+        warn!("Unable to find minotaur to change mode");
+        return;
+    };
+
+    minotaur.mode = minotaur.mode.toggled();
+    info!("Minotaur at {} is now {}", loc.0, minotaur.mode);
+
+    // redraw the building panel so it shows the new mode
+    selected.set_changed();
+}
+
+/// How a minotaur chooses where to clear next
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Reflect)]
+pub enum MinotaurMode {
+    /// Clears the cells closest to the fire, leaving a buffer in front of it
+    Aggressive,
+    /// Clears the cells furthest from the fire, making a firebreak
+    #[default]
+    Defensive,
+}
+
+impl MinotaurMode {
+    /// Gets the other mode
+    pub fn toggled(self) -> Self {
+        match self {
+            MinotaurMode::Aggressive => MinotaurMode::Defensive,
+            MinotaurMode::Defensive => MinotaurMode::Aggressive,
+        }
+    }
+}
+
+impl std::fmt::Display for MinotaurMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                MinotaurMode::Aggressive => "Aggressive",
+                MinotaurMode::Defensive => "Defensive",
+            }
+        )
+    }
+}
+
 /// A mana producing building
 #[derive(Component, Debug, Reflect)]
 #[reflect(Component)]
@@ -137,6 +222,10 @@ pub struct Minotaur {
     location: IVec2,
     /// The range of the minotaur (i.e. distance from the building location)
     range: i32,
+    /// Whether the minotaur clears towards or away from the fire
+    pub mode: MinotaurMode,
+    /// The number of grass cells the minotaur has eaten down to dirt
+    pub cells_cleared: u32,
 }
 
 impl Default for Minotaur {
@@ -146,15 +235,83 @@ impl Default for Minotaur {
             location: IVec2::ZERO,
             range: 6,
             time_since_last_consumed: 0.0,
+            mode: MinotaurMode::default(),
+            cells_cleared: 0,
         }
     }
 }
 
+/// The distance (in cells) within which two minotaurs will pair up
+const MINOTAUR_PAIR_RANGE: i32 = 8;
+/// How far (in cells) a paired minotaur will stray from the line between
+/// itself and its partner when picking cells to clear together
+const MINOTAUR_PAIR_CORRIDOR_WIDTH: f32 = 2.0;
+
+/// Links a minotaur to a nearby minotaur that it clears terrain with
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct MinotaurPair {
+    pub partner: Entity,
+}
+
+/// Pairs up any unpaired minotaurs that are close enough to work together
+fn detect_minotaur_pairs(
+    mut commands: Commands,
+    minotaurs: Query<(Entity, &BuildingLocation), (With<Minotaur>, Without<MinotaurPair>)>,
+) {
+    let mut unpaired = minotaurs
+        .iter()
+        .map(|(entity, loc)| (entity, loc.0))
+        .collect::<Vec<_>>();
+
+    while let Some((entity, loc)) = unpaired.pop() {
+        let Some(idx) = unpaired.iter().position(|(_, other)| {
+            loc.distance_squared(*other) <= MINOTAUR_PAIR_RANGE * MINOTAUR_PAIR_RANGE
+        }) else {
+            continue;
+        };
+
+        let (partner, partner_loc) = unpaired.swap_remove(idx);
+        info!("Minotaurs at {loc} and {partner_loc} are now working together");
+        commands.entity(entity).insert(MinotaurPair { partner });
+        commands
+            .entity(partner)
+            .insert(MinotaurPair { partner: entity });
+    }
+}
+
+/// When a paired minotaur is removed, frees its partner to pair up again
+fn unpair_despawned_minotaur(
+    trigger: Trigger<OnDespawn, MinotaurPair>,
+    mut commands: Commands,
+    pairs: Query<&MinotaurPair>,
+) {
+    let Ok(pair) = pairs.get(trigger.target()) else {
+        return;
+    };
+
+    // the partner may be despawning too, e.g. when leaving the gameplay screen
+    if pairs
+        .get(pair.partner)
+        .is_ok_and(|other| other.partner == trigger.target())
+    {
+        commands.entity(pair.partner).try_remove::<MinotaurPair>();
+    }
+}
+
 impl Minotaur {
-    /// Move the minotaur to a random new position
-    fn move_to_grass(&mut self, map: &mut GameMap, center: IVec2) {
+    /// Move the minotaur to a new position, picked based on the minotaur's
+    /// mode. If nothing is burning a random position is picked. Paired
+    /// minotaurs prefer cells between themselves and their partner
+    fn move_to_grass(
+        &mut self,
+        map: &mut GameMap,
+        center: IVec2,
+        mode: MinotaurMode,
+        partner: Option<IVec2>,
+    ) {
         // first find all the available cells that are grass or trees
-        let coords = map
+        let mut coords = map
             .cells_within_range(center, self.range)
             .filter(
                 // limit to trees and grass
@@ -174,6 +331,33 @@ impl Minotaur {
             return;
         }
 
+        // work on the ground between the pair where there is any left
+        if let Some(partner) = partner {
+            let between = coords
+                .iter()
+                .copied()
+                .filter(|coord| {
+                    distance_to_segment(*coord, center, partner) <= MINOTAUR_PAIR_CORRIDOR_WIDTH
+                })
+                .collect::<Vec<_>>();
+            if !between.is_empty() {
+                coords = between;
+            }
+        }
+
+        // prefer cells based on how close they are to the fire
+        if map.any_on_fire() {
+            coords.sort_by_cached_key(|coord| {
+                let distance = map.distance_squared_to_fire(*coord).unwrap_or(i32::MAX);
+                match mode {
+                    MinotaurMode::Aggressive => distance,
+                    MinotaurMode::Defensive => -distance,
+                }
+            });
+            self.location = coords[0];
+            return;
+        }
+
         // now pick one and move there
         let mut rng = rand::thread_rng();
         let idx = rng.gen_range(0..coords.len());
@@ -181,6 +365,19 @@ impl Minotaur {
     }
 }
 
+/// The distance from a cell to the line segment between two other cells
+fn distance_to_segment(point: IVec2, start: IVec2, end: IVec2) -> f32 {
+    let (point, start, end) = (point.as_vec2(), start.as_vec2(), end.as_vec2());
+    let segment = end - start;
+    let length_squared = segment.length_squared();
+    if length_squared == 0.0 {
+        return point.distance(start);
+    }
+
+    let t = ((point - start).dot(segment) / length_squared).clamp(0.0, 1.0);
+    point.distance(start + segment * t)
+}
+
 fn draw_minotaur_areas(
     mut painter: ShapePainter,
     map: Res<GameMap>,
@@ -203,17 +400,28 @@ fn draw_minotaur_areas(
 
 const MANA_CONSUMPTION_TIME: f32 = 1.0;
 const PRODUCTION_TIME: f32 = 0.3;
+/// Paired minotaurs clear terrain twice as quickly
+const PAIRED_PRODUCTION_TIME: f32 = PRODUCTION_TIME / 2.0;
 
 // #[cfg_attr(target_os = "macos", hot)]
 fn produce_from_minotaur(
     time: Res<Time>,
+    game_speed: Res<GameSpeed>,
     mut map: ResMut<GameMap>,
     mut resources: ResMut<PlayerResources>,
-    mut forges: Query<(&BuildingLocation, &mut Minotaur)>,
+    mut forges: Query<(&BuildingLocation, &mut Minotaur, Option<&MinotaurPair>)>,
+    partners: Query<&BuildingLocation, With<Minotaur>>,
 ) {
-    let delta = time.delta_secs();
+    let delta = time.delta_secs() * game_speed.0;
+
+    for (loc, mut minotaur, maybe_pair) in &mut forges {
+        let partner = maybe_pair.and_then(|pair| partners.get(pair.partner).ok().map(|p| p.0));
+        let production_time = if partner.is_some() {
+            PAIRED_PRODUCTION_TIME
+        } else {
+            PRODUCTION_TIME
+        };
 
-    for (loc, mut minotaur) in &mut forges {
         // consume mana if its time
         minotaur.time_since_last_consumed += delta;
         if minotaur.time_since_last_consumed >= MANA_CONSUMPTION_TIME {
@@ -222,7 +430,7 @@ fn produce_from_minotaur(
             // if we dont have enough, don't produce until we've recharged mana
             if resources.mana <= 0 {
                 info!("Not enough mana to produce from minotaur at {}", loc.0);
-                minotaur.time_since_last_tick = -MANA_CONSUMPTION_TIME + PRODUCTION_TIME;
+                minotaur.time_since_last_tick = -MANA_CONSUMPTION_TIME + production_time;
                 continue;
             }
 
@@ -231,10 +439,10 @@ fn produce_from_minotaur(
 
         // check if its time for the minotaur to eat
         minotaur.time_since_last_tick += delta;
-        if minotaur.time_since_last_tick + delta < PRODUCTION_TIME {
+        if minotaur.time_since_last_tick + delta < production_time {
             continue;
         }
-        minotaur.time_since_last_tick -= PRODUCTION_TIME;
+        minotaur.time_since_last_tick -= production_time;
 
         // reduce the current cell
         if let Some(current) = map.get_mut(minotaur.location) {
@@ -242,6 +450,7 @@ fn produce_from_minotaur(
                 TerrainType::Grassland => {
                     current.terrain = TerrainType::Dirt;
                     current.mark_dirty();
+                    minotaur.cells_cleared += 1;
                 }
                 TerrainType::Tree => {
                     current.terrain = TerrainType::Grassland;
@@ -253,13 +462,15 @@ fn produce_from_minotaur(
                 | TerrainType::Dirt
                 | TerrainType::Stone
                 | TerrainType::Fire
-                | TerrainType::Smoldering => {
+                | TerrainType::Smoldering
+                | TerrainType::Water => {
                     // nop
                 }
             }
         }
 
         // move the minotaur in a spiral
-        minotaur.move_to_grass(&mut map, loc.0);
+        let mode = minotaur.mode;
+        minotaur.move_to_grass(&mut map, loc.0, mode, partner);
     }
 }
@@ -0,0 +1,55 @@
+//! Tints the building following the cursor while placing it, green where it
+//! can be placed and red where it can't, so the player doesn't have to click
+//! to find out
+
+use bevy::{
+    color::palettes::tailwind::{GREEN_400, RED_500},
+    prelude::*,
+};
+
+use crate::{
+    Pause,
+    input::MousePosition,
+    screens::{
+        PlayerResources, Screen,
+        gameplay::{
+            BuildingMode, CursorModeFollower,
+            building::{BuildingLocation, TrackParentBuildingWhilePlacing, is_placement_valid},
+        },
+    },
+    wildfire::GameMap,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        tint_placement_preview.run_if(
+            in_state(Screen::Gameplay)
+                .and(in_state(Pause(false)))
+                .and(resource_exists::<GameMap>)
+                .and(resource_exists::<PlayerResources>),
+        ),
+    );
+}
+
+fn tint_placement_preview(
+    mode: Res<BuildingMode>,
+    mouse: Res<MousePosition>,
+    map: Res<GameMap>,
+    resources: Res<PlayerResources>,
+    existing: Query<&BuildingLocation>,
+    mut followers: Query<
+        (&mut Sprite, Option<&TrackParentBuildingWhilePlacing>),
+        With<CursorModeFollower>,
+    >,
+) {
+    let Some(building_type) = mode.placed_building() else {
+        return;
+    };
+
+    let coords = map.tile_coords(mouse.world_pos);
+    for (mut sprite, parent) in &mut followers {
+        let valid = is_placement_valid(building_type, coords, &map, &resources, &existing, parent);
+        sprite.color = if valid { GREEN_400 } else { RED_500 }.into();
+    }
+}
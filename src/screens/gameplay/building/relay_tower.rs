@@ -0,0 +1,126 @@
+//! Logic + code for placing relay towers, which carry mana further from a forge
+
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{
+    screens::{
+        PlayerResources, Screen,
+        gameplay::{
+            BuildTextHint, BuildingMode, RELAY_TOWER_COST_LUMBER,
+            building::{
+                BUILDING_FOOTPRINT_OFFSETS, BuildingAssets, BuildingLocation, BuildingType,
+                ManaEntityLink, ManaLine, ManaLineBalls, ManaSource,
+                TrackParentBuildingWhilePlacing, check_placement,
+            },
+        },
+    },
+    wildfire::{GameMap, TerrainType},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<RelayTower>();
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpawnRelayTower(pub Vec2);
+
+impl Command for SpawnRelayTower {
+    fn apply(self, world: &mut World) {
+        let _ = world.run_system_cached_with(spawn_relay_tower, self);
+    }
+}
+
+fn spawn_relay_tower(
+    In(config): In<SpawnRelayTower>,
+    mut commands: Commands,
+    mut resources: ResMut<PlayerResources>,
+    mut building_mode: ResMut<BuildingMode>,
+    buildings: Res<BuildingAssets>,
+    mut map: ResMut<GameMap>,
+    parent_forge: Single<(Entity, &TrackParentBuildingWhilePlacing)>,
+    forges: Query<&Transform, ManaSource>,
+    existing: Query<&BuildingLocation>,
+    mut hint: ResMut<BuildTextHint>,
+) {
+    if resources.lumber < RELAY_TOWER_COST_LUMBER {
+        warn!("Not enough resources to spawn relay tower");
+        return;
+    }
+
+    let (parent_tracking_entity, parent_forge) = *parent_forge;
+    let Some(parent_forge) = parent_forge.entity else {
+        warn!("No parent mana forge or relay inside tracking, skipping relay tower placement");
+        return;
+    };
+
+    let coords = map.tile_coords(config.0);
+    if !map.is_valid_coords(coords) {
+        warn!("Invalid map coordinates, aborting relay tower placement");
+        return;
+    }
+
+    if let Err(e) = check_placement(coords, &map, &existing) {
+        warn!("{e}, aborting relay tower placement");
+        hint.set(e.to_string());
+        return;
+    }
+
+    let Ok(parent_tx) = forges.get(parent_forge) else {
+        warn!("Unable to find parent mana forge or relay");
+        return;
+    };
+
+    commands.entity(parent_tracking_entity).despawn();
+    resources.lumber -= RELAY_TOWER_COST_LUMBER;
+
+    let world_coords = map.world_coords(coords);
+    info!("Spawning relay tower at {coords}");
+
+    commands.spawn((
+        BuildingLocation(coords),
+        BuildingType::RelayTower,
+        RelayTower::default(),
+        ManaLine::new(
+            parent_tx.translation.truncate().extend(0.05),
+            config.0.extend(0.05),
+        ),
+        ManaLineBalls::default(),
+        ManaEntityLink {
+            from_entity: parent_forge,
+            destruction_time: None,
+        },
+        StateScoped(Screen::Gameplay),
+        Transform::from_xyz(world_coords.x, world_coords.y, 0.1),
+        Visibility::Visible,
+        Sprite {
+            image: buildings.relay_tower.clone(),
+            custom_size: Some(Vec2::splat(16.0)),
+            anchor: Anchor::Center,
+            ..default()
+        },
+    ));
+
+    // update the map underneath to turn to buildings
+    BUILDING_FOOTPRINT_OFFSETS.iter().for_each(|offset| {
+        if let Some(cell) = map.get_mut(coords + *offset) {
+            cell.terrain = TerrainType::Building;
+        }
+    });
+
+    *building_mode = BuildingMode::None;
+}
+
+/// A building that passes mana on from a forge (or another relay), so that
+/// buildings can be placed further away
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct RelayTower {
+    /// How far away buildings can be linked to the relay
+    pub reach: f32,
+}
+
+impl Default for RelayTower {
+    fn default() -> Self {
+        Self { reach: 60.0 }
+    }
+}
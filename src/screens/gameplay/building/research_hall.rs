@@ -0,0 +1,232 @@
+//! Logic + code for placing research halls, which slowly unlock new buildings
+
+use bevy::{platform::collections::HashSet, prelude::*, sprite::Anchor};
+
+use crate::{
+    Pause,
+    screens::{
+        GameSpeed, PlayerResources, Screen,
+        gameplay::{
+            BuildTextHint, BuildingMode, RESEARCH_HALL_COST_LUMBER,
+            building::{
+                BUILDING_FOOTPRINT_OFFSETS, BuildingAssets, BuildingLocation, BuildingType,
+                ManaEntityLink, ManaLine, ManaLineBalls, ManaSource,
+                TrackParentBuildingWhilePlacing, check_placement,
+            },
+        },
+    },
+    wildfire::{GameMap, TerrainType},
+};
+
+/// The time (in seconds) it takes a research hall to unlock a new building
+const RESEARCH_TIME: f32 = 60.0;
+
+/// The buildings that are available without any research
+const STARTING_BUILDINGS: [BuildingType; 8] = [
+    BuildingType::CityHall,
+    BuildingType::ManaForge,
+    BuildingType::LumberMill,
+    BuildingType::ResearchHall,
+    BuildingType::RelayTower,
+    BuildingType::Sprinkler,
+    BuildingType::Treasury,
+    BuildingType::RiverFerry,
+];
+
+/// The order that buildings are unlocked by research halls
+const RESEARCH_ORDER: [BuildingType; 9] = [
+    BuildingType::Minotaur,
+    BuildingType::WaterGolem,
+    BuildingType::RiverGolem,
+    BuildingType::StormMage,
+    BuildingType::EmberGuard,
+    BuildingType::BeaconTower,
+    BuildingType::Arborist,
+    BuildingType::ManaBattery,
+    BuildingType::FireFighter,
+];
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ResearchHall>();
+    app.register_type::<ResearchState>();
+    app.register_type::<OnBuildingUnlocked>();
+
+    app.add_systems(
+        Update,
+        research_from_halls.run_if(
+            in_state(Pause(false))
+                .and(in_state(Screen::Gameplay))
+                .and(resource_exists::<ResearchState>),
+        ),
+    );
+}
+
+/// Triggered when a research hall unlocks a new building
+#[derive(Event, Reflect, Debug, Clone, Copy)]
+pub struct OnBuildingUnlocked(pub BuildingType);
+
+/// The buildings that the player is currently able to place
+#[derive(Resource, Reflect, Debug, Clone)]
+#[reflect(Resource)]
+pub struct ResearchState {
+    pub unlocked: HashSet<BuildingType>,
+}
+
+impl Default for ResearchState {
+    fn default() -> Self {
+        Self {
+            unlocked: HashSet::from_iter(STARTING_BUILDINGS),
+        }
+    }
+}
+
+impl ResearchState {
+    /// A research state with every building unlocked, i.e. for story mode
+    pub fn all_unlocked() -> Self {
+        Self {
+            unlocked: HashSet::from_iter(STARTING_BUILDINGS.into_iter().chain(RESEARCH_ORDER)),
+        }
+    }
+
+    /// Whether the given building can be placed
+    pub fn is_unlocked(&self, building_type: BuildingType) -> bool {
+        self.unlocked.contains(&building_type)
+    }
+
+    /// The next building that will be unlocked by research, if any
+    pub fn next_unlock(&self) -> Option<BuildingType> {
+        RESEARCH_ORDER
+            .into_iter()
+            .find(|building_type| !self.is_unlocked(*building_type))
+    }
+}
+
+/// A run condition that checks if the given building has been researched
+pub fn building_unlocked(
+    building_type: BuildingType,
+) -> impl Fn(Option<Res<ResearchState>>) -> bool {
+    move |research| research.is_none_or(|r| r.is_unlocked(building_type))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpawnResearchHall(pub Vec2);
+
+impl Command for SpawnResearchHall {
+    fn apply(self, world: &mut World) {
+        let _ = world.run_system_cached_with(spawn_research_hall, self);
+    }
+}
+
+fn spawn_research_hall(
+    In(config): In<SpawnResearchHall>,
+    mut commands: Commands,
+    mut resources: ResMut<PlayerResources>,
+    mut building_mode: ResMut<BuildingMode>,
+    buildings: Res<BuildingAssets>,
+    mut map: ResMut<GameMap>,
+    parent_forge: Single<(Entity, &TrackParentBuildingWhilePlacing)>,
+    forges: Query<&Transform, ManaSource>,
+    existing: Query<&BuildingLocation>,
+    mut hint: ResMut<BuildTextHint>,
+) {
+    if resources.lumber < RESEARCH_HALL_COST_LUMBER {
+        warn!("Not enough resources to spawn research hall");
+        return;
+    }
+
+    let (parent_tracking_entity, parent_forge) = *parent_forge;
+    let Some(parent_forge) = parent_forge.entity else {
+        warn!("No parent mana forge inside tracking, skipping research hall placement");
+        return;
+    };
+
+    let coords = map.tile_coords(config.0);
+    if !map.is_valid_coords(coords) {
+        warn!("Invalid map coordinates, aborting research hall placement");
+        return;
+    }
+
+    if let Err(e) = check_placement(coords, &map, &existing) {
+        warn!("{e}, aborting research hall placement");
+        hint.set(e.to_string());
+        return;
+    }
+
+    let Ok(parent_tx) = forges.get(parent_forge) else {
+        warn!("Unable to find parent mana forge");
+        return;
+    };
+
+    commands.entity(parent_tracking_entity).despawn();
+    resources.lumber -= RESEARCH_HALL_COST_LUMBER;
+
+    let world_coords = map.world_coords(coords);
+    info!("Spawning research hall at {coords}");
+
+    commands.spawn((
+        BuildingLocation(coords),
+        BuildingType::ResearchHall,
+        ResearchHall::default(),
+        ManaLine::new(
+            parent_tx.translation.truncate().extend(0.05),
+            config.0.extend(0.05),
+        ),
+        ManaLineBalls::default(),
+        ManaEntityLink {
+            from_entity: parent_forge,
+            destruction_time: None,
+        },
+        StateScoped(Screen::Gameplay),
+        Transform::from_xyz(world_coords.x, world_coords.y, 0.1),
+        Visibility::Visible,
+        Sprite {
+            image: buildings.research_hall.clone(),
+            custom_size: Some(Vec2::splat(16.0)),
+            anchor: Anchor::Center,
+            ..default()
+        },
+    ));
+
+    // update the map underneath to turn to buildings
+    BUILDING_FOOTPRINT_OFFSETS.iter().for_each(|offset| {
+        if let Some(cell) = map.get_mut(coords + *offset) {
+            cell.terrain = TerrainType::Building;
+        }
+    });
+
+    *building_mode = BuildingMode::None;
+}
+
+/// A building that unlocks a new building type every [RESEARCH_TIME] seconds
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct ResearchHall {
+    /// The time since the hall last unlocked a building
+    time_since_last_unlock: f32,
+}
+
+fn research_from_halls(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut research: ResMut<ResearchState>,
+    mut halls: Query<&mut ResearchHall>,
+) {
+    let delta = time.delta_secs() * game_speed.0;
+
+    for mut hall in &mut halls {
+        hall.time_since_last_unlock += delta;
+        if hall.time_since_last_unlock < RESEARCH_TIME {
+            continue;
+        }
+        hall.time_since_last_unlock = 0.0;
+
+        let Some(next) = research.next_unlock() else {
+            continue;
+        };
+
+        info!("Research complete, unlocked {next}");
+        research.unlocked.insert(next);
+        commands.trigger(OnBuildingUnlocked(next));
+    }
+}
@@ -0,0 +1,344 @@
+//! Logic + code for restoring buildings from a saved game. Restored buildings
+//! are free and re-link to the nearest parent building.
+
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{
+    screens::{
+        OnRedrawToolbar, RequiresCityHall, Screen,
+        gameplay::building::{
+            BUILDING_FOOTPRINT_OFFSETS, BuildingAssets, BuildingLocation, BuildingTier,
+            BuildingType, CityHall, MageRotation, ManaEntityLink, ManaLine, ManaLineBalls,
+            arborist::Arborist,
+            beacon_tower::BeaconTower,
+            ember_guard::EmberGuard,
+            fire_fighter::{FireFighter, fire_fighter_unit},
+            lumber_mill::LumberMill,
+            mana_battery::ManaBattery,
+            mana_forge::ManaForge,
+            minotaur::Minotaur,
+            relay_tower::RelayTower,
+            research_hall::ResearchHall,
+            river_ferry::WaterCrossing,
+            river_golem::RiverGolem,
+            sprinkler::Sprinkler,
+            storm_mage::StormMage,
+            treasury::Treasury,
+            water_golem::WaterGolem,
+        },
+    },
+    wildfire::{GameMap, TerrainType},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<SavedBuilding>();
+    app.register_type::<Vec<SavedBuilding>>();
+}
+
+/// A building as stored in a saved game
+#[derive(Reflect, Debug, Clone)]
+pub struct SavedBuilding {
+    pub building_type: BuildingType,
+    pub location: IVec2,
+    /// The tier of upgradeable buildings
+    pub tier: Option<u8>,
+    /// The rotation of storm mages
+    pub rotation: Option<MageRotation>,
+}
+
+/// Gets all the buildings on the map in a format that can be saved
+pub fn saved_buildings(
+    buildings: Query<(
+        &BuildingLocation,
+        &BuildingType,
+        Option<&BuildingTier>,
+        Option<&StormMage>,
+    )>,
+) -> Vec<SavedBuilding> {
+    buildings
+        .iter()
+        .map(|(loc, building_type, tier, mage)| SavedBuilding {
+            building_type: *building_type,
+            location: loc.0,
+            tier: tier.map(|t| t.0),
+            rotation: mage.map(|m| m.rotation()),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct RestoreBuildings(pub Vec<SavedBuilding>);
+
+impl Command for RestoreBuildings {
+    fn apply(self, world: &mut World) {
+        let _ = world.run_system_cached_with(restore_buildings, self);
+    }
+}
+
+/// The order buildings are restored in, so that parents exist before children
+fn restore_order(building_type: BuildingType) -> u8 {
+    match building_type {
+        BuildingType::CityHall => 0,
+        BuildingType::ManaForge => 1,
+        BuildingType::RelayTower => 2,
+        BuildingType::LumberMill
+        | BuildingType::Arborist
+        | BuildingType::Minotaur
+        | BuildingType::StormMage
+        | BuildingType::WaterGolem
+        | BuildingType::EmberGuard
+        | BuildingType::BeaconTower
+        | BuildingType::ResearchHall
+        | BuildingType::ManaBattery
+        | BuildingType::FireFighter
+        | BuildingType::Sprinkler
+        | BuildingType::Treasury
+        | BuildingType::RiverGolem
+        | BuildingType::RiverFerry => 3,
+    }
+}
+
+/// Finds the closest of the candidate parent buildings
+fn nearest(candidates: &[(Entity, Vec3)], translation: Vec3) -> Option<(Entity, Vec3)> {
+    candidates
+        .iter()
+        .min_by(|a, b| {
+            a.1.distance_squared(translation)
+                .total_cmp(&b.1.distance_squared(translation))
+        })
+        .copied()
+}
+
+fn restore_buildings(
+    In(config): In<RestoreBuildings>,
+    mut commands: Commands,
+    buildings: Res<BuildingAssets>,
+    mut map: ResMut<GameMap>,
+) {
+    let mut saved = config.0;
+    saved.sort_by_key(|b| restore_order(b.building_type));
+
+    // mana forges can be linked to the city hall or other forges
+    let mut forge_parents: Vec<(Entity, Vec3)> = Vec::new();
+    let mut forges: Vec<(Entity, Vec3)> = Vec::new();
+
+    for building in saved {
+        let coords = building.location;
+        let translation = map.world_coords(coords).extend(0.1);
+        let tier = building.tier.unwrap_or(1);
+        let rotation = building.rotation.unwrap_or_default();
+
+        let image = match building.building_type {
+            BuildingType::CityHall => buildings.city_hall.clone(),
+            BuildingType::ManaForge => buildings.mana_forge.clone(),
+            BuildingType::Minotaur => buildings.minotaur.clone(),
+            BuildingType::LumberMill => buildings.lumber_mill.clone(),
+            BuildingType::StormMage => buildings.storm_mage.clone(),
+            BuildingType::WaterGolem => buildings.water_golem.clone(),
+            BuildingType::EmberGuard => buildings.ember_guard.clone(),
+            BuildingType::Arborist => buildings.arborist.clone(),
+            BuildingType::BeaconTower => buildings.beacon_tower.clone(),
+            BuildingType::ResearchHall => buildings.research_hall.clone(),
+            BuildingType::RelayTower => buildings.relay_tower.clone(),
+            BuildingType::ManaBattery => buildings.mana_battery.clone(),
+            BuildingType::FireFighter => buildings.fire_fighter.clone(),
+            BuildingType::Sprinkler => buildings.sprinkler.clone(),
+            BuildingType::Treasury => buildings.treasury.clone(),
+            BuildingType::RiverGolem => buildings.river_golem.clone(),
+            BuildingType::RiverFerry => buildings.river_ferry.clone(),
+        };
+
+        let parent = match building.building_type {
+            BuildingType::CityHall
+            | BuildingType::LumberMill
+            | BuildingType::Arborist
+            | BuildingType::Sprinkler
+            | BuildingType::Treasury
+            | BuildingType::RiverFerry => None,
+            BuildingType::ManaForge => nearest(&forge_parents, translation),
+            BuildingType::Minotaur
+            | BuildingType::StormMage
+            | BuildingType::WaterGolem
+            | BuildingType::EmberGuard
+            | BuildingType::BeaconTower
+            | BuildingType::ResearchHall
+            | BuildingType::RelayTower
+            | BuildingType::ManaBattery
+            | BuildingType::FireFighter
+            | BuildingType::RiverGolem => nearest(&forges, translation),
+        };
+
+        let needs_parent = !matches!(
+            building.building_type,
+            BuildingType::CityHall
+                | BuildingType::LumberMill
+                | BuildingType::Arborist
+                | BuildingType::Sprinkler
+                | BuildingType::Treasury
+                | BuildingType::RiverFerry
+        );
+        if needs_parent && parent.is_none() {
+            warn!(
+                "Unable to find a parent for saved {:?} at {coords}, skipping",
+                building.building_type
+            );
+            continue;
+        }
+
+        info!("Restoring {:?} at {coords}", building.building_type);
+        let mut transform = Transform::from_translation(translation);
+        if building.building_type == BuildingType::StormMage {
+            transform.rotate_z(rotation.as_angle_rads());
+        }
+
+        let mut cmds = commands.spawn((
+            BuildingLocation(coords),
+            building.building_type,
+            StateScoped(Screen::Gameplay),
+            transform,
+            Visibility::Visible,
+            Sprite {
+                image,
+                custom_size: Some(Vec2::splat(16.0)),
+                anchor: Anchor::Center,
+                ..default()
+            },
+        ));
+        let id = cmds.id();
+
+        if let Some((parent_entity, parent_translation)) = parent {
+            cmds.insert((
+                ManaLine::new(
+                    parent_translation.truncate().extend(0.05),
+                    translation.truncate().extend(0.05),
+                ),
+                ManaEntityLink {
+                    from_entity: parent_entity,
+                    destruction_time: None,
+                },
+            ));
+        }
+
+        match building.building_type {
+            BuildingType::CityHall => {
+                cmds.insert(CityHall);
+                forge_parents.push((id, translation));
+                commands.remove_resource::<RequiresCityHall>();
+                commands.trigger(OnRedrawToolbar);
+            }
+            BuildingType::ManaForge => {
+                let mut forge = ManaForge::default();
+                forge.set_tier(tier);
+                cmds.insert((forge, BuildingTier(tier)));
+                forge_parents.push((id, translation));
+                forges.push((id, translation));
+            }
+            BuildingType::LumberMill => {
+                let mut mill = LumberMill::default();
+                mill.set_tier(tier);
+                // lumber mills are linked to themselves, see `spawn_lumber_mill`
+                cmds.insert((
+                    mill,
+                    BuildingTier(tier),
+                    ManaEntityLink {
+                        from_entity: id,
+                        destruction_time: None,
+                    },
+                ));
+            }
+            BuildingType::Arborist => {
+                // arborists are linked to themselves, see `spawn_arborist`
+                cmds.insert((
+                    Arborist::default(),
+                    ManaEntityLink {
+                        from_entity: id,
+                        destruction_time: None,
+                    },
+                ));
+            }
+            BuildingType::Sprinkler => {
+                // sprinklers are linked to themselves, see `spawn_sprinkler`
+                cmds.insert((
+                    Sprinkler::default(),
+                    ManaEntityLink {
+                        from_entity: id,
+                        destruction_time: None,
+                    },
+                ));
+            }
+            BuildingType::Treasury => {
+                // treasuries are linked to themselves, see `spawn_treasury`
+                cmds.insert((
+                    Treasury,
+                    ManaEntityLink {
+                        from_entity: id,
+                        destruction_time: None,
+                    },
+                ));
+            }
+            BuildingType::Minotaur => {
+                cmds.insert((Minotaur::default(), ManaLineBalls::default()));
+            }
+            BuildingType::WaterGolem => {
+                cmds.insert((WaterGolem::default(), ManaLineBalls::default()));
+            }
+            BuildingType::RiverGolem => {
+                cmds.insert((RiverGolem::default(), ManaLineBalls::default()));
+            }
+            BuildingType::StormMage => {
+                // the mage wind isn't saved with the map, so re-apply it
+                let mut mage = StormMage::new(rotation);
+                mage.apply_to_map(coords, rotation, &mut map);
+                cmds.insert((mage, ManaLineBalls::default()));
+            }
+            BuildingType::EmberGuard => {
+                // the ember resistance isn't saved with the map, so re-apply it
+                let guard = EmberGuard::default();
+                guard.apply_to_map(coords, &mut map);
+                cmds.insert((guard, ManaLineBalls::default()));
+            }
+            BuildingType::BeaconTower => {
+                cmds.insert((BeaconTower::default(), ManaLineBalls::default()));
+            }
+            BuildingType::ResearchHall => {
+                cmds.insert((ResearchHall::default(), ManaLineBalls::default()));
+            }
+            BuildingType::ManaBattery => {
+                cmds.insert((ManaBattery, ManaLineBalls::default()));
+            }
+            BuildingType::FireFighter => {
+                cmds.insert((
+                    FireFighter::new(coords),
+                    ManaLineBalls::default(),
+                    children![fire_fighter_unit(&buildings)],
+                ));
+            }
+            BuildingType::RelayTower => {
+                cmds.insert((RelayTower::default(), ManaLineBalls::default()));
+                // relays pass on mana, so later buildings can be linked to them
+                forges.push((id, translation));
+            }
+            BuildingType::RiverFerry => {
+                // the crossings aren't saved with the map, so re-apply them.
+                // Ferries are linked to themselves, see `spawn_river_ferry`
+                let crossing = WaterCrossing(coords);
+                crossing.apply_to_map(&mut map);
+                cmds.insert((
+                    crossing,
+                    ManaEntityLink {
+                        from_entity: id,
+                        destruction_time: None,
+                    },
+                ));
+                // ferries float on the water rather than covering it
+                continue;
+            }
+        }
+
+        BUILDING_FOOTPRINT_OFFSETS.iter().for_each(|offset| {
+            if let Some(cell) = map.get_mut(coords + *offset) {
+                cell.terrain = TerrainType::Building;
+            }
+        });
+    }
+}
@@ -0,0 +1,139 @@
+//! Logic + code for placing river ferries. Ferries are built on water and let
+//! other buildings, fire fighters and goblins use the water around them
+
+use bevy::{ecs::world::OnDespawn, prelude::*, sprite::Anchor};
+
+use crate::{
+    screens::{
+        PlayerResources, Screen,
+        gameplay::{
+            BuildTextHint, BuildingMode, RIVER_FERRY_COST_LUMBER,
+            building::{
+                BUILDING_FOOTPRINT_OFFSETS, BuildingAssets, BuildingLocation, BuildingType,
+                ManaEntityLink, check_overlap,
+            },
+        },
+    },
+    wildfire::{GameMap, TerrainType},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<WaterCrossing>();
+
+    app.add_observer(remove_water_crossing);
+}
+
+/// River ferries can only be placed on water
+pub fn river_ferry_location_valid(coords: IVec2, map: &GameMap) -> bool {
+    map.get(coords)
+        .is_some_and(|cell| cell.terrain == TerrainType::Water)
+}
+
+/// Marks the water covered by a river ferry, given the ferry location
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct WaterCrossing(pub IVec2);
+
+impl WaterCrossing {
+    /// Records the water under the ferry on the map
+    pub fn apply_to_map(&self, map: &mut GameMap) {
+        for offset in BUILDING_FOOTPRINT_OFFSETS {
+            map.add_water_crossing(self.0 + offset);
+        }
+    }
+}
+
+fn remove_water_crossing(
+    trigger: Trigger<OnDespawn, WaterCrossing>,
+    map: Option<ResMut<GameMap>>,
+    crossings: Query<&WaterCrossing>,
+) {
+    let Some(mut map) = map else {
+        warn!("Unable to remove water crossing, no game map exists");
+        return;
+    };
+
+    let Ok(crossing) = crossings.get(trigger.target()) else {
+        error!("Unable to find water crossing being removed, the map will be out of date");
+        return;
+    };
+
+    for offset in BUILDING_FOOTPRINT_OFFSETS {
+        map.remove_water_crossing(crossing.0 + offset);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpawnRiverFerry(pub Vec2);
+
+impl Command for SpawnRiverFerry {
+    fn apply(self, world: &mut World) {
+        let _ = world.run_system_cached_with(spawn_river_ferry, self);
+    }
+}
+
+fn spawn_river_ferry(
+    In(config): In<SpawnRiverFerry>,
+    mut commands: Commands,
+    mut resources: ResMut<PlayerResources>,
+    mut building_mode: ResMut<BuildingMode>,
+    buildings: Res<BuildingAssets>,
+    mut map: ResMut<GameMap>,
+    existing: Query<&BuildingLocation>,
+    mut hint: ResMut<BuildTextHint>,
+) {
+    if resources.lumber < RIVER_FERRY_COST_LUMBER {
+        warn!("Not enough resources to spawn river ferry");
+        return;
+    }
+
+    let coords = map.tile_coords(config.0);
+    if !map.is_valid_coords(coords) {
+        warn!("Invalid map coordinates, aborting river ferry placement");
+        return;
+    }
+
+    if let Err(e) = check_overlap(coords, &map, &existing) {
+        warn!("{e}, aborting river ferry placement");
+        hint.set(e.to_string());
+        return;
+    }
+
+    if !river_ferry_location_valid(coords, &map) {
+        warn!("No water at {coords}, aborting river ferry placement");
+        hint.set("River ferries must be placed on water");
+        return;
+    }
+
+    resources.lumber -= RIVER_FERRY_COST_LUMBER;
+
+    let world_coords = map.world_coords(coords);
+    info!("Spawning river ferry at {coords}");
+
+    // the ferry floats on the water, so the terrain underneath isn't changed
+    let crossing = WaterCrossing(coords);
+    crossing.apply_to_map(&mut map);
+
+    let mut cmds = commands.spawn((
+        BuildingLocation(coords),
+        BuildingType::RiverFerry,
+        crossing,
+        StateScoped(Screen::Gameplay),
+        Transform::from_translation(world_coords.extend(0.1)),
+        Visibility::Visible,
+        Sprite {
+            image: buildings.river_ferry.clone(),
+            custom_size: Some(Vec2::splat(16.0)),
+            anchor: Anchor::Center,
+            ..default()
+        },
+    ));
+    let id = cmds.id();
+    // same hack as lumber mills so ferries are destroyed by the mana link chain
+    cmds.insert(ManaEntityLink {
+        from_entity: id,
+        destruction_time: None,
+    });
+
+    *building_mode = BuildingMode::None;
+}
@@ -0,0 +1,219 @@
+//! Logic + code for placing river golem buildings. River golems are built
+//! beside water and pump it out over a wide area, soaking the driest
+//! vegetation they can reach.
+
+use bevy::{color::palettes::tailwind::SKY_600, prelude::*, sprite::Anchor};
+use bevy_vector_shapes::{prelude::ShapePainter, shapes::DiscPainter};
+
+use crate::{
+    Pause,
+    screens::{
+        GameSpeed, PlayerResources, Screen,
+        gameplay::{
+            BuildTextHint, BuildingMode, RIVER_GOLEM_COST_MANA,
+            building::{
+                BUILDING_FOOTPRINT_OFFSETS, BuildingAssets, BuildingLocation, BuildingType,
+                ManaEntityLink, ManaLine, ManaLineBalls, ManaSource,
+                TrackParentBuildingWhilePlacing, check_placement,
+            },
+        },
+    },
+    wildfire::{GameMap, TerrainType},
+};
+
+/// How far (in cells) a river golem pumps water
+pub const RIVER_GOLEM_RANGE: i32 = 15;
+/// How often (in seconds) a river golem pumps water
+const RIVER_GOLEM_PRODUCTION_TIME: f32 = 2.0;
+/// The moisture added to the driest cell in range each time the golem pumps
+const RIVER_GOLEM_MOISTURE_INCREASE: f32 = 0.1;
+/// The mana consumed each time the golem pumps
+const RIVER_GOLEM_MANA_CONSUMPTION: i32 = 2;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<RiverGolem>();
+
+    app.add_systems(
+        Update,
+        (produce_from_river_golem, draw_river_golem_areas).run_if(
+            in_state(Pause(false))
+                .and(in_state(Screen::Gameplay))
+                .and(resource_exists::<PlayerResources>)
+                .and(resource_exists::<GameMap>),
+        ),
+    );
+}
+
+/// River golems can only be placed with part of their footprint beside water
+pub fn river_golem_location_valid(coords: IVec2, map: &GameMap) -> bool {
+    BUILDING_FOOTPRINT_OFFSETS
+        .iter()
+        .any(|offset| map.has_neighbour(coords + *offset, TerrainType::Water))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpawnRiverGolem(pub Vec2);
+
+impl Command for SpawnRiverGolem {
+    fn apply(self, world: &mut World) {
+        let _ = world.run_system_cached_with(spawn_river_golem, self);
+    }
+}
+
+fn spawn_river_golem(
+    In(config): In<SpawnRiverGolem>,
+    mut commands: Commands,
+    mut resources: ResMut<PlayerResources>,
+    mut building_mode: ResMut<BuildingMode>,
+    buildings: Res<BuildingAssets>,
+    mut map: ResMut<GameMap>,
+    parent_forge: Single<(Entity, &TrackParentBuildingWhilePlacing)>,
+    forges: Query<&Transform, ManaSource>,
+    existing: Query<&BuildingLocation>,
+    mut hint: ResMut<BuildTextHint>,
+) {
+    if resources.mana < RIVER_GOLEM_COST_MANA {
+        warn!("Not enough resources to spawn river golem");
+        return;
+    }
+
+    let (parent_tracking_entity, parent_forge) = *parent_forge;
+    let Some(parent_forge) = parent_forge.entity else {
+        warn!("No parent mana forge inside tracking, skipping river golem placement");
+        return;
+    };
+
+    let coords = map.tile_coords(config.0);
+    if !map.is_valid_coords(coords) {
+        warn!("Invalid map coordinates, aborting river golem placement");
+        return;
+    }
+
+    if let Err(e) = check_placement(coords, &map, &existing) {
+        warn!("{e}, aborting river golem placement");
+        hint.set(e.to_string());
+        return;
+    }
+
+    if !river_golem_location_valid(coords, &map) {
+        warn!("No water beside {coords}, aborting river golem placement");
+        hint.set("River golems must be placed beside water");
+        return;
+    }
+
+    let Ok(parent_tx) = forges.get(parent_forge) else {
+        warn!("Unable to find parent mana forge");
+        return;
+    };
+
+    commands.entity(parent_tracking_entity).despawn();
+    resources.mana -= RIVER_GOLEM_COST_MANA;
+    resources.mana_drain -= 1;
+
+    let world_coords = map.world_coords(coords);
+    info!("Spawning river golem at {coords}");
+
+    commands.spawn((
+        BuildingLocation(coords),
+        BuildingType::RiverGolem,
+        RiverGolem::default(),
+        ManaLine::new(
+            parent_tx.translation.truncate().extend(0.05),
+            config.0.extend(0.05),
+        ),
+        ManaLineBalls::default(),
+        ManaEntityLink {
+            from_entity: parent_forge,
+            destruction_time: None,
+        },
+        StateScoped(Screen::Gameplay),
+        Transform::from_xyz(world_coords.x, world_coords.y, 0.1),
+        Visibility::Visible,
+        Sprite {
+            image: buildings.river_golem.clone(),
+            custom_size: Some(Vec2::splat(16.0)),
+            anchor: Anchor::Center,
+            ..default()
+        },
+    ));
+
+    // update the map underneath to turn to buildings
+    BUILDING_FOOTPRINT_OFFSETS.iter().for_each(|offset| {
+        if let Some(cell) = map.get_mut(coords + *offset) {
+            cell.terrain = TerrainType::Building;
+        }
+    });
+
+    *building_mode = BuildingMode::None;
+}
+
+/// A golem that pumps water from a river onto the driest nearby vegetation
+#[derive(Component, Reflect, Debug, Default)]
+#[reflect(Component)]
+pub struct RiverGolem {
+    /// The time since the golem last pumped water
+    time_since_last_tick: f32,
+    /// The number of times the golem has wet a grass or tree cell
+    pub cells_moistened: u32,
+}
+
+fn draw_river_golem_areas(
+    mut painter: ShapePainter,
+    map: Res<GameMap>,
+    golems: Query<&Transform, With<RiverGolem>>,
+) {
+    let original_tx = painter.transform;
+
+    for tx in &golems {
+        painter.hollow = true;
+        painter.set_color(SKY_600.with_alpha(0.4));
+        painter.translate(tx.translation - Vec3::new(0.0, 0.0, 0.05));
+        painter.circle(RIVER_GOLEM_RANGE as f32 * map.sprite_size);
+
+        painter.transform = original_tx;
+    }
+}
+
+fn produce_from_river_golem(
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut map: ResMut<GameMap>,
+    mut resources: ResMut<PlayerResources>,
+    mut golems: Query<(&BuildingLocation, &mut RiverGolem)>,
+) {
+    let delta = time.delta_secs() * game_speed.0;
+
+    for (loc, mut golem) in &mut golems {
+        if golem.time_since_last_tick + delta <= RIVER_GOLEM_PRODUCTION_TIME {
+            golem.time_since_last_tick += delta;
+            continue;
+        }
+
+        golem.time_since_last_tick = 0.0;
+
+        if resources.mana < RIVER_GOLEM_MANA_CONSUMPTION {
+            info!("Not enough mana to produce from river golem at {}", loc.0);
+            continue;
+        }
+
+        let driest = map
+            .cells_within_range(loc.0, RIVER_GOLEM_RANGE)
+            .filter_map(|coord| map.get(coord).map(|cell| (coord, cell)))
+            .filter(|(_, cell)| matches!(cell.terrain, TerrainType::Grassland | TerrainType::Tree))
+            .min_by(|(_, a), (_, b)| a.moisture.total_cmp(&b.moisture))
+            .map(|(coord, _)| coord);
+
+        // don't waste mana when everything in range is already soaked
+        let Some(cell) = driest.and_then(|coord| map.get_mut(coord)) else {
+            continue;
+        };
+        if cell.moisture >= 1.0 {
+            continue;
+        }
+
+        resources.mana -= RIVER_GOLEM_MANA_CONSUMPTION;
+        cell.moisture = (cell.moisture + RIVER_GOLEM_MOISTURE_INCREASE).min(1.0);
+        cell.mark_dirty();
+        golem.cells_moistened += 1;
+    }
+}
@@ -0,0 +1,246 @@
+//! Logic + code for placing sprinklers, which are turned by a small waterwheel
+//! and slowly sweep a jet of water around themselves
+
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI, TAU};
+
+use bevy::{color::palettes::tailwind::SKY_400, prelude::*, sprite::Anchor};
+use bevy_vector_shapes::{prelude::ShapePainter, shapes::DiscPainter};
+use rand::Rng;
+
+use crate::{
+    Pause,
+    screens::{
+        GameSpeed, PlayerResources, Screen,
+        gameplay::{
+            BuildTextHint, BuildingMode, SPRINKLER_COST_MANA,
+            building::{
+                BUILDING_FOOTPRINT_OFFSETS, BuildingAssets, BuildingLocation, BuildingType,
+                ManaEntityLink, check_placement,
+            },
+        },
+    },
+    wildfire::{FireStats, GameMap, MoistureMap, TerrainType},
+};
+
+/// The time between each watering of the cells under the arc
+const SPRINKLER_TICK_TIME: f32 = 0.5;
+/// How quickly the sprinkler arc turns, in radians per second
+const SPRINKLER_ROTATION_SPEED: f32 = FRAC_PI_4;
+/// The moisture added under each grass or tree cell under the arc, which
+/// then spreads out and soaks into the ground through the [MoistureMap]
+const SPRINKLER_MOISTURE_INCREASE: f32 = 0.05;
+/// The chance that a burning cell under the arc is put out
+const SPRINKLER_QUENCH_CHANCE: f64 = 0.15;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Sprinkler>();
+
+    app.add_systems(
+        Update,
+        produce_from_sprinkler.run_if(
+            in_state(Pause(false))
+                .and(in_state(Screen::Gameplay))
+                .and(resource_exists::<GameMap>),
+        ),
+    );
+
+    app.add_systems(
+        Update,
+        draw_sprinkler_arcs.run_if(
+            in_state(Screen::Gameplay).and(in_state(Pause(false)).and(resource_exists::<GameMap>)),
+        ),
+    );
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpawnSprinkler(pub Vec2);
+
+impl Command for SpawnSprinkler {
+    fn apply(self, world: &mut World) {
+        let _ = world.run_system_cached_with(spawn_sprinkler, self);
+    }
+}
+
+fn spawn_sprinkler(
+    In(config): In<SpawnSprinkler>,
+    mut commands: Commands,
+    mut resources: ResMut<PlayerResources>,
+    mut building_mode: ResMut<BuildingMode>,
+    buildings: Res<BuildingAssets>,
+    mut map: ResMut<GameMap>,
+    existing: Query<&BuildingLocation>,
+    mut hint: ResMut<BuildTextHint>,
+) {
+    if resources.mana < SPRINKLER_COST_MANA {
+        warn!("Not enough resources to spawn sprinkler");
+        return;
+    }
+
+    let coords = map.tile_coords(config.0);
+    if !map.is_valid_coords(coords) {
+        warn!("Invalid map coordinates, aborting sprinkler placement");
+        return;
+    }
+
+    if let Err(e) = check_placement(coords, &map, &existing) {
+        warn!("{e}, aborting sprinkler placement");
+        hint.set(e.to_string());
+        return;
+    }
+
+    resources.mana -= SPRINKLER_COST_MANA;
+
+    let world_coords = map.world_coords(coords);
+    info!("Spawning sprinkler at {coords}");
+
+    let mut cmds = commands.spawn((
+        BuildingLocation(coords),
+        BuildingType::Sprinkler,
+        Sprinkler::default(),
+        StateScoped(Screen::Gameplay),
+        Transform::from_translation(world_coords.extend(0.1)),
+        Visibility::Visible,
+        Sprite {
+            image: buildings.sprinkler.clone(),
+            custom_size: Some(Vec2::splat(16.0)),
+            anchor: Anchor::Center,
+            ..default()
+        },
+    ));
+    let id = cmds.id();
+    // same hack as lumber mills so sprinklers are destroyed by the mana link chain
+    cmds.insert(ManaEntityLink {
+        from_entity: id,
+        destruction_time: None,
+    });
+
+    // update the map underneath to turn to buildings
+    BUILDING_FOOTPRINT_OFFSETS.iter().for_each(|offset| {
+        if let Some(cell) = map.get_mut(coords + *offset) {
+            cell.terrain = TerrainType::Building;
+        }
+    });
+
+    *building_mode = BuildingMode::None;
+}
+
+/// A building that sweeps a jet of water around itself, wetting the ground and
+/// sometimes putting out fires under the jet
+#[derive(Component, Debug, Reflect)]
+#[reflect(Component)]
+pub struct Sprinkler {
+    /// The direction the middle of the arc points in, in radians anticlockwise
+    /// from the positive x axis
+    pub angle: f32,
+    /// The total width of the arc in radians
+    pub arc_width: f32,
+    /// The range of the sprinkler (i.e. distance from the building location)
+    pub range: i32,
+    /// The time since the sprinkler last watered the cells under the arc
+    pub timer: f32,
+}
+
+impl Default for Sprinkler {
+    fn default() -> Self {
+        Self {
+            angle: 0.0,
+            arc_width: FRAC_PI_4,
+            range: 4,
+            timer: 0.0,
+        }
+    }
+}
+
+impl Sprinkler {
+    /// Whether the given offset from the sprinkler falls inside the arc
+    fn covers(&self, offset: IVec2) -> bool {
+        if offset == IVec2::ZERO {
+            return false;
+        }
+
+        let offset_angle = (offset.y as f32).atan2(offset.x as f32);
+        // wrap the difference into -PI..PI so the arc can cross the x axis
+        let difference = (offset_angle - self.angle + PI).rem_euclid(TAU) - PI;
+        difference.abs() <= self.arc_width * 0.5
+    }
+}
+
+fn produce_from_sprinkler(
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut map: ResMut<GameMap>,
+    mut moisture_map: ResMut<MoistureMap>,
+    mut stats: ResMut<FireStats>,
+    mut sprinklers: Query<(&BuildingLocation, &mut Sprinkler)>,
+) {
+    let delta = time.delta_secs() * game_speed.0;
+    let mut rng = rand::thread_rng();
+
+    for (loc, mut sprinkler) in &mut sprinklers {
+        sprinkler.angle = (sprinkler.angle + SPRINKLER_ROTATION_SPEED * delta).rem_euclid(TAU);
+
+        sprinkler.timer += delta;
+        if sprinkler.timer < SPRINKLER_TICK_TIME {
+            continue;
+        }
+        sprinkler.timer = 0.0;
+
+        let covered = map
+            .cells_within_range(loc.0, sprinkler.range)
+            .filter(|coord| sprinkler.covers(*coord - loc.0))
+            .collect::<Vec<_>>();
+
+        for coord in covered {
+            if let Some(cell) = map.get_mut(coord) {
+                match cell.terrain {
+                    TerrainType::Fire => {
+                        if rng.gen_bool(SPRINKLER_QUENCH_CHANCE) {
+                            cell.terrain = TerrainType::Smoldering;
+                            cell.mark_dirty();
+                            stats.fires_extinguished += 1;
+                        }
+                    }
+                    TerrainType::Grassland | TerrainType::Tree => {
+                        if let Some(moisture) = moisture_map.get_mut(coord) {
+                            *moisture += SPRINKLER_MOISTURE_INCREASE;
+                        }
+                    }
+                    TerrainType::Dirt
+                    | TerrainType::Building
+                    | TerrainType::Stone
+                    | TerrainType::Smoldering
+                    | TerrainType::Water => {
+                        // nop
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn draw_sprinkler_arcs(
+    mut painter: ShapePainter,
+    map: Res<GameMap>,
+    sprinklers: Query<(&Transform, &Sprinkler)>,
+) {
+    let original_tx = painter.transform;
+
+    for (tx, sprinkler) in &sprinklers {
+        let mut color = SKY_400;
+        color.alpha = 0.3;
+
+        painter.hollow = false;
+        painter.set_color(color);
+        painter.translate(tx.translation - Vec3::new(0.0, 0.0, 0.05));
+
+        // the painter measures angles clockwise from the positive y axis
+        let half_width = sprinkler.arc_width * 0.5;
+        painter.arc(
+            sprinkler.range as f32 * map.sprite_size,
+            FRAC_PI_2 - (sprinkler.angle + half_width),
+            FRAC_PI_2 - (sprinkler.angle - half_width),
+        );
+
+        painter.transform = original_tx;
+    }
+}
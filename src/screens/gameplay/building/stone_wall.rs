@@ -0,0 +1,128 @@
+//! Logic + code for placing stone walls, which create instant firebreaks
+
+use bevy::{color::palettes::tailwind::STONE_400, prelude::*};
+use bevy_vector_shapes::{prelude::ShapePainter, shapes::LinePainter};
+
+use crate::{
+    Pause,
+    input::MousePosition,
+    screens::{
+        PlayerResources, Screen,
+        gameplay::{BuildingMode, STONE_WALL_COST_LUMBER},
+    },
+    wildfire::{GameMap, TerrainType, line_between},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<WallStart>();
+
+    app.add_systems(
+        Update,
+        draw_wall_preview.run_if(
+            in_state(Screen::Gameplay)
+                .and(in_state(Pause(false)))
+                .and(resource_exists::<GameMap>)
+                .and(resource_exists::<WallStart>),
+        ),
+    );
+}
+
+/// The world position of the first click when placing a stone wall
+#[derive(Resource, Reflect, Debug, Clone, Copy)]
+#[reflect(Resource)]
+pub struct WallStart(pub Vec2);
+
+/// Builds a stone wall between two world positions
+#[derive(Debug, Clone, Copy)]
+pub struct SpawnStoneWall(pub Vec2, pub Vec2);
+
+impl Command for SpawnStoneWall {
+    fn apply(self, world: &mut World) {
+        let _ = world.run_system_cached_with(spawn_stone_wall, self);
+    }
+}
+
+fn spawn_stone_wall(
+    In(config): In<SpawnStoneWall>,
+    mut commands: Commands,
+    mut resources: ResMut<PlayerResources>,
+    mut building_mode: ResMut<BuildingMode>,
+    mut map: ResMut<GameMap>,
+) {
+    let start = map.tile_coords(config.0);
+    let end = map.tile_coords(config.1);
+    if !map.is_valid_coords(start) || !map.is_valid_coords(end) {
+        warn!("Invalid map coordinates, aborting stone wall placement");
+        return;
+    }
+
+    // only the tiles that actually turn to stone are paid for
+    let tiles = line_between(start, end)
+        .into_iter()
+        .filter(|tile| {
+            map.get(*tile)
+                .is_some_and(|cell| stone_wall_terrain_valid(cell.terrain))
+        })
+        .collect::<Vec<_>>();
+    if tiles.is_empty() {
+        warn!("Nothing to build a stone wall on between {start} and {end}");
+        return;
+    }
+
+    let cost = tiles.len() as i32 * STONE_WALL_COST_LUMBER;
+    if resources.lumber < cost {
+        warn!(
+            "Not enough resources to build a stone wall of length {}",
+            tiles.len()
+        );
+        return;
+    }
+
+    info!("Building stone wall from {start} to {end}");
+    resources.lumber -= cost;
+
+    for tile in tiles {
+        if let Some(cell) = map.get_mut(tile) {
+            cell.terrain = TerrainType::Stone;
+            cell.mark_dirty();
+        }
+    }
+
+    commands.remove_resource::<WallStart>();
+    *building_mode = BuildingMode::None;
+}
+
+/// Stone walls can be built over anything that isn't already a building,
+/// burning, stone or water
+fn stone_wall_terrain_valid(terrain: TerrainType) -> bool {
+    match terrain {
+        TerrainType::Grassland
+        | TerrainType::Tree
+        | TerrainType::Dirt
+        | TerrainType::Smoldering => true,
+        TerrainType::Building | TerrainType::Fire | TerrainType::Stone | TerrainType::Water => {
+            false
+        }
+    }
+}
+
+fn draw_wall_preview(
+    mut painter: ShapePainter,
+    map: Res<GameMap>,
+    mouse: Res<MousePosition>,
+    wall_start: Res<WallStart>,
+) {
+    let original_tx = painter.transform;
+
+    let start = map.world_coords(map.tile_coords(wall_start.0));
+    let end = map.world_coords(map.tile_coords(mouse.world_pos));
+
+    let mut color = STONE_400;
+    color.alpha = 0.6;
+
+    painter.set_color(color);
+    painter.thickness = map.sprite_size;
+    painter.line(start.extend(0.5), end.extend(0.5));
+
+    painter.transform = original_tx;
+}
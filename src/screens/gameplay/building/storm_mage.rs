@@ -7,29 +7,43 @@ use bevy_vector_shapes::{prelude::ShapePainter, shapes::RectPainter};
 
 use crate::{
     Pause,
+    input::MousePosition,
     screens::{
         PlayerResources, Screen,
         gameplay::{
-            BuildingMode, STORM_MAGE_COST_MANA, StormMagePlacementRotation,
+            BuildTextHint, BuildingMode, STORM_MAGE_COST_MANA, STORM_MAGE_ROTATE_COST_MANA,
+            StormMagePlacementRotation,
             building::{
                 BUILDING_FOOTPRINT_OFFSETS, BuildingAssets, BuildingLocation, BuildingType,
-                ManaEntityLink, ManaLine, ManaLineBalls, TrackParentBuildingWhilePlacing,
-                mana_forge::ManaForge,
+                ManaEntityLink, ManaLine, ManaLineBalls, ManaSource,
+                TrackParentBuildingWhilePlacing, check_placement,
             },
+            building_panel::SelectedBuilding,
         },
     },
-    wildfire::{GameMap, TerrainType},
+    wildfire::{GameMap, TerrainType, line_between},
 };
 
+/// The intensity that storm mages are placed with
+const DEFAULT_MAGE_INTENSITY: f32 = 0.5;
+/// The (range, wind strength) of a storm mage at intensity 0, 0.5 and 1. The
+/// range and strength are interpolated between these
+const MAGE_INTENSITY_STEPS: [(f32, f32); 3] = [(20.0, 500.0), (10.0, 2000.0), (5.0, 4000.0)];
+
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<StormMage>();
     app.add_observer(remove_storm_mage);
 
     app.add_systems(
         Update,
-        draw_mage_areas.run_if(
-            in_state(Screen::Gameplay).and(in_state(Pause(false)).and(resource_exists::<GameMap>)),
-        ),
+        (
+            draw_mage_areas,
+            preview_storm_mage.run_if(resource_exists::<StormMagePlacementRotation>),
+        )
+            .run_if(
+                in_state(Screen::Gameplay)
+                    .and(in_state(Pause(false)).and(resource_exists::<GameMap>)),
+            ),
     );
 }
 
@@ -61,6 +75,81 @@ fn remove_storm_mage(
     }
 }
 
+/// Turns a placed storm mage to push the wind in the next direction clockwise
+#[derive(Debug, Clone, Copy)]
+pub struct RotateStormMage(pub Entity);
+
+impl Command for RotateStormMage {
+    fn apply(self, world: &mut World) {
+        let _ = world.run_system_cached_with(rotate_storm_mage, self);
+    }
+}
+
+fn rotate_storm_mage(
+    In(config): In<RotateStormMage>,
+    mut resources: ResMut<PlayerResources>,
+    mut map: ResMut<GameMap>,
+    mut selected: ResMut<SelectedBuilding>,
+    mut mages: Query<(&BuildingLocation, &mut StormMage, &mut Transform)>,
+) {
+    if resources.mana < STORM_MAGE_ROTATE_COST_MANA {
+        warn!("Not enough resources to rotate storm mage");
+        return;
+    }
+
+    let Ok((loc, mut mage, mut tx)) = mages.get_mut(config.0) else {
+        warn!("Unable to find storm mage to rotate");
+        return;
+    };
+
+    resources.mana -= STORM_MAGE_ROTATE_COST_MANA;
+
+    let rotation = mage.rotation.next();
+    info!("Rotating storm mage at {} to {rotation}", loc.0);
+    mage.rotation = rotation;
+    mage.apply_to_map(loc.0, rotation, &mut map);
+    tx.rotation = Quat::from_axis_angle(Vec3::Z, rotation.as_angle_rads());
+
+    // redraw the building panel so it shows the new direction
+    selected.set_changed();
+}
+
+/// Sets how hard a placed storm mage pushes the wind. Higher intensities blow
+/// harder over a narrower area
+#[derive(Debug, Clone, Copy)]
+pub struct SetStormMageIntensity(pub Entity, pub f32);
+
+impl Command for SetStormMageIntensity {
+    fn apply(self, world: &mut World) {
+        let _ = world.run_system_cached_with(set_storm_mage_intensity, self);
+    }
+}
+
+fn set_storm_mage_intensity(
+    In(config): In<SetStormMageIntensity>,
+    mut map: ResMut<GameMap>,
+    mut selected: ResMut<SelectedBuilding>,
+    mut mages: Query<(&BuildingLocation, &mut StormMage)>,
+) {
+    let Ok((loc, mut mage)) = mages.get_mut(config.0) else {
+        warn!("Unable to find storm mage to change intensity");
+        return;
+    };
+
+    mage.intensity = config.1.clamp(0.0, 1.0);
+    mage.range = StormMage::range_for_intensity(mage.intensity);
+    info!(
+        "Setting storm mage at {} to intensity {:.2}",
+        loc.0, mage.intensity
+    );
+
+    let rotation = mage.rotation;
+    mage.apply_to_map(loc.0, rotation, &mut map);
+
+    // redraw the building panel so it shows the new range
+    selected.set_changed();
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct SpawnStormMage(pub Vec2, pub MageRotation);
 
@@ -79,7 +168,9 @@ fn spawn_storm_mage(
     mut map: ResMut<GameMap>,
     mage_rotation: Res<StormMagePlacementRotation>,
     parent_forge: Single<(Entity, &TrackParentBuildingWhilePlacing)>,
-    forges: Query<&Transform, With<ManaForge>>,
+    forges: Query<&Transform, ManaSource>,
+    existing: Query<&BuildingLocation>,
+    mut hint: ResMut<BuildTextHint>,
 ) {
     if resources.mana < 30 {
         warn!("Not enough resources to spawn storm mage");
@@ -98,6 +189,12 @@ fn spawn_storm_mage(
         return;
     }
 
+    if let Err(e) = check_placement(coords, &map, &existing) {
+        warn!("{e}, aborting storm mage placement");
+        hint.set(e.to_string());
+        return;
+    }
+
     commands.entity(parent_forge_entity).despawn();
     resources.mana -= STORM_MAGE_COST_MANA;
     resources.mana_drain -= 2;
@@ -187,6 +284,43 @@ fn draw_mage_areas(
     }
 }
 
+/// Highlights the cells a storm mage would push the wind in if it was placed
+/// under the cursor with the current placement rotation
+fn preview_storm_mage(
+    mut painter: ShapePainter,
+    mode: Res<BuildingMode>,
+    mouse: Res<MousePosition>,
+    map: Res<GameMap>,
+    mage_rotation: Res<StormMagePlacementRotation>,
+) {
+    if !matches!(*mode, BuildingMode::PlaceStormMage) {
+        return;
+    }
+
+    let mage_cell = map.tile_coords(mouse.world_pos);
+    if !map.is_valid_coords(mage_cell) {
+        return;
+    }
+
+    let original_tx = painter.transform;
+    let mut color = SLATE_500;
+    color.alpha = 0.2;
+    painter.hollow = false;
+    painter.set_color(color);
+
+    let range = StormMage::range_for_intensity(DEFAULT_MAGE_INTENSITY);
+    for cell in StormMage::get_relevant_cells(mage_rotation.0, range) {
+        let coords = cell + mage_cell;
+        if !map.is_valid_coords(coords) || StormMage::is_sheltered(&map, mage_cell, coords) {
+            continue;
+        }
+
+        painter.translate(map.world_coords(coords).extend(0.05));
+        painter.rect(Vec2::splat(map.sprite_size));
+        painter.transform = original_tx;
+    }
+}
+
 /// A mana producing building
 #[derive(Component, Debug, Reflect)]
 #[reflect(Component)]
@@ -199,6 +333,9 @@ pub struct StormMage {
     range: i32,
     /// The current rotation of the mage
     rotation: MageRotation,
+    /// How hard the mage pushes the wind, from 0 to 1. Stronger wind covers
+    /// a narrower area
+    intensity: f32,
 }
 
 impl Default for StormMage {
@@ -206,8 +343,9 @@ impl Default for StormMage {
         Self {
             cells: Vec::new(),
             wind: Vec2::ZERO,
-            range: 10,
+            range: Self::range_for_intensity(DEFAULT_MAGE_INTENSITY),
             rotation: MageRotation::default(),
+            intensity: DEFAULT_MAGE_INTENSITY,
         }
     }
 }
@@ -258,8 +396,71 @@ impl MageRotation {
     }
 }
 
+impl std::fmt::Display for MageRotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let direction = match self {
+            MageRotation::Left => "West",
+            MageRotation::Up => "North",
+            MageRotation::Right => "East",
+            MageRotation::Down => "South",
+        };
+        write!(f, "{direction}")
+    }
+}
+
 ///The different cells to use depending on the rotation of the mage
 impl StormMage {
+    pub fn new(rotation: MageRotation) -> Self {
+        Self {
+            rotation,
+            ..default()
+        }
+    }
+
+    /// The direction the mage is pushing the wind
+    pub fn rotation(&self) -> MageRotation {
+        self.rotation
+    }
+
+    /// How hard the mage pushes the wind, from 0 to 1
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    /// The range that the mage works in
+    pub fn range(&self) -> i32 {
+        self.range
+    }
+
+    /// Interpolates the (range, wind strength) for an intensity between the
+    /// [MAGE_INTENSITY_STEPS]
+    fn intensity_params(intensity: f32) -> (f32, f32) {
+        let intensity = intensity.clamp(0.0, 1.0);
+        let ((from_range, from_wind), (to_range, to_wind), t) = if intensity <= 0.5 {
+            (
+                MAGE_INTENSITY_STEPS[0],
+                MAGE_INTENSITY_STEPS[1],
+                intensity * 2.0,
+            )
+        } else {
+            (
+                MAGE_INTENSITY_STEPS[1],
+                MAGE_INTENSITY_STEPS[2],
+                (intensity - 0.5) * 2.0,
+            )
+        };
+
+        (from_range.lerp(to_range, t), from_wind.lerp(to_wind, t))
+    }
+
+    fn range_for_intensity(intensity: f32) -> i32 {
+        Self::intensity_params(intensity).0.round() as i32
+    }
+
+    fn wind_for_intensity(intensity: f32) -> f32 {
+        Self::intensity_params(intensity).1
+    }
+
     /// Gets the cells that the mage handles based on its rotation
     fn get_relevant_cells(rotation: MageRotation, range: i32) -> impl Iterator<Item = IVec2> {
         const MIN_D: i32 = 1;
@@ -275,11 +476,42 @@ impl StormMage {
         x_range.flat_map(move |x| y_range.clone().map(move |y| IVec2::new(x, y)))
     }
 
-    /// applies the effects of the storm mage to the map
+    /// Whether stone between the mage and the target cell shelters the cell
+    /// from the mage. The ends of the ray are the mage itself and the cell
+    /// being pushed, so only the cells in between are checked
+    fn is_sheltered(map: &GameMap, mage_cell: IVec2, target: IVec2) -> bool {
+        let ray = line_between(mage_cell, target);
+        ray.iter()
+            .skip(1)
+            .take(ray.len().saturating_sub(2))
+            .any(|coord| {
+                map.get(*coord)
+                    .is_some_and(|ray_cell| ray_cell.terrain == TerrainType::Stone)
+            })
+    }
+
+    /// applies the effects of the storm mage to the map, removing any wind it
+    /// was already blowing first
     pub fn apply_to_map(&mut self, mage_cell: IVec2, rotation: MageRotation, map: &mut GameMap) {
-        self.wind = rotation.as_wind(2000.0);
+        // remove the old wind, the same as when the mage is despawned
+        for coord in self.cells.drain(..) {
+            let Some(cell) = map.get_mut(coord + mage_cell) else {
+                warn!("No cell found for storm mage");
+                continue;
+            };
+
+            cell.wind -= self.wind;
+        }
+
+        self.wind = rotation.as_wind(Self::wind_for_intensity(self.intensity));
 
         for cell in Self::get_relevant_cells(rotation, self.range) {
+            // stone acts as a windbreak, so cells behind it are sheltered
+            // from the mage
+            if Self::is_sheltered(map, mage_cell, cell + mage_cell) {
+                continue;
+            }
+
             let Some(map_cell) = map.get_mut(cell + mage_cell) else {
                 warn!("Unable to locate cell in map, skipping wind from storm mage");
                 continue;
@@ -0,0 +1,102 @@
+//! Logic + code for placing treasuries, which raise the most lumber the
+//! player can hold. They have no other effect
+
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{
+    screens::{
+        PlayerResources, Screen,
+        gameplay::{
+            BuildTextHint, BuildingMode, TREASURY_COST_LUMBER,
+            building::{
+                BUILDING_FOOTPRINT_OFFSETS, BuildingAssets, BuildingLocation, BuildingType,
+                ManaEntityLink, check_placement,
+            },
+        },
+    },
+    wildfire::{GameMap, TerrainType},
+};
+
+/// The extra lumber that each treasury can hold
+pub const TREASURY_CAPACITY: i32 = 100;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Treasury>();
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpawnTreasury(pub Vec2);
+
+impl Command for SpawnTreasury {
+    fn apply(self, world: &mut World) {
+        let _ = world.run_system_cached_with(spawn_treasury, self);
+    }
+}
+
+fn spawn_treasury(
+    In(config): In<SpawnTreasury>,
+    mut commands: Commands,
+    mut resources: ResMut<PlayerResources>,
+    mut building_mode: ResMut<BuildingMode>,
+    buildings: Res<BuildingAssets>,
+    mut map: ResMut<GameMap>,
+    existing: Query<&BuildingLocation>,
+    mut hint: ResMut<BuildTextHint>,
+) {
+    if resources.lumber < TREASURY_COST_LUMBER {
+        warn!("Not enough resources to spawn treasury");
+        return;
+    }
+
+    let coords = map.tile_coords(config.0);
+    if !map.is_valid_coords(coords) {
+        warn!("Invalid map coordinates, aborting treasury placement");
+        return;
+    }
+
+    if let Err(e) = check_placement(coords, &map, &existing) {
+        warn!("{e}, aborting treasury placement");
+        hint.set(e.to_string());
+        return;
+    }
+
+    resources.lumber -= TREASURY_COST_LUMBER;
+
+    let world_coords = map.world_coords(coords);
+    info!("Spawning treasury at {coords}");
+
+    let mut cmds = commands.spawn((
+        BuildingLocation(coords),
+        BuildingType::Treasury,
+        Treasury,
+        StateScoped(Screen::Gameplay),
+        Transform::from_translation(world_coords.extend(0.1)),
+        Visibility::Visible,
+        Sprite {
+            image: buildings.treasury.clone(),
+            custom_size: Some(Vec2::splat(16.0)),
+            anchor: Anchor::Center,
+            ..default()
+        },
+    ));
+    let id = cmds.id();
+    // same hack as lumber mills so treasuries are destroyed by the mana link chain
+    cmds.insert(ManaEntityLink {
+        from_entity: id,
+        destruction_time: None,
+    });
+
+    // update the map underneath to turn to buildings
+    BUILDING_FOOTPRINT_OFFSETS.iter().for_each(|offset| {
+        if let Some(cell) = map.get_mut(coords + *offset) {
+            cell.terrain = TerrainType::Building;
+        }
+    });
+
+    *building_mode = BuildingMode::None;
+}
+
+/// A building that adds [TREASURY_CAPACITY] to the player's lumber cap
+#[derive(Component, Debug, Default, Reflect)]
+#[reflect(Component)]
+pub struct Treasury;
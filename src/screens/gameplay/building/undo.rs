@@ -0,0 +1,208 @@
+//! Lets the player undo their last few building placements for a full
+//! refund, as long as the building hasn't been touched since
+
+use std::collections::VecDeque;
+
+use bevy::{
+    input::common_conditions::{input_just_pressed, input_pressed},
+    prelude::*,
+};
+
+use crate::{
+    Pause,
+    screens::{
+        BuildingType, EventLog, PlayerResources, Screen,
+        gameplay::building::{
+            BUILDING_FOOTPRINT_OFFSETS, BuildingLocation, BuildingTier, ManaEntityLink,
+            demolish::{Demolished, build_cost},
+            destroy::BuildingMarkedForDestruction,
+        },
+        log_event,
+    },
+    wildfire::{FireStats, GameMap, OnSpawnMap, TerrainCellState, TerrainType},
+};
+
+/// The most placements that can be undone
+const MAX_UNDO_ACTIONS: usize = 3;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<UndoStack>();
+    app.register_type::<UndoFootprint>();
+
+    app.init_resource::<UndoStack>();
+
+    app.add_observer(reset_undo_stack);
+
+    app.add_systems(
+        Update,
+        (
+            record_placed_buildings.run_if(resource_exists::<UndoFootprint>),
+            undo_last_action
+                .run_if(input_just_pressed(KeyCode::KeyZ).and(input_pressed(KeyCode::ControlLeft))),
+        )
+            .run_if(
+                in_state(Screen::Gameplay)
+                    .and(in_state(Pause(false)))
+                    .and(resource_exists::<GameMap>)
+                    .and(resource_exists::<PlayerResources>),
+            ),
+    );
+}
+
+/// An action taken by the player that can be undone
+#[derive(Reflect, Debug, Clone)]
+pub enum UndoAction {
+    PlacedBuilding {
+        entity: Entity,
+        coords: IVec2,
+        building_type: BuildingType,
+        cost_mana: i32,
+        cost_lumber: i32,
+        /// The cells under the building before it was placed
+        previous_cells: Vec<TerrainCellState>,
+    },
+}
+
+/// The most recent actions that can be undone, newest last
+#[derive(Resource, Reflect, Debug, Default)]
+#[reflect(Resource)]
+pub struct UndoStack(pub VecDeque<UndoAction>);
+
+/// The cells under the mouse when the player clicked to place a building,
+/// captured before the building changes them. This is turned into an
+/// [UndoAction] once the building has been spawned
+#[derive(Resource, Reflect, Debug, Clone)]
+#[reflect(Resource)]
+pub struct UndoFootprint {
+    coords: IVec2,
+    cells: Vec<TerrainCellState>,
+}
+
+impl UndoFootprint {
+    pub fn capture(map: &GameMap, coords: IVec2) -> Self {
+        Self {
+            coords,
+            cells: BUILDING_FOOTPRINT_OFFSETS
+                .iter()
+                .map(|offset| map.get(coords + *offset).copied().unwrap_or_default())
+                .collect(),
+        }
+    }
+}
+
+/// New maps (including loaded games) start with nothing to undo
+fn reset_undo_stack(
+    _trigger: Trigger<OnSpawnMap>,
+    mut commands: Commands,
+    mut stack: ResMut<UndoStack>,
+) {
+    stack.0.clear();
+    commands.remove_resource::<UndoFootprint>();
+}
+
+/// Only buildings placed at the captured footprint are recorded, so that
+/// restored buildings can't be undone
+fn record_placed_buildings(
+    mut commands: Commands,
+    footprint: Res<UndoFootprint>,
+    mut stack: ResMut<UndoStack>,
+    placed: Query<(Entity, &BuildingType, &BuildingLocation), Added<BuildingType>>,
+) {
+    let Some((entity, building_type, loc)) =
+        placed.iter().find(|(_, _, loc)| loc.0 == footprint.coords)
+    else {
+        return;
+    };
+
+    let (cost_lumber, cost_mana) = build_cost(*building_type);
+    stack.0.push_back(UndoAction::PlacedBuilding {
+        entity,
+        coords: loc.0,
+        building_type: *building_type,
+        cost_mana,
+        cost_lumber,
+        previous_cells: footprint.cells.clone(),
+    });
+    while stack.0.len() > MAX_UNDO_ACTIONS {
+        stack.0.pop_front();
+    }
+
+    commands.remove_resource::<UndoFootprint>();
+}
+
+fn undo_last_action(
+    mut commands: Commands,
+    mut stack: ResMut<UndoStack>,
+    mut resources: ResMut<PlayerResources>,
+    mut map: ResMut<GameMap>,
+    mut stats: ResMut<FireStats>,
+    mut log: ResMut<EventLog>,
+    buildings: Query<
+        (Option<&BuildingTier>, Has<BuildingMarkedForDestruction>),
+        With<BuildingType>,
+    >,
+    links: Query<(Entity, &ManaEntityLink)>,
+) {
+    let Some(action) = stack.0.pop_back() else {
+        info!("Nothing to undo");
+        return;
+    };
+
+    match action {
+        UndoAction::PlacedBuilding {
+            entity,
+            coords,
+            building_type,
+            cost_mana,
+            cost_lumber,
+            previous_cells,
+        } => {
+            // city halls are never recorded, but check anyway as undoing one
+            // would end the game
+            if building_type == BuildingType::CityHall {
+                warn!("The city hall cannot be undone");
+                return;
+            }
+
+            let Ok((maybe_tier, marked_for_destruction)) = buildings.get(entity) else {
+                warn!("Cannot undo placing {building_type} as it no longer exists");
+                return;
+            };
+
+            let on_fire = BUILDING_FOOTPRINT_OFFSETS.iter().any(|offset| {
+                map.get(coords + *offset)
+                    .is_some_and(|cell| cell.terrain == TerrainType::Fire)
+            });
+            let upgraded = maybe_tier.is_some_and(|tier| *tier != BuildingTier::default());
+            let powering_others = links
+                .iter()
+                .any(|(linked, link)| link.from_entity == entity && linked != entity);
+            if marked_for_destruction || on_fire || upgraded || powering_others {
+                warn!("Cannot undo placing {building_type} as it has been damaged or modified");
+                log_event(
+                    &mut log,
+                    stats.duration,
+                    format!("Cannot undo, the {building_type} has changed"),
+                );
+                return;
+            }
+
+            info!("Undoing placing {building_type} at {coords}");
+            resources.add_mana(cost_mana);
+            resources.lumber += cost_lumber;
+            stats.buildings_built = stats.buildings_built.saturating_sub(1);
+
+            for (offset, previous) in BUILDING_FOOTPRINT_OFFSETS.iter().zip(previous_cells) {
+                if let Some(cell) = map.get_mut(coords + *offset) {
+                    cell.terrain = previous.terrain;
+                    cell.fuel_load = previous.fuel_load;
+                    cell.mark_dirty();
+                }
+            }
+
+            // marked as demolished so it isn't counted as lost to the fire
+            commands.entity(entity).insert(Demolished).despawn();
+            log_event(&mut log, stats.duration, "Undone!");
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Logic + code for upgrading buildings to higher tiers
+
+use bevy::prelude::*;
+
+use crate::screens::{
+    PlayerResources,
+    gameplay::{
+        building::{BuildingType, lumber_mill::LumberMill, mana_forge::ManaForge},
+        building_panel::SelectedBuilding,
+    },
+};
+
+/// The highest tier that a building can be upgraded to
+pub const MAX_BUILDING_TIER: u8 = 3;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<BuildingTier>();
+}
+
+/// The current tier of an upgradeable building, from 1 to [MAX_BUILDING_TIER]
+#[derive(Component, Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+#[reflect(Component)]
+pub struct BuildingTier(pub u8);
+
+impl Default for BuildingTier {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+/// Gets the amount of lumber required to upgrade a building to the given
+/// tier, or `None` if the building can't be upgraded to that tier
+pub fn upgrade_cost_lumber(building_type: BuildingType, next_tier: u8) -> Option<i32> {
+    match (building_type, next_tier) {
+        (BuildingType::ManaForge, 2) => Some(30),
+        (BuildingType::ManaForge, 3) => Some(50),
+        (BuildingType::LumberMill, 2) => Some(20),
+        (BuildingType::LumberMill, 3) => Some(40),
+        _ => None,
+    }
+}
+
+/// Describes the effect of upgrading a building to the given tier
+pub fn upgrade_description(building_type: BuildingType, next_tier: u8) -> String {
+    match building_type {
+        BuildingType::ManaForge => format!(
+            "Produces {} mana / sec",
+            ManaForge::mana_per_second_for_tier(next_tier)
+        ),
+        BuildingType::LumberMill => format!(
+            "Harvests every {:.2} sec from up to {} tiles away",
+            LumberMill::tick_time_for_tier(next_tier),
+            LumberMill::range_for_tier(next_tier)
+        ),
+        BuildingType::CityHall
+        | BuildingType::Minotaur
+        | BuildingType::StormMage
+        | BuildingType::WaterGolem
+        | BuildingType::EmberGuard
+        | BuildingType::Arborist
+        | BuildingType::BeaconTower
+        | BuildingType::ResearchHall
+        | BuildingType::RelayTower
+        | BuildingType::ManaBattery
+        | BuildingType::FireFighter
+        | BuildingType::Sprinkler
+        | BuildingType::Treasury
+        | BuildingType::RiverGolem
+        | BuildingType::RiverFerry => "Cannot be upgraded".into(),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct UpgradeBuilding(pub Entity);
+
+impl Command for UpgradeBuilding {
+    fn apply(self, world: &mut World) {
+        let _ = world.run_system_cached_with(upgrade_building, self);
+    }
+}
+
+fn upgrade_building(
+    In(config): In<UpgradeBuilding>,
+    mut resources: ResMut<PlayerResources>,
+    mut selected: ResMut<SelectedBuilding>,
+    mut buildings: Query<(
+        &BuildingType,
+        &mut BuildingTier,
+        Option<&mut ManaForge>,
+        Option<&mut LumberMill>,
+    )>,
+) {
+    let Ok((building_type, mut tier, maybe_forge, maybe_mill)) = buildings.get_mut(config.0) else {
+        warn!("Unable to find building to upgrade");
+        return;
+    };
+
+    let next_tier = tier.0 + 1;
+    let Some(cost) = upgrade_cost_lumber(*building_type, next_tier) else {
+        warn!("{building_type:?} can't be upgraded past tier {}", tier.0);
+        return;
+    };
+
+    if resources.lumber < cost {
+        warn!("Not enough lumber to upgrade {building_type:?}");
+        return;
+    }
+
+    info!("Upgrading {building_type:?} to tier {next_tier}");
+    resources.lumber -= cost;
+    tier.0 = next_tier;
+
+    if let Some(mut forge) = maybe_forge {
+        let previous = forge.mana_per_second;
+        forge.set_tier(next_tier);
+        resources.mana_drain += forge.mana_per_second - previous;
+    }
+
+    if let Some(mut mill) = maybe_mill {
+        mill.set_tier(next_tier);
+    }
+
+    // redraw the panel to show the new tier
+    selected.set_changed();
+}
@@ -1,27 +1,49 @@
 //! Logic + code for placing water golem buildings
 
-use bevy::{color::palettes::tailwind::INDIGO_600, prelude::*, sprite::Anchor};
+use bevy::{
+    color::palettes::tailwind::{INDIGO_600, SKY_300},
+    prelude::*,
+    sprite::Anchor,
+};
 use bevy_vector_shapes::{prelude::ShapePainter, shapes::DiscPainter};
 use rand::Rng;
 
 use crate::{
     Pause,
     screens::{
-        PlayerResources, Screen,
+        EventLog, GameSpeed, PlayerResources, Screen,
         gameplay::{
-            BuildingMode, WATER_GOLEM_COST_MANA,
+            BuildTextHint, BuildingMode, WATER_GOLEM_COST_MANA,
             building::{
                 BUILDING_FOOTPRINT_OFFSETS, BuildingAssets, BuildingLocation, BuildingType,
-                ManaEntityLink, ManaLine, ManaLineBalls, TrackParentBuildingWhilePlacing,
-                mana_forge::ManaForge,
+                ManaEntityLink, ManaLine, ManaLineBalls, ManaSource,
+                TrackParentBuildingWhilePlacing, check_placement,
             },
         },
+        log_event,
     },
-    wildfire::{GameMap, TerrainType},
+    wildfire::{FireStats, GameMap, MoistureMap, TerrainType},
 };
 
+/// The total mana a golem must consume to reach each level above the first
+const GOLEM_LEVEL_THRESHOLDS: [u32; 4] = [50, 150, 350, 750];
+/// The range of a level one golem
+const GOLEM_BASE_RANGE: i32 = 4;
+/// How much more likely a golem is to put out a fire for each level above the first
+const GOLEM_QUENCH_CHANCE_PER_LEVEL: f64 = 0.05;
+/// How long the burst shown when a golem levels up lasts, in seconds
+const GOLEM_LEVEL_UP_BURST_TIME: f32 = 1.0;
+/// The number of droplets thrown out when a golem levels up
+const GOLEM_LEVEL_UP_DROPLETS: usize = 12;
+/// The radius of the glow around a golem that has mana to work with, in pixels
+const GOLEM_GLOW_RADIUS: f32 = 12.0;
+
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<WaterGolem>();
+    app.register_type::<GolemLeveledUp>();
+    app.register_type::<GolemLevelUpBurst>();
+
+    app.add_observer(handle_golem_level_up);
 
     app.add_systems(
         Update,
@@ -35,8 +57,12 @@ pub(super) fn plugin(app: &mut App) {
 
     app.add_systems(
         Update,
-        draw_golem_areas.run_if(
-            in_state(Screen::Gameplay).and(in_state(Pause(false)).and(resource_exists::<GameMap>)),
+        (draw_golem_areas, draw_golem_level_up_bursts).run_if(
+            in_state(Screen::Gameplay).and(
+                in_state(Pause(false))
+                    .and(resource_exists::<GameMap>)
+                    .and(resource_exists::<PlayerResources>),
+            ),
         ),
     );
 }
@@ -58,7 +84,9 @@ fn spawn_water_golem(
     buildings: Res<BuildingAssets>,
     mut map: ResMut<GameMap>,
     parent_forge: Single<(Entity, &TrackParentBuildingWhilePlacing)>,
-    forges: Query<&Transform, With<ManaForge>>,
+    forges: Query<&Transform, ManaSource>,
+    existing: Query<&BuildingLocation>,
+    mut hint: ResMut<BuildTextHint>,
 ) {
     if resources.mana < WATER_GOLEM_COST_MANA {
         warn!("Not enough resources to spawn water golem");
@@ -77,6 +105,12 @@ fn spawn_water_golem(
         return;
     }
 
+    if let Err(e) = check_placement(coords, &map, &existing) {
+        warn!("{e}, aborting water golem placement");
+        hint.set(e.to_string());
+        return;
+    }
+
     commands.entity(parent_tracking_entity).despawn();
     resources.mana -= WATER_GOLEM_COST_MANA;
     resources.mana_drain -= 2;
@@ -131,23 +165,150 @@ pub struct WaterGolem {
     time_since_last_tick: f32,
     /// The range of the golem (i.e. distance from the building location)
     range: i32,
+    /// The number of fires the golem has put out
+    pub quench_count: u32,
+    /// The number of times the golem has wet a grass or tree cell
+    pub cells_moistened: u32,
+    /// The golem's level, from 1 to 5. Golems level up as they consume mana
+    pub level: u8,
+    /// The total mana the golem has consumed
+    pub total_mana_consumed: u32,
 }
 
 impl Default for WaterGolem {
     fn default() -> Self {
         Self {
             time_since_last_tick: 0.0,
-            range: 4,
+            range: GOLEM_BASE_RANGE,
+            quench_count: 0,
+            cells_moistened: 0,
+            level: 1,
+            total_mana_consumed: 0,
+        }
+    }
+}
+
+impl std::fmt::Display for WaterGolem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Level {}, quenched {} fires",
+            self.level, self.quench_count
+        )
+    }
+}
+
+impl WaterGolem {
+    /// The chance that each fire in range is put out when the golem produces
+    pub fn quench_chance(&self) -> f64 {
+        GOLEM_QUENCH_CHANCE + GOLEM_QUENCH_CHANCE_PER_LEVEL * (self.level - 1) as f64
+    }
+
+    /// Records the mana consumed by the golem, returning true if this takes
+    /// the golem up a level
+    fn consume_mana(&mut self, mana: u32) -> bool {
+        self.total_mana_consumed += mana;
+
+        let level = 1 + GOLEM_LEVEL_THRESHOLDS
+            .iter()
+            .filter(|threshold| self.total_mana_consumed >= **threshold)
+            .count() as u8;
+        if level == self.level {
+            return false;
+        }
+
+        self.level = level;
+        self.range = GOLEM_BASE_RANGE + (level - 1) as i32;
+        true
+    }
+}
+
+/// Triggered when a water golem reaches a new level
+#[derive(Event, Reflect, Debug, Clone, Copy)]
+pub struct GolemLeveledUp {
+    pub golem: Entity,
+    pub level: u8,
+}
+
+/// A burst of droplets thrown out from a golem when it levels up
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+struct GolemLevelUpBurst {
+    age: f32,
+}
+
+fn handle_golem_level_up(
+    trigger: Trigger<GolemLeveledUp>,
+    mut commands: Commands,
+    mut log: ResMut<EventLog>,
+    stats: Res<FireStats>,
+    golems: Query<&Transform, With<WaterGolem>>,
+) {
+    let event = trigger.event();
+    log_event(
+        &mut log,
+        stats.duration,
+        format!("A water golem reached level {}", event.level),
+    );
+
+    if let Ok(tx) = golems.get(event.golem) {
+        commands.spawn((
+            Name::new("Golem Level Up Burst"),
+            GolemLevelUpBurst { age: 0.0 },
+            Transform::from_translation(tx.translation),
+            StateScoped(Screen::Gameplay),
+        ));
+    }
+}
+
+/// Draws droplets radiating out from golems that have just levelled up, fading
+/// as they go
+fn draw_golem_level_up_bursts(
+    mut commands: Commands,
+    mut painter: ShapePainter,
+    time: Res<Time>,
+    map: Res<GameMap>,
+    mut bursts: Query<(Entity, &mut GolemLevelUpBurst, &Transform)>,
+) {
+    let original_tx = painter.transform;
+
+    for (entity, mut burst, tx) in &mut bursts {
+        burst.age += time.delta_secs();
+        if burst.age >= GOLEM_LEVEL_UP_BURST_TIME {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let progress = burst.age / GOLEM_LEVEL_UP_BURST_TIME;
+        let mut color = SKY_300;
+        color.alpha = 1.0 - progress;
+
+        painter.hollow = false;
+        painter.set_color(color);
+
+        for idx in 0..GOLEM_LEVEL_UP_DROPLETS {
+            let angle = std::f32::consts::TAU * idx as f32 / GOLEM_LEVEL_UP_DROPLETS as f32;
+            let offset = Vec2::from_angle(angle) * progress * 8.0 * map.sprite_size;
+
+            painter.transform = original_tx;
+            painter.translate(tx.translation + offset.extend(0.1));
+            painter.circle(map.sprite_size * 0.5);
         }
+
+        painter.transform = original_tx;
     }
 }
 
 fn draw_golem_areas(
+    time: Res<Time>,
     mut painter: ShapePainter,
     map: Res<GameMap>,
+    resources: Res<PlayerResources>,
     golems: Query<(&Transform, &WaterGolem)>,
 ) {
     let original_tx = painter.transform;
+    // golems only work while there is mana for them to consume
+    let active = resources.mana >= WATER_GOLEM_MANA_CONSUMPTION;
 
     for (tx, golem) in &golems {
         let mut color = INDIGO_600;
@@ -158,24 +319,35 @@ fn draw_golem_areas(
         painter.translate(tx.translation - Vec3::new(0.0, 0.0, 0.05));
         painter.circle(golem.range as f32 * map.sprite_size);
 
+        if active {
+            color.alpha = 0.2 + 0.1 * (3.0 * time.elapsed_secs()).sin();
+            painter.set_color(color);
+            painter.circle(GOLEM_GLOW_RADIUS);
+        }
+
         painter.transform = original_tx;
     }
 }
 
 pub const WATER_GOLEM_PRODUCTION_TIME: f32 = 2.0;
-pub const WATER_GOLEM_QUENCH_CHANCE: f64 = 0.3;
+/// The chance that each fire in range of a golem is put out when it produces
+pub const GOLEM_QUENCH_CHANCE: f64 = 0.2;
 pub const WATER_GOLEM_MOISTURE_INCREASE: f32 = 0.05;
 pub const WATER_GOLEM_MANA_CONSUMPTION: i32 = 4;
 
 fn produce_from_water_golem(
     time: Res<Time>,
+    game_speed: Res<GameSpeed>,
     mut map: ResMut<GameMap>,
+    mut moisture_map: ResMut<MoistureMap>,
+    mut commands: Commands,
     mut resources: ResMut<PlayerResources>,
-    mut golems: Query<(&BuildingLocation, &mut WaterGolem)>,
+    mut stats: ResMut<FireStats>,
+    mut golems: Query<(Entity, &BuildingLocation, &mut WaterGolem)>,
 ) {
-    let delta = time.delta_secs();
+    let delta = time.delta_secs() * game_speed.0;
 
-    for (loc, mut golem) in &mut golems {
+    for (entity, loc, mut golem) in &mut golems {
         if golem.time_since_last_tick + delta <= WATER_GOLEM_PRODUCTION_TIME {
             golem.time_since_last_tick += delta;
             continue;
@@ -190,31 +362,44 @@ fn produce_from_water_golem(
         }
         resources.mana = (resources.mana - WATER_GOLEM_MANA_CONSUMPTION).max(0);
 
+        if golem.consume_mana(WATER_GOLEM_MANA_CONSUMPTION as u32) {
+            info!("Water golem at {} reached level {}", loc.0, golem.level);
+            commands.trigger(GolemLeveledUp {
+                golem: entity,
+                level: golem.level,
+            });
+        }
+
         // find all cells in rand and handle them
         let neighbours = map
             .cells_within_range(loc.0, golem.range)
             .collect::<Vec<_>>();
 
         let mut rng = rand::thread_rng();
+        let quench_chance = golem.quench_chance();
 
         for coord in &neighbours {
             if let Some(cell) = map.get_mut(*coord) {
                 match cell.terrain {
                     TerrainType::Fire => {
-                        if rng.gen_bool(WATER_GOLEM_QUENCH_CHANCE) {
+                        if rng.gen_bool(quench_chance) {
                             cell.terrain = TerrainType::Smoldering;
                             cell.mark_dirty();
+                            golem.quench_count += 1;
+                            stats.fires_extinguished += 1;
                         }
                     }
                     TerrainType::Grassland | TerrainType::Tree => {
-                        cell.moisture =
-                            (cell.moisture + WATER_GOLEM_MOISTURE_INCREASE).clamp(0.0, 1.0);
-                        cell.mark_dirty();
+                        if let Some(moisture) = moisture_map.get_mut(*coord) {
+                            *moisture += WATER_GOLEM_MOISTURE_INCREASE;
+                        }
+                        golem.cells_moistened += 1;
                     }
                     TerrainType::Dirt
                     | TerrainType::Building
                     | TerrainType::Stone
-                    | TerrainType::Smoldering => {
+                    | TerrainType::Smoldering
+                    | TerrainType::Water => {
                         // nop
                     }
                 }
@@ -0,0 +1,293 @@
+//! A context panel that is shown next to a building when the player clicks on
+//! it, showing what the building has done so far and allowing it to be
+//! upgraded, demolished, storm mages to be rotated or minotaurs to change mode
+
+use bevy::{color::palettes::tailwind::SLATE_700, prelude::*, window::PrimaryWindow};
+
+use crate::{
+    Pause,
+    screens::{
+        BuildingMode, BuildingType, Screen,
+        gameplay::{
+            BuildTextHint, HintMessage, STORM_MAGE_ROTATE_COST_MANA,
+            building::{
+                BuildingAge, BuildingTier, DemolishBuilding, LumberMill, MAX_BUILDING_TIER,
+                ManaForge, Minotaur, MinotaurPair, RotateStormMage, StormMage, ToggleMinotaurMode,
+                UpgradeBuilding, WaterGolem, upgrade_cost_lumber, upgrade_description,
+            },
+        },
+    },
+    theme::{node_builder::NodeBuilder, widget},
+    wildfire::TerrainCell,
+};
+
+/// The width of the panel, in pixels
+const BUILDING_PANEL_WIDTH: f32 = 250.0;
+/// Roughly how tall the panel is, used to keep it on screen
+const BUILDING_PANEL_HEIGHT: f32 = 280.0;
+/// How far from the click the panel is shown, in pixels
+const BUILDING_PANEL_OFFSET: f32 = 20.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<SelectedBuilding>();
+    app.register_type::<BuildingPanelAnchor>();
+    app.register_type::<BuildingPanel>();
+
+    app.init_resource::<SelectedBuilding>();
+    app.init_resource::<BuildingPanelAnchor>();
+    app.add_observer(select_clicked_building);
+
+    app.add_systems(OnExit(Screen::Gameplay), clear_selected_building);
+    app.add_systems(
+        Update,
+        (
+            clear_destroyed_selection,
+            redraw_building_panel.run_if(resource_changed::<SelectedBuilding>),
+        )
+            .chain()
+            .run_if(in_state(Screen::Gameplay).and(in_state(Pause(false)))),
+    );
+}
+
+/// The building that the player last clicked on, if any
+#[derive(Resource, Reflect, Debug, Default)]
+#[reflect(Resource)]
+pub struct SelectedBuilding(pub Option<Entity>);
+
+/// Where on screen the selected building was clicked, so the panel can be
+/// shown next to it
+#[derive(Resource, Reflect, Debug, Default)]
+#[reflect(Resource)]
+pub(super) struct BuildingPanelAnchor(pub(super) Vec2);
+
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct BuildingPanel;
+
+fn select_clicked_building(
+    trigger: Trigger<Pointer<Click>>,
+    mode: Res<BuildingMode>,
+    mut selected: ResMut<SelectedBuilding>,
+    mut anchor: ResMut<BuildingPanelAnchor>,
+    buildings: Query<(), With<BuildingType>>,
+    cells: Query<(), With<TerrainCell>>,
+) {
+    if trigger.event().button != PointerButton::Primary || *mode != BuildingMode::None {
+        return;
+    }
+
+    let target = trigger.target();
+    if buildings.contains(target) {
+        anchor.0 = trigger.event().pointer_location.position;
+        selected.0 = Some(target);
+    } else if cells.contains(target) && selected.0.is_some() {
+        // clicking on the map closes the panel
+        selected.0 = None;
+    }
+}
+
+fn clear_selected_building(mut selected: ResMut<SelectedBuilding>) {
+    selected.0 = None;
+}
+
+/// Closes the panel if the selected building has been destroyed
+fn clear_destroyed_selection(
+    mut selected: ResMut<SelectedBuilding>,
+    buildings: Query<(), With<BuildingType>>,
+) {
+    if let Some(entity) = selected.0 {
+        if !buildings.contains(entity) {
+            selected.0 = None;
+        }
+    }
+}
+
+fn redraw_building_panel(
+    mut commands: Commands,
+    selected: Res<SelectedBuilding>,
+    anchor: Res<BuildingPanelAnchor>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut hint: ResMut<BuildTextHint>,
+    buildings: Query<(
+        &BuildingType,
+        Option<&BuildingAge>,
+        Option<&BuildingTier>,
+        Option<&WaterGolem>,
+        Option<&StormMage>,
+        Option<&Minotaur>,
+    )>,
+    producers: Query<(
+        Option<&LumberMill>,
+        Option<&ManaForge>,
+        Option<&MinotaurPair>,
+    )>,
+    previous_panels: Query<Entity, With<BuildingPanel>>,
+) {
+    for previous in &previous_panels {
+        commands.entity(previous).despawn();
+    }
+
+    let Some((building_type, maybe_age, maybe_tier, maybe_golem, maybe_mage, maybe_minotaur)) =
+        selected.0.and_then(|e| buildings.get(e).ok())
+    else {
+        hint.clear();
+        return;
+    };
+    let (maybe_mill, maybe_forge, maybe_pair) = selected
+        .0
+        .and_then(|e| producers.get(e).ok())
+        .unwrap_or_default();
+
+    let title = match maybe_tier {
+        Some(tier) => format!("{building_type} (Tier {})", tier.0),
+        None => format!("{building_type}"),
+    };
+
+    let upgrade = maybe_tier.and_then(|tier| {
+        let next_tier = tier.0 + 1;
+        upgrade_cost_lumber(*building_type, next_tier)
+            .map(|cost| (cost, upgrade_description(*building_type, next_tier)))
+    });
+
+    let mut details = match (&upgrade, maybe_tier) {
+        (Some((cost, description)), _) => {
+            hint.0 = HintMessage::BuildingData {
+                name: format!("Upgrade {building_type}"),
+                cost: format!("{cost} Lumber"),
+                details: description.clone(),
+            };
+            format!("Next tier: {description}\nCosts: {cost} Lumber")
+        }
+        (None, Some(tier)) if tier.0 >= MAX_BUILDING_TIER => "Fully upgraded".to_string(),
+        _ => "Cannot be upgraded".to_string(),
+    };
+
+    if let Some(golem) = maybe_golem {
+        details = format!(
+            "{golem}\nMoistened {} cells\n{details}",
+            golem.cells_moistened
+        );
+    }
+
+    if let Some(mage) = maybe_mage {
+        details = format!("Wind blowing {}\n{details}", mage.rotation());
+    }
+
+    if let Some(minotaur) = maybe_minotaur {
+        details = format!(
+            "Mode: {}\nCleared {} cells\n{details}",
+            minotaur.mode, minotaur.cells_cleared
+        );
+
+        if maybe_pair.is_some() {
+            details = format!("Paired with a nearby minotaur\n{details}");
+        }
+    }
+
+    if let Some(mill) = maybe_mill {
+        details = format!("Harvested {} trees\n{details}", mill.total_harvested);
+    }
+
+    if let Some(forge) = maybe_forge {
+        details = format!("Produced {} mana\n{details}", forge.total_mana);
+    }
+
+    if let Some(age) = maybe_age {
+        let minutes = (age.0 / 60.0).floor() as i32;
+        let seconds = (age.0 % 60.0).floor() as i32;
+        details = format!("Active for {minutes}:{seconds:02}\n{details}");
+    }
+
+    // show the panel beside the click, but keep it on screen
+    let left = (anchor.0.x + BUILDING_PANEL_OFFSET)
+        .min(window.width() - BUILDING_PANEL_WIDTH - BUILDING_PANEL_OFFSET)
+        .max(0.0);
+    let top = (anchor.0.y - BUILDING_PANEL_OFFSET)
+        .min(window.height() - BUILDING_PANEL_HEIGHT)
+        .max(0.0);
+
+    commands
+        .spawn((
+            Name::new("Building Panel"),
+            BuildingPanel,
+            GlobalZIndex(4),
+            StateScoped(Screen::Gameplay),
+            NodeBuilder::new()
+                .position(PositionType::Absolute)
+                .flex_direction(FlexDirection::Column)
+                .row_gap(Val::Px(10.0))
+                .padding(UiRect::all(Val::Px(10.0)))
+                .left(left)
+                .top(top)
+                .width(Val::Px(BUILDING_PANEL_WIDTH))
+                .background(SLATE_700)
+                .build(),
+            children![
+                (Text::new(title), TextFont::from_font_size(16.0)),
+                (Text::new(details), TextFont::from_font_size(12.0)),
+            ],
+        ))
+        .with_children(|parent| {
+            if upgrade.is_some() {
+                parent.spawn(widget::button_menu("Upgrade", upgrade_selected_building));
+            }
+
+            if maybe_mage.is_some() {
+                parent.spawn(widget::button_menu(
+                    format!("Rotate ({STORM_MAGE_ROTATE_COST_MANA} Mana)"),
+                    rotate_selected_mage,
+                ));
+            }
+
+            if let Some(minotaur) = maybe_minotaur {
+                parent.spawn(widget::button_menu(
+                    format!("Make {}", minotaur.mode.toggled()),
+                    toggle_selected_minotaur,
+                ));
+            }
+
+            if *building_type != BuildingType::CityHall {
+                parent.spawn(widget::button_menu("Demolish", demolish_selected_building));
+            }
+        });
+}
+
+fn upgrade_selected_building(
+    _trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    selected: Res<SelectedBuilding>,
+) {
+    if let Some(entity) = selected.0 {
+        commands.queue(UpgradeBuilding(entity));
+    }
+}
+
+fn rotate_selected_mage(
+    _trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    selected: Res<SelectedBuilding>,
+) {
+    if let Some(entity) = selected.0 {
+        commands.queue(RotateStormMage(entity));
+    }
+}
+
+fn toggle_selected_minotaur(
+    _trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    selected: Res<SelectedBuilding>,
+) {
+    if let Some(entity) = selected.0 {
+        commands.queue(ToggleMinotaurMode(entity));
+    }
+}
+
+fn demolish_selected_building(
+    _trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut selected: ResMut<SelectedBuilding>,
+) {
+    if let Some(entity) = selected.0.take() {
+        commands.trigger(DemolishBuilding(entity));
+    }
+}
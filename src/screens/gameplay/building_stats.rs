@@ -0,0 +1,105 @@
+//! Shows how a placed building is performing in the build hint when the
+//! player hovers over it
+
+use bevy::prelude::*;
+
+use crate::{
+    screens::{
+        BuildingLocation, BuildingMode, BuildingType,
+        gameplay::{
+            BuildTextHint,
+            building::{LumberMill, ManaForge, Minotaur, RiverGolem, WaterGolem},
+            building_panel::SelectedBuilding,
+        },
+    },
+    wildfire::{GameMap, TerrainType},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_observer(show_building_stats);
+    app.add_observer(hide_building_stats);
+}
+
+fn show_building_stats(
+    trigger: Trigger<Pointer<Over>>,
+    mode: Res<BuildingMode>,
+    maybe_map: Option<Res<GameMap>>,
+    mut hint: ResMut<BuildTextHint>,
+    buildings: Query<(&BuildingType, &BuildingLocation)>,
+    mills: Query<&LumberMill>,
+    golems: Query<&WaterGolem>,
+    river_golems: Query<&RiverGolem>,
+    minotaurs: Query<&Minotaur>,
+    forges: Query<&ManaForge>,
+) {
+    if *mode != BuildingMode::None {
+        return;
+    }
+
+    let target = trigger.target();
+    let (Some(map), Ok((building_type, loc))) = (maybe_map, buildings.get(target)) else {
+        return;
+    };
+
+    let stats = if let Ok(mill) = mills.get(target) {
+        let (trees, grass) =
+            map.cells_within_range(loc.0, mill.range())
+                .fold((0, 0), |(trees, grass), coord| {
+                    match map.get(coord).map(|cell| cell.terrain) {
+                        Some(TerrainType::Tree) => (trees + 1, grass),
+                        Some(TerrainType::Grassland) => (trees, grass + 1),
+                        _ => (trees, grass),
+                    }
+                });
+        let rate = if trees > 0 {
+            mill.lumber_per_second()
+        } else {
+            0.0
+        };
+
+        format!(
+            "Trees in range: {trees}\nGrass in range: {grass}\nTrees harvested: {}\nProducing {rate:.1} lumber/sec",
+            mill.total_harvested
+        )
+    } else if let Ok(golem) = golems.get(target) {
+        format!(
+            "Level: {}\nCells moistened: {}\nFires quenched: {}",
+            golem.level, golem.cells_moistened, golem.quench_count
+        )
+    } else if let Ok(golem) = river_golems.get(target) {
+        format!("Cells moistened: {}", golem.cells_moistened)
+    } else if let Ok(minotaur) = minotaurs.get(target) {
+        format!(
+            "Cells cleared: {}\nMode: {}",
+            minotaur.cells_cleared, minotaur.mode
+        )
+    } else if let Ok(forge) = forges.get(target) {
+        format!(
+            "Total mana produced: {}\nProducing {} mana/sec",
+            forge.total_mana, forge.mana_per_second
+        )
+    } else {
+        return;
+    };
+
+    hint.set(format!("{building_type}\n------\n{stats}"));
+}
+
+fn hide_building_stats(
+    trigger: Trigger<Pointer<Out>>,
+    mode: Res<BuildingMode>,
+    mut hint: ResMut<BuildTextHint>,
+    mut selected: ResMut<SelectedBuilding>,
+    buildings: Query<(), With<BuildingType>>,
+) {
+    if *mode != BuildingMode::None || !buildings.contains(trigger.target()) {
+        return;
+    }
+
+    if selected.0.is_some() {
+        // redraw the building panel to put back its upgrade hint
+        selected.set_changed();
+    } else {
+        hint.clear();
+    }
+}
@@ -0,0 +1,268 @@
+//! Challenge modifiers that add handicaps on top of the normal story levels.
+//! Completing a level with modifiers active is recorded separately.
+
+#[cfg(not(target_family = "wasm"))]
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+#[cfg(not(target_family = "wasm"))]
+use bevy::reflect::serde::{TypedReflectDeserializer, TypedReflectSerializer};
+#[cfg(not(target_family = "wasm"))]
+use serde::de::DeserializeSeed;
+
+use crate::{
+    Pause,
+    screens::{GameSpeed, PlayerResources, Screen, StoryModeLevel},
+};
+
+#[cfg(not(target_family = "wasm"))]
+use super::SAVE_DIRECTORY;
+
+/// The mana per second the player receives when mana forges are not allowed
+pub const CHALLENGE_MANA_INCOME: i32 = 10;
+
+/// The modifiers that can be chosen from the challenge menu
+pub const CHALLENGES: [ChallengeModifier; 4] = [
+    ChallengeModifier::NoManaForge,
+    ChallengeModifier::HighWinds,
+    ChallengeModifier::DroughtStart,
+    ChallengeModifier::LimitedLumber(200),
+];
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ChallengeModifier>();
+    app.register_type::<ChallengeModifiers>();
+    app.register_type::<ChallengeLumberLimit>();
+    app.register_type::<ChallengeCompletion>();
+    app.register_type::<ChallengeCompletions>();
+    app.register_type::<Vec<ChallengeModifier>>();
+    app.register_type::<Vec<ChallengeCompletion>>();
+
+    app.init_resource::<ChallengeModifiers>();
+    app.init_resource::<ChallengeCompletions>();
+
+    #[cfg(not(target_family = "wasm"))]
+    app.add_systems(Startup, load_challenge_completions);
+    app.add_systems(OnEnter(Screen::LevelWon), record_challenge_completion);
+
+    app.add_systems(
+        Update,
+        produce_challenge_mana.run_if(
+            in_state(Screen::Gameplay)
+                .and(in_state(Pause(false)))
+                .and(resource_exists::<PlayerResources>)
+                .and(challenge_active(ChallengeModifier::NoManaForge)),
+        ),
+    );
+}
+
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChallengeModifier {
+    /// Mana forges can't be built, instead mana trickles in at a fixed rate
+    NoManaForge,
+    /// The wind is three times as strong
+    HighWinds,
+    /// The whole map starts bone dry
+    DroughtStart,
+    /// Lumber mills stop harvesting after this much lumber
+    LimitedLumber(i32),
+}
+
+impl std::fmt::Display for ChallengeModifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChallengeModifier::NoManaForge => write!(f, "No Mana Forges"),
+            ChallengeModifier::HighWinds => write!(f, "High Winds"),
+            ChallengeModifier::DroughtStart => write!(f, "Drought"),
+            ChallengeModifier::LimitedLumber(limit) => write!(f, "Limited Lumber ({limit})"),
+        }
+    }
+}
+
+impl ChallengeModifier {
+    /// A longer description of the modifier, shown in the challenge menu
+    pub fn description(&self) -> String {
+        match self {
+            ChallengeModifier::NoManaForge => format!(
+                "Mana forges can't be built, but you receive {CHALLENGE_MANA_INCOME} mana per second"
+            ),
+            ChallengeModifier::HighWinds => "The wind blows three times as hard".into(),
+            ChallengeModifier::DroughtStart => "Every cell starts with no moisture".into(),
+            ChallengeModifier::LimitedLumber(limit) => {
+                format!("Lumber mills can only harvest {limit} lumber in total")
+            }
+        }
+    }
+}
+
+/// The challenge modifiers applied to the current story level
+#[derive(Resource, Reflect, Debug, Clone, Default)]
+#[reflect(Resource)]
+pub struct ChallengeModifiers(pub Vec<ChallengeModifier>);
+
+impl ChallengeModifiers {
+    /// Whether the given modifier is active
+    pub fn has(&self, modifier: ChallengeModifier) -> bool {
+        self.0.contains(&modifier)
+    }
+
+    /// Turns the given modifier on or off
+    pub fn toggle(&mut self, modifier: ChallengeModifier) {
+        if let Some(idx) = self.0.iter().position(|m| *m == modifier) {
+            self.0.remove(idx);
+        } else {
+            self.0.push(modifier);
+        }
+    }
+
+    /// The total lumber that can be harvested, if it is limited
+    pub fn lumber_limit(&self) -> Option<i32> {
+        self.0.iter().find_map(|m| match m {
+            ChallengeModifier::LimitedLumber(limit) => Some(*limit),
+            _ => None,
+        })
+    }
+}
+
+/// A run condition that checks if the given challenge modifier is active
+pub fn challenge_active(
+    modifier: ChallengeModifier,
+) -> impl Fn(Option<Res<ChallengeModifiers>>) -> bool {
+    move |modifiers| modifiers.is_some_and(|m| m.has(modifier))
+}
+
+/// The lumber that can still be harvested in a [ChallengeModifier::LimitedLumber] level
+#[derive(Resource, Reflect, Debug, Clone, Copy)]
+#[reflect(Resource)]
+pub struct ChallengeLumberLimit {
+    pub remaining: i32,
+}
+
+/// A story level that was completed with challenge modifiers active
+#[derive(Reflect, Debug, Clone, PartialEq)]
+pub struct ChallengeCompletion {
+    pub level: usize,
+    pub modifiers: Vec<ChallengeModifier>,
+}
+
+/// The challenges the player has completed. This is written to disk so that
+/// it persists between sessions.
+#[derive(Resource, Reflect, Debug, Clone, Default)]
+#[reflect(Resource)]
+pub struct ChallengeCompletions {
+    pub completed: Vec<ChallengeCompletion>,
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn completions_path() -> PathBuf {
+    PathBuf::from(SAVE_DIRECTORY).join("challenges.ron")
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn load_challenge_completions(
+    mut completions: ResMut<ChallengeCompletions>,
+    registry: Res<AppTypeRegistry>,
+) {
+    let path = completions_path();
+    let Ok(contents) = std::fs::read_to_string(&path) else {
+        // nothing has been completed yet
+        return;
+    };
+
+    let registry = registry.read();
+    let mut deserializer = match ron::Deserializer::from_str(&contents) {
+        Ok(deserializer) => deserializer,
+        Err(e) => {
+            warn!("Unable to parse challenge completions: {e}");
+            return;
+        }
+    };
+
+    let reflected = match TypedReflectDeserializer::of::<ChallengeCompletions>(&registry)
+        .deserialize(&mut deserializer)
+    {
+        Ok(reflected) => reflected,
+        Err(e) => {
+            warn!("Unable to deserialize challenge completions: {e}");
+            return;
+        }
+    };
+
+    match ChallengeCompletions::from_reflect(&*reflected) {
+        Some(loaded) => *completions = loaded,
+        None => warn!("Challenge completions are invalid, ignoring"),
+    }
+}
+
+fn record_challenge_completion(
+    mut completions: ResMut<ChallengeCompletions>,
+    modifiers: Res<ChallengeModifiers>,
+    maybe_level: Option<Res<StoryModeLevel>>,
+    #[cfg(not(target_family = "wasm"))] registry: Res<AppTypeRegistry>,
+) {
+    let Some(level) = maybe_level else {
+        return;
+    };
+
+    if modifiers.0.is_empty() {
+        return;
+    }
+
+    let completion = ChallengeCompletion {
+        level: level.level_number,
+        modifiers: modifiers.0.clone(),
+    };
+    if completions.completed.contains(&completion) {
+        return;
+    }
+
+    info!(
+        "Completed level {} with {} challenge modifiers",
+        completion.level,
+        completion.modifiers.len()
+    );
+    completions.completed.push(completion);
+
+    #[cfg(not(target_family = "wasm"))]
+    save_challenge_completions(&completions, &registry);
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn save_challenge_completions(completions: &ChallengeCompletions, registry: &AppTypeRegistry) {
+    let registry = registry.read();
+    let serializer = TypedReflectSerializer::new(completions, &registry);
+    let contents = match ron::ser::to_string_pretty(&serializer, ron::ser::PrettyConfig::default())
+    {
+        Ok(contents) => contents,
+        Err(e) => {
+            warn!("Unable to serialize challenge completions: {e}");
+            return;
+        }
+    };
+
+    let path = completions_path();
+    if let Err(e) =
+        std::fs::create_dir_all(SAVE_DIRECTORY).and_then(|_| std::fs::write(&path, contents))
+    {
+        warn!(
+            "Unable to write challenge completions to {}: {e}",
+            path.display()
+        );
+    }
+}
+
+/// Without mana forges the player receives a steady trickle of mana instead
+fn produce_challenge_mana(
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut since_last_tick: Local<f32>,
+    mut resources: ResMut<PlayerResources>,
+) {
+    *since_last_tick += time.delta_secs() * game_speed.0;
+    if *since_last_tick < 1.0 {
+        return;
+    }
+
+    *since_last_tick -= 1.0;
+    resources.add_mana(CHALLENGE_MANA_INCOME);
+}
@@ -0,0 +1,341 @@
+//! A menu shown when the player right clicks on a building, with shortcuts to
+//! inspect, upgrade or demolish it, or toggle its fire alarm. Lumber mills
+//! can switch how they harvest, and storm mages also get buttons to change
+//! their intensity
+
+use bevy::{
+    color::palettes::tailwind::SLATE_800, input::common_conditions::input_just_pressed, prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    Pause,
+    input::MousePosition,
+    screens::{
+        BuildingLocation, BuildingMode, BuildingType, Screen,
+        gameplay::{
+            building::{
+                BUILDING_FOOTPRINT_OFFSETS, BuildingTier, DemolishBuilding, FireAlarm, LumberMill,
+                SetStormMageIntensity, StormMage, UpgradeBuilding, upgrade_cost_lumber,
+            },
+            building_panel::{BuildingPanelAnchor, SelectedBuilding},
+            cancel_cursor_mode,
+        },
+    },
+    theme::{node_builder::NodeBuilder, widget},
+    wildfire::{GameMap, TerrainCell},
+};
+
+/// The width of the menu, in pixels
+const CONTEXT_MENU_WIDTH: f32 = 140.0;
+/// Roughly how tall the menu is with all of its buttons, used to keep it on
+/// screen
+const CONTEXT_MENU_HEIGHT: f32 = 320.0;
+/// How much each press of the intensity buttons changes a storm mage's
+/// intensity
+const STORM_MAGE_INTENSITY_STEP: f32 = 0.25;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ContextMenu>();
+    app.register_type::<ContextMenuDismiss>();
+    app.register_type::<StormMageIntensityLabel>();
+
+    app.add_observer(dismiss_context_menu);
+    app.add_observer(dismiss_context_menu_on_click_away);
+
+    app.add_systems(
+        Update,
+        (
+            open_context_menu
+                .before(cancel_cursor_mode)
+                .run_if(input_just_pressed(MouseButton::Right)),
+            dismiss_destroyed_building_menu,
+            update_storm_mage_intensity_label,
+        )
+            .run_if(
+                in_state(Screen::Gameplay)
+                    .and(in_state(Pause(false)))
+                    .and(resource_exists::<GameMap>),
+            ),
+    );
+}
+
+/// The right click menu for a building
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct ContextMenu {
+    pub building: Entity,
+}
+
+/// Closes any open context menu
+#[derive(Event, Reflect, Debug, Clone, Copy)]
+pub struct ContextMenuDismiss;
+
+/// Shows the intensity and range of the storm mage the menu was opened for
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct StormMageIntensityLabel;
+
+fn open_context_menu(
+    mut commands: Commands,
+    mode: Res<BuildingMode>,
+    mouse: Res<MousePosition>,
+    map: Res<GameMap>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    buildings: Query<(
+        Entity,
+        &BuildingType,
+        &BuildingLocation,
+        Option<&BuildingTier>,
+        Option<&StormMage>,
+        Option<&FireAlarm>,
+        Option<&LumberMill>,
+    )>,
+) {
+    // right click cancels placement when building
+    if *mode != BuildingMode::None {
+        return;
+    }
+
+    commands.trigger(ContextMenuDismiss);
+
+    let coords = map.tile_coords(mouse.world_pos);
+    let Some((entity, building_type, _, maybe_tier, maybe_mage, maybe_alarm, maybe_mill)) =
+        buildings.iter().find(|(_, _, loc, _, _, _, _)| {
+            BUILDING_FOOTPRINT_OFFSETS
+                .iter()
+                .any(|offset| loc.0 + *offset == coords)
+        })
+    else {
+        return;
+    };
+
+    let can_upgrade =
+        maybe_tier.is_some_and(|tier| upgrade_cost_lumber(*building_type, tier.0 + 1).is_some());
+    let can_demolish = *building_type != BuildingType::CityHall;
+    let alarm_enabled = maybe_alarm.is_some_and(|alarm| alarm.enabled);
+
+    // keep the menu on screen
+    let left = mouse
+        .viewport_pos
+        .x
+        .min(window.width() - CONTEXT_MENU_WIDTH)
+        .max(0.0);
+    let top = mouse
+        .viewport_pos
+        .y
+        .min(window.height() - CONTEXT_MENU_HEIGHT)
+        .max(0.0);
+
+    commands
+        .spawn((
+            Name::new("Building Context Menu"),
+            ContextMenu { building: entity },
+            GlobalZIndex(5),
+            StateScoped(Screen::Gameplay),
+            NodeBuilder::new()
+                .position(PositionType::Absolute)
+                .flex_direction(FlexDirection::Column)
+                .row_gap(Val::Px(5.0))
+                .padding(UiRect::all(Val::Px(5.0)))
+                .left(left)
+                .top(top)
+                .width(Val::Px(CONTEXT_MENU_WIDTH))
+                .background(SLATE_800)
+                .build(),
+            children![(
+                Text::new(building_type.to_string()),
+                TextFont::from_font_size(14.0)
+            )],
+        ))
+        .with_children(|parent| {
+            parent.spawn(widget::button_menu("Inspect", inspect_building));
+
+            if can_upgrade {
+                parent.spawn(widget::button_menu("Upgrade", upgrade_building));
+            }
+
+            if alarm_enabled {
+                parent.spawn(widget::button_menu("Disable Alarm", toggle_fire_alarm));
+            } else {
+                parent.spawn(widget::button_menu("Enable Alarm", toggle_fire_alarm));
+            }
+
+            if let Some(mill) = maybe_mill {
+                parent.spawn(widget::button_menu(
+                    format!("Harvest: {}", mill.harvest_mode),
+                    toggle_harvest_mode,
+                ));
+            }
+
+            if let Some(mage) = maybe_mage {
+                parent.spawn((
+                    StormMageIntensityLabel,
+                    Text::new(storm_mage_intensity_text(mage)),
+                    TextFont::from_font_size(12.0),
+                ));
+                parent.spawn(widget::button_menu("Intensity +", increase_mage_intensity));
+                parent.spawn(widget::button_menu("Intensity -", decrease_mage_intensity));
+            }
+
+            if can_demolish {
+                parent.spawn(widget::button_menu("Demolish", demolish_building));
+            }
+        });
+}
+
+fn storm_mage_intensity_text(mage: &StormMage) -> String {
+    format!(
+        "Intensity {:.0}%, range {}",
+        mage.intensity() * 100.0,
+        mage.range()
+    )
+}
+
+fn update_storm_mage_intensity_label(
+    menus: Query<&ContextMenu>,
+    mages: Query<&StormMage, Changed<StormMage>>,
+    mut labels: Query<&mut Text, With<StormMageIntensityLabel>>,
+) {
+    for menu in &menus {
+        let Ok(mage) = mages.get(menu.building) else {
+            continue;
+        };
+
+        for mut label in &mut labels {
+            label.0 = storm_mage_intensity_text(mage);
+        }
+    }
+}
+
+fn dismiss_context_menu(
+    _trigger: Trigger<ContextMenuDismiss>,
+    mut commands: Commands,
+    menus: Query<Entity, With<ContextMenu>>,
+) {
+    for menu in &menus {
+        commands.entity(menu).despawn();
+    }
+}
+
+/// Clicking anywhere on the map closes the menu
+fn dismiss_context_menu_on_click_away(
+    trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    menus: Query<(), With<ContextMenu>>,
+    targets: Query<(), Or<(With<TerrainCell>, With<BuildingType>)>>,
+) {
+    if trigger.event().button != PointerButton::Primary {
+        return;
+    }
+
+    if !menus.is_empty() && targets.contains(trigger.target()) {
+        commands.trigger(ContextMenuDismiss);
+    }
+}
+
+fn dismiss_destroyed_building_menu(
+    mut commands: Commands,
+    menus: Query<&ContextMenu>,
+    buildings: Query<(), With<BuildingType>>,
+) {
+    if menus.iter().any(|menu| !buildings.contains(menu.building)) {
+        commands.trigger(ContextMenuDismiss);
+    }
+}
+
+fn inspect_building(
+    _trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mouse: Res<MousePosition>,
+    mut selected: ResMut<SelectedBuilding>,
+    mut anchor: ResMut<BuildingPanelAnchor>,
+    menu: Single<&ContextMenu>,
+) {
+    anchor.0 = mouse.viewport_pos;
+    selected.0 = Some(menu.building);
+    commands.trigger(ContextMenuDismiss);
+}
+
+fn upgrade_building(
+    _trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    menu: Single<&ContextMenu>,
+) {
+    commands.queue(UpgradeBuilding(menu.building));
+    commands.trigger(ContextMenuDismiss);
+}
+
+fn toggle_fire_alarm(
+    _trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    menu: Single<&ContextMenu>,
+    mut alarms: Query<&mut FireAlarm>,
+) {
+    match alarms.get_mut(menu.building) {
+        Ok(mut alarm) => alarm.enabled = !alarm.enabled,
+        Err(_) => {
+            commands.entity(menu.building).insert(FireAlarm::new(true));
+        }
+    }
+
+    commands.trigger(ContextMenuDismiss);
+}
+
+fn toggle_harvest_mode(
+    _trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    menu: Single<&ContextMenu>,
+    mut mills: Query<&mut LumberMill>,
+) {
+    if let Ok(mut mill) = mills.get_mut(menu.building) {
+        mill.harvest_mode = mill.harvest_mode.toggled();
+        info!("Lumber mill now harvesting in {} mode", mill.harvest_mode);
+    }
+
+    commands.trigger(ContextMenuDismiss);
+}
+
+/// Changing the intensity keeps the menu open so the player can step through
+/// several levels
+fn increase_mage_intensity(
+    _trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    menu: Single<&ContextMenu>,
+    mages: Query<&StormMage>,
+) {
+    if let Ok(mage) = mages.get(menu.building) {
+        commands.queue(SetStormMageIntensity(
+            menu.building,
+            mage.intensity() + STORM_MAGE_INTENSITY_STEP,
+        ));
+    }
+}
+
+fn decrease_mage_intensity(
+    _trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    menu: Single<&ContextMenu>,
+    mages: Query<&StormMage>,
+) {
+    if let Ok(mage) = mages.get(menu.building) {
+        commands.queue(SetStormMageIntensity(
+            menu.building,
+            mage.intensity() - STORM_MAGE_INTENSITY_STEP,
+        ));
+    }
+}
+
+fn demolish_building(
+    _trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut selected: ResMut<SelectedBuilding>,
+    menu: Single<&ContextMenu>,
+) {
+    if selected.0 == Some(menu.building) {
+        selected.0 = None;
+    }
+
+    commands.trigger(DemolishBuilding(menu.building));
+    commands.trigger(ContextMenuDismiss);
+}
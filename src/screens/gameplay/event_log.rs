@@ -0,0 +1,106 @@
+//! A short log of notable events, shown in the bottom left of the screen so
+//! that the player finds out about things that happen off-screen
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{screens::Screen, theme::node_builder::NodeBuilder, wildfire::FireStats};
+
+/// The most entries kept in the log, older entries are dropped first
+const MAX_EVENT_LOG_ENTRIES: usize = 10;
+
+/// How long (in game seconds) an entry is shown before it is removed
+const EVENT_LOG_ENTRY_LIFETIME: f32 = 15.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<EventLog>();
+    app.register_type::<EventLogTextMarker>();
+
+    app.init_resource::<EventLog>();
+
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        (reset_event_log, spawn_event_log),
+    );
+    app.add_systems(
+        Update,
+        (expire_event_log_entries, update_event_log_text)
+            .chain()
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// Recent notable events, along with the game time (in seconds) they happened at
+#[derive(Resource, Reflect, Debug, Clone, Default)]
+#[reflect(Resource)]
+pub struct EventLog {
+    pub entries: VecDeque<(f32, String)>,
+}
+
+/// Adds an entry to the event log, dropping the oldest entry if the log is full
+pub fn log_event(log: &mut EventLog, elapsed: f32, msg: impl Into<String>) {
+    let msg = msg.into();
+    info!("Event: {msg}");
+
+    log.entries.push_back((elapsed, msg));
+    while log.entries.len() > MAX_EVENT_LOG_ENTRIES {
+        let _ = log.entries.pop_front();
+    }
+}
+
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+struct EventLogTextMarker;
+
+fn reset_event_log(mut log: ResMut<EventLog>) {
+    log.entries.clear();
+}
+
+fn spawn_event_log(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Event Log"),
+        EventLogTextMarker,
+        GlobalZIndex(3),
+        StateScoped(Screen::Gameplay),
+        NodeBuilder::new()
+            .position(PositionType::Absolute)
+            .left(10.0)
+            .bottom(10.0)
+            .width(Val::Px(300.0))
+            .build(),
+        Text::new(""),
+        TextFont::from_font_size(12.0),
+    ));
+}
+
+/// Removes entries once they have been shown for long enough. This uses the
+/// level duration, so entries stay on screen while the game is paused.
+fn expire_event_log_entries(stats: Res<FireStats>, mut log: ResMut<EventLog>) {
+    let expired = log
+        .entries
+        .iter()
+        .take_while(|(elapsed, _)| stats.duration - elapsed > EVENT_LOG_ENTRY_LIFETIME)
+        .count();
+
+    // avoid triggering change detection every frame
+    if expired > 0 {
+        log.entries.drain(..expired);
+    }
+}
+
+fn update_event_log_text(
+    log: Res<EventLog>,
+    mut text: Single<&mut Text, With<EventLogTextMarker>>,
+) {
+    if !log.is_changed() {
+        return;
+    }
+
+    text.0 = log
+        .entries
+        .iter()
+        .map(|(elapsed, msg)| format!("[{elapsed:.0}s] {msg}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+}
@@ -0,0 +1,118 @@
+//! A bar below the toolbar showing how much of the map is currently burning,
+//! a bit like the health bar of a unit in an RTS
+
+use bevy::{
+    color::palettes::tailwind::{RED_600, SLATE_700},
+    prelude::*,
+};
+
+use crate::{
+    screens::{RequiresCityHall, Screen},
+    theme::node_builder::NodeBuilder,
+    wildfire::GameMap,
+};
+
+/// The width of the background of the fire bar in pixels
+const FIRE_BAR_WIDTH: f32 = 300.0;
+
+/// The fraction of the map on fire above which the bar starts flashing
+const FIRE_BAR_WARNING_FRACTION: f32 = 0.15;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<FireHealthBar>();
+    app.register_type::<FireHealthBarFill>();
+    app.register_type::<FireHealthBarText>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), spawn_fire_health_bar);
+    app.add_systems(
+        Update,
+        update_fire_health_bar.run_if(in_state(Screen::Gameplay).and(resource_exists::<GameMap>)),
+    );
+}
+
+/// The container for the fire bar, hidden until the city hall is placed
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+struct FireHealthBar;
+
+/// The red part of the fire bar, which grows as more of the map burns
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+struct FireHealthBarFill;
+
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+struct FireHealthBarText;
+
+fn spawn_fire_health_bar(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Fire Health Bar"),
+        FireHealthBar,
+        GlobalZIndex(3),
+        StateScoped(Screen::Gameplay),
+        NodeBuilder::new()
+            .position(PositionType::Absolute)
+            .width(Val::Percent(100.0))
+            .top(40.0)
+            .left(0.0)
+            .center_content()
+            .build(),
+        children![
+            (
+                Name::new("Fire Health Bar Background"),
+                NodeBuilder::new()
+                    .width(Val::Px(FIRE_BAR_WIDTH))
+                    .height(Val::Px(10.0))
+                    .background(SLATE_700)
+                    .build(),
+                children![(
+                    FireHealthBarFill,
+                    NodeBuilder::new()
+                        .width(Val::Percent(0.0))
+                        .height(Val::Percent(100.0))
+                        .background(RED_600)
+                        .build(),
+                )],
+            ),
+            (
+                FireHealthBarText,
+                NodeBuilder::new()
+                    .margin(UiRect::left(Val::Px(5.0)))
+                    .build(),
+                Text::new(""),
+                TextFont::from_font_size(12.0),
+            ),
+        ],
+    ));
+}
+
+fn update_fire_health_bar(
+    time: Res<Time>,
+    map: Res<GameMap>,
+    maybe_requires_city_hall: Option<Res<RequiresCityHall>>,
+    mut container: Single<&mut Visibility, With<FireHealthBar>>,
+    mut fill: Single<(&mut Node, &mut BackgroundColor), With<FireHealthBarFill>>,
+    mut text: Single<&mut Text, With<FireHealthBarText>>,
+) {
+    if maybe_requires_city_hall.is_some() {
+        **container = Visibility::Hidden;
+        return;
+    }
+    **container = Visibility::Inherited;
+
+    let fire_count = map.fire_count();
+    let fire_fraction = fire_count as f32 / (map.size_x * map.size_y) as f32;
+
+    let (node, colour) = &mut *fill;
+    node.width = Val::Percent(fire_fraction * 100.0);
+
+    // flash the bar when a large part of the map is burning
+    let alpha = if fire_fraction > FIRE_BAR_WARNING_FRACTION {
+        0.6 + 0.4 * (time.elapsed_secs() * 8.0).sin()
+    } else {
+        1.0
+    };
+    colour.0 = RED_600.with_alpha(alpha).into();
+
+    text.0 = format!("{fire_count} burning ({:.1}%)", fire_fraction * 100.0);
+}
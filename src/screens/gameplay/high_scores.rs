@@ -0,0 +1,371 @@
+//! The best endless mode runs and story level completion times, written to
+//! disk so they persist between sessions. Only available on native builds.
+
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{
+    prelude::*,
+    reflect::serde::{TypedReflectDeserializer, TypedReflectSerializer},
+};
+use serde::de::DeserializeSeed;
+
+use crate::{
+    screens::{EndlessMode, Screen, StoryModeLevel},
+    wildfire::{FireStats, OnSpawnMap},
+};
+
+use super::{
+    SAVE_DIRECTORY,
+    milestones::{EndlessTimer, MilestoneReached},
+};
+
+/// The number of runs kept in the high score table
+const MAX_HIGH_SCORES: usize = 10;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<HighScoreEntry>();
+    app.register_type::<HighScoreTable>();
+    app.register_type::<HighScores>();
+    app.register_type::<EndlessRun>();
+    app.register_type::<Vec<HighScoreEntry>>();
+    app.register_type::<LevelCompletionRecord>();
+    app.register_type::<LevelRecordTable>();
+    app.register_type::<LevelRecords>();
+    app.register_type::<Vec<LevelCompletionRecord>>();
+
+    app.init_resource::<HighScores>();
+    app.init_resource::<EndlessRun>();
+    app.init_resource::<LevelRecords>();
+
+    app.add_systems(Startup, (load_high_scores, load_level_records));
+    app.add_observer(start_endless_run);
+    app.add_observer(record_high_score_at_milestone);
+    app.add_systems(
+        OnEnter(Screen::Replay),
+        record_high_score_at_game_over.run_if(resource_exists::<EndlessMode>),
+    );
+    // runs before the victory screen is spawned so it can show the result
+    app.add_systems(
+        OnTransition {
+            exited: Screen::Gameplay,
+            entered: Screen::LevelWon,
+        },
+        record_level_completion.run_if(resource_exists::<StoryModeLevel>),
+    );
+}
+
+/// A single endless mode run
+#[derive(Reflect, Debug, Clone, PartialEq)]
+pub struct HighScoreEntry {
+    pub score: u32,
+    /// The day the run was played, as YYYY-MM-DD
+    pub date: String,
+    pub map_seed: i32,
+    /// How long the city hall survived, in seconds
+    pub survival_time: f32,
+    pub buildings_built: u32,
+}
+
+impl HighScoreEntry {
+    /// Scores a run, rewarding survival and building while penalising
+    /// buildings lost to the fire
+    pub fn new(map_seed: i32, survival_time: f32, stats: &FireStats) -> Self {
+        let score = survival_time as i64 * 10 + stats.buildings_built as i64 * 50
+            - stats.buildings_lost as i64 * 100;
+
+        Self {
+            score: score.max(0) as u32,
+            date: today(),
+            map_seed,
+            survival_time,
+            buildings_built: stats.buildings_built,
+        }
+    }
+}
+
+/// The best runs, highest score first
+#[derive(Reflect, Debug, Clone, Default)]
+pub struct HighScoreTable {
+    pub entries: Vec<HighScoreEntry>,
+}
+
+impl HighScoreTable {
+    /// Adds a run to the table, replacing an earlier score from the same run
+    /// if there is one, and drops anything outside the top scores
+    fn record(&mut self, entry: HighScoreEntry, replaces: Option<&HighScoreEntry>) {
+        if let Some(previous) = replaces {
+            if let Some(idx) = self.entries.iter().position(|e| e == previous) {
+                self.entries.remove(idx);
+            }
+        }
+
+        self.entries.push(entry);
+        self.entries.sort_by(|a, b| b.score.cmp(&a.score));
+        self.entries.truncate(MAX_HIGH_SCORES);
+    }
+}
+
+#[derive(Resource, Reflect, Debug, Clone, Default)]
+#[reflect(Resource)]
+pub struct HighScores(pub HighScoreTable);
+
+/// The fastest completion of a story level
+#[derive(Reflect, Debug, Clone, PartialEq)]
+pub struct LevelCompletionRecord {
+    pub level_number: usize,
+    /// The story time taken to put out the fire, in seconds
+    pub completion_time: f32,
+    pub buildings_lost: u32,
+}
+
+/// The fastest completion of each story level, one record per level
+#[derive(Reflect, Debug, Clone, Default)]
+pub struct LevelRecordTable {
+    pub records: Vec<LevelCompletionRecord>,
+}
+
+impl LevelRecordTable {
+    /// The personal best for the given level, if it has been completed
+    pub fn best(&self, level_number: usize) -> Option<&LevelCompletionRecord> {
+        self.records.iter().find(|r| r.level_number == level_number)
+    }
+
+    /// Keeps the record if it is the fastest completion of its level,
+    /// returning whether it was kept
+    fn record(&mut self, record: LevelCompletionRecord) -> bool {
+        match self
+            .records
+            .iter_mut()
+            .find(|r| r.level_number == record.level_number)
+        {
+            Some(best) if best.completion_time <= record.completion_time => false,
+            Some(best) => {
+                *best = record;
+                true
+            }
+            None => {
+                self.records.push(record);
+                self.records.sort_by_key(|r| r.level_number);
+                true
+            }
+        }
+    }
+}
+
+#[derive(Resource, Reflect, Debug, Clone, Default)]
+#[reflect(Resource)]
+pub struct LevelRecords(pub LevelRecordTable);
+
+/// The result of the story level that was just completed, shown on the
+/// victory screen
+#[derive(Resource, Debug, Clone)]
+pub struct LevelCompletionResult {
+    /// The personal best before this completion, if there was one
+    pub previous_best: Option<f32>,
+    pub is_new_record: bool,
+}
+
+/// The endless run currently being played
+#[derive(Resource, Reflect, Debug, Clone, Default)]
+#[reflect(Resource)]
+struct EndlessRun {
+    map_seed: i32,
+    /// The score last recorded for this run, which is replaced as the run
+    /// goes on
+    recorded: Option<HighScoreEntry>,
+}
+
+/// Today's date as YYYY-MM-DD, in UTC
+fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs() / 86_400)
+        .unwrap_or_default() as i64;
+
+    // convert the days since the unix epoch into a calendar date
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+fn high_scores_path() -> PathBuf {
+    PathBuf::from(SAVE_DIRECTORY).join("highscores.ron")
+}
+
+fn level_records_path() -> PathBuf {
+    PathBuf::from(SAVE_DIRECTORY).join("level_records.ron")
+}
+
+/// Reads a table written by [write_table], returning None if the file
+/// doesn't exist or can't be read
+pub(super) fn read_table<T: FromReflect + TypePath>(
+    path: &Path,
+    description: &str,
+    registry: &AppTypeRegistry,
+) -> Option<T> {
+    // the file doesn't exist until something has been recorded
+    let contents = std::fs::read_to_string(path).ok()?;
+
+    let registry = registry.read();
+    let mut deserializer = match ron::Deserializer::from_str(&contents) {
+        Ok(deserializer) => deserializer,
+        Err(e) => {
+            warn!("Unable to parse {description}: {e}");
+            return None;
+        }
+    };
+
+    let reflected =
+        match TypedReflectDeserializer::of::<T>(&registry).deserialize(&mut deserializer) {
+            Ok(reflected) => reflected,
+            Err(e) => {
+                warn!("Unable to deserialize {description}: {e}");
+                return None;
+            }
+        };
+
+    let loaded = T::from_reflect(&*reflected);
+    if loaded.is_none() {
+        warn!("The {description} are invalid, ignoring");
+    }
+    loaded
+}
+
+/// Writes a table to disk as ron
+pub(super) fn write_table(
+    table: &dyn PartialReflect,
+    path: &Path,
+    description: &str,
+    registry: &AppTypeRegistry,
+) {
+    let registry = registry.read();
+    let serializer = TypedReflectSerializer::new(table, &registry);
+    let contents = match ron::ser::to_string_pretty(&serializer, ron::ser::PrettyConfig::default())
+    {
+        Ok(contents) => contents,
+        Err(e) => {
+            warn!("Unable to serialize {description}: {e}");
+            return;
+        }
+    };
+
+    if let Err(e) =
+        std::fs::create_dir_all(SAVE_DIRECTORY).and_then(|_| std::fs::write(path, contents))
+    {
+        warn!("Unable to write {description} to {}: {e}", path.display());
+    }
+}
+
+fn load_high_scores(mut scores: ResMut<HighScores>, registry: Res<AppTypeRegistry>) {
+    if let Some(loaded) = read_table(&high_scores_path(), "high scores", &registry) {
+        scores.0 = loaded;
+    }
+}
+
+fn load_level_records(mut records: ResMut<LevelRecords>, registry: Res<AppTypeRegistry>) {
+    if let Some(loaded) = read_table(&level_records_path(), "level records", &registry) {
+        records.0 = loaded;
+    }
+}
+
+fn start_endless_run(trigger: Trigger<OnSpawnMap>, mut run: ResMut<EndlessRun>) {
+    *run = EndlessRun {
+        map_seed: trigger.event().seed,
+        recorded: None,
+    };
+}
+
+fn record_high_score_at_milestone(
+    _trigger: Trigger<MilestoneReached>,
+    mut scores: ResMut<HighScores>,
+    mut run: ResMut<EndlessRun>,
+    timer: Res<EndlessTimer>,
+    stats: Res<FireStats>,
+    registry: Res<AppTypeRegistry>,
+) {
+    record_high_score(&mut scores, &mut run, &timer, &stats, &registry);
+}
+
+fn record_high_score_at_game_over(
+    mut scores: ResMut<HighScores>,
+    mut run: ResMut<EndlessRun>,
+    timer: Res<EndlessTimer>,
+    stats: Res<FireStats>,
+    registry: Res<AppTypeRegistry>,
+) {
+    record_high_score(&mut scores, &mut run, &timer, &stats, &registry);
+}
+
+/// Records the current run in the high score table and writes it to disk
+fn record_high_score(
+    scores: &mut HighScores,
+    run: &mut EndlessRun,
+    timer: &EndlessTimer,
+    stats: &FireStats,
+    registry: &AppTypeRegistry,
+) {
+    let entry = HighScoreEntry::new(run.map_seed, timer.0, stats);
+    info!(
+        "Recording endless score of {} after {:.0}s",
+        entry.score, entry.survival_time
+    );
+    scores.0.record(entry.clone(), run.recorded.as_ref());
+    run.recorded = Some(entry);
+
+    write_table(&scores.0, &high_scores_path(), "high scores", registry);
+}
+
+/// Records how quickly the story level was completed, writing it to disk if
+/// it is a new personal best
+fn record_level_completion(
+    mut commands: Commands,
+    mut records: ResMut<LevelRecords>,
+    level: Res<StoryModeLevel>,
+    stats: Res<FireStats>,
+    registry: Res<AppTypeRegistry>,
+) {
+    let previous_best = records
+        .0
+        .best(level.level_number)
+        .map(|best| best.completion_time);
+    let is_new_record = records.0.record(LevelCompletionRecord {
+        level_number: level.level_number,
+        completion_time: level.elapsed_time,
+        buildings_lost: stats.buildings_lost,
+    });
+
+    info!(
+        "Completed level {} in {:.0}s",
+        level.level_number, level.elapsed_time
+    );
+    if is_new_record {
+        write_table(
+            &records.0,
+            &level_records_path(),
+            "level records",
+            &registry,
+        );
+    }
+
+    commands.insert_resource(LevelCompletionResult {
+        previous_best,
+        // the first completion is a personal best, but not a new record
+        is_new_record: is_new_record && previous_best.is_some(),
+    });
+}
@@ -0,0 +1,120 @@
+//! An overlay listing every keyboard shortcut, toggled with F1. It works while
+//! the game is paused so the player can look things up without losing time
+
+use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+
+use crate::{screens::Screen, theme::widget};
+
+/// Each hotkey and what it does, in the order they are shown
+const HOTKEYS: [(&str, &str); 41] = [
+    ("0", "Meteor"),
+    ("1", "Mana Forge"),
+    ("2", "Lumber Mill"),
+    ("3", "Minotaur Hutch"),
+    ("4", "Water Golem"),
+    ("I", "River Golem"),
+    ("5", "Storm Mage"),
+    ("6", "Controlled Burn"),
+    ("7", "Ember Guard"),
+    ("8", "Rain Cloud"),
+    ("9", "Stone Wall"),
+    ("T", "Arborist"),
+    ("B", "Beacon Tower"),
+    ("Y", "Relay Tower"),
+    ("H", "Research Hall"),
+    ("G", "Mana Battery"),
+    ("F", "Fire Fighter"),
+    ("S", "Sprinkler"),
+    ("U", "Treasury"),
+    ("E", "River Ferry"),
+    ("D", "Demolish"),
+    ("R", "Rotate Storm Mage"),
+    ("Ctrl+Z", "Undo Placement"),
+    ("Space / Right Click", "Cancel"),
+    ("Arrows", "Move Camera"),
+    ("[ / ]", "Game Speed"),
+    ("P / Esc", "Pause"),
+    ("Tab", "Fire Risk Overlay"),
+    ("O", "Burn Heatmap"),
+    ("N", "Moisture Overlay"),
+    ("V", "Fire Prediction"),
+    ("L", "Tile Coordinates"),
+    ("W", "Wind Overlay"),
+    ("Q", "Wind Rose"),
+    ("K", "Region Map"),
+    ("J", "Mana Network"),
+    ("M", "New Map (Endless)"),
+    ("F5", "Quick Save"),
+    ("F9", "Quick Load"),
+    ("Scroll", "Zoom"),
+    ("F1", "Show Hotkeys"),
+];
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<HotkeyOverlay>();
+
+    app.add_systems(
+        Update,
+        (
+            toggle_hotkey_overlay.run_if(input_just_pressed(KeyCode::F1)),
+            close_hotkey_overlay.run_if(
+                input_just_pressed(KeyCode::Escape).and(any_with_component::<HotkeyOverlay>),
+            ),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct HotkeyOverlay;
+
+fn toggle_hotkey_overlay(mut commands: Commands, overlays: Query<Entity, With<HotkeyOverlay>>) {
+    if overlays.is_empty() {
+        spawn_hotkey_overlay(&mut commands);
+    } else {
+        for overlay in &overlays {
+            commands.entity(overlay).despawn();
+        }
+    }
+}
+
+fn close_hotkey_overlay(mut commands: Commands, overlays: Query<Entity, With<HotkeyOverlay>>) {
+    for overlay in &overlays {
+        commands.entity(overlay).despawn();
+    }
+}
+
+fn spawn_hotkey_overlay(commands: &mut Commands) {
+    // two hotkeys are shown on each row of the grid to keep the list short
+    let rows = HOTKEYS
+        .chunks(2)
+        .map(|pair| {
+            let [left, right] = [pair.first(), pair.get(1)]
+                .map(|hotkey| hotkey.map(|(key, action)| format!("[{key}] {action}")));
+            [left.unwrap_or_default(), right.unwrap_or_default()]
+        })
+        .collect();
+
+    commands.spawn((
+        Name::new("Hotkey Overlay"),
+        HotkeyOverlay,
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        GlobalZIndex(10),
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+        StateScoped(Screen::Gameplay),
+        children![(
+            widget::ui_root("Hotkey List"),
+            children![
+                widget::header("HOTKEYS"),
+                widget::grid(rows),
+                widget::label("Press F1 or Esc to close"),
+            ],
+        )],
+    ));
+}
@@ -0,0 +1,118 @@
+//! Sharing maps between players as base64 strings. Only the terrain and
+//! moisture of each cell are shared, so an imported map starts a fresh game
+//! with no buildings. The strings are copied to and pasted from the clipboard.
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use bevy::prelude::*;
+
+use crate::{
+    menus::Menu,
+    screens::{
+        BuildingMode, BuildingType, EndlessMode, EventLog, OnRedrawToolbar, PlayerResources,
+        RequiresCityHall, ResearchState, StoryModeLevel,
+        gameplay::building::TrackParentBuildingWhilePlacing, log_event,
+    },
+    wildfire::{
+        FireStats, GameMap, LoadedMapData, MapSize, OnSpawnMap, SelectedMapSize, SpawnedMap,
+    },
+};
+
+/// Elevation and fuel load aren't shared, so imported maps sample them from
+/// noise with this seed. This means the same code always gives the same map
+const IMPORTED_MAP_SEED: i32 = 0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ExportMap>();
+    app.register_type::<ImportMap>();
+
+    app.add_observer(handle_export_map)
+        .add_observer(handle_import_map);
+}
+
+/// Copies the current map to the clipboard as a base64 string
+#[derive(Event, Reflect, Debug, Clone, Copy)]
+pub struct ExportMap;
+
+/// Replaces the current game with the map encoded in the given base64 string
+#[derive(Event, Reflect, Debug, Clone)]
+pub struct ImportMap(pub String);
+
+fn handle_export_map(
+    _trigger: Trigger<ExportMap>,
+    stats: Res<FireStats>,
+    map: Res<GameMap>,
+    mut log: ResMut<EventLog>,
+) {
+    let code = STANDARD.encode(map.to_compact_bytes());
+
+    match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.set_text(code)) {
+        Ok(()) => log_event(&mut log, stats.duration, "Map copied to clipboard"),
+        Err(e) => warn!("Unable to copy map to clipboard: {e}"),
+    }
+}
+
+fn handle_import_map(
+    trigger: Trigger<ImportMap>,
+    mut commands: Commands,
+    stats: Res<FireStats>,
+    mut log: ResMut<EventLog>,
+    mut mode: ResMut<BuildingMode>,
+    mut next_menu: ResMut<NextState<Menu>>,
+    maps: Query<Entity, With<SpawnedMap>>,
+    buildings: Query<Entity, With<BuildingType>>,
+    placements: Query<Entity, With<TrackParentBuildingWhilePlacing>>,
+) {
+    let bytes = match STANDARD.decode(trigger.event().0.trim()) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!("Unable to decode map code: {e}");
+            log_event(&mut log, stats.duration, "Invalid map code");
+            return;
+        }
+    };
+
+    // use the sprite size of the matching preset so large maps fit on screen
+    let map_size = GameMap::compact_dimensions(&bytes)
+        .and_then(MapSize::from_dimensions)
+        .unwrap_or_default();
+    let Some(map) = GameMap::from_compact_bytes(&bytes, IMPORTED_MAP_SEED, map_size.sprite_size())
+    else {
+        warn!("Map code is not a valid map, aborting import");
+        log_event(&mut log, stats.duration, "Invalid map code");
+        return;
+    };
+
+    info!("Importing {}x{} {} map", map.size_x, map.size_y, map.biome);
+
+    // remove the resources first, so that despawning the existing buildings
+    // doesn't trigger any of the destruction side effects
+    commands.remove_resource::<PlayerResources>();
+    for entity in buildings.iter().chain(&placements).chain(&maps) {
+        commands.entity(entity).despawn();
+    }
+
+    let size = UVec2::new(map.size_x as u32, map.size_y as u32);
+    commands.insert_resource(LoadedMapData(map.data));
+    commands.trigger(OnSpawnMap {
+        size,
+        sprite_size: map.sprite_size,
+        biome: map.biome,
+        ..OnSpawnMap::new(IMPORTED_MAP_SEED, map_size)
+    });
+
+    // an imported map is played like an endless game, even if it was shared
+    // from a story level
+    commands.init_resource::<EndlessMode>();
+    commands.remove_resource::<StoryModeLevel>();
+    commands.insert_resource(SelectedMapSize(map_size));
+    commands.insert_resource(FireStats::default());
+
+    commands.insert_resource(PlayerResources::default());
+    commands.insert_resource(ResearchState::default());
+    commands.init_resource::<RequiresCityHall>();
+    *mode = BuildingMode::PlaceCityHall;
+    commands.trigger(OnRedrawToolbar);
+
+    log_event(&mut log, stats.duration, "Imported a shared map");
+    next_menu.set(Menu::None);
+}
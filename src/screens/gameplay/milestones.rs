@@ -0,0 +1,276 @@
+//! Survival milestones for endless mode. The longer the city hall survives,
+//! the more medals the player earns, with a bonus of resources for each one.
+
+use bevy::{
+    color::palettes::tailwind::{AMBER_400, SLATE_700},
+    prelude::*,
+};
+
+use crate::{
+    Pause,
+    screens::{
+        EndlessMode, EventLog, GameSpeed, PlayerResources, RequiresCityHall, Screen, log_event,
+    },
+    theme::{node_builder::NodeBuilder, widget},
+    wildfire::FireStats,
+};
+
+/// The survival times (in seconds) that award a medal in endless mode
+pub const ENDLESS_MILESTONES: [f32; 4] = [60.0, 120.0, 300.0, 600.0];
+
+/// Story levels completed within this many seconds of story time earn gold
+const GOLD_COMPLETION_TIME: f32 = 60.0;
+/// Story levels completed within this many seconds of story time earn silver
+const SILVER_COMPLETION_TIME: f32 = 120.0;
+
+/// The mana awarded each time a milestone is reached
+const MILESTONE_BONUS_MANA: i32 = 100;
+/// The lumber awarded each time a milestone is reached
+const MILESTONE_BONUS_LUMBER: i32 = 50;
+/// How long the milestone panel is shown for, in seconds
+const MILESTONE_PANEL_DURATION: f32 = 5.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<EndlessTimer>();
+    app.register_type::<MilestoneReached>();
+    app.register_type::<Medal>();
+    app.register_type::<MedalCollection>();
+    app.register_type::<MilestonePanel>();
+    app.register_type::<LegendaryDefenderPanel>();
+
+    app.init_resource::<EndlessTimer>();
+    app.init_resource::<MedalCollection>();
+
+    app.add_observer(handle_milestone_reached);
+    app.add_systems(OnEnter(Screen::Gameplay), reset_endless_milestones);
+    app.add_systems(
+        Update,
+        (
+            update_endless_timer.run_if(
+                resource_exists::<EndlessMode>.and(not(resource_exists::<RequiresCityHall>)),
+            ),
+            hide_milestone_panels,
+        )
+            .run_if(in_state(Screen::Gameplay).and(in_state(Pause(false)))),
+    );
+}
+
+/// How long the city hall has survived in endless mode, in seconds
+#[derive(Resource, Reflect, Debug, Clone, Copy, Default)]
+#[reflect(Resource)]
+pub struct EndlessTimer(pub f32);
+
+/// Triggered when the endless timer passes one of the [ENDLESS_MILESTONES]
+#[derive(Event, Reflect, Debug, Clone, Copy)]
+pub struct MilestoneReached(pub f32);
+
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Medal {
+    Bronze,
+    Silver,
+    Gold,
+    Legendary,
+}
+
+impl Medal {
+    /// The medal awarded for surviving the given number of seconds
+    fn for_milestone(milestone: f32) -> Self {
+        match ENDLESS_MILESTONES.iter().position(|m| *m == milestone) {
+            Some(0) | None => Medal::Bronze,
+            Some(1) => Medal::Silver,
+            Some(2) => Medal::Gold,
+            Some(_) => Medal::Legendary,
+        }
+    }
+
+    /// The medal awarded for completing a story level in the given number
+    /// of seconds
+    pub fn for_completion_time(completion_time: f32) -> Self {
+        if completion_time < GOLD_COMPLETION_TIME {
+            Medal::Gold
+        } else if completion_time < SILVER_COMPLETION_TIME {
+            Medal::Silver
+        } else {
+            Medal::Bronze
+        }
+    }
+}
+
+impl std::fmt::Display for Medal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Medal::Bronze => "Bronze",
+                Medal::Silver => "Silver",
+                Medal::Gold => "Gold",
+                Medal::Legendary => "Legendary",
+            }
+        )
+    }
+}
+
+/// The medals earned during the current endless game
+#[derive(Resource, Reflect, Debug, Clone, Default)]
+#[reflect(Resource)]
+pub struct MedalCollection(pub Vec<Medal>);
+
+/// A panel announcing a milestone, removed after a few seconds
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+struct MilestonePanel {
+    remaining: f32,
+}
+
+/// The screen shown once the final milestone is reached
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+struct LegendaryDefenderPanel;
+
+fn reset_endless_milestones(mut timer: ResMut<EndlessTimer>, mut medals: ResMut<MedalCollection>) {
+    timer.0 = 0.0;
+    medals.0.clear();
+}
+
+fn update_endless_timer(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut timer: ResMut<EndlessTimer>,
+) {
+    let previous = timer.0;
+    timer.0 += time.delta_secs() * game_speed.0;
+
+    for milestone in ENDLESS_MILESTONES {
+        if previous < milestone && timer.0 >= milestone {
+            commands.trigger(MilestoneReached(milestone));
+        }
+    }
+}
+
+fn handle_milestone_reached(
+    trigger: Trigger<MilestoneReached>,
+    mut commands: Commands,
+    mut resources: ResMut<PlayerResources>,
+    mut medals: ResMut<MedalCollection>,
+    mut log: ResMut<EventLog>,
+    mut next_pause: ResMut<NextState<Pause>>,
+    stats: Res<FireStats>,
+) {
+    let milestone = trigger.event().0;
+    let minutes = (milestone / 60.0).round() as i32;
+    let medal = Medal::for_milestone(milestone);
+
+    resources.add_mana(MILESTONE_BONUS_MANA);
+    resources.lumber += MILESTONE_BONUS_LUMBER;
+    medals.0.push(medal);
+
+    let message = format!(
+        "You survived {minutes} minute{}!",
+        if minutes == 1 { "" } else { "s" }
+    );
+    log_event(
+        &mut log,
+        stats.duration,
+        format!("{message} Earned a {medal} medal"),
+    );
+
+    if medal == Medal::Legendary {
+        // the final milestone gets its own screen, and the game waits for
+        // the player to decide whether to keep going
+        next_pause.set(Pause(true));
+        commands.spawn((
+            widget::ui_root("Legendary Defender"),
+            LegendaryDefenderPanel,
+            GlobalZIndex(4),
+            StateScoped(Screen::Gameplay),
+            children![
+                widget::header("LEGENDARY DEFENDER"),
+                widget::label(message),
+                widget::label(format!(
+                    "Medals earned: {}",
+                    medals
+                        .0
+                        .iter()
+                        .map(|m| m.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )),
+                widget::button("Keep Defending", keep_defending),
+                widget::button("Retire with honour!", retire),
+            ],
+        ));
+        return;
+    }
+
+    commands.spawn((
+        Name::new("Milestone Panel"),
+        MilestonePanel {
+            remaining: MILESTONE_PANEL_DURATION,
+        },
+        GlobalZIndex(4),
+        StateScoped(Screen::Gameplay),
+        Pickable::IGNORE,
+        NodeBuilder::new()
+            .position(PositionType::Absolute)
+            .width(Val::Percent(100.0))
+            .top(80.0)
+            .center_content()
+            .build(),
+        children![(
+            NodeBuilder::new()
+                .flex_direction(FlexDirection::Column)
+                .align(AlignItems::Center)
+                .padding(UiRect::all(Val::Px(15.0)))
+                .background(SLATE_700)
+                .build(),
+            Pickable::IGNORE,
+            children![
+                (
+                    Text::new(message),
+                    TextFont::from_font_size(28.0),
+                    TextColor(AMBER_400.into()),
+                    Pickable::IGNORE,
+                ),
+                (
+                    Text::new(format!(
+                        "{medal} medal, +{MILESTONE_BONUS_MANA} mana, +{MILESTONE_BONUS_LUMBER} lumber"
+                    )),
+                    TextFont::from_font_size(16.0),
+                    Pickable::IGNORE,
+                ),
+            ],
+        )],
+    ));
+}
+
+fn hide_milestone_panels(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut panels: Query<(Entity, &mut MilestonePanel)>,
+) {
+    for (entity, mut panel) in &mut panels {
+        panel.remaining -= time.delta_secs();
+        if panel.remaining <= 0.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn keep_defending(
+    _: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut next_pause: ResMut<NextState<Pause>>,
+    panels: Query<Entity, With<LegendaryDefenderPanel>>,
+) {
+    next_pause.set(Pause(false));
+
+    for panel in &panels {
+        commands.entity(panel).despawn();
+    }
+}
+
+fn retire(_: Trigger<Pointer<Click>>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
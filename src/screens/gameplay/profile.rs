@@ -0,0 +1,127 @@
+//! The player's lifetime statistics across every game they have played,
+//! written to disk so they persist between sessions. Only available on native
+//! builds.
+
+use std::path::PathBuf;
+
+use bevy::{platform::collections::HashSet, prelude::*};
+
+use crate::{
+    screens::{EndlessMode, Screen, StoryModeLevel},
+    wildfire::{FireStats, GameMap, TerrainType},
+};
+
+use super::{
+    SAVE_DIRECTORY,
+    high_scores::{read_table, write_table},
+    milestones::EndlessTimer,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<PlayerProfile>();
+    app.register_type::<HashSet<usize>>();
+    app.register_type::<StartingVegetation>();
+
+    // loading the profile needs the types above to be registered
+    app.init_resource::<PlayerProfile>();
+    app.init_resource::<StartingVegetation>();
+
+    app.add_systems(
+        Update,
+        count_starting_vegetation.run_if(resource_added::<GameMap>),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), record_game_in_profile);
+}
+
+/// Totals across every game the player has finished
+#[derive(Resource, Reflect, Debug, Clone, Default)]
+#[reflect(Resource)]
+pub struct PlayerProfile {
+    pub total_games: u32,
+    /// Grass and trees that were not burning or burnt out when a game ended
+    pub total_cells_saved: u32,
+    pub total_buildings_placed: u32,
+    pub total_fires_extinguished: u32,
+    pub story_levels_completed: HashSet<usize>,
+    /// The longest survival in endless mode, in seconds
+    pub endless_best_time: f32,
+}
+
+impl FromWorld for PlayerProfile {
+    fn from_world(world: &mut World) -> Self {
+        let registry = world.resource::<AppTypeRegistry>();
+        read_table(&profile_path(), "player profile", registry).unwrap_or_default()
+    }
+}
+
+/// The number of grass and tree cells when the current map was spawned
+#[derive(Resource, Reflect, Debug, Clone, Copy, Default)]
+#[reflect(Resource)]
+struct StartingVegetation(u32);
+
+fn profile_path() -> PathBuf {
+    PathBuf::from(SAVE_DIRECTORY).join("profile.ron")
+}
+
+fn count_starting_vegetation(map: Res<GameMap>, mut vegetation: ResMut<StartingVegetation>) {
+    vegetation.0 = map
+        .data
+        .iter()
+        .flatten()
+        .filter(|cell| matches!(cell.terrain, TerrainType::Grassland | TerrainType::Tree))
+        .count() as u32;
+}
+
+/// Adds the game that just ended to the profile and writes it to disk. Games
+/// that are quit part way through are not counted.
+fn record_game_in_profile(
+    mut profile: ResMut<PlayerProfile>,
+    screen: Res<State<Screen>>,
+    vegetation: Res<StartingVegetation>,
+    stats: Res<FireStats>,
+    timer: Res<EndlessTimer>,
+    maybe_map: Option<Res<GameMap>>,
+    maybe_endless: Option<Res<EndlessMode>>,
+    maybe_level: Option<Res<StoryModeLevel>>,
+    registry: Res<AppTypeRegistry>,
+) {
+    // the state has already changed to the screen being entered
+    let won = match screen.get() {
+        Screen::LevelWon => true,
+        Screen::Replay => false,
+        _ => return,
+    };
+
+    let Some(map) = maybe_map else {
+        warn!("No map found at the end of the game, skipping profile update");
+        return;
+    };
+
+    let burnt = map
+        .data
+        .iter()
+        .flatten()
+        .filter(|cell| matches!(cell.terrain, TerrainType::Fire | TerrainType::Smoldering))
+        .count() as u32;
+
+    profile.total_games += 1;
+    profile.total_cells_saved += vegetation.0.saturating_sub(burnt);
+    profile.total_buildings_placed += stats.buildings_built;
+    profile.total_fires_extinguished += stats.fires_extinguished;
+
+    if maybe_endless.is_some() {
+        profile.endless_best_time = profile.endless_best_time.max(timer.0);
+    }
+
+    if won {
+        if let Some(level) = maybe_level {
+            profile.story_levels_completed.insert(level.level_number);
+        }
+    }
+
+    info!(
+        "Recorded game {} in the player profile",
+        profile.total_games
+    );
+    write_table(&*profile, &profile_path(), "player profile", &registry);
+}
@@ -0,0 +1,169 @@
+//! A temporary popup that is shown when the player clicks on the mana or lumber
+//! count in the toolbar, breaking down where their income is coming from
+
+use bevy::{color::palettes::tailwind::SLATE_700, prelude::*};
+
+use crate::{
+    screens::{
+        Screen,
+        gameplay::{
+            building::{
+                LumberMill, ManaForge, ManaStorage, Minotaur, WATER_GOLEM_MANA_CONSUMPTION,
+                WATER_GOLEM_PRODUCTION_TIME, WaterGolem,
+            },
+            challenge::{CHALLENGE_MANA_INCOME, ChallengeModifier, ChallengeModifiers},
+            toolbar::{EnergyTextMarker, LumberTextMarker},
+        },
+    },
+    theme::node_builder::NodeBuilder,
+};
+
+/// How long (in seconds) the popup is shown before it despawns
+const POPUP_LIFETIME: f32 = 3.0;
+
+/// The mana consumed by each minotaur every second
+const MINOTAUR_MANA_PER_SECOND: f32 = 1.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ResourceBreakdownPopup>();
+
+    app.add_observer(show_resource_breakdown);
+
+    app.add_systems(
+        Update,
+        despawn_expired_popups.run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct ResourceBreakdownPopup {
+    time_remaining: f32,
+}
+
+fn show_resource_breakdown(
+    trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    previous_popups: Query<Entity, With<ResourceBreakdownPopup>>,
+    energy_text: Query<(), With<EnergyTextMarker>>,
+    lumber_text: Query<(), With<LumberTextMarker>>,
+    forges: Query<&ManaForge>,
+    mills: Query<&LumberMill>,
+    minotaurs: Query<(), With<Minotaur>>,
+    golems: Query<(), With<WaterGolem>>,
+    challenge: Res<ChallengeModifiers>,
+    storage: Res<ManaStorage>,
+) {
+    // only handle the click once, rather than as it bubbles up the hierarchy
+    if trigger.target() != trigger.event().target {
+        return;
+    }
+
+    // any further click closes the popup
+    for previous in &previous_popups {
+        commands.entity(previous).despawn();
+    }
+
+    let target = trigger.target();
+    let (title, lines) = if energy_text.contains(target) {
+        let mut lines = Vec::new();
+        let mut total = 0.0;
+
+        let forge_count = forges.iter().count();
+        if forge_count > 0 {
+            let production = forges.iter().map(|f| f.mana_per_second).sum::<i32>() as f32;
+            total += production;
+            lines.push(format!("{forge_count} Mana Forges: +{production:.1}/sec"));
+        }
+
+        if challenge.has(ChallengeModifier::NoManaForge) {
+            total += CHALLENGE_MANA_INCOME as f32;
+            lines.push(format!("Challenge: +{CHALLENGE_MANA_INCOME:.1}/sec"));
+        }
+
+        let minotaur_count = minotaurs.iter().count();
+        if minotaur_count > 0 {
+            let consumption = minotaur_count as f32 * MINOTAUR_MANA_PER_SECOND;
+            total -= consumption;
+            lines.push(format!("{minotaur_count} Minotaurs: -{consumption:.1}/sec"));
+        }
+
+        let golem_count = golems.iter().count();
+        if golem_count > 0 {
+            let consumption = golem_count as f32 * WATER_GOLEM_MANA_CONSUMPTION as f32
+                / WATER_GOLEM_PRODUCTION_TIME;
+            total -= consumption;
+            lines.push(format!("{golem_count} Water Golems: -{consumption:.1}/sec"));
+        }
+
+        if lines.is_empty() {
+            lines.push("Build a Mana Forge to produce mana".to_string());
+        } else {
+            lines.push(format!("Total: {total:+.1}/sec"));
+        }
+
+        if storage.capacity > 0 {
+            lines.push(format!(
+                "Batteries: {} / {} stored",
+                storage.stored, storage.capacity
+            ));
+        }
+
+        ("Mana Income", lines)
+    } else if lumber_text.contains(target) {
+        let mill_count = mills.iter().count();
+        let lines = if mill_count > 0 {
+            let production = mills.iter().map(LumberMill::lumber_per_second).sum::<f32>();
+            vec![
+                format!("{mill_count} Lumber Mills: up to +{production:.1}/sec"),
+                "Mills only produce lumber while there are trees in range".to_string(),
+            ]
+        } else {
+            vec!["Build a Lumber Mill to produce lumber".to_string()]
+        };
+
+        ("Lumber Income", lines)
+    } else {
+        return;
+    };
+
+    let cursor = trigger.event().pointer_location.position;
+
+    commands.spawn((
+        Name::new("Resource Breakdown Popup"),
+        ResourceBreakdownPopup {
+            time_remaining: POPUP_LIFETIME,
+        },
+        GlobalZIndex(3),
+        StateScoped(Screen::Gameplay),
+        NodeBuilder::new()
+            .position(PositionType::Absolute)
+            .flex_direction(FlexDirection::Column)
+            .row_gap(Val::Px(5.0))
+            .padding(UiRect::all(Val::Px(10.0)))
+            .left(cursor.x)
+            .top(cursor.y)
+            .width(Val::Px(250.0))
+            .background(SLATE_700)
+            .build(),
+        children![
+            (Text::new(title), TextFont::from_font_size(16.0)),
+            (Text::new(lines.join("\n")), TextFont::from_font_size(12.0)),
+        ],
+    ));
+}
+
+/// Despawns popups once they have been shown for long enough. This uses real
+/// time so that the popup still closes while the game is paused.
+fn despawn_expired_popups(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut popups: Query<(Entity, &mut ResourceBreakdownPopup)>,
+) {
+    for (entity, mut popup) in &mut popups {
+        popup.time_remaining -= time.delta_secs();
+        if popup.time_remaining <= 0.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
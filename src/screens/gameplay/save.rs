@@ -0,0 +1,386 @@
+//! Saving and loading games. The map, player resources, research, wind and
+//! buildings are written to a RON file for each save slot using reflection.
+//! Endless games are also saved to an autosave slot every minute. Saves are
+//! written to disk so aren't available on the web.
+
+use std::path::PathBuf;
+
+use bevy::{
+    input::common_conditions::input_just_pressed,
+    prelude::*,
+    reflect::serde::{TypedReflectDeserializer, TypedReflectSerializer},
+};
+use serde::de::DeserializeSeed;
+
+use crate::{
+    Pause,
+    demo::level::spawn_level,
+    menus::Menu,
+    screens::{
+        BuildingMode, BuildingType, EndlessMode, EventLog, PlayerResources, RequiresCityHall,
+        ResearchState, Screen, StoryModeLevel,
+        gameplay::building::{
+            MageRotation, RestoreBuildings, SavedBuilding, TrackParentBuildingWhilePlacing,
+            saved_buildings,
+        },
+        log_event,
+    },
+    wildfire::{
+        Biome, CurrentWeather, FireStats, GameMap, LoadedMapData, MapSize, OnSpawnMap,
+        SelectedMapSize, SpawnedMap, TerrainCellState, WindDirection,
+    },
+};
+
+use super::SAVE_DIRECTORY;
+
+/// The slot used by the quick save / quick load hotkeys
+const QUICK_SAVE_SLOT: usize = 0;
+/// The number of save slots shown in the save menu
+pub const SAVE_SLOTS: usize = 3;
+/// The slot written to by the auto save, which isn't shown in the save menu
+const AUTOSAVE_SLOT: usize = SAVE_SLOTS + 1;
+/// How often endless games are auto saved, in seconds
+const AUTOSAVE_INTERVAL: f32 = 60.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<SaveGame>();
+    app.register_type::<LoadGame>();
+    app.register_type::<SaveData>();
+    app.register_type::<SavedGameMode>();
+    app.register_type::<Vec<TerrainCellState>>();
+    app.register_type::<Vec<Vec<TerrainCellState>>>();
+    app.register_type::<Option<u8>>();
+    app.register_type::<Option<MageRotation>>();
+    app.register_type::<Vec<BuildingType>>();
+    app.register_type::<AutoSaveTimer>();
+    app.register_type::<ResumeAutosave>();
+
+    app.init_resource::<AutoSaveTimer>();
+
+    app.add_observer(handle_save_game)
+        .add_observer(handle_load_game);
+
+    app.add_systems(
+        Update,
+        (
+            quick_save.run_if(input_just_pressed(KeyCode::F5)),
+            quick_load.run_if(input_just_pressed(KeyCode::F9)),
+        )
+            .run_if(
+                in_state(Screen::Gameplay)
+                    .and(in_state(Pause(false)))
+                    .and(resource_exists::<GameMap>),
+            ),
+    );
+
+    app.add_systems(OnEnter(Screen::Gameplay), reset_autosave_timer);
+    app.add_systems(
+        Update,
+        auto_save.run_if(
+            in_state(Screen::Gameplay)
+                .and(in_state(Pause(false)))
+                .and(resource_exists::<EndlessMode>)
+                .and(resource_exists::<GameMap>)
+                .and(not(resource_exists::<RequiresCityHall>)),
+        ),
+    );
+    // loading the autosave has to wait for the new game to be set up, as
+    // loading replaces the map and buildings that are spawned with it
+    app.add_systems(
+        OnEnter(Screen::Gameplay),
+        resume_autosave
+            .after(spawn_level)
+            .run_if(resource_exists::<ResumeAutosave>),
+    );
+    // a finished game can't be continued
+    app.add_systems(OnEnter(Screen::Replay), delete_autosave);
+    app.add_systems(OnEnter(Screen::LevelWon), delete_autosave);
+}
+
+/// Saves the current game into the given slot
+#[derive(Event, Reflect, Debug, Clone, Copy)]
+pub struct SaveGame(pub usize);
+
+/// Loads the game from the given slot
+#[derive(Event, Reflect, Debug, Clone, Copy)]
+pub struct LoadGame(pub usize);
+
+/// Counts down to the next auto save
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct AutoSaveTimer(pub Timer);
+
+impl Default for AutoSaveTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(AUTOSAVE_INTERVAL, TimerMode::Repeating))
+    }
+}
+
+/// When this resource exists, the autosave is loaded as soon as the game
+/// starts
+#[derive(Resource, Reflect, Debug, Default)]
+#[reflect(Resource)]
+pub struct ResumeAutosave;
+
+/// Everything that is written to a save file
+#[derive(Reflect, Debug)]
+struct SaveData {
+    /// The seed the map was generated from, which also names its regions
+    seed: i32,
+    /// The size of the map, which also sets the size of its sprites
+    map_size: MapSize,
+    biome: Biome,
+    game_mode: SavedGameMode,
+    cells: Vec<Vec<TerrainCellState>>,
+    resources: PlayerResources,
+    /// The buildings that have been unlocked by research
+    research: Vec<BuildingType>,
+    wind: WindDirection,
+    buildings: Vec<SavedBuilding>,
+}
+
+/// The kind of game that was saved
+#[derive(Reflect, Debug, Clone)]
+enum SavedGameMode {
+    Endless,
+    /// A story level, including how long it has been running and the events,
+    /// goblins and gusts that are still to come
+    Story(StoryModeLevel),
+}
+
+fn save_path(slot: usize) -> PathBuf {
+    if slot == AUTOSAVE_SLOT {
+        PathBuf::from(SAVE_DIRECTORY).join("autosave.ron")
+    } else {
+        PathBuf::from(SAVE_DIRECTORY).join(format!("slot_{slot}.ron"))
+    }
+}
+
+/// Whether there is a saved game in the given slot
+pub fn save_exists(slot: usize) -> bool {
+    save_path(slot).exists()
+}
+
+/// Whether there is an unfinished endless game in the autosave slot
+pub fn autosave_exists() -> bool {
+    save_exists(AUTOSAVE_SLOT)
+}
+
+fn quick_save(mut commands: Commands) {
+    commands.trigger(SaveGame(QUICK_SAVE_SLOT));
+}
+
+fn quick_load(mut commands: Commands) {
+    commands.trigger(LoadGame(QUICK_SAVE_SLOT));
+}
+
+fn reset_autosave_timer(mut timer: ResMut<AutoSaveTimer>) {
+    timer.0.reset();
+}
+
+fn auto_save(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut timer: ResMut<AutoSaveTimer>,
+    mut log: ResMut<EventLog>,
+    stats: Res<FireStats>,
+) {
+    timer.0.tick(time.delta());
+    if !timer.0.just_finished() {
+        return;
+    }
+
+    commands.trigger(SaveGame(AUTOSAVE_SLOT));
+    log_event(&mut log, stats.duration, "Auto-saved");
+}
+
+fn resume_autosave(mut commands: Commands) {
+    commands.remove_resource::<ResumeAutosave>();
+    commands.trigger(LoadGame(AUTOSAVE_SLOT));
+}
+
+fn delete_autosave() {
+    let path = save_path(AUTOSAVE_SLOT);
+    if !path.exists() {
+        return;
+    }
+
+    match std::fs::remove_file(&path) {
+        Ok(_) => info!("Removed autosave at {}", path.display()),
+        Err(e) => warn!("Unable to remove autosave at {}: {e}", path.display()),
+    }
+}
+
+fn handle_save_game(trigger: Trigger<SaveGame>, mut commands: Commands) {
+    commands.queue(WriteSaveFile(trigger.event().0));
+}
+
+fn handle_load_game(trigger: Trigger<LoadGame>, mut commands: Commands) {
+    commands.queue(ReadSaveFile(trigger.event().0));
+}
+
+#[derive(Debug, Clone, Copy)]
+struct WriteSaveFile(usize);
+
+impl Command for WriteSaveFile {
+    fn apply(self, world: &mut World) {
+        let Ok(buildings) = world.run_system_cached(saved_buildings) else {
+            warn!("Unable to find buildings to save, aborting save");
+            return;
+        };
+
+        let _ = world.run_system_cached_with(write_save_file, (self.0, buildings));
+    }
+}
+
+fn write_save_file(
+    In((slot, buildings)): In<(usize, Vec<SavedBuilding>)>,
+    registry: Res<AppTypeRegistry>,
+    maybe_map: Option<Res<GameMap>>,
+    maybe_resources: Option<Res<PlayerResources>>,
+    research: Res<ResearchState>,
+    wind: Res<WindDirection>,
+    selected_size: Res<SelectedMapSize>,
+    maybe_story: Option<Res<StoryModeLevel>>,
+) {
+    let (Some(map), Some(resources)) = (maybe_map, maybe_resources) else {
+        warn!("Skipping save as there is no map or resources yet");
+        return;
+    };
+
+    // story levels are always laid out on a medium map
+    let (game_mode, map_size) = match maybe_story {
+        Some(story) => (SavedGameMode::Story(story.clone()), MapSize::Medium),
+        None => (SavedGameMode::Endless, selected_size.0),
+    };
+
+    let data = SaveData {
+        seed: map.seed,
+        map_size,
+        biome: map.biome,
+        game_mode,
+        cells: map.data.clone(),
+        resources: resources.clone(),
+        research: research.unlocked.iter().copied().collect(),
+        wind: *wind,
+        buildings,
+    };
+
+    let registry = registry.read();
+    let serializer = TypedReflectSerializer::new(&data, &registry);
+    let contents = match ron::ser::to_string_pretty(&serializer, ron::ser::PrettyConfig::default())
+    {
+        Ok(contents) => contents,
+        Err(e) => {
+            warn!("Unable to serialize save game: {e}");
+            return;
+        }
+    };
+
+    let path = save_path(slot);
+    match std::fs::create_dir_all(SAVE_DIRECTORY).and_then(|_| std::fs::write(&path, contents)) {
+        Ok(_) => info!("Saved game to {}", path.display()),
+        Err(e) => warn!("Unable to write save game to {}: {e}", path.display()),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ReadSaveFile(usize);
+
+impl Command for ReadSaveFile {
+    fn apply(self, world: &mut World) {
+        let _ = world.run_system_cached_with(read_save_file, self.0);
+    }
+}
+
+fn read_save_file(
+    In(slot): In<usize>,
+    mut commands: Commands,
+    registry: Res<AppTypeRegistry>,
+    mut mode: ResMut<BuildingMode>,
+    mut wind: ResMut<WindDirection>,
+    mut next_menu: ResMut<NextState<Menu>>,
+    maps: Query<Entity, With<SpawnedMap>>,
+    buildings: Query<Entity, With<BuildingType>>,
+    placements: Query<Entity, With<TrackParentBuildingWhilePlacing>>,
+) {
+    let path = save_path(slot);
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            warn!("Unable to read save game from {}: {e}", path.display());
+            return;
+        }
+    };
+
+    let registry = registry.read();
+    let mut deserializer = match ron::Deserializer::from_str(&contents) {
+        Ok(deserializer) => deserializer,
+        Err(e) => {
+            warn!("Unable to parse save game: {e}");
+            return;
+        }
+    };
+
+    let reflected =
+        match TypedReflectDeserializer::of::<SaveData>(&registry).deserialize(&mut deserializer) {
+            Ok(reflected) => reflected,
+            Err(e) => {
+                warn!("Unable to deserialize save game: {e}");
+                return;
+            }
+        };
+
+    let Some(data) = SaveData::from_reflect(&*reflected) else {
+        warn!("Save game data is invalid, aborting load");
+        return;
+    };
+
+    let size = UVec2::from(data.map_size);
+    if data.cells.len() != size.y as usize
+        || data.cells.iter().any(|row| row.len() != size.x as usize)
+    {
+        warn!(
+            "Save game cells don't fit a {} map, aborting load",
+            data.map_size
+        );
+        return;
+    }
+
+    info!("Loading game from {}", path.display());
+
+    // remove the resources first, so that despawning the existing buildings
+    // doesn't trigger any of the destruction side effects
+    commands.remove_resource::<PlayerResources>();
+    for entity in buildings.iter().chain(&placements).chain(&maps) {
+        commands.entity(entity).despawn();
+    }
+
+    commands.insert_resource(LoadedMapData(data.cells));
+    commands.trigger(OnSpawnMap::new(data.seed, data.map_size).with_biome(data.biome));
+
+    match data.game_mode {
+        SavedGameMode::Endless => {
+            commands.init_resource::<EndlessMode>();
+            commands.remove_resource::<StoryModeLevel>();
+            commands.insert_resource(SelectedMapSize(data.map_size));
+        }
+        SavedGameMode::Story(level) => {
+            commands.remove_resource::<EndlessMode>();
+            // spawning the map resets the weather, which is constant for
+            // the whole story level
+            commands.insert_resource(CurrentWeather(level.weather));
+            commands.insert_resource(level);
+        }
+    }
+
+    commands.insert_resource(data.resources);
+    commands.insert_resource(ResearchState {
+        unlocked: data.research.into_iter().collect(),
+    });
+    commands.queue(RestoreBuildings(data.buildings));
+
+    *wind = data.wind;
+    *mode = BuildingMode::None;
+    next_menu.set(Menu::None);
+}
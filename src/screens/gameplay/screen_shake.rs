@@ -0,0 +1,106 @@
+//! Shakes the camera for big events, such as buildings being destroyed and
+//! meteors hitting the ground. Start a shake by queueing [ShakeScreen].
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{MainCamera, Pause, screens::Screen};
+
+/// How long each shake lasts, in seconds
+const SCREEN_SHAKE_DURATION: f32 = 0.4;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ScreenShake>();
+
+    app.add_systems(
+        Update,
+        apply_screen_shake.run_if(
+            in_state(Screen::Gameplay)
+                .and(in_state(Pause(false)))
+                .and(resource_exists::<ScreenShake>),
+        ),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), remove_screen_shake);
+}
+
+/// Shakes the screen with the given intensity (the largest offset in pixels).
+/// A weaker shake won't interrupt one that is already shaking.
+#[derive(Debug, Clone, Copy)]
+pub struct ShakeScreen(pub f32);
+
+impl Command for ShakeScreen {
+    fn apply(self, world: &mut World) {
+        let _ = world.run_system_cached_with(start_screen_shake, self);
+    }
+}
+
+/// The shake currently being applied to the camera
+#[derive(Resource, Reflect, Debug, Clone, Copy)]
+#[reflect(Resource)]
+pub struct ScreenShake {
+    pub intensity: f32,
+    pub duration: f32,
+    pub elapsed: f32,
+    /// The offset applied to the camera last frame, which is removed before
+    /// the next one is applied so the camera doesn't drift
+    offset: Vec2,
+}
+
+fn start_screen_shake(
+    In(config): In<ShakeScreen>,
+    mut commands: Commands,
+    maybe_shake: Option<ResMut<ScreenShake>>,
+) {
+    let intensity = config.0;
+
+    match maybe_shake {
+        Some(mut shake) => {
+            if intensity > shake.intensity {
+                shake.intensity = intensity;
+                shake.duration = SCREEN_SHAKE_DURATION;
+                shake.elapsed = 0.0;
+            }
+        }
+        None => {
+            commands.insert_resource(ScreenShake {
+                intensity,
+                duration: SCREEN_SHAKE_DURATION,
+                elapsed: 0.0,
+                offset: Vec2::ZERO,
+            });
+        }
+    }
+}
+
+fn apply_screen_shake(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut shake: ResMut<ScreenShake>,
+    mut camera: Single<&mut Transform, With<MainCamera>>,
+) {
+    camera.translation -= shake.offset.extend(0.0);
+
+    if shake.elapsed >= shake.duration {
+        commands.remove_resource::<ScreenShake>();
+        return;
+    }
+
+    let magnitude = shake.intensity * (1.0 - shake.elapsed / shake.duration);
+    let angle = rand::thread_rng().gen_range(0.0..std::f32::consts::TAU);
+    shake.offset = Vec2::from_angle(angle) * magnitude;
+    shake.elapsed += time.delta_secs();
+
+    camera.translation += shake.offset.extend(0.0);
+}
+
+/// Stops shaking when leaving the game, putting the camera back where it was
+fn remove_screen_shake(
+    mut commands: Commands,
+    maybe_shake: Option<Res<ScreenShake>>,
+    mut camera: Single<&mut Transform, With<MainCamera>>,
+) {
+    if let Some(shake) = maybe_shake {
+        camera.translation -= shake.offset.extend(0.0);
+        commands.remove_resource::<ScreenShake>();
+    }
+}
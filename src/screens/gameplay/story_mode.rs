@@ -3,26 +3,51 @@
 use std::collections::VecDeque;
 
 use bevy::prelude::*;
+use rand::Rng;
 
 use crate::{
     Pause,
-    screens::{Screen, gameplay::building::SpawnCityHall},
-    wildfire::{GOOD_SEEDS, GameMap, OnMeteorStrike, WindDirection},
+    screens::{
+        GameSpeed, PlayerResources, Screen,
+        gameplay::{
+            EventLog,
+            building::{BuildingLocation, CityHall, SpawnCityHall},
+            challenge::{
+                CHALLENGE_MANA_INCOME, ChallengeLumberLimit, ChallengeModifier, ChallengeModifiers,
+            },
+            log_event,
+        },
+    },
+    wildfire::{
+        Biome, CurrentWeather, DifficultySettings, FireStats, GOOD_SEEDS, GameMap, MapRegions,
+        OnMeteorStrike, OnTornado, SpawnGoblinArsonist, SpawnGoblinShaman, WeatherEvent,
+        WindDirection, WindGust,
+    },
 };
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<StoryModeLevel>();
     app.register_type::<NextStoryLevel>();
+    app.register_type::<StoryEvent>();
+    app.register_type::<StoryStrikeWave>();
 
     app.init_resource::<NextStoryLevel>();
 
     app.add_systems(
         Update,
-        (spawn_story_meteor_bolts, update_story_elapsed_time).run_if(
-            in_state(Screen::Gameplay)
-                .and(in_state(Pause(false)))
-                .and(resource_exists::<StoryModeLevel>),
-        ),
+        (
+            spawn_story_events.run_if(resource_exists::<MapRegions>),
+            update_story_strike_waves.run_if(resource_exists::<GameMap>),
+            spawn_story_goblins,
+            spawn_story_goblin_waves,
+            spawn_story_gusts,
+            update_story_elapsed_time,
+        )
+            .run_if(
+                in_state(Screen::Gameplay)
+                    .and(in_state(Pause(false)))
+                    .and(resource_exists::<StoryModeLevel>),
+            ),
     );
 }
 
@@ -36,6 +61,37 @@ impl Default for NextStoryLevel {
     }
 }
 
+/// A scripted event that happens part way through a story level
+#[derive(Reflect, Clone, Copy, Debug)]
+pub enum StoryEvent {
+    /// A single meteor strikes the given tile
+    MeteorBolt(IVec2),
+    /// A storm of strikes in quick succession, at random tiles within
+    /// `radius` of `center`
+    LightningWave {
+        center: IVec2,
+        count: u8,
+        radius: i32,
+        interval: f32,
+    },
+    /// The prevailing wind changes direction and speed
+    WindShift { angle: f32, speed: f32 },
+    /// A fire tornado touches down at `start` and travels towards `angle`
+    /// (in degrees, using the same convention as the wind)
+    Tornado { start: IVec2, angle: f32 },
+}
+
+/// A wave of strikes that is still in progress
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct StoryStrikeWave {
+    center: IVec2,
+    remaining: u8,
+    radius: i32,
+    interval: f32,
+    time_until_next: f32,
+}
+
 /// Contains the details for a story mode level
 #[derive(Resource, Reflect, Clone, Debug)]
 #[reflect(Resource)]
@@ -44,8 +100,15 @@ pub struct StoryModeLevel {
     pub level_number: usize,
     /// The seed to use when spwaning the map
     pub map_seed: i32,
-    /// The meteor bolts to deploy to start the wildfire
-    pub bolts: VecDeque<(f32, IVec2)>,
+    /// The scripted events, such as the meteor bolts that start the
+    /// wildfire, as (time, event)
+    pub events: VecDeque<(f32, StoryEvent)>,
+    /// The goblin arsonists to spawn, as (time, tile location)
+    pub goblins: VecDeque<(f32, IVec2)>,
+    /// Waves of goblins that attack from an edge of the map, as (time, count)
+    pub goblin_waves: VecDeque<(f32, u8)>,
+    /// The wind gusts to blow, as (time, gust)
+    pub gusts: VecDeque<(f32, WindGust)>,
     /// The starting location for the city hall (in tile coords)
     pub starting_location: IVec2,
     /// The amount of time since this story level was started
@@ -54,6 +117,25 @@ pub struct StoryModeLevel {
     /// Store the wind speed and angle, which is constant for story mode
     pub wind_speed: f32,
     pub wind_angle: f32,
+
+    /// The weather, which is also constant for story mode
+    pub weather: WeatherEvent,
+    /// The biome used to generate the map
+    pub biome: Biome,
+}
+
+impl StoryModeLevel {
+    /// A rough difficulty rating from 1 to 5, based on how much the level
+    /// throws at the player
+    pub fn difficulty(&self) -> usize {
+        let drought = usize::from(self.weather == WeatherEvent::Drought);
+        let threats = self.events.len() / 4
+            + self.goblins.len()
+            + self.goblin_waves.len()
+            + self.gusts.len()
+            + drought;
+        (1 + threats).clamp(1, 5)
+    }
 }
 
 impl Command for StoryModeLevel {
@@ -65,33 +147,292 @@ impl Command for StoryModeLevel {
 fn spawn_story(
     In(config): In<StoryModeLevel>,
     mut commands: Commands,
-    map: Res<GameMap>,
+    mut map: ResMut<GameMap>,
     mut wind: ResMut<WindDirection>,
+    mut weather: ResMut<CurrentWeather>,
+    mut resources: ResMut<PlayerResources>,
+    challenge: Res<ChallengeModifiers>,
+    difficulty: Res<DifficultySettings>,
 ) {
-    info!("Spawning items for level");
+    info!("Spawning items for level on {} difficulty", difficulty.0);
 
     let world_coords = map.world_coords(config.starting_location);
     commands.queue(SpawnCityHall(world_coords));
 
-    wind.r#override(config.wind_angle, config.wind_speed);
+    let wind_speed = if challenge.has(ChallengeModifier::HighWinds) {
+        config.wind_speed * 3.0
+    } else {
+        config.wind_speed
+    };
+    wind.r#override(
+        config.wind_angle,
+        wind_speed * difficulty.0.wind_multiplier(),
+    );
+    weather.0 = config.weather;
+
+    if challenge.has(ChallengeModifier::NoManaForge) {
+        resources.mana_drain += CHALLENGE_MANA_INCOME;
+    }
+
+    if challenge.has(ChallengeModifier::DroughtStart) {
+        info!("Starting level in drought");
+        for cell in map.data.iter_mut().flatten() {
+            cell.moisture = 0.0;
+            cell.mark_dirty();
+        }
+    }
+
+    commands.remove_resource::<ChallengeLumberLimit>();
+    if let Some(remaining) = challenge.lumber_limit() {
+        commands.insert_resource(ChallengeLumberLimit { remaining });
+    }
+
+    // the shaman hides out behind where the first fire starts
+    let first_fire = config
+        .events
+        .iter()
+        .find_map(|(_, event)| match event {
+            StoryEvent::MeteorBolt(loc) => Some(*loc),
+            StoryEvent::LightningWave { center, .. } => Some(*center),
+            StoryEvent::Tornado { start, .. } => Some(*start),
+            StoryEvent::WindShift { .. } => None,
+        })
+        .unwrap_or(config.starting_location);
+    let away_from_city_hall = (first_fire - config.starting_location)
+        .as_vec2()
+        .normalize_or_zero();
+    let max = IVec2::new(map.size_x as i32 - 1, map.size_y as i32 - 1);
+    let shaman_location = (first_fire + (away_from_city_hall * SHAMAN_FIRE_OFFSET).as_ivec2())
+        .clamp(IVec2::ZERO, max);
+    commands.trigger(SpawnGoblinShaman(shaman_location));
 }
 
+/// How far (in cells) behind the first fire the goblin shaman hides
+const SHAMAN_FIRE_OFFSET: f32 = 20.0;
+
 /// Tick the level elapsed time while unpaused
-fn update_story_elapsed_time(time: Res<Time>, mut level: ResMut<StoryModeLevel>) {
-    level.elapsed_time += time.delta_secs();
+fn update_story_elapsed_time(
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut level: ResMut<StoryModeLevel>,
+) {
+    level.elapsed_time += time.delta_secs() * game_speed.0;
+}
+
+/// If an event is due, start it
+fn spawn_story_events(
+    mut commands: Commands,
+    mut level: ResMut<StoryModeLevel>,
+    mut wind: ResMut<WindDirection>,
+    mut log: ResMut<EventLog>,
+    stats: Res<FireStats>,
+    challenge: Res<ChallengeModifiers>,
+    regions: Res<MapRegions>,
+) {
+    // max one event per frame just because its easier to write about
+    let Some((event_time, event)) = level.events.front().copied() else {
+        return;
+    };
+
+    if level.elapsed_time < event_time {
+        return;
+    }
+
+    match event {
+        StoryEvent::MeteorBolt(bolt_loc) => {
+            commands.trigger(OnMeteorStrike(bolt_loc));
+            log_event(
+                &mut log,
+                stats.duration,
+                format!(
+                    "Fire spotted in {}! A meteor struck at {bolt_loc}",
+                    regions.name_at(bolt_loc)
+                ),
+            );
+        }
+        StoryEvent::LightningWave {
+            center,
+            count,
+            radius,
+            interval,
+        } => {
+            info!("Starting a wave of {count} strikes around {center}");
+            commands.spawn((
+                Name::new("Story Strike Wave"),
+                StateScoped(Screen::Gameplay),
+                StoryStrikeWave {
+                    center,
+                    remaining: count,
+                    radius,
+                    interval,
+                    time_until_next: 0.0,
+                },
+            ));
+            log_event(
+                &mut log,
+                stats.duration,
+                format!(
+                    "A storm is striking {} around {center}!",
+                    regions.name_at(center)
+                ),
+            );
+        }
+        StoryEvent::WindShift { angle, speed } => {
+            let speed = if challenge.has(ChallengeModifier::HighWinds) {
+                speed * 3.0
+            } else {
+                speed
+            };
+            wind.r#override(angle, speed);
+            log_event(
+                &mut log,
+                stats.duration,
+                format!("The wind shifted to {}", *wind),
+            );
+        }
+        StoryEvent::Tornado { start, angle } => {
+            // the tornado adds its own warning to the event log
+            commands.trigger(OnTornado(start, Vec2::from_angle(angle.to_radians())));
+        }
+    }
+
+    let _ = level.events.pop_front();
+    info!("Level has {} events remaining", level.events.len());
+}
+
+/// Strikes random tiles for each wave in progress, despawning the wave once
+/// all of its strikes have landed
+fn update_story_strike_waves(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    map: Res<GameMap>,
+    mut waves: Query<(Entity, &mut StoryStrikeWave)>,
+) {
+    let delta = time.delta_secs() * game_speed.0;
+    let mut rng = rand::thread_rng();
+
+    for (entity, mut wave) in &mut waves {
+        wave.time_until_next -= delta;
+        if wave.time_until_next > 0.0 {
+            continue;
+        }
+
+        wave.time_until_next += wave.interval;
+
+        let offset = IVec2::new(
+            rng.gen_range(-wave.radius..=wave.radius),
+            rng.gen_range(-wave.radius..=wave.radius),
+        );
+        // waves near the edge would otherwise throw some strikes off the map
+        let max_coords = IVec2::new(map.size_x as i32 - 1, map.size_y as i32 - 1);
+        commands.trigger(OnMeteorStrike(
+            (wave.center + offset).clamp(IVec2::ZERO, max_coords),
+        ));
+
+        wave.remaining = wave.remaining.saturating_sub(1);
+        if wave.remaining == 0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// If a goblin is due, spawn it
+fn spawn_story_goblins(mut commands: Commands, mut level: ResMut<StoryModeLevel>) {
+    while let Some((goblin_time, goblin_loc)) = level.goblins.front() {
+        if level.elapsed_time < *goblin_time {
+            return;
+        }
+
+        commands.trigger(SpawnGoblinArsonist(*goblin_loc));
+        let _ = level.goblins.pop_front();
+    }
+}
+
+/// How far along the map edge the goblins in a wave are spread, in cells
+const GOBLIN_WAVE_SPREAD: i32 = 10;
+
+/// If a goblin wave is due, spawn its goblins together on a random edge of
+/// the map and warn the player which way they are coming from
+fn spawn_story_goblin_waves(
+    mut commands: Commands,
+    mut level: ResMut<StoryModeLevel>,
+    mut log: ResMut<EventLog>,
+    stats: Res<FireStats>,
+    maybe_map: Option<Res<GameMap>>,
+    city_halls: Query<&BuildingLocation, With<CityHall>>,
+) {
+    let Some((wave_time, count)) = level.goblin_waves.front().copied() else {
+        return;
+    };
+
+    if level.elapsed_time < wave_time {
+        return;
+    }
+
+    let Some(map) = maybe_map else {
+        return;
+    };
+
+    let _ = level.goblin_waves.pop_front();
+
+    let mut rng = rand::thread_rng();
+    let max = IVec2::new(map.size_x as i32 - 1, map.size_y as i32 - 1);
+    let edge = rng.gen_range(0..4);
+    let centre = IVec2::new(rng.gen_range(0..=max.x), rng.gen_range(0..=max.y));
+    // moves the given cell onto the chosen edge of the map
+    let on_edge = |cell: IVec2| match edge {
+        0 => IVec2::new(0, cell.y),
+        1 => IVec2::new(max.x, cell.y),
+        2 => IVec2::new(cell.x, 0),
+        _ => IVec2::new(cell.x, max.y),
+    };
+    let origin = on_edge(centre);
+
+    info!("Spawning a wave of {count} goblins around {origin}");
+    for _ in 0..count {
+        let offset = IVec2::splat(rng.gen_range(-GOBLIN_WAVE_SPREAD..=GOBLIN_WAVE_SPREAD));
+        let location = on_edge((centre + offset).clamp(IVec2::ZERO, max));
+        commands.trigger(SpawnGoblinArsonist(location));
+    }
+
+    let city_hall = city_halls
+        .iter()
+        .next()
+        .map(|loc| loc.0)
+        .unwrap_or(level.starting_location);
+    log_event(
+        &mut log,
+        stats.duration,
+        format!(
+            "GOBLINS INCOMING! {count} goblins approach from the {}",
+            compass_direction(origin - city_hall)
+        ),
+    );
 }
 
-/// If a bolt is due, spawn it
-fn spawn_story_meteor_bolts(mut commands: Commands, mut level: ResMut<StoryModeLevel>) {
-    // max one meteor bolt per frame just because its easier to write about
-    if let Some((bolt_time, bolt_loc)) = level.bolts.front() {
-        if level.elapsed_time < *bolt_time {
+/// The compass direction that most closely matches the given tile offset
+fn compass_direction(offset: IVec2) -> &'static str {
+    if offset.x.abs() > offset.y.abs() {
+        if offset.x > 0 { "east" } else { "west" }
+    } else if offset.y > 0 {
+        "north"
+    } else {
+        "south"
+    }
+}
+
+/// If a gust is due, start it. Only one gust blows at a time, so a new gust
+/// replaces any that is still blowing
+fn spawn_story_gusts(mut commands: Commands, mut level: ResMut<StoryModeLevel>) {
+    if let Some((gust_time, gust)) = level.gusts.front() {
+        if level.elapsed_time < *gust_time {
             return;
         }
 
-        commands.trigger(OnMeteorStrike(*bolt_loc));
-        let _ = level.bolts.pop_front();
-        info!("Level has {} bolts remaining", level.bolts.len());
+        info!("Scripted wind gust for {:.1}s", gust.duration);
+        commands.insert_resource(*gust);
+        let _ = level.gusts.pop_front();
     }
 }
 
@@ -102,15 +443,20 @@ pub fn get_level_data(lvl: usize) -> Option<StoryModeLevel> {
             level_number: lvl,
             map_seed: GOOD_SEEDS[lvl - 1],
             starting_location: IVec2 { x: 168, y: 243 },
-            bolts: vec![
-                (10.0, IVec2 { x: 21, y: 46 }),
-                (30.0, IVec2 { x: 27, y: 175 }),
-                (30.2, IVec2 { x: 29, y: 177 }),
-                (30.2, IVec2 { x: 25, y: 173 }),
+            events: vec![
+                (10.0, StoryEvent::MeteorBolt(IVec2 { x: 21, y: 46 })),
+                (30.0, StoryEvent::MeteorBolt(IVec2 { x: 27, y: 175 })),
+                (30.2, StoryEvent::MeteorBolt(IVec2 { x: 29, y: 177 })),
+                (30.2, StoryEvent::MeteorBolt(IVec2 { x: 25, y: 173 })),
             ]
             .into(),
             wind_speed: 15.0,
             wind_angle: 32.0,
+            goblins: vec![].into(),
+            goblin_waves: vec![(90.0, 2)].into(),
+            gusts: vec![].into(),
+            weather: WeatherEvent::Normal,
+            biome: Biome::Temperate,
             elapsed_time: 0.0,
         })
     } else if lvl == 2 {
@@ -118,29 +464,54 @@ pub fn get_level_data(lvl: usize) -> Option<StoryModeLevel> {
             level_number: lvl,
             map_seed: GOOD_SEEDS[lvl - 1],
             starting_location: IVec2 { x: 27, y: 228 },
-            bolts: vec![
-                (21.0, IVec2 { x: 242, y: 98 }),
-                (50.0, IVec2 { x: 3, y: 30 }),
+            events: vec![
+                (21.0, StoryEvent::MeteorBolt(IVec2 { x: 242, y: 98 })),
+                (50.0, StoryEvent::MeteorBolt(IVec2 { x: 3, y: 30 })),
             ]
             .into(),
 
             wind_speed: 14.0,
             wind_angle: 32.0,
+            goblins: vec![].into(),
+            goblin_waves: vec![(60.0, 3), (90.0, 3)].into(),
+            gusts: vec![].into(),
+            weather: WeatherEvent::Normal,
+            biome: Biome::Temperate,
             elapsed_time: 0.0,
         })
     } else if lvl == 3 {
         Some(StoryModeLevel {
             level_number: lvl,
             map_seed: GOOD_SEEDS[lvl - 1],
-            starting_location: IVec2 { x: 27, y: 228 },
-            bolts: vec![
-                (21.0, IVec2 { x: 242, y: 98 }),
-                (50.0, IVec2 { x: 3, y: 30 }),
+            starting_location: IVec2 { x: 128, y: 128 },
+            events: vec![
+                // fire from the north
+                (15.0, StoryEvent::MeteorBolt(IVec2 { x: 120, y: 240 })),
+                (15.0, StoryEvent::MeteorBolt(IVec2 { x: 124, y: 243 })),
+                (15.2, StoryEvent::MeteorBolt(IVec2 { x: 132, y: 241 })),
+                (15.2, StoryEvent::MeteorBolt(IVec2 { x: 137, y: 238 })),
+                // then from the south
+                (25.0, StoryEvent::MeteorBolt(IVec2 { x: 118, y: 14 })),
+                (25.0, StoryEvent::MeteorBolt(IVec2 { x: 125, y: 11 })),
+                (25.2, StoryEvent::MeteorBolt(IVec2 { x: 131, y: 16 })),
+                (25.2, StoryEvent::MeteorBolt(IVec2 { x: 138, y: 12 })),
+                (
+                    45.0,
+                    StoryEvent::WindShift {
+                        angle: 32.0,
+                        speed: 40.0,
+                    },
+                ),
             ]
             .into(),
 
-            wind_speed: 13.0,
+            wind_speed: 10.0,
             wind_angle: 32.0,
+            goblins: vec![(40.0, IVec2 { x: 250, y: 20 })].into(),
+            goblin_waves: vec![(60.0, 2), (90.0, 3), (120.0, 4)].into(),
+            gusts: vec![(70.0, WindGust::new(32.0, 50.0, 5.0))].into(),
+            weather: WeatherEvent::Drought,
+            biome: Biome::Temperate,
             elapsed_time: 0.0,
         })
     } else if lvl == 4 {
@@ -148,16 +519,52 @@ pub fn get_level_data(lvl: usize) -> Option<StoryModeLevel> {
             level_number: lvl,
             map_seed: GOOD_SEEDS[lvl - 1],
             starting_location: IVec2 { x: 187, y: 195 },
-            bolts: vec![
-                (21.0, IVec2 { x: 68, y: 210 }),
-                (21.0, IVec2 { x: 67, y: 212 }),
-                (23.0, IVec2 { x: 63, y: 214 }),
-                (23.0, IVec2 { x: 61, y: 208 }),
+            events: vec![
+                (21.0, StoryEvent::MeteorBolt(IVec2 { x: 68, y: 210 })),
+                (21.0, StoryEvent::MeteorBolt(IVec2 { x: 67, y: 212 })),
+                (23.0, StoryEvent::MeteorBolt(IVec2 { x: 63, y: 214 })),
+                (23.0, StoryEvent::MeteorBolt(IVec2 { x: 61, y: 208 })),
+                (
+                    45.0,
+                    StoryEvent::LightningWave {
+                        center: IVec2 { x: 120, y: 60 },
+                        count: 5,
+                        radius: 10,
+                        interval: 0.5,
+                    },
+                ),
+                (
+                    90.0,
+                    StoryEvent::WindShift {
+                        angle: 200.0,
+                        speed: 18.0,
+                    },
+                ),
+                (
+                    110.0,
+                    StoryEvent::Tornado {
+                        start: IVec2 { x: 30, y: 120 },
+                        angle: 20.0,
+                    },
+                ),
             ]
             .into(),
 
             wind_speed: 13.0,
             wind_angle: 32.0,
+            goblins: vec![
+                (30.0, IVec2 { x: 5, y: 5 }),
+                (60.0, IVec2 { x: 250, y: 250 }),
+            ]
+            .into(),
+            goblin_waves: vec![].into(),
+            gusts: vec![
+                (35.0, WindGust::new(45.0, 60.0, 4.0)),
+                (80.0, WindGust::new(20.0, 70.0, 6.0)),
+            ]
+            .into(),
+            weather: WeatherEvent::Normal,
+            biome: Biome::Temperate,
             elapsed_time: 0.0,
         })
     } else {
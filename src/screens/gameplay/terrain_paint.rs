@@ -0,0 +1,175 @@
+//! A sandbox tool for painting terrain straight onto the map, for quickly
+//! setting up scenarios without going through the level editor. Sandbox mode
+//! is toggled with <ctrl+shift+x>.
+
+use bevy::{
+    input::{
+        common_conditions::{input_just_pressed, input_pressed},
+        mouse::MouseWheel,
+    },
+    picking::hover::HoverMap,
+    prelude::*,
+};
+
+use crate::{
+    Pause,
+    input::MousePosition,
+    screens::{BuildingMode, OnRedrawToolbar, Screen},
+    wildfire::{GameMap, TerrainType},
+};
+
+/// The terrain types offered in the terrain paint toolbar
+pub const PAINTABLE_TERRAIN: [TerrainType; 6] = [
+    TerrainType::Grassland,
+    TerrainType::Tree,
+    TerrainType::Stone,
+    TerrainType::Water,
+    TerrainType::Dirt,
+    TerrainType::Fire,
+];
+
+/// The largest brush radius, in cells
+const MAX_PAINT_RADIUS: i32 = 5;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<SandboxMode>();
+    app.register_type::<TerrainPaintRadius>();
+
+    app.init_resource::<TerrainPaintRadius>();
+
+    app.add_systems(
+        Update,
+        toggle_sandbox_mode.run_if(
+            in_state(Screen::Gameplay)
+                .and(input_just_pressed(KeyCode::KeyX))
+                .and(input_pressed(KeyCode::ControlLeft))
+                .and(input_pressed(KeyCode::ShiftLeft)),
+        ),
+    );
+    app.add_systems(
+        Update,
+        (change_paint_radius, paint_terrain).chain().run_if(
+            in_state(Screen::Gameplay)
+                .and(in_state(Pause(false)))
+                .and(resource_exists::<GameMap>)
+                .and(painting_terrain),
+        ),
+    );
+}
+
+/// Unlocks sandbox tools such as terrain painting
+#[derive(Resource, Reflect, Debug, Clone, Default)]
+#[reflect(Resource, Default)]
+pub struct SandboxMode;
+
+/// The radius of the terrain paint brush, from 1 (a single cell) to
+/// [MAX_PAINT_RADIUS]
+#[derive(Resource, Reflect, Debug, Clone, Copy)]
+#[reflect(Resource)]
+pub struct TerrainPaintRadius(pub i32);
+
+impl Default for TerrainPaintRadius {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+/// Whether the player is currently painting terrain. The mouse buttons and
+/// scroll wheel are used by the brush rather than the camera while painting
+pub fn painting_terrain(mode: Res<BuildingMode>) -> bool {
+    matches!(*mode, BuildingMode::TerrainPaint(_))
+}
+
+fn toggle_sandbox_mode(
+    mut commands: Commands,
+    mut mode: ResMut<BuildingMode>,
+    maybe_sandbox: Option<Res<SandboxMode>>,
+) {
+    if maybe_sandbox.is_some() {
+        info!("Disabling sandbox mode");
+        commands.remove_resource::<SandboxMode>();
+
+        if matches!(*mode, BuildingMode::TerrainPaint(_)) {
+            *mode = BuildingMode::None;
+        }
+    } else {
+        info!("Enabling sandbox mode");
+        commands.init_resource::<SandboxMode>();
+    }
+
+    commands.trigger(OnRedrawToolbar);
+}
+
+fn change_paint_radius(
+    mut wheel_events: EventReader<MouseWheel>,
+    mut radius: ResMut<TerrainPaintRadius>,
+) {
+    for event in wheel_events.read() {
+        let next = (radius.0 + event.y.signum() as i32).clamp(1, MAX_PAINT_RADIUS);
+        if next != radius.0 {
+            radius.0 = next;
+            info!("Setting terrain paint radius to {next}");
+        }
+    }
+}
+
+/// Paints the selected terrain under the mouse while the left button is
+/// held, or earth while the right button is held
+fn paint_terrain(
+    mode: Res<BuildingMode>,
+    mouse: Res<MousePosition>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    radius: Res<TerrainPaintRadius>,
+    hover_map: Res<HoverMap>,
+    mut map: ResMut<GameMap>,
+    nodes: Query<(), With<Node>>,
+) {
+    let BuildingMode::TerrainPaint(selected) = *mode else {
+        return;
+    };
+
+    let terrain = if buttons.pressed(MouseButton::Left) {
+        selected
+    } else if buttons.pressed(MouseButton::Right) {
+        TerrainType::Dirt
+    } else {
+        return;
+    };
+
+    // don't paint the map underneath the toolbars
+    if hover_map
+        .values()
+        .flat_map(|hits| hits.keys())
+        .any(|entity| nodes.contains(*entity))
+    {
+        return;
+    }
+
+    let centre = map.tile_coords(mouse.world_pos);
+    let coords = map
+        .cells_within_range(centre, radius.0 - 1)
+        .collect::<Vec<_>>();
+
+    for coord in coords {
+        let Some(cell) = map.get_mut(coord) else {
+            continue;
+        };
+
+        // leave building footprints alone so buildings aren't painted over
+        if cell.terrain == TerrainType::Building || cell.terrain == terrain {
+            continue;
+        }
+
+        cell.fuel_load = terrain.typical_fuel_load();
+        if terrain == TerrainType::Fire {
+            cell.ignite();
+            map.mark_on_fire(coord);
+        } else {
+            cell.terrain = terrain;
+            cell.mark_dirty();
+            if terrain == TerrainType::Smoldering {
+                map.mark_smoldering(coord);
+            }
+        }
+    }
+}
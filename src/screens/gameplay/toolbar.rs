@@ -4,9 +4,13 @@
 use std::time::Duration;
 
 use bevy::{
-    color::palettes::tailwind::{SLATE_400, SLATE_700, SLATE_800, SLATE_950},
+    color::palettes::tailwind::{
+        AMBER_300, GREEN_400, ORANGE_400, RED_500, SLATE_400, SLATE_700, SLATE_800, SLATE_950,
+        YELLOW_400, YELLOW_500,
+    },
     ecs::relationship::RelatedSpawnerCommands,
     input::common_conditions::input_just_pressed,
+    platform::collections::HashSet,
     prelude::*,
     time::common_conditions::on_timer,
 };
@@ -16,16 +20,28 @@ use crate::{
     demo::level::spawn_level,
     input::MousePosition,
     screens::{
-        BuildingMode, EndlessMode, PlayerResources, RequiresCityHall, Screen,
+        BuildingMode, BuildingType, EndlessMode, GameSpeed, PlayerResources, RequiresCityHall,
+        Screen,
         gameplay::{
-            BuildTextHint, BuildTextMarker, HintMessage, LUMBER_MILL_COST_LUMBER,
-            MANA_FORGE_COST_LUMBER, MINOTAUR_COST_MANA, STORM_MAGE_COST_MANA,
-            WATER_GOLEM_COST_MANA,
-            building::{BuildingAssets, ResourceAssets},
+            ARBORIST_COST_LUMBER, BEACON_TOWER_COST_MANA, BuildTextHint, BuildTextMarker,
+            EMBER_GUARD_COST_MANA, FIRE_FIGHTER_COST_MANA, HintMessage, LUMBER_MILL_COST_LUMBER,
+            MANA_BATTERY_COST_LUMBER, MANA_FORGE_COST_LUMBER, MINOTAUR_COST_MANA,
+            RELAY_TOWER_COST_LUMBER, RESEARCH_HALL_COST_LUMBER, RIVER_FERRY_COST_LUMBER,
+            RIVER_GOLEM_COST_MANA, ResearchState, SPRINKLER_COST_MANA, STONE_WALL_COST_LUMBER,
+            STORM_MAGE_COST_MANA, TREASURY_COST_LUMBER, WATER_GOLEM_COST_MANA,
+            building::{
+                BuildingAssets, MANA_BATTERY_CAPACITY, ManaStorage, OnBuildingUnlocked,
+                RIVER_GOLEM_RANGE, ResourceAssets, TREASURY_CAPACITY, building_unlocked,
+            },
+            challenge::{ChallengeModifier, ChallengeModifiers, challenge_active},
+            terrain_paint::{PAINTABLE_TERRAIN, SandboxMode},
         },
     },
     theme::node_builder::NodeBuilder,
-    wildfire::{GameMap, WindDirection},
+    wildfire::{
+        CONTROLLED_BURN_COST_MANA, CurrentWeather, GameMap, MapRegions, MoistureTrend, OnSpawnMap,
+        RAIN_CLOUD_COST_MANA, SpreadRateTracker, TerrainType, WindDirection, WindGust,
+    },
 };
 
 pub(super) fn plugin(app: &mut App) {
@@ -33,7 +49,17 @@ pub(super) fn plugin(app: &mut App) {
     app.register_type::<ToolbarButtonType>();
     app.register_type::<EnergyTextMarker>();
     app.register_type::<LumberTextMarker>();
+    app.register_type::<LumberIconMarker>();
     app.register_type::<BuildingHintToolbar>();
+    app.register_type::<SpeedTextMarker>();
+    app.register_type::<GustWarningMarker>();
+    app.register_type::<SpreadRateTextMarker>();
+    app.register_type::<TileCoordMarker>();
+    app.register_type::<ToolbarLockIcon>();
+    app.register_type::<NewlyUnlocked>();
+    app.register_type::<NewlyUnlockedBuildings>();
+
+    app.init_resource::<NewlyUnlockedBuildings>();
 
     app.add_systems(
         OnEnter(Screen::Gameplay),
@@ -46,23 +72,84 @@ pub(super) fn plugin(app: &mut App) {
                 resource_exists::<PlayerResources>.and(on_timer(Duration::from_millis(300))),
             ),
             update_build_hint_ui,
+            update_speed_text,
+            update_gust_warning,
+            update_spread_rate_text,
+            update_tile_coord_text.run_if(resource_exists::<GameMap>),
+            (mark_newly_unlocked_buttons, pulse_newly_unlocked_buttons),
         )
             .chain()
             .run_if(in_state(Screen::Gameplay).and(in_state(Pause(false)))),
     );
 
     app.add_observer(handle_on_redraw_toolbar)
-        .add_observer(handle_disabling_toolbar_buttons);
+        .add_observer(handle_disabling_toolbar_buttons)
+        .add_observer(handle_building_unlocked)
+        .add_observer(clear_newly_unlocked_buildings);
 
     app.add_systems(
         Update,
         (
             meteor_hotkey.run_if(input_just_pressed(KeyCode::Digit0)),
-            mana_forge_hotkey.run_if(input_just_pressed(KeyCode::Digit1)),
+            mana_forge_hotkey.run_if(
+                input_just_pressed(KeyCode::Digit1)
+                    .and(not(challenge_active(ChallengeModifier::NoManaForge))),
+            ),
             lumber_mill_hotkey.run_if(input_just_pressed(KeyCode::Digit2)),
-            minotaur_hotkey.run_if(input_just_pressed(KeyCode::Digit3)),
-            water_golem_hotkey.run_if(input_just_pressed(KeyCode::Digit4)),
-            storm_mage_hotkey.run_if(input_just_pressed(KeyCode::Digit5)),
+            minotaur_hotkey.run_if(
+                input_just_pressed(KeyCode::Digit3).and(building_unlocked(BuildingType::Minotaur)),
+            ),
+            water_golem_hotkey.run_if(
+                input_just_pressed(KeyCode::Digit4)
+                    .and(building_unlocked(BuildingType::WaterGolem)),
+            ),
+            river_golem_hotkey.run_if(
+                input_just_pressed(KeyCode::KeyI).and(building_unlocked(BuildingType::RiverGolem)),
+            ),
+            storm_mage_hotkey.run_if(
+                input_just_pressed(KeyCode::Digit5).and(building_unlocked(BuildingType::StormMage)),
+            ),
+            controlled_burn_hotkey.run_if(input_just_pressed(KeyCode::Digit6)),
+            ember_guard_hotkey.run_if(
+                input_just_pressed(KeyCode::Digit7)
+                    .and(building_unlocked(BuildingType::EmberGuard)),
+            ),
+            rain_cloud_hotkey.run_if(input_just_pressed(KeyCode::Digit8)),
+            stone_wall_hotkey.run_if(input_just_pressed(KeyCode::Digit9)),
+            arborist_hotkey.run_if(
+                input_just_pressed(KeyCode::KeyT).and(building_unlocked(BuildingType::Arborist)),
+            ),
+            beacon_tower_hotkey.run_if(
+                input_just_pressed(KeyCode::KeyB).and(building_unlocked(BuildingType::BeaconTower)),
+            ),
+            relay_tower_hotkey.run_if(
+                input_just_pressed(KeyCode::KeyY).and(building_unlocked(BuildingType::RelayTower)),
+            ),
+            research_hall_hotkey.run_if(
+                input_just_pressed(KeyCode::KeyH)
+                    .and(building_unlocked(BuildingType::ResearchHall)),
+            ),
+            mana_battery_hotkey.run_if(
+                input_just_pressed(KeyCode::KeyG).and(building_unlocked(BuildingType::ManaBattery)),
+            ),
+            fire_fighter_hotkey.run_if(
+                input_just_pressed(KeyCode::KeyF).and(building_unlocked(BuildingType::FireFighter)),
+            ),
+            sprinkler_hotkey.run_if(
+                input_just_pressed(KeyCode::KeyS).and(building_unlocked(BuildingType::Sprinkler)),
+            ),
+            // grouped to stay within the limit on the number of systems in a tuple
+            (
+                treasury_hotkey.run_if(
+                    input_just_pressed(KeyCode::KeyU)
+                        .and(building_unlocked(BuildingType::Treasury)),
+                ),
+                river_ferry_hotkey.run_if(
+                    input_just_pressed(KeyCode::KeyE)
+                        .and(building_unlocked(BuildingType::RiverFerry)),
+                ),
+            ),
+            demolish_hotkey.run_if(input_just_pressed(KeyCode::KeyD)),
         )
             .run_if(in_state(Screen::Gameplay).and(in_state(Pause(false)))),
     );
@@ -103,6 +190,13 @@ fn water_golem_hotkey(mut mode: ResMut<BuildingMode>, mut hint: ResMut<BuildText
     }
 }
 
+fn river_golem_hotkey(mut mode: ResMut<BuildingMode>, mut hint: ResMut<BuildTextHint>) {
+    if *mode == BuildingMode::None {
+        *mode = BuildingMode::PlaceRiverGolem;
+        hint.0 = toolbar_data(ToolbarButtonType::RiverGolem).1;
+    }
+}
+
 fn storm_mage_hotkey(mut mode: ResMut<BuildingMode>, mut hint: ResMut<BuildTextHint>) {
     if *mode == BuildingMode::None {
         *mode = BuildingMode::PlaceStormMage;
@@ -110,6 +204,104 @@ fn storm_mage_hotkey(mut mode: ResMut<BuildingMode>, mut hint: ResMut<BuildTextH
     }
 }
 
+fn controlled_burn_hotkey(mut mode: ResMut<BuildingMode>, mut hint: ResMut<BuildTextHint>) {
+    if *mode == BuildingMode::None {
+        *mode = BuildingMode::ControlledBurn;
+        hint.0 = toolbar_data(ToolbarButtonType::ControlledBurn).1;
+    }
+}
+
+fn ember_guard_hotkey(mut mode: ResMut<BuildingMode>, mut hint: ResMut<BuildTextHint>) {
+    if *mode == BuildingMode::None {
+        *mode = BuildingMode::PlaceEmberGuard;
+        hint.0 = toolbar_data(ToolbarButtonType::EmberGuard).1;
+    }
+}
+
+fn rain_cloud_hotkey(mut mode: ResMut<BuildingMode>, mut hint: ResMut<BuildTextHint>) {
+    if *mode == BuildingMode::None {
+        *mode = BuildingMode::RainCloud;
+        hint.0 = toolbar_data(ToolbarButtonType::RainCloud).1;
+    }
+}
+
+fn stone_wall_hotkey(mut mode: ResMut<BuildingMode>, mut hint: ResMut<BuildTextHint>) {
+    if *mode == BuildingMode::None {
+        *mode = BuildingMode::PlaceStoneWall;
+        hint.0 = toolbar_data(ToolbarButtonType::StoneWall).1;
+    }
+}
+
+fn arborist_hotkey(mut mode: ResMut<BuildingMode>, mut hint: ResMut<BuildTextHint>) {
+    if *mode == BuildingMode::None {
+        *mode = BuildingMode::PlaceArborist;
+        hint.0 = toolbar_data(ToolbarButtonType::Arborist).1;
+    }
+}
+
+fn beacon_tower_hotkey(mut mode: ResMut<BuildingMode>, mut hint: ResMut<BuildTextHint>) {
+    if *mode == BuildingMode::None {
+        *mode = BuildingMode::PlaceBeaconTower;
+        hint.0 = toolbar_data(ToolbarButtonType::BeaconTower).1;
+    }
+}
+
+fn relay_tower_hotkey(mut mode: ResMut<BuildingMode>, mut hint: ResMut<BuildTextHint>) {
+    if *mode == BuildingMode::None {
+        *mode = BuildingMode::PlaceRelayTower;
+        hint.0 = toolbar_data(ToolbarButtonType::RelayTower).1;
+    }
+}
+
+fn research_hall_hotkey(mut mode: ResMut<BuildingMode>, mut hint: ResMut<BuildTextHint>) {
+    if *mode == BuildingMode::None {
+        *mode = BuildingMode::PlaceResearchHall;
+        hint.0 = toolbar_data(ToolbarButtonType::ResearchHall).1;
+    }
+}
+
+fn mana_battery_hotkey(mut mode: ResMut<BuildingMode>, mut hint: ResMut<BuildTextHint>) {
+    if *mode == BuildingMode::None {
+        *mode = BuildingMode::PlaceManaBattery;
+        hint.0 = toolbar_data(ToolbarButtonType::ManaBattery).1;
+    }
+}
+
+fn fire_fighter_hotkey(mut mode: ResMut<BuildingMode>, mut hint: ResMut<BuildTextHint>) {
+    if *mode == BuildingMode::None {
+        *mode = BuildingMode::PlaceFireFighter;
+        hint.0 = toolbar_data(ToolbarButtonType::FireFighter).1;
+    }
+}
+
+fn sprinkler_hotkey(mut mode: ResMut<BuildingMode>, mut hint: ResMut<BuildTextHint>) {
+    if *mode == BuildingMode::None {
+        *mode = BuildingMode::PlaceSprinkler;
+        hint.0 = toolbar_data(ToolbarButtonType::Sprinkler).1;
+    }
+}
+
+fn treasury_hotkey(mut mode: ResMut<BuildingMode>, mut hint: ResMut<BuildTextHint>) {
+    if *mode == BuildingMode::None {
+        *mode = BuildingMode::PlaceTreasury;
+        hint.0 = toolbar_data(ToolbarButtonType::Treasury).1;
+    }
+}
+
+fn river_ferry_hotkey(mut mode: ResMut<BuildingMode>, mut hint: ResMut<BuildTextHint>) {
+    if *mode == BuildingMode::None {
+        *mode = BuildingMode::PlaceRiverFerry;
+        hint.0 = toolbar_data(ToolbarButtonType::RiverFerry).1;
+    }
+}
+
+fn demolish_hotkey(mut mode: ResMut<BuildingMode>, mut hint: ResMut<BuildTextHint>) {
+    if *mode == BuildingMode::None {
+        *mode = BuildingMode::Demolish;
+        hint.0 = toolbar_data(ToolbarButtonType::Demolish).1;
+    }
+}
+
 #[derive(Component, Reflect, Debug, Clone, Copy)]
 #[reflect(Component)]
 pub struct EnergyTextMarker;
@@ -118,10 +310,34 @@ pub struct EnergyTextMarker;
 #[reflect(Component)]
 pub struct LumberTextMarker;
 
+/// The lumber icon in the toolbar, tinted while the lumber bank is full
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct LumberIconMarker;
+
 #[derive(Component, Reflect, Debug, Clone, Copy)]
 #[reflect(Component)]
 pub struct WindTextMarker;
 
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct SpeedTextMarker;
+
+/// A warning shown in the toolbar while a wind gust is blowing
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct GustWarningMarker;
+
+/// Shows how quickly the fire front is growing
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct SpreadRateTextMarker;
+
+/// Shows the tile coordinates under the mouse
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct TileCoordMarker;
+
 #[derive(Component, Reflect, Debug, Clone, Copy)]
 #[reflect(Component)]
 pub struct BuildingHintToolbar;
@@ -134,19 +350,86 @@ pub struct OnUpdateToolbarButtonDisabledState;
 
 #[derive(Component, Reflect, Debug, Clone, Copy, Eq, PartialEq)]
 #[reflect(Component)]
-enum ToolbarButtonType {
+pub(super) enum ToolbarButtonType {
     Meteor,
     LumberMill,
     ManaForge,
     MinotaurHutch,
     StormMage,
     WaterGolem,
+    RiverGolem,
+    ControlledBurn,
+    EmberGuard,
+    RainCloud,
+    StoneWall,
+    Arborist,
+    BeaconTower,
+    ResearchHall,
+    RelayTower,
+    ManaBattery,
+    FireFighter,
+    Sprinkler,
+    Treasury,
+    RiverFerry,
+    Demolish,
+}
+
+impl ToolbarButtonType {
+    /// The building placed by this button, if it places one
+    fn building_type(&self) -> Option<BuildingType> {
+        match self {
+            ToolbarButtonType::LumberMill => Some(BuildingType::LumberMill),
+            ToolbarButtonType::ManaForge => Some(BuildingType::ManaForge),
+            ToolbarButtonType::MinotaurHutch => Some(BuildingType::Minotaur),
+            ToolbarButtonType::StormMage => Some(BuildingType::StormMage),
+            ToolbarButtonType::WaterGolem => Some(BuildingType::WaterGolem),
+            ToolbarButtonType::RiverGolem => Some(BuildingType::RiverGolem),
+            ToolbarButtonType::EmberGuard => Some(BuildingType::EmberGuard),
+            ToolbarButtonType::Arborist => Some(BuildingType::Arborist),
+            ToolbarButtonType::BeaconTower => Some(BuildingType::BeaconTower),
+            ToolbarButtonType::ResearchHall => Some(BuildingType::ResearchHall),
+            ToolbarButtonType::RelayTower => Some(BuildingType::RelayTower),
+            ToolbarButtonType::ManaBattery => Some(BuildingType::ManaBattery),
+            ToolbarButtonType::FireFighter => Some(BuildingType::FireFighter),
+            ToolbarButtonType::Sprinkler => Some(BuildingType::Sprinkler),
+            ToolbarButtonType::Treasury => Some(BuildingType::Treasury),
+            ToolbarButtonType::RiverFerry => Some(BuildingType::RiverFerry),
+            ToolbarButtonType::Meteor
+            | ToolbarButtonType::ControlledBurn
+            | ToolbarButtonType::RainCloud
+            | ToolbarButtonType::StoneWall
+            | ToolbarButtonType::Demolish => None,
+        }
+    }
+
+    /// Whether this button places a building that hasn't been researched yet
+    fn is_locked(&self, research: &ResearchState) -> bool {
+        self.building_type()
+            .is_some_and(|building_type| !research.is_unlocked(building_type))
+    }
 }
 
 #[derive(Component, Reflect, Debug)]
 #[reflect(Component)]
 struct ToolbarButtonDisabled;
 
+/// A padlock shown over toolbar buttons for buildings that haven't been researched
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+struct ToolbarLockIcon;
+
+/// Marks a toolbar button whose building was just unlocked, so that it
+/// pulses until the player hovers over or clicks it
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+struct NewlyUnlocked;
+
+/// Buildings that have been unlocked but whose toolbar button the player
+/// hasn't looked at yet. Kept so the pulse survives the toolbar being redrawn
+#[derive(Resource, Reflect, Debug, Default)]
+#[reflect(Resource)]
+struct NewlyUnlockedBuildings(HashSet<BuildingType>);
+
 fn handle_on_redraw_toolbar(_trigger: Trigger<OnRedrawToolbar>, mut commands: Commands) {
     commands.run_system_cached(spawn_toolbar);
 }
@@ -155,10 +438,26 @@ fn handle_disabling_toolbar_buttons(
     _trigger: Trigger<OnUpdateToolbarButtonDisabledState>,
     mut commands: Commands,
     player_resources: Res<PlayerResources>,
-    mut buttons: Query<(Entity, &ToolbarButtonType, &mut BackgroundColor)>,
+    research: Res<ResearchState>,
+    mut buttons: Query<(Entity, &ToolbarButtonType, &mut BackgroundColor, &Children)>,
+    mut lock_icons: Query<&mut Visibility, With<ToolbarLockIcon>>,
+    buildings: Query<&BuildingType>,
 ) {
-    for (entity, button, mut bg) in &mut buttons {
-        if toolbar_button_disabled(*button, &player_resources) {
+    let has_mana_forge = buildings.iter().any(|b| *b == BuildingType::ManaForge);
+
+    for (entity, button, mut bg, children) in &mut buttons {
+        let locked = button.is_locked(&research);
+        for child in children {
+            if let Ok(mut visibility) = lock_icons.get_mut(*child) {
+                *visibility = if locked {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                };
+            }
+        }
+
+        if toolbar_button_disabled(*button, &player_resources, has_mana_forge, &research) {
             commands.entity(entity).insert(ToolbarButtonDisabled);
             bg.0 = SLATE_400.into();
         } else {
@@ -168,6 +467,64 @@ fn handle_disabling_toolbar_buttons(
     }
 }
 
+fn handle_building_unlocked(
+    trigger: Trigger<OnBuildingUnlocked>,
+    mut newly_unlocked: ResMut<NewlyUnlockedBuildings>,
+) {
+    newly_unlocked.0.insert(trigger.event().0);
+}
+
+fn clear_newly_unlocked_buildings(
+    _trigger: Trigger<OnSpawnMap>,
+    mut newly_unlocked: ResMut<NewlyUnlockedBuildings>,
+) {
+    newly_unlocked.0.clear();
+}
+
+fn mark_newly_unlocked_buttons(
+    mut commands: Commands,
+    newly_unlocked: Res<NewlyUnlockedBuildings>,
+    buttons: Query<(Entity, &ToolbarButtonType), Without<NewlyUnlocked>>,
+) {
+    if newly_unlocked.0.is_empty() {
+        return;
+    }
+
+    for (entity, button) in &buttons {
+        if button
+            .building_type()
+            .is_some_and(|building_type| newly_unlocked.0.contains(&building_type))
+        {
+            commands.entity(entity).insert(NewlyUnlocked);
+        }
+    }
+}
+
+fn pulse_newly_unlocked_buttons(
+    time: Res<Time>,
+    mut buttons: Query<&mut BackgroundColor, With<NewlyUnlocked>>,
+) {
+    let t = ((time.elapsed_secs() * 4.0).sin() + 1.0) / 2.0;
+    let colour = SLATE_800.mix(&YELLOW_500, t);
+
+    for mut bg in &mut buttons {
+        bg.0 = colour.into();
+    }
+}
+
+/// Stops a newly unlocked button pulsing once the player has seen it
+fn acknowledge_newly_unlocked(
+    commands: &mut Commands,
+    newly_unlocked: &mut NewlyUnlockedBuildings,
+    entity: Entity,
+    toolbar_type: ToolbarButtonType,
+) {
+    if let Some(building_type) = toolbar_type.building_type() {
+        newly_unlocked.0.remove(&building_type);
+    }
+    commands.entity(entity).remove::<NewlyUnlocked>();
+}
+
 fn toolbar_node() -> NodeBuilder {
     NodeBuilder::new()
         .position(PositionType::Absolute)
@@ -208,17 +565,27 @@ fn toolbar_button(
                         .build(),
                     ImageNode { image, ..default() }
                 ),
-                (Text::new(label), TextFont::from_font_size(12.0),)
+                (Text::new(label), TextFont::from_font_size(12.0),),
+                lock_icon(),
             ],
         ))
         .observe(
             move |_trigger: Trigger<Pointer<Click>>,
+                  mut commands: Commands,
                   mut new_mode: ResMut<BuildingMode>,
                   mut hint: ResMut<BuildTextHint>,
+                  mut newly_unlocked: ResMut<NewlyUnlockedBuildings>,
                   mut buttons: Query<
                 &mut BackgroundColor,
                 (Without<ToolbarButtonDisabled>, With<Button>),
             >| {
+                acknowledge_newly_unlocked(
+                    &mut commands,
+                    &mut newly_unlocked,
+                    _trigger.target(),
+                    toolbar_type,
+                );
+
                 if let Ok(mut bg) = buttons.get_mut(_trigger.target()) {
                     info!("Setting building mode to {mode:?}");
                     *new_mode = mode;
@@ -229,12 +596,22 @@ fn toolbar_button(
         )
         .observe(
             move |_trigger: Trigger<Pointer<Over>>,
+                  mut commands: Commands,
                   mode: Res<BuildingMode>,
+                  research: Res<ResearchState>,
                   mut hint: ResMut<BuildTextHint>,
+                  mut newly_unlocked: ResMut<NewlyUnlockedBuildings>,
                   mut buttons: Query<
                 &mut BackgroundColor,
                 (Without<ToolbarButtonDisabled>, With<Button>),
             >| {
+                acknowledge_newly_unlocked(
+                    &mut commands,
+                    &mut newly_unlocked,
+                    _trigger.target(),
+                    toolbar_type,
+                );
+
                 if !matches!(*mode, BuildingMode::None) {
                     return;
                 }
@@ -243,7 +620,11 @@ fn toolbar_button(
                     bg.0 = SLATE_950.into();
                 }
 
-                hint.0 = hover.clone();
+                hint.0 = if toolbar_type.is_locked(&research) {
+                    locked_hint(&hover)
+                } else {
+                    hover.clone()
+                };
             },
         )
         .observe(
@@ -265,6 +646,100 @@ fn toolbar_button(
         );
 }
 
+/// A button in the sandbox terrain paint toolbar, showing a swatch of the
+/// terrain it paints
+fn terrain_paint_button(toolbar: &mut RelatedSpawnerCommands<ChildOf>, terrain: TerrainType) {
+    toolbar
+        .spawn((
+            NodeBuilder::new()
+                .height(Val::Px(32.0))
+                .center_content()
+                .background(SLATE_800)
+                .margin(UiRect::right(Val::Px(10.0)))
+                .padding(UiRect::all(Val::Px(5.0)))
+                .build(),
+            Button,
+            children![
+                (
+                    NodeBuilder::new()
+                        .width(Val::Px(16.0))
+                        .height(Val::Px(16.0))
+                        .margin(UiRect::horizontal(Val::Px(5.0)))
+                        .background(terrain.typical_colour())
+                        .build(),
+                    Pickable::IGNORE,
+                ),
+                (
+                    Text::new(terrain.to_string()),
+                    TextFont::from_font_size(12.0),
+                    Pickable::IGNORE,
+                ),
+            ],
+        ))
+        .observe(
+            move |_trigger: Trigger<Pointer<Click>>,
+                  mut mode: ResMut<BuildingMode>,
+                  mut hint: ResMut<BuildTextHint>| {
+                info!("Painting {terrain} terrain");
+                *mode = BuildingMode::TerrainPaint(terrain);
+                hint.set(format!(
+                    "Hold the left mouse button to paint {terrain}, or the right mouse button to paint Earth. Scroll to change the brush size, press <space> to stop."
+                ));
+            },
+        );
+}
+
+/// A small padlock drawn from UI nodes, hidden until the button is locked
+fn lock_icon() -> impl Bundle {
+    (
+        Name::new("Lock Icon"),
+        ToolbarLockIcon,
+        Visibility::Hidden,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(2.0),
+            right: Val::Px(2.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        children![
+            (
+                Node {
+                    width: Val::Px(6.0),
+                    height: Val::Px(4.0),
+                    border: UiRect::new(Val::Px(2.0), Val::Px(2.0), Val::Px(2.0), Val::Px(0.0)),
+                    ..default()
+                },
+                BorderColor(AMBER_300.into()),
+                BorderRadius::top(Val::Px(3.0)),
+            ),
+            (
+                Node {
+                    width: Val::Px(10.0),
+                    height: Val::Px(7.0),
+                    ..default()
+                },
+                BackgroundColor(AMBER_300.into()),
+            ),
+        ],
+    )
+}
+
+/// Replaces the details of a hover hint to explain that it needs research
+fn locked_hint(hover: &HintMessage) -> HintMessage {
+    match hover {
+        HintMessage::BuildingData { name, cost, .. } => HintMessage::BuildingData {
+            name: name.clone(),
+            cost: cost.clone(),
+            details:
+                "Requires Research Hall. Research halls unlock a new building every 60 seconds."
+                    .into(),
+        },
+        HintMessage::None | HintMessage::Text(_) => "Requires Research Hall".into(),
+    }
+}
+
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 struct ToolbarUi;
@@ -272,6 +747,7 @@ struct ToolbarUi;
 fn _toolbar_buttons(
     toolbar: &mut RelatedSpawnerCommands<ChildOf>,
     in_endless_mode: bool,
+    forges_banned: bool,
     building_assets: &Res<BuildingAssets>,
 ) {
     #[cfg(debug_assertions)]
@@ -279,13 +755,15 @@ fn _toolbar_buttons(
     #[cfg(not(debug_assertions))]
     let show_bolt_in_story = false;
 
-    toolbar_button(
-        toolbar,
-        "Forge",
-        BuildingMode::PlaceManaForge,
-        building_assets.mana_forge.clone(),
-        ToolbarButtonType::ManaForge,
-    );
+    if !forges_banned {
+        toolbar_button(
+            toolbar,
+            "Forge",
+            BuildingMode::PlaceManaForge,
+            building_assets.mana_forge.clone(),
+            ToolbarButtonType::ManaForge,
+        );
+    }
 
     toolbar_button(
         toolbar,
@@ -295,6 +773,54 @@ fn _toolbar_buttons(
         ToolbarButtonType::LumberMill,
     );
 
+    toolbar_button(
+        toolbar,
+        "Arborist",
+        BuildingMode::PlaceArborist,
+        building_assets.arborist.clone(),
+        ToolbarButtonType::Arborist,
+    );
+
+    toolbar_button(
+        toolbar,
+        "Sprinkler",
+        BuildingMode::PlaceSprinkler,
+        building_assets.sprinkler.clone(),
+        ToolbarButtonType::Sprinkler,
+    );
+
+    toolbar_button(
+        toolbar,
+        "Treasury",
+        BuildingMode::PlaceTreasury,
+        building_assets.treasury.clone(),
+        ToolbarButtonType::Treasury,
+    );
+
+    toolbar_button(
+        toolbar,
+        "Ferry",
+        BuildingMode::PlaceRiverFerry,
+        building_assets.river_ferry.clone(),
+        ToolbarButtonType::RiverFerry,
+    );
+
+    toolbar_button(
+        toolbar,
+        "Relay",
+        BuildingMode::PlaceRelayTower,
+        building_assets.relay_tower.clone(),
+        ToolbarButtonType::RelayTower,
+    );
+
+    toolbar_button(
+        toolbar,
+        "Battery",
+        BuildingMode::PlaceManaBattery,
+        building_assets.mana_battery.clone(),
+        ToolbarButtonType::ManaBattery,
+    );
+
     toolbar_button(
         toolbar,
         "Minotaur",
@@ -311,6 +837,14 @@ fn _toolbar_buttons(
         ToolbarButtonType::WaterGolem,
     );
 
+    toolbar_button(
+        toolbar,
+        "River Golem",
+        BuildingMode::PlaceRiverGolem,
+        building_assets.river_golem.clone(),
+        ToolbarButtonType::RiverGolem,
+    );
+
     toolbar_button(
         toolbar,
         "Storm Mage",
@@ -319,6 +853,73 @@ fn _toolbar_buttons(
         ToolbarButtonType::StormMage,
     );
 
+    toolbar_button(
+        toolbar,
+        "Fire Fighter",
+        BuildingMode::PlaceFireFighter,
+        building_assets.fire_fighter.clone(),
+        ToolbarButtonType::FireFighter,
+    );
+
+    toolbar_button(
+        toolbar,
+        "Ember Guard",
+        BuildingMode::PlaceEmberGuard,
+        building_assets.ember_guard.clone(),
+        ToolbarButtonType::EmberGuard,
+    );
+
+    toolbar_button(
+        toolbar,
+        "Beacon",
+        BuildingMode::PlaceBeaconTower,
+        building_assets.beacon_tower.clone(),
+        ToolbarButtonType::BeaconTower,
+    );
+
+    // story levels have everything unlocked, so there's nothing to research
+    if in_endless_mode {
+        toolbar_button(
+            toolbar,
+            "Research",
+            BuildingMode::PlaceResearchHall,
+            building_assets.research_hall.clone(),
+            ToolbarButtonType::ResearchHall,
+        );
+    }
+
+    toolbar_button(
+        toolbar,
+        "Wall",
+        BuildingMode::PlaceStoneWall,
+        building_assets.stone_wall.clone(),
+        ToolbarButtonType::StoneWall,
+    );
+
+    toolbar_button(
+        toolbar,
+        "Burn",
+        BuildingMode::ControlledBurn,
+        building_assets.controlled_burn.clone(),
+        ToolbarButtonType::ControlledBurn,
+    );
+
+    toolbar_button(
+        toolbar,
+        "Rain",
+        BuildingMode::RainCloud,
+        building_assets.rain_cloud.clone(),
+        ToolbarButtonType::RainCloud,
+    );
+
+    toolbar_button(
+        toolbar,
+        "Demolish",
+        BuildingMode::Demolish,
+        building_assets.demolish.clone(),
+        ToolbarButtonType::Demolish,
+    );
+
     if in_endless_mode || show_bolt_in_story {
         toolbar_button(
             toolbar,
@@ -333,7 +934,9 @@ fn _toolbar_buttons(
 fn spawn_toolbar(
     mut commands: Commands,
     requires_city_hall: Option<Res<RequiresCityHall>>,
+    maybe_sandbox: Option<Res<SandboxMode>>,
     maybe_endless_mode: Option<Res<EndlessMode>>,
+    challenge: Res<ChallengeModifiers>,
     resource_assets: Res<ResourceAssets>,
     building_assets: Res<BuildingAssets>,
     previous_toolbars: Query<Entity, With<ToolbarUi>>,
@@ -391,6 +994,7 @@ fn spawn_toolbar(
                         }
                     ),
                     (
+                        LumberIconMarker,
                         Node {
                             margin: UiRect::right(Val::Px(5.0)),
                             ..default()
@@ -417,6 +1021,41 @@ fn spawn_toolbar(
                             ..default()
                         }
                     ),
+                    (
+                        SpeedTextMarker,
+                        NodeBuilder::new()
+                            .margin(UiRect::horizontal(Val::Px(5.0)))
+                            .build(),
+                        Text::new(""),
+                        TextFont {
+                            font_size: 12.0,
+                            ..default()
+                        }
+                    ),
+                    (
+                        GustWarningMarker,
+                        NodeBuilder::new()
+                            .margin(UiRect::horizontal(Val::Px(5.0)))
+                            .build(),
+                        Text::new("GUST!"),
+                        TextFont {
+                            font_size: 12.0,
+                            ..default()
+                        },
+                        TextColor(RED_500.into()),
+                        Visibility::Hidden,
+                    ),
+                    (
+                        SpreadRateTextMarker,
+                        NodeBuilder::new()
+                            .margin(UiRect::horizontal(Val::Px(5.0)))
+                            .build(),
+                        Text::new(""),
+                        TextFont {
+                            font_size: 12.0,
+                            ..default()
+                        }
+                    ),
                     (
                         WindTextMarker,
                         Text::new(""),
@@ -425,6 +1064,18 @@ fn spawn_toolbar(
                             ..default()
                         }
                     ),
+                    (
+                        TileCoordMarker,
+                        NodeBuilder::new()
+                            .margin(UiRect::horizontal(Val::Px(5.0)))
+                            .build(),
+                        Text::new(""),
+                        TextFont {
+                            font_size: 10.0,
+                            ..default()
+                        },
+                        TextColor(SLATE_400.into()),
+                    ),
                 ],
             ));
 
@@ -434,7 +1085,12 @@ fn spawn_toolbar(
                     NodeBuilder::new().center_content().build(),
                 ))
                 .with_children(|toolbar| {
-                    _toolbar_buttons(toolbar, maybe_endless_mode.is_some(), &building_assets);
+                    _toolbar_buttons(
+                        toolbar,
+                        maybe_endless_mode.is_some(),
+                        challenge.has(ChallengeModifier::NoManaForge),
+                        &building_assets,
+                    );
                 });
         });
 
@@ -458,16 +1114,61 @@ fn spawn_toolbar(
             TextFont::from_font_size(12.0),
         )],
     ));
+
+    if maybe_sandbox.is_some() && !requires_city_hall {
+        commands
+            .spawn((
+                Name::new("Terrain Paint Toolbar"),
+                ToolbarUi,
+                GlobalZIndex(3),
+                StateScoped(Screen::Gameplay),
+                NodeBuilder::new()
+                    .position(PositionType::Absolute)
+                    .width(Val::Percent(100.0))
+                    .bottom(10.0)
+                    .left(0.0)
+                    .center_content()
+                    .build(),
+                Pickable::IGNORE,
+            ))
+            .with_children(|parent| {
+                parent
+                    .spawn((
+                        NodeBuilder::new()
+                            .padding(UiRect::horizontal(Val::Px(10.0)))
+                            .background(SLATE_800)
+                            .center_content()
+                            .build(),
+                        children![(
+                            Text::new("Terrain Paint"),
+                            TextFont::from_font_size(12.0),
+                            Node {
+                                margin: UiRect::right(Val::Px(10.0)),
+                                ..default()
+                            },
+                        )],
+                    ))
+                    .with_children(|toolbar| {
+                        for terrain in PAINTABLE_TERRAIN {
+                            terrain_paint_button(toolbar, terrain);
+                        }
+                    });
+            });
+    }
 }
 
 fn update_toolbar(
     mut commands: Commands,
     player_resource: Res<PlayerResources>,
+    storage: Res<ManaStorage>,
     wind: Res<WindDirection>,
+    weather: Res<CurrentWeather>,
+    moisture: Res<MoistureTrend>,
     mouse: Res<MousePosition>,
     map: Res<GameMap>,
+    regions: Res<MapRegions>,
     mut energy_text: Single<
-        &mut Text,
+        (&mut Text, &mut TextColor),
         (
             Without<LumberTextMarker>,
             Without<WindTextMarker>,
@@ -490,30 +1191,129 @@ fn update_toolbar(
             Without<WindTextMarker>,
         ),
     >,
+    mut lumber_icon: Single<&mut ImageNode, With<LumberIconMarker>>,
 ) {
     let cell_state = if let Some(cell) = map.tile_at_world_pos(mouse.world_pos) {
+        let region = regions.name_at(map.tile_coords(mouse.world_pos));
         if cfg!(debug_assertions) {
             format!(
-                "{}, local wind: {} | mouse {:.0},{:.0}",
+                "{region} — {}, local wind: {} | mouse {:.0},{:.0}",
                 *cell, cell.wind, mouse.world_pos.x, mouse.world_pos.y
             )
         } else {
-            format!("{}", *cell)
+            format!("{region} — {}", *cell)
         }
     } else {
         String::new()
     };
 
+    let (energy_text, energy_colour) = &mut *energy_text;
+    let at_cap = player_resource.mana >= player_resource.max_mana;
     energy_text.0 = format!(
-        "{} ({:+})",
-        player_resource.mana, player_resource.mana_drain
+        "{}/{}{} ({:+})",
+        player_resource.mana,
+        player_resource.max_mana,
+        if at_cap { " MAX" } else { "" },
+        player_resource.mana_drain
+    );
+    if storage.capacity > 0 {
+        energy_text.0 += &format!(" (+{} stored)", storage.stored);
+    }
+    energy_colour.0 = if at_cap {
+        ORANGE_400.into()
+    } else {
+        Color::WHITE
+    };
+    lumber_text.0 = format!("{}/{}", player_resource.lumber, player_resource.max_lumber);
+    lumber_icon.color = if player_resource.lumber >= player_resource.max_lumber {
+        YELLOW_400.into()
+    } else {
+        Color::WHITE
+    };
+    wind_text.0 = format!(
+        " | WIND: {} | WEATHER: {} | MOISTURE: {} | {cell_state}",
+        *wind, weather.0, *moisture
     );
-    lumber_text.0 = format!("{}", player_resource.lumber);
-    wind_text.0 = format!(" | WIND: {} | {cell_state}", *wind);
 
     commands.trigger(OnUpdateToolbarButtonDisabledState);
 }
 
+fn update_tile_coord_text(
+    mouse: Res<MousePosition>,
+    map: Res<GameMap>,
+    mut tile_text: Single<&mut Text, With<TileCoordMarker>>,
+) {
+    let coords = map.tile_coords(mouse.world_pos);
+    let text = if map.is_valid_coords(coords) {
+        format!("| Tile: ({}, {})", coords.x, coords.y)
+    } else {
+        String::new()
+    };
+
+    if tile_text.0 != text {
+        tile_text.0 = text;
+    }
+}
+
+fn update_speed_text(
+    game_speed: Res<GameSpeed>,
+    mut speed_text: Single<&mut Text, With<SpeedTextMarker>>,
+) {
+    let text = format!("| SPEED: {}", *game_speed);
+    if speed_text.0 != text {
+        speed_text.0 = text;
+    }
+}
+
+/// Flashes the gust warning while a gust is blowing
+fn update_gust_warning(
+    time: Res<Time>,
+    maybe_gust: Option<Res<WindGust>>,
+    mut warning: Single<(&mut Visibility, &mut TextColor), With<GustWarningMarker>>,
+) {
+    let (visibility, colour) = &mut *warning;
+
+    if maybe_gust.is_none() {
+        **visibility = Visibility::Hidden;
+        return;
+    }
+
+    **visibility = Visibility::Inherited;
+    let pulse = 0.6 + 0.4 * (time.elapsed_secs() * 8.0).sin();
+    colour.0 = RED_500.with_alpha(pulse).into();
+}
+
+/// The spread rate (in cells per second) above which the fire is growing
+/// quickly enough to show in red
+const FAST_SPREAD_RATE: f32 = 5.0;
+/// The spread rate (in cells per second) above which the indicator flashes
+const URGENT_SPREAD_RATE: f32 = 20.0;
+
+fn update_spread_rate_text(
+    time: Res<Time>,
+    tracker: Res<SpreadRateTracker>,
+    mut spread_text: Single<(&mut Text, &mut TextColor), With<SpreadRateTextMarker>>,
+) {
+    let (text, colour) = &mut *spread_text;
+    let rate = tracker.cells_per_second();
+
+    let content = format!("| FIRE: {rate:+.0} cells/sec");
+    if text.0 != content {
+        text.0 = content;
+    }
+
+    colour.0 = if rate < 0.0 {
+        GREEN_400.into()
+    } else if rate <= FAST_SPREAD_RATE {
+        YELLOW_400.into()
+    } else if rate <= URGENT_SPREAD_RATE {
+        RED_500.into()
+    } else {
+        let pulse = 0.6 + 0.4 * (time.elapsed_secs() * 8.0).sin();
+        RED_500.with_alpha(pulse).into()
+    };
+}
+
 fn update_build_hint_ui(
     maybe_requires_city_hall: Option<Res<RequiresCityHall>>,
     build_text: Res<BuildTextHint>,
@@ -595,23 +1395,165 @@ fn toolbar_data(toolbar_type: ToolbarButtonType) -> (HintMessage, HintMessage) {
              HintMessage::BuildingData {
                  name: "Water Golem".into(),
                  cost: format!("{WATER_GOLEM_COST_MANA} Mana"),
-                 details: "The Water Golem inhabits the area, consuming 4 mana every 2 seconds. When it consumes mana it makes the whole area wetter (less likely to catch fire) and has a 20% chance to quench nearby flames. Requires Mana Forge nearby".into(),
+                 details: "The Water Golem inhabits the area, consuming 4 mana every 2 seconds. When it consumes mana it makes the whole area wetter (less likely to catch fire) and has a 20% chance to put out each fire in range, turning it to smoldering ash. Select a golem to see how many fires it has quenched. Requires Mana Forge nearby".into(),
              },
              "Click the map to place a water golem (close to a mana forge). Press <space> to cancel placement".into()
          ),
+         ToolbarButtonType::RiverGolem => (
+             HintMessage::BuildingData {
+                 name: "River Golem".into(),
+                 cost: format!("{RIVER_GOLEM_COST_MANA} Mana"),
+                 details: format!("The River Golem pumps water from a river, consuming 2 mana every 2 seconds to soak the driest grass or tree within {RIVER_GOLEM_RANGE} tiles. Must be placed beside water. Requires Mana Forge nearby"),
+             },
+             "Click the map to place a river golem (beside water and close to a mana forge). Press <space> to cancel placement".into()
+         ),
+         ToolbarButtonType::ControlledBurn => (
+             HintMessage::BuildingData {
+                 name: "Controlled Burn".into(),
+                 cost: format!("{CONTROLLED_BURN_COST_MANA} Mana"),
+                 details: "Deliberately set fire to grass or trees. Burn a break ahead of the main fire front so there's no fuel left when it arrives - but watch the wind! Requires a Mana Forge".into(),
+             },
+             "Click grass or trees to start a controlled burn. Press <space> to stop.".into()
+         ),
+         ToolbarButtonType::EmberGuard => (
+             HintMessage::BuildingData {
+                 name: "Ember Guard".into(),
+                 cost: format!("{EMBER_GUARD_COST_MANA} Mana"),
+                 details: "Strong winds can carry burning embers over firebreaks. The Ember Guard catches embers in a wide area, making spot fires much less likely. Requires Mana Forge nearby".into(),
+             },
+             "Click the map to place an ember guard (close to a mana forge). Press <space> to cancel placement".into()
+         ),
+         ToolbarButtonType::RainCloud => (
+             HintMessage::BuildingData {
+                 name: "Rain Cloud".into(),
+                 cost: format!("{RAIN_CLOUD_COST_MANA} Mana"),
+                 details: "Summon a rain cloud that soaks the area for 10 seconds, making it much wetter and with a 30% chance to put out each fire underneath.".into(),
+             },
+             "Click the map to summon a rain cloud. Press <space> to stop.".into()
+         ),
+         ToolbarButtonType::StoneWall => (
+             HintMessage::BuildingData {
+                 name: "Stone Wall".into(),
+                 cost: format!("{STONE_WALL_COST_LUMBER} Lumber per tile"),
+                 details: "Build a line of stone that fire can't cross. A quick way to make a firebreak, but it gets expensive for long walls.".into(),
+             },
+             "Click to start the wall, then click again to finish it. Press <space> to cancel placement.".into()
+         ),
+         ToolbarButtonType::Arborist => (
+             HintMessage::BuildingData {
+                 name: "Arborist".into(),
+                 cost: format!("{ARBORIST_COST_LUMBER} Lumber"),
+                 details: "Replants the closest degraded land every 2 seconds, turning dirt into grass and then grass into trees. Doesn't need any mana.".into(),
+             },
+             "Click the map to place an arborist. Press <space> to cancel placement.".into()
+         ),
+         ToolbarButtonType::Sprinkler => (
+             HintMessage::BuildingData {
+                 name: "Sprinkler".into(),
+                 cost: format!("{SPRINKLER_COST_MANA} Mana"),
+                 details: "Turned by a small waterwheel, the sprinkler sweeps a jet of water around itself that wets grass and trees within 4 tiles and sometimes puts out fires. Doesn't need a mana forge.".into(),
+             },
+             "Click the map to place a sprinkler. Press <space> to cancel placement.".into()
+         ),
+         ToolbarButtonType::Treasury => (
+             HintMessage::BuildingData {
+                 name: "Treasury".into(),
+                 cost: format!("{TREASURY_COST_LUMBER} Lumber"),
+                 details: format!("Raises the most lumber you can hold by {TREASURY_CAPACITY}. Lumber mills stop adding lumber once you are at the cap. Doesn't need any mana."),
+             },
+             "Click the map to place a treasury. Press <space> to cancel placement.".into()
+         ),
+         ToolbarButtonType::RiverFerry => (
+             HintMessage::BuildingData {
+                 name: "River Ferry".into(),
+                 cost: format!("{RIVER_FERRY_COST_LUMBER} Lumber"),
+                 details: "Floats on a river so that buildings can be placed on the water around it, and fire fighters and goblins can cross there. Must be placed on water. Doesn't need any mana.".into(),
+             },
+             "Click on water to place a river ferry. Press <space> to cancel placement.".into()
+         ),
+         ToolbarButtonType::BeaconTower => (
+             HintMessage::BuildingData {
+                 name: "Beacon Tower".into(),
+                 cost: format!("{BEACON_TOWER_COST_MANA} Mana"),
+                 details: "Draws in meteors that strike within 40 tiles, so they land on the beacon instead. Needs 30 seconds to recharge and is destroyed after 3 meteors. Requires Mana Forge nearby".into(),
+             },
+             "Click the map to place a beacon tower (close to a mana forge). Press <space> to cancel placement".into()
+         ),
+         ToolbarButtonType::RelayTower => (
+             HintMessage::BuildingData {
+                 name: "Relay Tower".into(),
+                 cost: format!("{RELAY_TOWER_COST_LUMBER} Lumber"),
+                 details: "Carries mana further from a Mana Forge, so other buildings can be placed near the relay instead. Relays can be chained together, but if the forge is destroyed the whole chain goes with it. Requires Mana Forge or Relay Tower nearby".into(),
+             },
+             "Click the map to place a relay tower (close to a mana forge or another relay). Press <space> to cancel placement".into()
+         ),
+         ToolbarButtonType::ManaBattery => (
+             HintMessage::BuildingData {
+                 name: "Mana Battery".into(),
+                 cost: format!("{MANA_BATTERY_COST_LUMBER} Lumber"),
+                 details: format!("Stores up to {MANA_BATTERY_CAPACITY} surplus mana while your income is high, and releases it again when you run out. Requires Mana Forge nearby"),
+             },
+             "Click the map to place a mana battery (close to a mana forge). Press <space> to cancel placement".into()
+         ),
+         ToolbarButtonType::FireFighter => (
+             HintMessage::BuildingData {
+                 name: "Fire Fighter Hutch".into(),
+                 cost: format!("{FIRE_FIGHTER_COST_MANA} Mana"),
+                 details: "The fire fighter inside runs to the closest fire in range and puts it out, then moves on to the next one. If the hutch catches fire, the fire fighter rushes home. Requires Mana Forge nearby".into(),
+             },
+             "Click the map to place a fire fighter hutch (close to a mana forge). Press <space> to cancel placement".into()
+         ),
+         ToolbarButtonType::ResearchHall => (
+             HintMessage::BuildingData {
+                 name: "Research Hall".into(),
+                 cost: format!("{RESEARCH_HALL_COST_LUMBER} Lumber"),
+                 details: "Studies the arcane arts, unlocking a new building every 60 seconds. More halls research faster. Requires Mana Forge nearby".into(),
+             },
+             "Click the map to place a research hall (close to a mana forge). Press <space> to cancel placement".into()
+         ),
+         ToolbarButtonType::Demolish => (
+             HintMessage::BuildingData {
+                 name: "Demolish".into(),
+                 cost: "Free!".into(),
+                 details: "Tear down one of your buildings, refunding half of what it cost to build. Anything that draws mana through it will be lost too. The City Hall can't be demolished.".into(),
+             },
+             "Click a building to demolish it. Press <space> to cancel.".into()
+         ),
     }
 }
 
 fn toolbar_button_disabled(
     toolbar_type: ToolbarButtonType,
     resources: &Res<PlayerResources>,
+    has_mana_forge: bool,
+    research: &ResearchState,
 ) -> bool {
+    if toolbar_type.is_locked(research) {
+        return true;
+    }
+
     match toolbar_type {
-        ToolbarButtonType::Meteor => false,
+        ToolbarButtonType::Meteor | ToolbarButtonType::Demolish => false,
         ToolbarButtonType::LumberMill => resources.lumber < LUMBER_MILL_COST_LUMBER,
         ToolbarButtonType::ManaForge => resources.lumber < MANA_FORGE_COST_LUMBER,
         ToolbarButtonType::MinotaurHutch => resources.mana < MINOTAUR_COST_MANA,
         ToolbarButtonType::StormMage => resources.mana < STORM_MAGE_COST_MANA,
         ToolbarButtonType::WaterGolem => resources.mana < WATER_GOLEM_COST_MANA,
+        ToolbarButtonType::RiverGolem => resources.mana < RIVER_GOLEM_COST_MANA,
+        ToolbarButtonType::EmberGuard => resources.mana < EMBER_GUARD_COST_MANA,
+        ToolbarButtonType::RainCloud => resources.mana < RAIN_CLOUD_COST_MANA,
+        ToolbarButtonType::StoneWall => resources.lumber < STONE_WALL_COST_LUMBER,
+        ToolbarButtonType::Arborist => resources.lumber < ARBORIST_COST_LUMBER,
+        ToolbarButtonType::BeaconTower => resources.mana < BEACON_TOWER_COST_MANA,
+        ToolbarButtonType::ResearchHall => resources.lumber < RESEARCH_HALL_COST_LUMBER,
+        ToolbarButtonType::RelayTower => resources.lumber < RELAY_TOWER_COST_LUMBER,
+        ToolbarButtonType::ManaBattery => resources.lumber < MANA_BATTERY_COST_LUMBER,
+        ToolbarButtonType::FireFighter => resources.mana < FIRE_FIGHTER_COST_MANA,
+        ToolbarButtonType::Sprinkler => resources.mana < SPRINKLER_COST_MANA,
+        ToolbarButtonType::Treasury => resources.lumber < TREASURY_COST_LUMBER,
+        ToolbarButtonType::RiverFerry => resources.lumber < RIVER_FERRY_COST_LUMBER,
+        ToolbarButtonType::ControlledBurn => {
+            !has_mana_forge || resources.mana < CONTROLLED_BURN_COST_MANA
+        }
     }
 }
@@ -0,0 +1,352 @@
+//! A step by step tutorial shown the first time the game is played, which
+//! walks the player through placing their first few buildings
+
+#[cfg(not(target_family = "wasm"))]
+use std::path::PathBuf;
+
+use bevy::{
+    color::palettes::tailwind::{AMBER_300, SLATE_700},
+    prelude::*,
+};
+
+use crate::{
+    Pause,
+    screens::{
+        BuildingMode, BuildingType, ChallengeModifier, ChallengeModifiers, RequiresCityHall,
+        Screen, gameplay::toolbar::ToolbarButtonType,
+    },
+    theme::node_builder::NodeBuilder,
+    wildfire::GameMap,
+};
+
+#[cfg(not(target_family = "wasm"))]
+use super::SAVE_DIRECTORY;
+
+/// How long (in game seconds) the first fire is shown before the tutorial ends
+const FIRST_FIRE_TUTORIAL_TIME: f32 = 10.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<TutorialState>();
+    app.register_type::<HasSeenTutorial>();
+    app.register_type::<TutorialTextMarker>();
+    app.register_type::<TutorialArrowMarker>();
+
+    app.init_resource::<HasSeenTutorial>();
+
+    #[cfg(not(target_family = "wasm"))]
+    app.add_systems(Startup, load_has_seen_tutorial);
+    app.add_systems(OnEnter(Screen::Gameplay), start_tutorial);
+    app.add_systems(OnExit(Screen::Gameplay), stop_tutorial);
+    app.add_systems(
+        Update,
+        (advance_tutorial, update_tutorial_ui).chain().run_if(
+            in_state(Screen::Gameplay)
+                .and(in_state(Pause(false)))
+                .and(resource_exists::<TutorialState>),
+        ),
+    );
+}
+
+/// Whether the player has finished the tutorial before. On native builds this
+/// is written to disk so that it persists between sessions, on the web it only
+/// lasts until the page is reloaded.
+#[derive(Resource, Reflect, Debug, Clone, Copy, Default)]
+#[reflect(Resource)]
+pub struct HasSeenTutorial(pub bool);
+
+impl HasSeenTutorial {
+    /// Shows the tutorial again the next time a game is started
+    pub fn reset(&mut self) {
+        self.0 = false;
+
+        #[cfg(not(target_family = "wasm"))]
+        remove_tutorial_complete();
+    }
+}
+
+/// The tutorial step the player is currently on
+#[derive(Resource, Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+#[reflect(Resource)]
+pub enum TutorialState {
+    PlaceCityHall,
+    PlaceManaForge,
+    PlaceLumberMill,
+    IgnoreFirstFire,
+    Complete,
+}
+
+impl TutorialState {
+    /// The instructions shown to the player for this step
+    fn message(&self) -> &'static str {
+        match self {
+            TutorialState::PlaceCityHall => {
+                "Welcome to Spellfire! Start by clicking on grass or trees to place your City Hall. If it burns down, everything is lost!"
+            }
+            TutorialState::PlaceManaForge => {
+                "Mana powers your spells and creatures. Click the Forge button and place a Mana Forge close to your City Hall."
+            }
+            TutorialState::PlaceLumberMill => {
+                "Lumber is used to build more buildings. Click the Mill button and place a Lumber Mill next to some trees."
+            }
+            TutorialState::IgnoreFirstFire => {
+                "A fire has started! You don't need to put out every fire, just keep the flames away from your buildings."
+            }
+            TutorialState::Complete => "You're ready to go. Good luck!",
+        }
+    }
+
+    /// The toolbar button that the tutorial arrow points at for this step
+    fn toolbar_button(&self) -> Option<ToolbarButtonType> {
+        match self {
+            TutorialState::PlaceManaForge => Some(ToolbarButtonType::ManaForge),
+            TutorialState::PlaceLumberMill => Some(ToolbarButtonType::LumberMill),
+            TutorialState::PlaceCityHall
+            | TutorialState::IgnoreFirstFire
+            | TutorialState::Complete => None,
+        }
+    }
+
+    /// Whether the player can use the given building mode during this step
+    pub fn allows(&self, mode: BuildingMode) -> bool {
+        // clearing the current mode is always fine
+        if mode == BuildingMode::None {
+            return true;
+        }
+
+        match self {
+            TutorialState::PlaceCityHall => mode == BuildingMode::PlaceCityHall,
+            TutorialState::PlaceManaForge => mode == BuildingMode::PlaceManaForge,
+            TutorialState::PlaceLumberMill => mode == BuildingMode::PlaceLumberMill,
+            TutorialState::IgnoreFirstFire | TutorialState::Complete => true,
+        }
+    }
+
+    /// Moves past the mana forge step when the challenge doesn't allow forges,
+    /// as the player would otherwise be stuck on it
+    fn skip_unavailable(self, challenge: &ChallengeModifiers) -> Self {
+        if self == TutorialState::PlaceManaForge && challenge.has(ChallengeModifier::NoManaForge) {
+            TutorialState::PlaceLumberMill
+        } else {
+            self
+        }
+    }
+
+    /// The step that follows once the given building has been placed
+    fn next_after(&self, building_type: BuildingType) -> Option<TutorialState> {
+        match (self, building_type) {
+            (TutorialState::PlaceCityHall, BuildingType::CityHall) => {
+                Some(TutorialState::PlaceManaForge)
+            }
+            (TutorialState::PlaceManaForge, BuildingType::ManaForge) => {
+                Some(TutorialState::PlaceLumberMill)
+            }
+            (TutorialState::PlaceLumberMill, BuildingType::LumberMill) => {
+                Some(TutorialState::IgnoreFirstFire)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+struct TutorialTextMarker;
+
+/// Points at the toolbar button the player should click next
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+struct TutorialArrowMarker;
+
+#[cfg(not(target_family = "wasm"))]
+fn tutorial_path() -> PathBuf {
+    PathBuf::from(SAVE_DIRECTORY).join("tutorial_complete")
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn load_has_seen_tutorial(mut seen: ResMut<HasSeenTutorial>) {
+    seen.0 = tutorial_path().exists();
+}
+
+/// Removes the marker written by [record_tutorial_complete]
+#[cfg(not(target_family = "wasm"))]
+fn remove_tutorial_complete() {
+    let path = tutorial_path();
+    if !path.exists() {
+        return;
+    }
+
+    if let Err(e) = std::fs::remove_file(&path) {
+        warn!("Unable to reset tutorial at {}: {e}", path.display());
+    }
+}
+
+/// Writes a marker to disk so the tutorial isn't shown in later sessions
+#[cfg(not(target_family = "wasm"))]
+fn record_tutorial_complete() {
+    let path = tutorial_path();
+    if let Err(e) = std::fs::create_dir_all(SAVE_DIRECTORY).and_then(|_| std::fs::write(&path, ""))
+    {
+        warn!(
+            "Unable to record tutorial completion at {}: {e}",
+            path.display()
+        );
+    }
+}
+
+fn start_tutorial(
+    mut commands: Commands,
+    seen: Res<HasSeenTutorial>,
+    maybe_requires_city_hall: Option<Res<RequiresCityHall>>,
+    challenge: Res<ChallengeModifiers>,
+) {
+    if seen.0 {
+        return;
+    }
+
+    // story levels start with their city hall already placed
+    let first_step = if maybe_requires_city_hall.is_some() {
+        TutorialState::PlaceCityHall
+    } else {
+        TutorialState::PlaceManaForge.skip_unavailable(&challenge)
+    };
+    info!("Starting tutorial at {first_step:?}");
+    commands.insert_resource(first_step);
+
+    commands.spawn((
+        Name::new("Tutorial Tooltip"),
+        TutorialTextMarker,
+        GlobalZIndex(5),
+        StateScoped(Screen::Gameplay),
+        Pickable::IGNORE,
+        NodeBuilder::new()
+            .position(PositionType::Absolute)
+            .width(Val::Percent(100.0))
+            .top(120.0)
+            .center_content()
+            .build(),
+        children![(
+            NodeBuilder::new()
+                .width(Val::Px(500.0))
+                .padding(UiRect::all(Val::Px(20.0)))
+                .background(SLATE_700)
+                .build(),
+            Pickable::IGNORE,
+            children![(
+                Text::new(first_step.message()),
+                TextFont::from_font_size(24.0),
+                Pickable::IGNORE,
+            )],
+        )],
+    ));
+
+    commands.spawn((
+        Name::new("Tutorial Arrow"),
+        TutorialArrowMarker,
+        GlobalZIndex(5),
+        StateScoped(Screen::Gameplay),
+        Pickable::IGNORE,
+        NodeBuilder::new().position(PositionType::Absolute).build(),
+        Text::new("^"),
+        TextFont::from_font_size(36.0),
+        TextColor(AMBER_300.into()),
+        Visibility::Hidden,
+    ));
+}
+
+fn stop_tutorial(mut commands: Commands) {
+    commands.remove_resource::<TutorialState>();
+}
+
+fn advance_tutorial(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut state: ResMut<TutorialState>,
+    mut seen: ResMut<HasSeenTutorial>,
+    mut fire_timer: Local<f32>,
+    map: Res<GameMap>,
+    challenge: Res<ChallengeModifiers>,
+    new_buildings: Query<&BuildingType, Added<BuildingType>>,
+    tutorial_ui: Query<Entity, Or<(With<TutorialTextMarker>, With<TutorialArrowMarker>)>>,
+) {
+    // the timer outlives the tutorial, so start it again for each new game
+    if state.is_added() {
+        *fire_timer = 0.0;
+    }
+
+    for building_type in &new_buildings {
+        if let Some(next) = state
+            .next_after(*building_type)
+            .map(|next| next.skip_unavailable(&challenge))
+        {
+            info!("Tutorial moving on to {next:?}");
+            *state = next;
+            *fire_timer = 0.0;
+        }
+    }
+
+    match *state {
+        TutorialState::IgnoreFirstFire => {
+            if map.any_on_fire() {
+                *fire_timer += time.delta_secs();
+            }
+
+            if *fire_timer >= FIRST_FIRE_TUTORIAL_TIME {
+                *state = TutorialState::Complete;
+                *fire_timer = 0.0;
+            }
+        }
+        TutorialState::Complete => {
+            // leave the final message up briefly before removing the tutorial
+            *fire_timer += time.delta_secs();
+            if *fire_timer < FIRST_FIRE_TUTORIAL_TIME * 0.5 {
+                return;
+            }
+
+            info!("Tutorial complete");
+            seen.0 = true;
+            #[cfg(not(target_family = "wasm"))]
+            record_tutorial_complete();
+
+            *fire_timer = 0.0;
+            commands.remove_resource::<TutorialState>();
+            for entity in &tutorial_ui {
+                commands.entity(entity).despawn();
+            }
+        }
+        TutorialState::PlaceCityHall
+        | TutorialState::PlaceManaForge
+        | TutorialState::PlaceLumberMill => {}
+    }
+}
+
+fn update_tutorial_ui(
+    state: Res<TutorialState>,
+    text_root: Single<Entity, With<TutorialTextMarker>>,
+    mut text: Query<&mut Text, Without<TutorialArrowMarker>>,
+    children: Query<&Children>,
+    mut arrow: Single<(&mut Node, &mut Visibility), With<TutorialArrowMarker>>,
+    buttons: Query<(&ToolbarButtonType, &GlobalTransform, &ComputedNode)>,
+) {
+    for child in children.iter_descendants(*text_root) {
+        if let Ok(mut text) = text.get_mut(child) {
+            text.0 = state.message().into();
+        }
+    }
+
+    let (node, visibility) = &mut *arrow;
+    let target = state
+        .toolbar_button()
+        .and_then(|target| buttons.iter().find(|(button, _, _)| **button == target));
+    let Some((_, tx, computed)) = target else {
+        **visibility = Visibility::Hidden;
+        return;
+    };
+
+    // UI transforms are in physical pixels at the centre of the node
+    let scale = computed.inverse_scale_factor();
+    let centre = tx.translation().truncate() * scale;
+    let size = computed.size() * scale;
+    node.left = Val::Px(centre.x - 8.0);
+    node.top = Val::Px(centre.y + size.y * 0.5);
+    **visibility = Visibility::Inherited;
+}
@@ -5,8 +5,8 @@ use std::time::Duration;
 use bevy::{prelude::*, time::common_conditions::on_timer};
 
 use crate::{
-    screens::{RequiresCityHall, Screen, StoryModeLevel, gameplay::CityHall},
-    wildfire::GameMap,
+    screens::{RequiresCityHall, Screen, StoryModeLevel, StoryStrikeWave, gameplay::CityHall},
+    wildfire::{GameMap, Tornado},
 };
 
 pub(super) fn plugin(app: &mut App) {
@@ -26,25 +26,32 @@ pub(super) fn plugin(app: &mut App) {
     );
 }
 
-/// If the resource exists and the city hall was removed, go to the defeated
-/// state
+/// If the resource exists and the city hall was removed, replay how the fire
+/// spread before going to the defeated state
 fn track_defeat_conditions(
     mut next_state: ResMut<NextState<Screen>>,
     halls: Query<Entity, With<CityHall>>,
 ) {
     if halls.is_empty() {
-        next_state.set(Screen::GameOver);
+        next_state.set(Screen::Replay);
     }
 }
 
-/// In story mode, victory is when there is no more fire and the last meteor
-/// has been launched
+/// In story mode, victory is when there is no more fire and the last event
+/// and goblin have been launched
 fn track_victory_conditions(
     map: Res<GameMap>,
     level: Res<StoryModeLevel>,
+    waves: Query<(), With<StoryStrikeWave>>,
+    tornadoes: Query<(), With<Tornado>>,
     mut next_state: ResMut<NextState<Screen>>,
 ) {
-    if level.bolts.is_empty() && !map.any_on_fire() {
+    if level.events.is_empty()
+        && waves.is_empty()
+        && tornadoes.is_empty()
+        && level.goblins.is_empty()
+        && !map.any_on_fire()
+    {
         next_state.set(Screen::LevelWon);
     }
 }
@@ -0,0 +1,563 @@
+//! A level editor for authoring story mode levels. Levels can be played
+//! straight from the editor, and exported as RON to be added to the story.
+
+use std::collections::VecDeque;
+
+use bevy::{
+    color::palettes::tailwind::{AMBER_300, RED_500, SLATE_700, SLATE_800, SLATE_950},
+    input::{
+        ButtonState,
+        keyboard::{Key, KeyboardInput},
+    },
+    prelude::*,
+    reflect::serde::TypedReflectSerializer,
+};
+use bevy_vector_shapes::{prelude::ShapePainter, shapes::DiscPainter};
+
+use crate::{
+    asset_tracking::ResourceHandles,
+    input::MousePosition,
+    screens::{EndlessMode, Screen, StoryEvent, StoryModeLevel},
+    theme::{node_builder::NodeBuilder, widget},
+    wildfire::{
+        Biome, GOOD_SEEDS, GameMap, MapSize, MinimapUi, OnSpawnMap, SpawnedMap, TerrainCell,
+        WeatherEvent, WindGust,
+    },
+};
+
+/// The folder that exported levels are written to
+#[cfg(not(target_family = "wasm"))]
+const EXPORT_DIRECTORY: &str = "levels";
+
+/// The number of scripted events listed in the editor panel
+const MAX_LISTED_EVENTS: usize = 8;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<LevelEditor>();
+    app.register_type::<EditorTool>();
+    app.register_type::<EditorField>();
+    app.register_type::<EditorTestLevel>();
+    app.register_type::<LevelEditorPanel>();
+    app.register_type::<(f32, IVec2)>();
+    app.register_type::<VecDeque<(f32, IVec2)>>();
+    app.register_type::<(f32, StoryEvent)>();
+    app.register_type::<VecDeque<(f32, StoryEvent)>>();
+    app.register_type::<VecDeque<(f32, WindGust)>>();
+
+    app.add_systems(OnEnter(Screen::LevelEditor), enter_level_editor);
+    app.add_systems(OnExit(Screen::LevelEditor), exit_level_editor);
+
+    app.add_observer(handle_editor_map_click);
+
+    app.add_systems(
+        Update,
+        (
+            edit_text_field,
+            redraw_editor_panel.run_if(resource_changed::<LevelEditor>),
+            draw_editor_markers.run_if(resource_exists::<GameMap>),
+        )
+            .chain()
+            .run_if(in_state(Screen::LevelEditor).and(resource_exists::<LevelEditor>)),
+    );
+}
+
+/// The level being authored. This is kept when leaving the editor so that the
+/// level can be tested and then tweaked further.
+#[derive(Resource, Reflect, Debug, Clone)]
+#[reflect(Resource)]
+pub struct LevelEditor {
+    pub level: StoryModeLevel,
+    pub tool: EditorTool,
+    /// The time (in seconds) that new meteor bolts are scheduled for
+    pub bolt_time: f32,
+    /// The field being typed into, and the text typed so far
+    pub editing: Option<(EditorField, String)>,
+    /// The most recent export of the level
+    pub exported: Option<String>,
+}
+
+impl Default for LevelEditor {
+    fn default() -> Self {
+        Self {
+            level: StoryModeLevel {
+                level_number: 0,
+                map_seed: GOOD_SEEDS[0],
+                events: VecDeque::new(),
+                goblins: VecDeque::new(),
+                goblin_waves: VecDeque::new(),
+                gusts: VecDeque::new(),
+                starting_location: IVec2::splat(128),
+                elapsed_time: 0.0,
+                wind_speed: 15.0,
+                wind_angle: 32.0,
+                weather: WeatherEvent::Normal,
+                biome: Biome::Temperate,
+            },
+            tool: EditorTool::default(),
+            bolt_time: 10.0,
+            editing: None,
+            exported: None,
+        }
+    }
+}
+
+impl LevelEditor {
+    /// The current value of a field, as shown in the editor panel
+    fn field_value(&self, field: EditorField) -> String {
+        match field {
+            EditorField::Seed => format!("{}", self.level.map_seed),
+            EditorField::BoltTime => format!("{:.1}", self.bolt_time),
+            EditorField::WindAngle => format!("{:.0}", self.level.wind_angle),
+            EditorField::WindSpeed => format!("{:.0}", self.level.wind_speed),
+        }
+    }
+
+    /// Starts typing into a field, applying any field that was being edited
+    fn start_editing(&mut self, field: EditorField) -> bool {
+        let seed_changed = self.finish_editing();
+        self.editing = Some((field, self.field_value(field)));
+        seed_changed
+    }
+
+    /// Applies the text typed into the current field, returning true if the
+    /// map seed has changed. Invalid values are ignored.
+    fn finish_editing(&mut self) -> bool {
+        let Some((field, text)) = self.editing.take() else {
+            return false;
+        };
+
+        match field {
+            EditorField::Seed => match text.parse::<i32>() {
+                Ok(seed) if seed != self.level.map_seed => {
+                    self.level.map_seed = seed;
+                    return true;
+                }
+                Ok(_) => {}
+                Err(_) => warn!("Ignoring invalid map seed '{text}'"),
+            },
+            EditorField::BoltTime => match text.parse::<f32>() {
+                Ok(time) => self.bolt_time = time.max(0.0),
+                Err(_) => warn!("Ignoring invalid bolt time '{text}'"),
+            },
+            EditorField::WindAngle => match text.parse::<f32>() {
+                Ok(angle) => self.level.wind_angle = angle.rem_euclid(360.0),
+                Err(_) => warn!("Ignoring invalid wind angle '{text}'"),
+            },
+            EditorField::WindSpeed => match text.parse::<f32>() {
+                Ok(speed) => self.level.wind_speed = speed.max(0.0),
+                Err(_) => warn!("Ignoring invalid wind speed '{text}'"),
+            },
+        }
+
+        false
+    }
+}
+
+/// What happens when the map is clicked in the editor
+#[derive(Reflect, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EditorTool {
+    #[default]
+    StartingLocation,
+    MeteorBolt,
+}
+
+/// The values in the editor that can be typed in
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditorField {
+    Seed,
+    BoltTime,
+    WindAngle,
+    WindSpeed,
+}
+
+impl std::fmt::Display for EditorField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                EditorField::Seed => "Map seed",
+                EditorField::BoltTime => "Bolt time (sec)",
+                EditorField::WindAngle => "Wind angle (deg)",
+                EditorField::WindSpeed => "Wind speed",
+            }
+        )
+    }
+}
+
+/// A level from the editor that should be played instead of the next story level
+#[derive(Resource, Reflect, Debug, Clone)]
+#[reflect(Resource)]
+pub struct EditorTestLevel(pub StoryModeLevel);
+
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct LevelEditorPanel;
+
+fn enter_level_editor(mut commands: Commands, maybe_editor: Option<ResMut<LevelEditor>>) {
+    let level = match maybe_editor {
+        Some(mut editor) => {
+            // redraw the panel for the level we were working on
+            editor.set_changed();
+            editor.level.clone()
+        }
+        None => {
+            let editor = LevelEditor::default();
+            let level = editor.level.clone();
+            commands.insert_resource(editor);
+            level
+        }
+    };
+
+    commands.trigger(
+        OnSpawnMap::new(level.map_seed, MapSize::Medium)
+            .with_biome(level.biome)
+            .with_starting_location(level.starting_location),
+    );
+}
+
+fn exit_level_editor(
+    mut commands: Commands,
+    maps: Query<Entity, Or<(With<SpawnedMap>, With<MinimapUi>)>>,
+) {
+    for entity in &maps {
+        commands.entity(entity).despawn();
+    }
+
+    commands.remove_resource::<GameMap>();
+}
+
+/// Spawns a new map after the seed has been changed
+fn respawn_editor_map(
+    mut commands: Commands,
+    editor: Res<LevelEditor>,
+    maps: Query<Entity, With<SpawnedMap>>,
+) {
+    for entity in &maps {
+        commands.entity(entity).despawn();
+    }
+
+    commands.trigger(
+        OnSpawnMap::new(editor.level.map_seed, MapSize::Medium)
+            .with_biome(editor.level.biome)
+            .with_starting_location(editor.level.starting_location),
+    );
+}
+
+fn handle_editor_map_click(
+    trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    screen: Res<State<Screen>>,
+    mouse: Res<MousePosition>,
+    maybe_map: Option<Res<GameMap>>,
+    maybe_editor: Option<ResMut<LevelEditor>>,
+    cells: Query<(), With<TerrainCell>>,
+) {
+    if *screen.get() != Screen::LevelEditor
+        || trigger.event().button != PointerButton::Primary
+        || !cells.contains(trigger.target())
+    {
+        return;
+    }
+
+    let (Some(map), Some(mut editor)) = (maybe_map, maybe_editor) else {
+        return;
+    };
+
+    // clicking the map applies whatever was being typed
+    if editor.finish_editing() {
+        commands.run_system_cached(respawn_editor_map);
+    }
+
+    let coords = map.tile_coords(mouse.world_pos);
+    if !map.is_valid_coords(coords) {
+        return;
+    }
+
+    match editor.tool {
+        EditorTool::StartingLocation => {
+            info!("Setting level starting location to {coords}");
+            editor.level.starting_location = coords;
+            // rivers are kept away from the starting location, so they move too
+            commands.run_system_cached(respawn_editor_map);
+        }
+        EditorTool::MeteorBolt => {
+            let time = editor.bolt_time;
+            info!("Adding meteor bolt at {coords} after {time:.1} seconds");
+
+            // events are started in order, so keep them sorted by time
+            let events = &mut editor.level.events;
+            events.push_back((time, StoryEvent::MeteorBolt(coords)));
+            events
+                .make_contiguous()
+                .sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        }
+    }
+}
+
+/// Types into the field that is being edited. <enter> applies the new value
+/// and <esc> discards it.
+fn edit_text_field(
+    mut commands: Commands,
+    mut keys: EventReader<KeyboardInput>,
+    mut editor: ResMut<LevelEditor>,
+) {
+    let Some((field, mut text)) = editor.editing.clone() else {
+        keys.clear();
+        return;
+    };
+
+    let mut apply = false;
+    let mut discard = false;
+
+    for key in keys.read() {
+        if key.state != ButtonState::Pressed {
+            continue;
+        }
+
+        match &key.logical_key {
+            Key::Character(c) => {
+                if c.chars()
+                    .all(|c| c.is_ascii_digit() || c == '.' || c == '-')
+                {
+                    text.push_str(c);
+                }
+            }
+            Key::Backspace => {
+                text.pop();
+            }
+            Key::Enter => apply = true,
+            Key::Escape => discard = true,
+            _ => {}
+        }
+    }
+
+    if discard {
+        editor.editing = None;
+    } else if apply {
+        editor.editing = Some((field, text));
+        if editor.finish_editing() {
+            commands.run_system_cached(respawn_editor_map);
+        }
+    } else if editor
+        .editing
+        .as_ref()
+        .is_some_and(|(_, prev)| *prev != text)
+    {
+        editor.editing = Some((field, text));
+    }
+}
+
+/// A button for the editor panel, highlighted if it is selected
+fn editor_button(label: impl Into<String>, selected: bool) -> impl Bundle {
+    (
+        Button,
+        NodeBuilder::new()
+            .padding(UiRect::all(Val::Px(5.0)))
+            .background(if selected { SLATE_950 } else { SLATE_800 })
+            .build(),
+        children![(
+            Text::new(label),
+            TextFont::from_font_size(12.0),
+            Pickable::IGNORE,
+        )],
+    )
+}
+
+fn redraw_editor_panel(
+    mut commands: Commands,
+    editor: Res<LevelEditor>,
+    previous_panels: Query<Entity, With<LevelEditorPanel>>,
+) {
+    for previous in &previous_panels {
+        commands.entity(previous).despawn();
+    }
+
+    let level = &editor.level;
+    let mut summary = format!(
+        "City hall at {}, {}\n{} events",
+        level.starting_location.x,
+        level.starting_location.y,
+        level.events.len()
+    );
+    for (time, event) in level.events.iter().take(MAX_LISTED_EVENTS) {
+        let description = match event {
+            StoryEvent::MeteorBolt(loc) => format!("meteor at {}, {}", loc.x, loc.y),
+            StoryEvent::LightningWave { center, count, .. } => {
+                format!("{count} strikes around {}, {}", center.x, center.y)
+            }
+            StoryEvent::WindShift { angle, speed } => {
+                format!("wind shifts to {angle:.0} deg at {speed:.0}")
+            }
+            StoryEvent::Tornado { start, angle } => {
+                format!("tornado at {}, {} heading {angle:.0} deg", start.x, start.y)
+            }
+        };
+        summary.push_str(&format!("\n  {time:.1} sec {description}"));
+    }
+    if level.events.len() > MAX_LISTED_EVENTS {
+        summary.push_str(&format!(
+            "\n  ... and {} more",
+            level.events.len() - MAX_LISTED_EVENTS
+        ));
+    }
+
+    commands
+        .spawn((
+            Name::new("Level Editor Panel"),
+            LevelEditorPanel,
+            GlobalZIndex(3),
+            StateScoped(Screen::LevelEditor),
+            NodeBuilder::new()
+                .position(PositionType::Absolute)
+                .flex_direction(FlexDirection::Column)
+                .row_gap(Val::Px(5.0))
+                .padding(UiRect::all(Val::Px(10.0)))
+                .left(10.0)
+                .top(10.0)
+                .width(Val::Px(300.0))
+                .background(SLATE_700)
+                .build(),
+            children![
+                (Text::new("Level Editor"), TextFont::from_font_size(16.0)),
+                (
+                    Text::new(
+                        "Click a value to type a new one, then press <enter>. Click the map to use the selected tool."
+                    ),
+                    TextFont::from_font_size(12.0),
+                ),
+            ],
+        ))
+        .with_children(|parent| {
+            for field in [
+                EditorField::Seed,
+                EditorField::WindAngle,
+                EditorField::WindSpeed,
+                EditorField::BoltTime,
+            ] {
+                let (value, editing) = match &editor.editing {
+                    Some((edited, text)) if *edited == field => (format!("{text}_"), true),
+                    _ => (editor.field_value(field), false),
+                };
+
+                parent
+                    .spawn(editor_button(format!("{field}: {value}"), editing))
+                    .observe(
+                        move |_trigger: Trigger<Pointer<Click>>,
+                              mut commands: Commands,
+                              mut editor: ResMut<LevelEditor>| {
+                            if editor.start_editing(field) {
+                                commands.run_system_cached(respawn_editor_map);
+                            }
+                        },
+                    );
+            }
+
+            for (tool, label) in [
+                (EditorTool::StartingLocation, "Tool: Place City Hall"),
+                (EditorTool::MeteorBolt, "Tool: Add Meteor Bolt"),
+            ] {
+                parent
+                    .spawn(editor_button(label, editor.tool == tool))
+                    .observe(
+                        move |_trigger: Trigger<Pointer<Click>>,
+                              mut editor: ResMut<LevelEditor>| {
+                            editor.tool = tool;
+                        },
+                    );
+            }
+
+            parent.spawn((Text::new(summary), TextFont::from_font_size(12.0)));
+
+            parent.spawn(widget::button_menu("Clear Events", clear_events));
+            parent.spawn(widget::button_menu("Test Level", test_level));
+            parent.spawn(widget::button_menu("Export", export_level));
+            parent.spawn(widget::button_menu("Back", back_to_title));
+
+            if let Some(exported) = &editor.exported {
+                parent.spawn((Text::new(exported.clone()), TextFont::from_font_size(10.0)));
+            }
+        });
+}
+
+/// Draws the city hall location and meteor strikes onto the map
+fn draw_editor_markers(mut painter: ShapePainter, map: Res<GameMap>, editor: Res<LevelEditor>) {
+    let original_tx = painter.transform;
+    painter.hollow = true;
+
+    painter.set_color(AMBER_300);
+    painter.translate(map.world_coords(editor.level.starting_location).extend(1.0));
+    painter.circle(4.0 * map.sprite_size);
+    painter.transform = original_tx;
+
+    painter.set_color(RED_500);
+    for (_, event) in &editor.level.events {
+        let (loc, radius) = match event {
+            StoryEvent::MeteorBolt(loc) => (*loc, 2),
+            StoryEvent::LightningWave { center, radius, .. } => (*center, *radius),
+            StoryEvent::Tornado { start, .. } => (*start, 3),
+            StoryEvent::WindShift { .. } => continue,
+        };
+
+        painter.translate(map.world_coords(loc).extend(1.0));
+        painter.circle(radius as f32 * map.sprite_size);
+        painter.transform = original_tx;
+    }
+}
+
+fn clear_events(_: Trigger<Pointer<Click>>, mut editor: ResMut<LevelEditor>) {
+    editor.level.events.clear();
+}
+
+fn test_level(
+    _: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut editor: ResMut<LevelEditor>,
+    resource_handles: Res<ResourceHandles>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    editor.finish_editing();
+    commands.remove_resource::<EndlessMode>();
+    commands.insert_resource(EditorTestLevel(editor.level.clone()));
+
+    if resource_handles.is_all_done() {
+        next_screen.set(Screen::Gameplay);
+    } else {
+        next_screen.set(Screen::Loading);
+    }
+}
+
+fn export_level(
+    _: Trigger<Pointer<Click>>,
+    registry: Res<AppTypeRegistry>,
+    mut editor: ResMut<LevelEditor>,
+) {
+    let registry = registry.read();
+    let serializer = TypedReflectSerializer::new(&editor.level, &registry);
+    let contents = match ron::ser::to_string_pretty(&serializer, ron::ser::PrettyConfig::default())
+    {
+        Ok(contents) => contents,
+        Err(e) => {
+            warn!("Unable to serialize level: {e}");
+            return;
+        }
+    };
+
+    #[cfg(not(target_family = "wasm"))]
+    {
+        let path = std::path::PathBuf::from(EXPORT_DIRECTORY)
+            .join(format!("level_{}.ron", editor.level.map_seed));
+        match std::fs::create_dir_all(EXPORT_DIRECTORY)
+            .and_then(|_| std::fs::write(&path, &contents))
+        {
+            Ok(_) => info!("Exported level to {}", path.display()),
+            Err(e) => warn!("Unable to export level to {}: {e}", path.display()),
+        }
+    }
+
+    editor.exported = Some(contents);
+}
+
+fn back_to_title(_: Trigger<Pointer<Click>>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
@@ -0,0 +1,199 @@
+//! A screen for choosing which story mode level to play. Each level is shown
+//! as a card with a small thumbnail of its map.
+
+use bevy::{
+    asset::RenderAssetUsages,
+    color::palettes::tailwind::SLATE_800,
+    ecs::spawn::SpawnWith,
+    image::ImageSampler,
+    input::{
+        common_conditions::input_just_pressed,
+        mouse::{MouseScrollUnit, MouseWheel},
+    },
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::{
+    screens::{NextStoryLevel, Screen, get_level_data},
+    theme::{node_builder::NodeBuilder, widget},
+    wildfire::{MapSize, NoiseMap},
+};
+
+/// The width and height of each level thumbnail, in pixels
+const THUMBNAIL_SIZE: u32 = 64;
+
+/// How far the level list moves for each line scrolled on the mouse wheel
+const SCROLL_LINE_HEIGHT: f32 = 30.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<LevelThumbnails>();
+    app.register_type::<LevelSelectList>();
+
+    app.add_systems(Startup, generate_level_thumbnails);
+    app.add_systems(OnEnter(Screen::LevelSelect), spawn_level_select);
+    app.add_systems(
+        Update,
+        (
+            scroll_level_list,
+            go_back.run_if(input_just_pressed(KeyCode::Escape)),
+        )
+            .run_if(in_state(Screen::LevelSelect)),
+    );
+}
+
+/// A thumbnail of the map for each story level, in level order
+#[derive(Resource, Reflect, Debug, Default)]
+#[reflect(Resource)]
+struct LevelThumbnails(Vec<Handle<Image>>);
+
+/// The scrollable list that holds the level cards
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+struct LevelSelectList;
+
+/// Draws a small image of each story level's map, sampling the map noise
+/// directly rather than generating the whole map
+fn generate_level_thumbnails(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    // story levels are laid out for a medium map
+    let map_size = UVec2::from(MapSize::Medium);
+    let cells_per_pixel = (map_size.x / THUMBNAIL_SIZE) as usize;
+
+    let thumbnails = (1..)
+        .map_while(get_level_data)
+        .map(|level| {
+            let noise_map = NoiseMap::new(level.map_seed);
+            let config = level.biome.config();
+
+            let mut image = Image::new_fill(
+                Extent3d {
+                    width: THUMBNAIL_SIZE,
+                    height: THUMBNAIL_SIZE,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                &[0, 0, 0, 255],
+                TextureFormat::Rgba8UnormSrgb,
+                RenderAssetUsages::default(),
+            );
+            // Use `nearest` image sampling to preserve pixel art style.
+            image.sampler = ImageSampler::nearest();
+
+            for pixel_y in 0..THUMBNAIL_SIZE {
+                for pixel_x in 0..THUMBNAIL_SIZE {
+                    let cell = noise_map.sample_cell(
+                        pixel_x as usize * cells_per_pixel,
+                        pixel_y as usize * cells_per_pixel,
+                        &config,
+                    );
+
+                    // images go top to bottom but the map goes bottom to top
+                    let _ =
+                        image.set_color_at(pixel_x, THUMBNAIL_SIZE - 1 - pixel_y, cell.colour());
+                }
+            }
+
+            images.add(image)
+        })
+        .collect();
+
+    commands.insert_resource(LevelThumbnails(thumbnails));
+}
+
+fn spawn_level_select(mut commands: Commands, thumbnails: Res<LevelThumbnails>) {
+    let cards = (1..)
+        .map_while(get_level_data)
+        .zip(thumbnails.0.iter().cloned())
+        .map(|(level, thumbnail)| (level.level_number, level.difficulty(), thumbnail))
+        .collect::<Vec<_>>();
+
+    commands.spawn((
+        widget::ui_root("Level Select"),
+        GlobalZIndex(2),
+        StateScoped(Screen::LevelSelect),
+        children![
+            widget::header("Level Select"),
+            (
+                Name::new("Level List"),
+                LevelSelectList,
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(10.0),
+                    height: Val::Percent(60.0),
+                    overflow: Overflow::scroll_y(),
+                    ..default()
+                },
+                Children::spawn(SpawnWith(move |parent: &mut ChildSpawner| {
+                    for (level_number, difficulty, thumbnail) in cards {
+                        parent.spawn(level_card(level_number, difficulty, thumbnail));
+                    }
+                })),
+            ),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+}
+
+fn level_card(level_number: usize, difficulty: usize, thumbnail: Handle<Image>) -> impl Bundle {
+    (
+        Name::new(format!("Level {level_number} Card")),
+        NodeBuilder::new()
+            .width(Val::Px(400.0))
+            .padding(UiRect::all(Val::Px(10.0)))
+            .flex_direction(FlexDirection::Row)
+            .justify(JustifyContent::SpaceBetween)
+            .align(AlignItems::Center)
+            .background(SLATE_800)
+            .build(),
+        children![
+            (
+                Node {
+                    width: Val::Px(THUMBNAIL_SIZE as f32),
+                    height: Val::Px(THUMBNAIL_SIZE as f32),
+                    ..default()
+                },
+                ImageNode::new(thumbnail),
+            ),
+            (
+                NodeBuilder::new()
+                    .flex_direction(FlexDirection::Column)
+                    .build(),
+                children![
+                    widget::label(format!("Level {level_number}")),
+                    widget::label(format!("Difficulty: {difficulty}/5")),
+                ],
+            ),
+            widget::button_small(
+                "Play",
+                move |_: Trigger<Pointer<Click>>,
+                      mut next_level: ResMut<NextStoryLevel>,
+                      mut next_screen: ResMut<NextState<Screen>>| {
+                    next_level.0 = level_number;
+                    next_screen.set(Screen::Loading);
+                },
+            ),
+        ],
+    )
+}
+
+fn scroll_level_list(
+    mut wheel: EventReader<MouseWheel>,
+    mut list: Single<&mut ScrollPosition, With<LevelSelectList>>,
+) {
+    for event in wheel.read() {
+        let dy = match event.unit {
+            MouseScrollUnit::Line => event.y * SCROLL_LINE_HEIGHT,
+            MouseScrollUnit::Pixel => event.y,
+        };
+        list.offset_y -= dy;
+    }
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
+
+fn go_back(mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
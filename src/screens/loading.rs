@@ -3,20 +3,53 @@
 
 use bevy::prelude::*;
 
+#[cfg(not(target_family = "wasm"))]
+use crate::screens::{EndlessMode, ResumeAutosave, autosave_exists};
 use crate::{asset_tracking::ResourceHandles, screens::Screen, theme::prelude::*};
 
 pub(super) fn plugin(app: &mut App) {
+    app.register_type::<LoadingScreen>();
+
     app.add_systems(OnEnter(Screen::Loading), spawn_loading_screen);
 
     app.add_systems(
         Update,
-        enter_gameplay_screen.run_if(in_state(Screen::Loading).and(all_assets_loaded)),
+        enter_gameplay_screen.run_if(
+            in_state(Screen::Loading)
+                .and(all_assets_loaded)
+                .and(not(can_resume_autosave)),
+        ),
     );
+
+    #[cfg(not(target_family = "wasm"))]
+    {
+        app.register_type::<AutosavePrompt>();
+        app.add_systems(
+            Update,
+            spawn_autosave_prompt.run_if(
+                in_state(Screen::Loading)
+                    .and(all_assets_loaded)
+                    .and(can_resume_autosave)
+                    .and(not(any_with_component::<AutosavePrompt>)),
+            ),
+        );
+    }
 }
 
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+struct LoadingScreen;
+
+/// Asks whether to continue the unfinished endless game in the autosave
+#[cfg(not(target_family = "wasm"))]
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+struct AutosavePrompt;
+
 fn spawn_loading_screen(mut commands: Commands) {
     commands.spawn((
         widget::ui_root("Loading Screen"),
+        LoadingScreen,
         StateScoped(Screen::Loading),
         children![widget::label("Loading...")],
     ));
@@ -26,6 +59,52 @@ fn enter_gameplay_screen(mut next_screen: ResMut<NextState<Screen>>) {
     next_screen.set(Screen::Gameplay);
 }
 
+/// Only endless games are auto saved, so the prompt isn't shown for story
+/// levels
+#[cfg(not(target_family = "wasm"))]
+fn can_resume_autosave(endless_mode: Option<Res<EndlessMode>>) -> bool {
+    endless_mode.is_some() && autosave_exists()
+}
+
+/// Games can't be saved on the web, so there is never an autosave
+#[cfg(target_family = "wasm")]
+fn can_resume_autosave() -> bool {
+    false
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn spawn_autosave_prompt(mut commands: Commands, loading: Query<Entity, With<LoadingScreen>>) {
+    // the prompt replaces the loading text now that everything has loaded
+    for entity in &loading {
+        commands.entity(entity).despawn();
+    }
+
+    commands.spawn((
+        widget::ui_root("Autosave Prompt"),
+        AutosavePrompt,
+        GlobalZIndex(2),
+        StateScoped(Screen::Loading),
+        children![
+            widget::header("Continue from autosave?"),
+            widget::button(
+                "Continue",
+                |_: Trigger<Pointer<Click>>,
+                 mut commands: Commands,
+                 mut next_screen: ResMut<NextState<Screen>>| {
+                    commands.init_resource::<ResumeAutosave>();
+                    next_screen.set(Screen::Gameplay);
+                },
+            ),
+            widget::button(
+                "New Game",
+                |_: Trigger<Pointer<Click>>, mut next_screen: ResMut<NextState<Screen>>| {
+                    next_screen.set(Screen::Gameplay);
+                },
+            ),
+        ],
+    ));
+}
+
 fn all_assets_loaded(resource_handles: Res<ResourceHandles>) -> bool {
     resource_handles.is_all_done()
 }
@@ -1,17 +1,31 @@
 //! The game's main screen states and transitions between them.
 
+mod campaign_map;
 mod game_over;
 mod gameplay;
+mod level_editor;
+mod level_select;
 mod loading;
+mod replay;
 mod splash;
 mod title;
 mod victory;
 
 pub use game_over::GameOverAssets;
 pub use gameplay::{
-    BuildingMode, BuildingType, EndlessMode, OnRedrawToolbar, PlayerResources, RequiresCityHall,
-    story_mode::{NextStoryLevel, StoryModeLevel, get_level_data},
+    BEACON_TOWER_MAX_REDIRECTS, BEACON_TOWER_RANGE, BeaconTower, BuildingAssets, BuildingLocation,
+    BuildingMode, BuildingType, CHALLENGES, ChallengeCompletions, ChallengeLumberLimit,
+    ChallengeModifier, ChallengeModifiers, EndlessMode, EventLog, GameSpeed, HasSeenTutorial,
+    Medal, MedalCollection, OnRedrawToolbar, PlayerResources, RequiresCityHall, ResearchState,
+    ShakeScreen, WearOutBeaconTower, log_event, painting_terrain,
+    story_mode::{NextStoryLevel, StoryEvent, StoryModeLevel, StoryStrikeWave, get_level_data},
 };
+#[cfg(not(target_family = "wasm"))]
+pub use gameplay::{
+    ExportMap, HighScores, ImportMap, LevelCompletionResult, LevelRecords, LoadGame, PlayerProfile,
+    ResumeAutosave, SAVE_SLOTS, SaveGame, autosave_exists, save_exists,
+};
+pub use level_editor::EditorTestLevel;
 
 use bevy::prelude::*;
 
@@ -19,9 +33,13 @@ pub(super) fn plugin(app: &mut App) {
     app.init_state::<Screen>();
 
     app.add_plugins((
+        campaign_map::plugin,
         gameplay::plugin,
         game_over::plugin,
+        level_editor::plugin,
+        level_select::plugin,
         loading::plugin,
+        replay::plugin,
         splash::plugin,
         title::plugin,
         victory::plugin,
@@ -37,6 +55,12 @@ pub enum Screen {
     Title,
     Loading,
     Gameplay,
+    /// Plays back how the fire spread before showing the game over screen
+    Replay,
     GameOver,
     LevelWon,
+    LevelEditor,
+    LevelSelect,
+    /// A map of the story levels showing the player's progress
+    CampaignMap,
 }
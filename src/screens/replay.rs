@@ -0,0 +1,359 @@
+//! Plays back the progression of the fire after the city hall is lost, before
+//! moving on to the game over screen
+
+use std::collections::VecDeque;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    color::palettes::tailwind::{AMBER_400, SLATE_700},
+    image::ImageSampler,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::{
+    Pause,
+    screens::Screen,
+    theme::{node_builder::NodeBuilder, widget},
+    wildfire::{FireStats, GameMap, TerrainType},
+};
+
+/// How often a snapshot of the map is recorded, in game seconds
+const SNAPSHOT_INTERVAL: f32 = 5.0;
+/// The most snapshots that are kept, older ones are discarded
+const MAX_SNAPSHOTS: usize = 60;
+/// How long each snapshot is shown for during playback, in seconds
+const PLAYBACK_FRAME_TIME: f32 = 0.5;
+/// The size the replay is drawn at on screen, in pixels
+const REPLAY_DISPLAY_SIZE: f32 = 400.0;
+/// The width of the timeline scrubber, in pixels
+const SCRUBBER_WIDTH: f32 = 400.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<GameReplay>();
+    app.register_type::<ReplayPlayback>();
+    app.register_type::<ReplayScrubber>();
+    app.register_type::<ReplayScrubberHandle>();
+    app.register_type::<ReplayTimeText>();
+
+    app.init_resource::<GameReplay>();
+
+    app.add_observer(scrub_replay_on_click);
+    app.add_observer(scrub_replay_on_drag);
+
+    app.add_systems(OnEnter(Screen::Gameplay), reset_replay);
+    app.add_systems(
+        Update,
+        record_snapshots.run_if(
+            in_state(Screen::Gameplay)
+                .and(in_state(Pause(false)))
+                .and(resource_exists::<GameMap>),
+        ),
+    );
+    // catch the final state of the map before it is despawned
+    app.add_systems(OnExit(Screen::Gameplay), record_final_snapshot);
+
+    app.add_systems(OnEnter(Screen::Replay), spawn_replay_screen);
+    app.add_systems(OnExit(Screen::Replay), remove_replay_playback);
+    app.add_systems(
+        Update,
+        (
+            advance_playback,
+            draw_replay_frame.run_if(resource_changed::<ReplayPlayback>),
+        )
+            .chain()
+            .run_if(in_state(Screen::Replay).and(resource_exists::<ReplayPlayback>)),
+    );
+}
+
+/// The terrain of the whole map at a point in time
+#[derive(Reflect, Debug, Clone)]
+pub struct MapSnapshot {
+    /// The terrain of each cell, indexed by `[y][x]` like the map
+    pub terrain: Vec<Vec<TerrainType>>,
+    /// The game time the snapshot was recorded at, in seconds
+    pub elapsed: f32,
+}
+
+impl MapSnapshot {
+    fn from_map(map: &GameMap, elapsed: f32) -> Self {
+        Self {
+            terrain: map
+                .data
+                .iter()
+                .map(|row| row.iter().map(|cell| cell.terrain).collect())
+                .collect(),
+            elapsed,
+        }
+    }
+}
+
+/// Snapshots of the map recorded during the current game, oldest first
+#[derive(Resource, Reflect, Debug, Clone, Default)]
+#[reflect(Resource)]
+pub struct GameReplay {
+    pub snapshots: VecDeque<MapSnapshot>,
+}
+
+impl GameReplay {
+    fn record(&mut self, snapshot: MapSnapshot) {
+        if self.snapshots.len() >= MAX_SNAPSHOTS {
+            self.snapshots.pop_front();
+        }
+
+        self.snapshots.push_back(snapshot);
+    }
+}
+
+/// Tracks which snapshot is being shown on the replay screen
+#[derive(Resource, Reflect, Debug, Clone)]
+#[reflect(Resource)]
+struct ReplayPlayback {
+    image: Handle<Image>,
+    index: usize,
+    since_last_frame: f32,
+}
+
+/// The timeline track that can be clicked or dragged to move through the replay
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+struct ReplayScrubber;
+
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+struct ReplayScrubberHandle;
+
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+struct ReplayTimeText;
+
+fn reset_replay(mut replay: ResMut<GameReplay>) {
+    replay.snapshots.clear();
+}
+
+fn record_snapshots(map: Res<GameMap>, stats: Res<FireStats>, mut replay: ResMut<GameReplay>) {
+    let due = match replay.snapshots.back() {
+        Some(last) => stats.duration - last.elapsed >= SNAPSHOT_INTERVAL,
+        None => true,
+    };
+
+    if due {
+        replay.record(MapSnapshot::from_map(&map, stats.duration));
+    }
+}
+
+fn record_final_snapshot(
+    maybe_map: Option<Res<GameMap>>,
+    stats: Res<FireStats>,
+    mut replay: ResMut<GameReplay>,
+) {
+    if let Some(map) = maybe_map {
+        replay.record(MapSnapshot::from_map(&map, stats.duration));
+    }
+}
+
+fn spawn_replay_screen(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    replay: Res<GameReplay>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    let Some(first) = replay.snapshots.front() else {
+        info!("No replay was recorded, skipping to game over");
+        next_screen.set(Screen::GameOver);
+        return;
+    };
+
+    let size_y = first.terrain.len() as u32;
+    let size_x = first.terrain.first().map(|row| row.len()).unwrap_or(0) as u32;
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: size_x.max(1),
+            height: size_y.max(1),
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    // Use `nearest` image sampling to preserve pixel art style.
+    image.sampler = ImageSampler::nearest();
+    let image = images.add(image);
+
+    commands.insert_resource(ReplayPlayback {
+        image: image.clone(),
+        index: 0,
+        since_last_frame: 0.0,
+    });
+
+    commands.spawn((
+        widget::ui_root("Replay Screen"),
+        StateScoped(Screen::Replay),
+        children![
+            widget::header("HOW THE FIRE SPREAD"),
+            (
+                Name::new("Replay Map"),
+                NodeBuilder::new()
+                    .width(Val::Px(REPLAY_DISPLAY_SIZE))
+                    .height(Val::Px(REPLAY_DISPLAY_SIZE))
+                    .build(),
+                ImageNode::new(image),
+            ),
+            (ReplayTimeText, widget::label("")),
+            (
+                Name::new("Replay Scrubber"),
+                ReplayScrubber,
+                NodeBuilder::new()
+                    .width(Val::Px(SCRUBBER_WIDTH))
+                    .height(Val::Px(12.0))
+                    .background(SLATE_700)
+                    .build(),
+                children![(
+                    Name::new("Replay Scrubber Handle"),
+                    ReplayScrubberHandle,
+                    NodeBuilder::new()
+                        .position(PositionType::Absolute)
+                        .width(Val::Px(8.0))
+                        .height(Val::Px(20.0))
+                        .top(-4.0)
+                        .left(0.0)
+                        .background(AMBER_400)
+                        .build(),
+                    Pickable::IGNORE,
+                )],
+            ),
+            (
+                NodeBuilder::new()
+                    .flex_direction(FlexDirection::Row)
+                    .build(),
+                Pickable::IGNORE,
+                children![
+                    widget::button("Skip to End", skip_to_end),
+                    widget::button("Back to Menu", back_to_menu),
+                ],
+            ),
+        ],
+    ));
+}
+
+fn remove_replay_playback(mut commands: Commands) {
+    commands.remove_resource::<ReplayPlayback>();
+}
+
+/// Steps through the snapshots, holding on the last one once it is reached
+fn advance_playback(
+    time: Res<Time>,
+    replay: Res<GameReplay>,
+    mut playback: ResMut<ReplayPlayback>,
+) {
+    if playback.index + 1 >= replay.snapshots.len() {
+        return;
+    }
+
+    playback.since_last_frame += time.delta_secs();
+    if playback.since_last_frame >= PLAYBACK_FRAME_TIME {
+        playback.since_last_frame = 0.0;
+        playback.index += 1;
+    }
+}
+
+fn draw_replay_frame(
+    replay: Res<GameReplay>,
+    playback: Res<ReplayPlayback>,
+    mut images: ResMut<Assets<Image>>,
+    mut time_text: Single<&mut Text, With<ReplayTimeText>>,
+    mut handle: Single<&mut Node, With<ReplayScrubberHandle>>,
+) {
+    let Some(snapshot) = replay.snapshots.get(playback.index) else {
+        return;
+    };
+
+    let minutes = (snapshot.elapsed / 60.0).floor() as i32;
+    let seconds = (snapshot.elapsed % 60.0).floor() as i32;
+    time_text.0 = format!("{minutes}:{seconds:02}");
+
+    let progress = playback.index as f32 / (replay.snapshots.len().max(2) - 1) as f32;
+    handle.left = Val::Px(progress * (SCRUBBER_WIDTH - 8.0));
+
+    let Some(image) = images.get_mut(&playback.image) else {
+        warn!("Unable to find replay image, skipping frame");
+        return;
+    };
+
+    let size_y = snapshot.terrain.len() as u32;
+    for (y, row) in snapshot.terrain.iter().enumerate() {
+        for (x, terrain) in row.iter().enumerate() {
+            // images go top to bottom but the map goes bottom to top
+            let pixel_y = size_y - 1 - y as u32;
+            if let Err(e) = image.set_color_at(x as u32, pixel_y, terrain.typical_colour()) {
+                warn!("Unable to update replay pixel at {x},{pixel_y}: {e:?}");
+            }
+        }
+    }
+}
+
+fn scrub_replay_on_click(
+    trigger: Trigger<Pointer<Click>>,
+    replay: Res<GameReplay>,
+    maybe_playback: Option<ResMut<ReplayPlayback>>,
+    scrubbers: Query<(&GlobalTransform, &ComputedNode), With<ReplayScrubber>>,
+) {
+    if let Some(mut playback) = maybe_playback {
+        scrub_replay(
+            trigger.target(),
+            trigger.event().pointer_location.position,
+            &replay,
+            &mut playback,
+            &scrubbers,
+        );
+    }
+}
+
+fn scrub_replay_on_drag(
+    trigger: Trigger<Pointer<Drag>>,
+    replay: Res<GameReplay>,
+    maybe_playback: Option<ResMut<ReplayPlayback>>,
+    scrubbers: Query<(&GlobalTransform, &ComputedNode), With<ReplayScrubber>>,
+) {
+    if let Some(mut playback) = maybe_playback {
+        scrub_replay(
+            trigger.target(),
+            trigger.event().pointer_location.position,
+            &replay,
+            &mut playback,
+            &scrubbers,
+        );
+    }
+}
+
+/// Jumps to the snapshot under the pointer on the timeline
+fn scrub_replay(
+    scrubber: Entity,
+    pointer: Vec2,
+    replay: &GameReplay,
+    playback: &mut ReplayPlayback,
+    scrubbers: &Query<(&GlobalTransform, &ComputedNode), With<ReplayScrubber>>,
+) {
+    let Ok((tx, computed)) = scrubbers.get(scrubber) else {
+        return;
+    };
+
+    // UI transforms are in physical pixels at the centre of the node
+    let scale = computed.inverse_scale_factor();
+    let centre = tx.translation().truncate() * scale;
+    let width = computed.size().x * scale;
+    let progress = ((pointer.x - (centre.x - width * 0.5)) / width).clamp(0.0, 1.0);
+
+    let last = replay.snapshots.len().saturating_sub(1);
+    playback.index = (progress * last as f32).round() as usize;
+    playback.since_last_frame = 0.0;
+}
+
+fn skip_to_end(_: Trigger<Pointer<Click>>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::GameOver);
+}
+
+fn back_to_menu(_: Trigger<Pointer<Click>>, mut next_screen: ResMut<NextState<Screen>>) {
+    next_screen.set(Screen::Title);
+}
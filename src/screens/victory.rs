@@ -1,21 +1,49 @@
 //! The victory screen
 
-use bevy::prelude::*;
+use bevy::{
+    color::palettes::tailwind::{AMBER_400, ORANGE_700, PURPLE_400, SLATE_300},
+    prelude::*,
+};
 
 use crate::audio::sound_effect;
-use crate::screens::{GameOverAssets, NextStoryLevel, Screen, StoryModeLevel, get_level_data};
+#[cfg(not(target_family = "wasm"))]
+use crate::screens::LevelCompletionResult;
+use crate::screens::{
+    GameOverAssets, Medal, NextStoryLevel, Screen, StoryModeLevel, get_level_data,
+};
 use crate::theme::widget;
+use crate::wildfire::{FireStats, fire_stats_panel};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(Screen::LevelWon), spawn_level_victory_screen);
 }
 
+pub(super) fn medal_color(medal: Medal) -> Color {
+    match medal {
+        Medal::Bronze => ORANGE_700.into(),
+        Medal::Silver => SLATE_300.into(),
+        Medal::Gold => AMBER_400.into(),
+        Medal::Legendary => PURPLE_400.into(),
+    }
+}
+
+/// Formats a time in seconds as m:ss
+fn format_time(seconds: f32) -> String {
+    let seconds = seconds as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
 fn spawn_level_victory_screen(
     mut commands: Commands,
     story_level: Res<StoryModeLevel>,
     game_over_assets: Res<GameOverAssets>,
     mut next_level: ResMut<NextStoryLevel>,
+    stats: Res<FireStats>,
+    #[cfg(not(target_family = "wasm"))] result: Option<Res<LevelCompletionResult>>,
 ) {
+    let completion_time = story_level.elapsed_time;
+    let medal = Medal::for_completion_time(completion_time);
+
     next_level.0 = story_level.level_number + 1;
     let has_next = get_level_data(next_level.0).is_some();
 
@@ -38,6 +66,39 @@ fn spawn_level_victory_screen(
             ],
         ))
         .with_children(|parent| {
+            #[cfg(not(target_family = "wasm"))]
+            if let Some(result) = &result {
+                if result.is_new_record {
+                    parent.spawn((
+                        Text::new("New Record!"),
+                        TextFont::from_font_size(32.0),
+                        TextColor(AMBER_400.into()),
+                    ));
+                }
+            }
+
+            parent.spawn((
+                Text::new(format!(
+                    "Completed in {} - {medal} medal",
+                    format_time(completion_time)
+                )),
+                TextFont::from_font_size(24.0),
+                TextColor(medal_color(medal)),
+            ));
+
+            #[cfg(not(target_family = "wasm"))]
+            if let Some(result) = &result {
+                let best = result
+                    .previous_best
+                    .map_or(completion_time, |best| best.min(completion_time));
+                parent.spawn(widget::label(format!(
+                    "Personal best: {}",
+                    format_time(best)
+                )));
+            }
+
+            parent.spawn(fire_stats_panel(&stats));
+
             if has_next {
                 parent.spawn((widget::button(
                     "Next Level",
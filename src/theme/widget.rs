@@ -4,7 +4,10 @@ use std::borrow::Cow;
 
 use bevy::{
     color::palettes::tailwind::SLATE_400,
-    ecs::{spawn::SpawnWith, system::IntoObserverSystem},
+    ecs::{
+        spawn::{SpawnIter, SpawnWith},
+        system::IntoObserverSystem,
+    },
     prelude::*,
     ui::Val::*,
 };
@@ -194,3 +197,35 @@ where
         Some(20.0),
     )
 }
+
+/// A two column grid of labels, with the first column right aligned
+pub fn grid<T>(content: Vec<[T; 2]>) -> impl Bundle
+where
+    T: Into<String> + Send + Sync + 'static,
+{
+    (
+        Name::new("Grid"),
+        Node {
+            display: Display::Grid,
+            row_gap: Px(10.0),
+            column_gap: Px(30.0),
+            grid_template_columns: RepeatedGridTrack::px(2, 400.0),
+            ..default()
+        },
+        Children::spawn(SpawnIter(content.into_iter().flatten().enumerate().map(
+            |(i, text)| {
+                (
+                    label(text),
+                    Node {
+                        justify_self: if i % 2 == 0 {
+                            JustifySelf::End
+                        } else {
+                            JustifySelf::Start
+                        },
+                        ..default()
+                    },
+                )
+            },
+        ))),
+    )
+}
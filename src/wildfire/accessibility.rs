@@ -0,0 +1,198 @@
+//! Accessibility options for colour blind players. The terrain colours can be
+//! corrected for red-green colour blindness, and small patterns can be drawn
+//! over the terrain so that grass, forest, fire and stone can be told apart
+//! without relying on colour at all. Both are set from the settings menu.
+
+use bevy::{color::palettes::css::BLACK, prelude::*};
+use bevy_vector_shapes::{
+    prelude::ShapePainter,
+    shapes::{DiscPainter, LinePainter},
+};
+
+use crate::{
+    MainCamera,
+    screens::Screen,
+    wildfire::{TerrainType, map::GameMap},
+};
+
+/// Simulates how deuteranopes see linear RGB colours, one row per channel
+const DEUTERANOPIA_SIMULATION: [[f32; 3]; 3] = [
+    [0.367_322, 0.860_646, -0.227_968],
+    [0.280_085, 0.672_501, 0.047_413],
+    [-0.011_820, 0.042_940, 0.968_881],
+];
+/// Simulates how protanopes see linear RGB colours, one row per channel
+const PROTANOPIA_SIMULATION: [[f32; 3]; 3] = [
+    [0.152_286, 1.052_583, -0.204_868],
+    [0.114_503, 0.786_281, 0.099_216],
+    [-0.003_882, -0.048_116, 1.051_998],
+];
+/// Moves the colour information that would be lost into the green and blue
+/// channels, which can still be seen
+const ERROR_SHIFT: [[f32; 3]; 3] = [[0.0, 0.0, 0.0], [0.7, 1.0, 0.0], [0.7, 0.0, 1.0]];
+
+/// The alpha of the terrain patterns
+const PATTERN_ALPHA: f32 = 0.6;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<ColourBlindMode>();
+    app.register_type::<ShowTerrainPatterns>();
+
+    app.init_resource::<ColourBlindMode>();
+
+    app.add_systems(
+        Update,
+        (
+            redraw_map_colours
+                .run_if(resource_exists::<GameMap>.and(resource_changed::<ColourBlindMode>)),
+            draw_terrain_patterns.run_if(
+                in_state(Screen::Gameplay)
+                    .and(resource_exists::<GameMap>)
+                    .and(resource_exists::<ShowTerrainPatterns>),
+            ),
+        ),
+    );
+}
+
+/// Corrects the terrain colours so they are easier to tell apart with the
+/// given type of colour blindness
+#[derive(Resource, Reflect, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[reflect(Resource)]
+pub enum ColourBlindMode {
+    #[default]
+    None,
+    Deuteranopia,
+    Protanopia,
+}
+
+impl ColourBlindMode {
+    /// The next mode when cycling through them in the settings menu
+    pub fn next(self) -> Self {
+        match self {
+            ColourBlindMode::None => ColourBlindMode::Deuteranopia,
+            ColourBlindMode::Deuteranopia => ColourBlindMode::Protanopia,
+            ColourBlindMode::Protanopia => ColourBlindMode::None,
+        }
+    }
+
+    /// Daltonises the colour, working out what would be lost to colour
+    /// blindness and shifting it into the channels that can still be seen
+    pub fn correct(self, colour: Color) -> Color {
+        let simulation = match self {
+            ColourBlindMode::None => return colour,
+            ColourBlindMode::Deuteranopia => DEUTERANOPIA_SIMULATION,
+            ColourBlindMode::Protanopia => PROTANOPIA_SIMULATION,
+        };
+
+        let linear = colour.to_linear();
+        let rgb = Vec3::new(linear.red, linear.green, linear.blue);
+        let error = rgb - apply_matrix(simulation, rgb);
+        let corrected = (rgb + apply_matrix(ERROR_SHIFT, error)).clamp(Vec3::ZERO, Vec3::ONE);
+
+        LinearRgba::new(corrected.x, corrected.y, corrected.z, linear.alpha).into()
+    }
+}
+
+impl std::fmt::Display for ColourBlindMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mode = match self {
+            ColourBlindMode::None => "Off",
+            ColourBlindMode::Deuteranopia => "Deuteranopia",
+            ColourBlindMode::Protanopia => "Protanopia",
+        };
+        write!(f, "{mode}")
+    }
+}
+
+fn apply_matrix(rows: [[f32; 3]; 3], rgb: Vec3) -> Vec3 {
+    Vec3::new(
+        Vec3::from(rows[0]).dot(rgb),
+        Vec3::from(rows[1]).dot(rgb),
+        Vec3::from(rows[2]).dot(rgb),
+    )
+}
+
+/// When this resource exists, grass, forest, fire and stone cells are marked
+/// with a dot, triangle, cross and grid respectively
+#[derive(Resource, Reflect, Debug, Clone, Copy, Default)]
+#[reflect(Resource, Default)]
+pub struct ShowTerrainPatterns;
+
+fn redraw_map_colours(mut map: ResMut<GameMap>) {
+    // force all the sprites to redraw with the new colours
+    for row in map.data.iter_mut() {
+        for cell in row.iter_mut() {
+            cell.dirty = true;
+        }
+    }
+}
+
+/// Draws the patterns over the cells that the camera can see
+fn draw_terrain_patterns(
+    mut painter: ShapePainter,
+    map: Res<GameMap>,
+    camera: Single<(&Camera, &Transform, &Projection), With<MainCamera>>,
+) {
+    let (camera, tx, projection) = *camera;
+    let Projection::Orthographic(proj) = projection else {
+        return;
+    };
+    let Some(viewport_size) = camera.logical_viewport_size() else {
+        return;
+    };
+
+    let half_size = viewport_size * proj.scale * 0.5;
+    let centre = tx.translation.truncate();
+    let max_coords = IVec2::new(map.size_x as i32 - 1, map.size_y as i32 - 1);
+    let min = map
+        .tile_coords(centre - half_size)
+        .clamp(IVec2::ZERO, max_coords);
+    let max = map
+        .tile_coords(centre + half_size)
+        .clamp(IVec2::ZERO, max_coords);
+
+    let original_tx = painter.transform;
+    let size = map.sprite_size * 0.3;
+    painter.thickness = map.sprite_size * 0.08;
+    painter.hollow = false;
+    painter.set_color(BLACK.with_alpha(PATTERN_ALPHA));
+
+    for y in min.y..=max.y {
+        for x in min.x..=max.x {
+            let cell = &map.data[y as usize][x as usize];
+            if cell.fog {
+                continue;
+            }
+
+            let pos = map.world_coords(IVec2::new(x, y));
+            let point = |dx: f32, dy: f32| (pos + Vec2::new(dx, dy) * size).extend(0.55);
+
+            match cell.terrain {
+                TerrainType::Grassland => {
+                    painter.translate(pos.extend(0.55));
+                    painter.circle(size * 0.3);
+                    painter.transform = original_tx;
+                }
+                TerrainType::Tree => {
+                    painter.line(point(-1.0, -0.8), point(1.0, -0.8));
+                    painter.line(point(1.0, -0.8), point(0.0, 1.0));
+                    painter.line(point(0.0, 1.0), point(-1.0, -0.8));
+                }
+                TerrainType::Fire => {
+                    painter.line(point(-1.0, -1.0), point(1.0, 1.0));
+                    painter.line(point(-1.0, 1.0), point(1.0, -1.0));
+                }
+                TerrainType::Stone => {
+                    painter.line(point(-0.4, -1.0), point(-0.4, 1.0));
+                    painter.line(point(0.4, -1.0), point(0.4, 1.0));
+                    painter.line(point(-1.0, -0.4), point(1.0, -0.4));
+                    painter.line(point(-1.0, 0.4), point(1.0, 0.4));
+                }
+                TerrainType::Dirt
+                | TerrainType::Building
+                | TerrainType::Smoldering
+                | TerrainType::Water => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Biome presets that change how the map is generated and how readily it
+//! burns. In endless mode the biome is random, in story mode it is fixed for
+//! each level.
+
+use bevy::prelude::*;
+use rand::Rng;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Biome>();
+    app.register_type::<BiomeConfig>();
+}
+
+#[derive(Reflect, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Biome {
+    /// Mostly dirt and dry grass that burns quickly
+    Desert,
+    #[default]
+    Temperate,
+    /// Wet, dense forest that is slow to catch alight
+    Boreal,
+}
+
+impl std::fmt::Display for Biome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Biome::Desert => "Desert",
+                Biome::Temperate => "Temperate",
+                Biome::Boreal => "Boreal",
+            }
+        )
+    }
+}
+
+impl Biome {
+    /// Picks a random biome, e.g. for endless mode
+    pub fn random(rng: &mut impl Rng) -> Self {
+        match rng.gen_range(0..3) {
+            0 => Biome::Desert,
+            1 => Biome::Temperate,
+            _ => Biome::Boreal,
+        }
+    }
+
+    /// The id used to store this biome in compact maps
+    pub fn compact_id(&self) -> u8 {
+        match self {
+            Biome::Desert => 0,
+            Biome::Temperate => 1,
+            Biome::Boreal => 2,
+        }
+    }
+
+    /// The biome stored with the given id in compact maps, if it is valid
+    pub fn from_compact_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Biome::Desert),
+            1 => Some(Biome::Temperate),
+            2 => Some(Biome::Boreal),
+            _ => None,
+        }
+    }
+
+    /// The map generation settings for this biome
+    pub fn config(&self) -> BiomeConfig {
+        match self {
+            Biome::Desert => BiomeConfig {
+                dirt_threshold: 0.2,
+                grass_threshold: 0.65,
+                tree_threshold: 0.8,
+                base_moisture: 0.25,
+                burn_rate_modifier: 1.25,
+            },
+            Biome::Temperate => BiomeConfig::default(),
+            Biome::Boreal => BiomeConfig {
+                dirt_threshold: 0.01,
+                grass_threshold: 0.35,
+                tree_threshold: 0.8,
+                base_moisture: 0.7,
+                burn_rate_modifier: 0.8,
+            },
+        }
+    }
+}
+
+/// Settings used to generate the map for a [Biome]
+#[derive(Reflect, Debug, Clone, Copy, PartialEq)]
+pub struct BiomeConfig {
+    /// Noise values below this are dirt
+    pub dirt_threshold: f32,
+    /// Noise values below this (and above the dirt threshold) are grass
+    pub grass_threshold: f32,
+    /// Noise values below this (and above the grass threshold) are trees,
+    /// anything higher is stone
+    pub tree_threshold: f32,
+    /// The average starting moisture of grass and trees
+    pub base_moisture: f32,
+    /// Multiplies the chance of grass, trees and buildings catching fire
+    pub burn_rate_modifier: f32,
+}
+
+impl Default for BiomeConfig {
+    fn default() -> Self {
+        Self {
+            dirt_threshold: 0.01,
+            grass_threshold: 0.5,
+            tree_threshold: 0.75,
+            base_moisture: 0.5,
+            burn_rate_modifier: 1.0,
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! A controlled burn lets the player deliberately set fire to terrain ahead
+//! of the main fire front, so there's no fuel left when it arrives. Triggered
+//! by [OnControlledBurn]
+
+use bevy::prelude::*;
+
+use crate::{
+    screens::{BuildingType, PlayerResources},
+    wildfire::{TerrainType, map::GameMap},
+};
+
+/// The amount of mana used each time a controlled burn is started
+pub const CONTROLLED_BURN_COST_MANA: i32 = 5;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<OnControlledBurn>();
+
+    app.add_observer(handle_controlled_burn);
+}
+
+#[derive(Debug, Clone, Event, Reflect)]
+pub struct OnControlledBurn(pub IVec2);
+
+fn handle_controlled_burn(
+    trigger: Trigger<OnControlledBurn>,
+    maybe_resources: Option<ResMut<PlayerResources>>,
+    maybe_map: Option<ResMut<GameMap>>,
+    buildings: Query<&BuildingType>,
+) {
+    let (Some(mut resources), Some(mut map)) = (maybe_resources, maybe_map) else {
+        warn!("Skipping controlled burn as there is no map or resources yet");
+        return;
+    };
+
+    // checked here rather than by the toolbar so that hotkeys can't skip it
+    if !buildings.iter().any(|b| *b == BuildingType::ManaForge) {
+        warn!("A mana forge is needed to start a controlled burn");
+        return;
+    }
+
+    if resources.mana < CONTROLLED_BURN_COST_MANA {
+        warn!("Not enough mana to start a controlled burn");
+        return;
+    }
+
+    let loc = trigger.event().0;
+    let Some(cell) = map.get_mut(loc) else {
+        info!("Unable to find cell for controlled burn at {loc:?}");
+        return;
+    };
+
+    match cell.terrain {
+        TerrainType::Grassland | TerrainType::Tree => {
+            info!("Starting controlled burn at {loc}");
+            resources.mana -= CONTROLLED_BURN_COST_MANA;
+
+            cell.ignite();
+            map.mark_on_fire(loc);
+        }
+        TerrainType::Building
+        | TerrainType::Fire
+        | TerrainType::Dirt
+        | TerrainType::Stone
+        | TerrainType::Smoldering
+        | TerrainType::Water => {}
+    }
+}
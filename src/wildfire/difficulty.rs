@@ -0,0 +1,113 @@
+//! The difficulty chosen from the main menu, which changes how aggressively
+//! the fire behaves without changing the layout of the levels
+
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<DifficultyLevel>();
+    app.register_type::<DifficultySettings>();
+
+    app.init_resource::<DifficultySettings>();
+}
+
+#[derive(Reflect, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DifficultyLevel {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Inferno,
+}
+
+impl std::fmt::Display for DifficultyLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                DifficultyLevel::Easy => "Easy",
+                DifficultyLevel::Normal => "Normal",
+                DifficultyLevel::Hard => "Hard",
+                DifficultyLevel::Inferno => "Inferno",
+            }
+        )
+    }
+}
+
+impl DifficultyLevel {
+    /// The next hardest difficulty, wrapping back around to easy
+    pub fn harder(&self) -> Self {
+        match self {
+            DifficultyLevel::Easy => DifficultyLevel::Normal,
+            DifficultyLevel::Normal => DifficultyLevel::Hard,
+            DifficultyLevel::Hard => DifficultyLevel::Inferno,
+            DifficultyLevel::Inferno => DifficultyLevel::Easy,
+        }
+    }
+
+    /// The next easiest difficulty, wrapping back around to inferno
+    pub fn easier(&self) -> Self {
+        match self {
+            DifficultyLevel::Easy => DifficultyLevel::Inferno,
+            DifficultyLevel::Normal => DifficultyLevel::Easy,
+            DifficultyLevel::Hard => DifficultyLevel::Normal,
+            DifficultyLevel::Inferno => DifficultyLevel::Hard,
+        }
+    }
+
+    /// How much more likely fire is to spread to a neighbouring cell
+    pub fn spread_multiplier(&self) -> f64 {
+        match self {
+            DifficultyLevel::Easy => 0.7,
+            DifficultyLevel::Normal => 1.0,
+            DifficultyLevel::Hard => 1.25,
+            DifficultyLevel::Inferno => 1.5,
+        }
+    }
+
+    /// How much faster the intensity of a burning cell falls each update.
+    /// Harder difficulties burn for longer
+    pub fn burn_decay_multiplier(&self) -> f64 {
+        match self {
+            DifficultyLevel::Easy => 1.3,
+            DifficultyLevel::Normal => 1.0,
+            DifficultyLevel::Hard => 0.85,
+            DifficultyLevel::Inferno => 0.7,
+        }
+    }
+
+    /// How much faster cells dry out next to a fire
+    pub fn moisture_decay_multiplier(&self) -> f32 {
+        match self {
+            DifficultyLevel::Easy => 0.7,
+            DifficultyLevel::Normal => 1.0,
+            DifficultyLevel::Hard => 1.3,
+            DifficultyLevel::Inferno => 1.6,
+        }
+    }
+
+    /// How much stronger the starting wind is
+    pub fn wind_multiplier(&self) -> f32 {
+        match self {
+            DifficultyLevel::Easy => 0.75,
+            DifficultyLevel::Normal => 1.0,
+            DifficultyLevel::Hard => 1.25,
+            DifficultyLevel::Inferno => 1.5,
+        }
+    }
+
+    /// Scales the time between random events in endless mode, such as goblins,
+    /// gusts and changes in the weather
+    pub fn event_interval_multiplier(&self) -> f32 {
+        match self {
+            DifficultyLevel::Easy | DifficultyLevel::Normal => 1.0,
+            DifficultyLevel::Hard => 0.75,
+            DifficultyLevel::Inferno => 0.5,
+        }
+    }
+}
+
+/// The difficulty selected by the player
+#[derive(Resource, Reflect, Debug, Clone, Copy, Default)]
+#[reflect(Resource)]
+pub struct DifficultySettings(pub DifficultyLevel);
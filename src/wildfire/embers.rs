@@ -0,0 +1,131 @@
+//! Burning embers thrown up by the fire in strong winds. Embers are carried
+//! across the map on the wind and may start a spot fire where they land.
+
+use bevy::{color::palettes::tailwind::ORANGE_500, prelude::*};
+use rand::Rng;
+
+use crate::{
+    Pause,
+    screens::{GameSpeed, Screen},
+    wildfire::map::GameMap,
+};
+
+/// The most embers that can be in flight at once. Any more are resolved
+/// straight away without being shown
+const MAX_EMBERS: usize = 200;
+/// Converts the wind at the burning cell into the ember's velocity
+const EMBER_VELOCITY_SCALE: f32 = 0.1;
+/// The most (in radians) an ember can stray from the wind direction
+const EMBER_MAX_DEVIATION: f32 = std::f32::consts::FRAC_PI_4;
+/// The shortest time an ember stays in the air, in seconds
+const EMBER_MIN_LIFETIME: f32 = 1.0;
+/// The longest time an ember stays in the air, in seconds
+const EMBER_MAX_LIFETIME: f32 = 4.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<FireEmber>();
+    app.register_type::<EmberPool>();
+
+    app.init_resource::<EmberPool>();
+
+    app.add_systems(OnExit(Screen::Gameplay), reset_ember_pool);
+    app.add_systems(
+        Update,
+        (spawn_fire_embers, update_embers)
+            .chain()
+            .run_if(in_state(Pause(false)).and(resource_exists::<GameMap>)),
+    );
+}
+
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct FireEmber {
+    /// The speed and direction the ember is travelling, in world units per second
+    pub velocity: Vec2,
+    /// The remaining time before the ember lands
+    pub lifetime: f32,
+    /// The burning cell that threw the ember
+    pub origin: IVec2,
+}
+
+/// The number of embers currently in flight
+#[derive(Resource, Reflect, Debug, Clone, Copy, Default)]
+#[reflect(Resource)]
+pub struct EmberPool(pub usize);
+
+fn reset_ember_pool(mut pool: ResMut<EmberPool>) {
+    pool.0 = 0;
+}
+
+/// Spawns an ember for each one thrown by the fire since the last frame
+fn spawn_fire_embers(
+    mut commands: Commands,
+    screen: Res<State<Screen>>,
+    mut map: ResMut<GameMap>,
+    mut pool: ResMut<EmberPool>,
+) {
+    let thrown = map.take_thrown_embers();
+    if thrown.is_empty() {
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    let in_gameplay = *screen.get() == Screen::Gameplay;
+
+    for (origin, wind) in thrown {
+        // the map keeps burning behind the menus, but embers are only shown
+        // during gameplay
+        if !in_gameplay || pool.0 >= MAX_EMBERS {
+            map.spot_ember(origin, wind, &mut rng);
+            continue;
+        }
+
+        let deviation = rng.gen_range(-EMBER_MAX_DEVIATION..EMBER_MAX_DEVIATION);
+        let velocity = Vec2::from_angle(deviation).rotate(wind * EMBER_VELOCITY_SCALE);
+
+        pool.0 += 1;
+        commands.spawn((
+            Name::new("Fire Ember"),
+            FireEmber {
+                velocity,
+                lifetime: rng.gen_range(EMBER_MIN_LIFETIME..EMBER_MAX_LIFETIME),
+                origin,
+            },
+            Sprite {
+                color: ORANGE_500.into(),
+                custom_size: Some(Vec2::splat(2.0)),
+                ..default()
+            },
+            Transform::from_translation(map.world_coords(origin).extend(0.6)),
+            StateScoped(Screen::Gameplay),
+        ));
+    }
+}
+
+/// Moves the embers along, and tries to start a spot fire once they land
+fn update_embers(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut map: ResMut<GameMap>,
+    mut pool: ResMut<EmberPool>,
+    mut embers: Query<(Entity, &mut FireEmber, &mut Transform)>,
+) {
+    let delta = time.delta_secs() * game_speed.0;
+    let mut rng = rand::thread_rng();
+
+    for (entity, mut ember, mut tx) in &mut embers {
+        tx.translation += (ember.velocity * delta).extend(0.0);
+
+        ember.lifetime -= delta;
+        if ember.lifetime > 0.0 {
+            continue;
+        }
+
+        let landed_at = map.tile_coords(tx.translation.truncate());
+        map.land_ember(landed_at, &mut rng);
+
+        commands.entity(entity).despawn();
+        pool.0 = pool.0.saturating_sub(1);
+    }
+}
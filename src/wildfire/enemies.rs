@@ -0,0 +1,370 @@
+//! Goblin arsonists wander towards the city hall, setting fires as they go.
+//! Spawn them by triggering [SpawnGoblinArsonist]. Goblin shamans stay out of
+//! sight and call up winds to fan the fire towards the city hall. Spawn them
+//! by triggering [SpawnGoblinShaman].
+
+use std::collections::VecDeque;
+
+use bevy::{platform::collections::HashMap, prelude::*, sprite::Anchor};
+use rand::Rng;
+
+use crate::{
+    Pause,
+    screens::{BuildingAssets, BuildingLocation, BuildingType, EndlessMode, GameSpeed, Screen},
+    wildfire::{DifficultySettings, FireStats, TerrainType, WindDirection, map::GameMap},
+};
+
+/// The time between goblin arsonists spawning in endless mode, in seconds
+const GOBLIN_SPAWN_INTERVAL: f32 = 90.0;
+/// The time it takes a goblin to move one cell, in seconds
+const GOBLIN_MOVE_TIME: f32 = 0.4;
+/// How far from the city hall a goblin will pick its next target
+const GOBLIN_TARGET_RANGE: i32 = 25;
+/// The maximum number of cells to search when finding a path, so that
+/// unreachable targets don't search the entire map
+const MAX_PATH_SEARCH: usize = 20_000;
+/// The time between a goblin shaman's spells, in seconds
+const SHAMAN_COOLDOWN: f32 = 30.0;
+/// How far each spell turns the wind towards the shaman's preferred angle
+const SHAMAN_WIND_SHIFT: f32 = 0.3;
+/// How long into an endless game the goblin shaman arrives, in seconds
+const ENDLESS_SHAMAN_DELAY: f32 = 120.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<GoblinArsonist>();
+    app.register_type::<SpawnGoblinArsonist>();
+    app.register_type::<GoblinShaman>();
+    app.register_type::<SpawnGoblinShaman>();
+
+    app.add_observer(spawn_goblin_arsonist);
+    app.add_observer(spawn_goblin_shaman);
+
+    app.add_systems(
+        Update,
+        (
+            move_goblin_arsonists,
+            spawn_endless_goblins.run_if(resource_exists::<EndlessMode>),
+            update_goblin_shamans,
+            spawn_endless_shaman.run_if(resource_exists::<EndlessMode>),
+        )
+            .run_if(
+                in_state(Screen::Gameplay)
+                    .and(in_state(Pause(false)))
+                    .and(resource_exists::<GameMap>),
+            ),
+    );
+}
+
+/// Spawns a goblin arsonist at the given tile location
+#[derive(Event, Reflect, Debug, Clone, Copy)]
+pub struct SpawnGoblinArsonist(pub IVec2);
+
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct GoblinArsonist {
+    /// The current tile location of the goblin
+    pub location: IVec2,
+    /// The remaining cells to walk through to reach the target
+    pub path: VecDeque<IVec2>,
+    /// The cell that the goblin is going to set on fire
+    pub target: IVec2,
+    /// The time since the goblin last moved
+    pub move_timer: f32,
+}
+
+fn spawn_goblin_arsonist(
+    trigger: Trigger<SpawnGoblinArsonist>,
+    mut commands: Commands,
+    maybe_map: Option<Res<GameMap>>,
+    buildings: Res<BuildingAssets>,
+) {
+    let Some(map) = maybe_map else {
+        warn!("Skipping goblin arsonist as there is no map yet");
+        return;
+    };
+
+    let location = trigger.event().0;
+    if !map.is_valid_coords(location) {
+        warn!("Unable to spawn goblin arsonist outside the map at {location}");
+        return;
+    }
+
+    info!("Spawning goblin arsonist at {location}");
+    commands.spawn((
+        Name::new("Goblin Arsonist"),
+        GoblinArsonist {
+            location,
+            path: VecDeque::new(),
+            target: location,
+            move_timer: 0.0,
+        },
+        StateScoped(Screen::Gameplay),
+        Transform::from_translation(map.world_coords(location).extend(0.2)),
+        Sprite {
+            image: buildings.goblin.clone(),
+            custom_size: Some(Vec2::splat(12.0)),
+            anchor: Anchor::Center,
+            ..default()
+        },
+    ));
+}
+
+/// Whether a goblin can walk through the given cell. Goblins can cross water
+/// beside a river ferry
+fn is_passable(map: &GameMap, coords: IVec2) -> bool {
+    map.get(coords).is_some_and(|cell| match cell.terrain {
+        TerrainType::Stone => false,
+        TerrainType::Water => map.is_crossable(coords),
+        TerrainType::Dirt
+        | TerrainType::Building
+        | TerrainType::Grassland
+        | TerrainType::Tree
+        | TerrainType::Fire
+        | TerrainType::Smoldering => true,
+    })
+}
+
+/// Finds a path from `start` to `end` using a breadth first search, avoiding
+/// stone and water. The returned path doesn't include the start cell.
+fn find_path(map: &GameMap, start: IVec2, end: IVec2) -> Option<VecDeque<IVec2>> {
+    const DIRECTIONS: [IVec2; 4] = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y];
+
+    let mut came_from = HashMap::<IVec2, IVec2>::default();
+    let mut frontier = VecDeque::from([start]);
+    came_from.insert(start, start);
+
+    while let Some(current) = frontier.pop_front() {
+        if current == end {
+            let mut path = VecDeque::new();
+            let mut step = end;
+            while step != start {
+                path.push_front(step);
+                step = came_from[&step];
+            }
+            return Some(path);
+        }
+
+        if came_from.len() > MAX_PATH_SEARCH {
+            return None;
+        }
+
+        for direction in DIRECTIONS {
+            let next = current + direction;
+            if !came_from.contains_key(&next) && is_passable(map, next) {
+                came_from.insert(next, current);
+                frontier.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+fn move_goblin_arsonists(
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut map: ResMut<GameMap>,
+    buildings: Query<(&BuildingType, &Transform), Without<GoblinArsonist>>,
+    mut goblins: Query<(&mut GoblinArsonist, &mut Transform)>,
+) {
+    let delta = time.delta_secs() * game_speed.0;
+    let city_hall = buildings
+        .iter()
+        .find(|(building_type, _)| **building_type == BuildingType::CityHall)
+        .map(|(_, tx)| map.tile_coords(tx.translation.truncate()));
+
+    let mut rng = rand::thread_rng();
+
+    for (mut goblin, mut tx) in &mut goblins {
+        goblin.move_timer += delta;
+        if goblin.move_timer < GOBLIN_MOVE_TIME {
+            continue;
+        }
+        goblin.move_timer = 0.0;
+
+        if let Some(next) = goblin.path.pop_front() {
+            goblin.location = next;
+            tx.translation = map.world_coords(next).extend(0.2);
+            continue;
+        }
+
+        // the goblin has reached its target, so set it on fire
+        if goblin.location == goblin.target {
+            if let Some(cell) = map.get_mut(goblin.target) {
+                match cell.terrain {
+                    TerrainType::Grassland | TerrainType::Tree | TerrainType::Building => {
+                        info!("Goblin arsonist set fire to {}", goblin.target);
+                        cell.ignite();
+                        map.mark_on_fire(goblin.target);
+                    }
+                    TerrainType::Dirt
+                    | TerrainType::Stone
+                    | TerrainType::Fire
+                    | TerrainType::Smoldering
+                    | TerrainType::Water => {}
+                }
+            }
+        }
+
+        // pick a new target near the city hall
+        let Some(city_hall) = city_hall else {
+            continue;
+        };
+
+        let target = city_hall
+            + IVec2::new(
+                rng.gen_range(-GOBLIN_TARGET_RANGE..=GOBLIN_TARGET_RANGE),
+                rng.gen_range(-GOBLIN_TARGET_RANGE..=GOBLIN_TARGET_RANGE),
+            );
+        if !is_passable(&map, target) {
+            continue;
+        }
+
+        if let Some(path) = find_path(&map, goblin.location, target) {
+            goblin.target = target;
+            goblin.path = path;
+        }
+    }
+}
+
+/// Spawns goblins at a random edge of the map every so often
+fn spawn_endless_goblins(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    map: Res<GameMap>,
+    difficulty: Res<DifficultySettings>,
+    mut since_last_spawn: Local<f32>,
+) {
+    *since_last_spawn += time.delta_secs() * game_speed.0;
+    if *since_last_spawn < GOBLIN_SPAWN_INTERVAL * difficulty.0.event_interval_multiplier() {
+        return;
+    }
+    *since_last_spawn = 0.0;
+
+    let mut rng = rand::thread_rng();
+    let max_x = map.size_x as i32 - 1;
+    let max_y = map.size_y as i32 - 1;
+    let location = match rng.gen_range(0..4) {
+        0 => IVec2::new(0, rng.gen_range(0..=max_y)),
+        1 => IVec2::new(max_x, rng.gen_range(0..=max_y)),
+        2 => IVec2::new(rng.gen_range(0..=max_x), 0),
+        _ => IVec2::new(rng.gen_range(0..=max_x), max_y),
+    };
+
+    commands.trigger(SpawnGoblinArsonist(location));
+}
+
+/// Spawns a goblin shaman at the given tile location
+#[derive(Event, Reflect, Debug, Clone, Copy)]
+pub struct SpawnGoblinShaman(pub IVec2);
+
+/// A goblin that works from the shadows, periodically turning the wind so
+/// that it blows the fire towards the city hall. Shamans have no sprite, their
+/// only visible effect is on the wind
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct GoblinShaman {
+    /// The tile location the shaman is hiding at
+    pub position: IVec2,
+    /// The time until the shaman next changes the wind, in seconds
+    pub cooldown: f32,
+    /// The angle (in degrees) the shaman wants the wind to blow towards,
+    /// from the fire to the city hall
+    pub preferred_wind_angle: f32,
+}
+
+fn spawn_goblin_shaman(
+    trigger: Trigger<SpawnGoblinShaman>,
+    mut commands: Commands,
+    maybe_map: Option<Res<GameMap>>,
+) {
+    let Some(map) = maybe_map else {
+        warn!("Skipping goblin shaman as there is no map yet");
+        return;
+    };
+
+    let position = trigger.event().0;
+    if !map.is_valid_coords(position) {
+        warn!("Unable to spawn goblin shaman outside the map at {position}");
+        return;
+    }
+
+    info!("Spawning goblin shaman at {position}");
+    commands.spawn((
+        Name::new("Goblin Shaman"),
+        GoblinShaman {
+            position,
+            cooldown: SHAMAN_COOLDOWN,
+            preferred_wind_angle: 0.0,
+        },
+        StateScoped(Screen::Gameplay),
+    ));
+}
+
+/// Every so often each shaman turns the wind part of the way towards
+/// blowing from the middle of the fire to the city hall
+fn update_goblin_shamans(
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    map: Res<GameMap>,
+    mut wind: ResMut<WindDirection>,
+    buildings: Query<(&BuildingType, &BuildingLocation)>,
+    mut shamans: Query<&mut GoblinShaman>,
+) {
+    let delta = time.delta_secs() * game_speed.0;
+    let city_hall = buildings
+        .iter()
+        .find(|(building_type, _)| **building_type == BuildingType::CityHall)
+        .map(|(_, loc)| loc.0.as_vec2());
+
+    for mut shaman in &mut shamans {
+        shaman.cooldown -= delta;
+        if shaman.cooldown > 0.0 {
+            continue;
+        }
+        shaman.cooldown = SHAMAN_COOLDOWN;
+
+        let (Some(city_hall), Some(fire)) = (city_hall, map.fire_centroid()) else {
+            continue;
+        };
+        let towards_city_hall = city_hall - fire;
+        if towards_city_hall == Vec2::ZERO {
+            continue;
+        }
+        shaman.preferred_wind_angle = towards_city_hall.to_angle().to_degrees();
+
+        // turn the short way round towards the preferred angle
+        let current = wind.angle();
+        let difference = (shaman.preferred_wind_angle - current + 180.0).rem_euclid(360.0) - 180.0;
+        let angle = current + difference * SHAMAN_WIND_SHIFT;
+
+        info!(
+            "Goblin shaman at {} turns the wind towards {:.0} degrees",
+            shaman.position, angle
+        );
+        let strength = wind.strength();
+        wind.r#override(angle, strength);
+    }
+}
+
+/// Spawns a single goblin shaman at a random location part way through an
+/// endless game
+fn spawn_endless_shaman(
+    mut commands: Commands,
+    map: Res<GameMap>,
+    stats: Res<FireStats>,
+    shamans: Query<(), With<GoblinShaman>>,
+) {
+    if stats.duration < ENDLESS_SHAMAN_DELAY || !shamans.is_empty() {
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    let location = IVec2::new(
+        rng.gen_range(0..map.size_x as i32),
+        rng.gen_range(0..map.size_y as i32),
+    );
+
+    commands.trigger(SpawnGoblinShaman(location));
+}
@@ -0,0 +1,99 @@
+//! Animated flames for burning cells
+
+use bevy::{
+    image::{ImageLoaderSettings, ImageSampler},
+    prelude::*,
+};
+
+use crate::{
+    Pause,
+    asset_tracking::LoadResource,
+    screens::{GameSpeed, Screen},
+};
+
+/// The number of frames in the fire sprite sheet
+pub const FIRE_FRAME_COUNT: u8 = 8;
+/// The size (in pixels) of each frame in the fire sprite sheet
+const FIRE_FRAME_SIZE: f32 = 8.0;
+/// How long (in seconds) each frame of the fire animation is shown
+const FIRE_FRAME_DURATION: f32 = 0.1;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<TerrainAssets>();
+    app.register_type::<FireAnimation>();
+
+    app.load_resource::<TerrainAssets>();
+
+    app.add_systems(
+        Update,
+        animate_fire_sprites.run_if(in_state(Screen::Gameplay).and(in_state(Pause(false)))),
+    );
+}
+
+#[derive(Resource, Asset, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct TerrainAssets {
+    #[dependency]
+    pub fire_sheet: Handle<Image>,
+}
+
+impl FromWorld for TerrainAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+
+        Self {
+            fire_sheet: assets.load_with_settings(
+                "images/fire_sheet.png",
+                |settings: &mut ImageLoaderSettings| {
+                    // Use `nearest` image sampling to preserve pixel art style.
+                    settings.sampler = ImageSampler::nearest();
+                },
+            ),
+        }
+    }
+}
+
+/// Added to terrain cell sprites while they are on fire, to cycle through
+/// the frames of the fire sprite sheet
+#[derive(Component, Reflect, Debug, Clone, Copy, Default)]
+#[reflect(Component)]
+pub struct FireAnimation {
+    pub frame: u8,
+    pub timer: f32,
+}
+
+impl FireAnimation {
+    /// Starts the animation on the given frame, so that neighbouring cells
+    /// don't flicker in lockstep
+    pub fn starting_at(frame: u8) -> Self {
+        Self {
+            frame: frame % FIRE_FRAME_COUNT,
+            timer: 0.0,
+        }
+    }
+
+    /// The area of the fire sprite sheet to show for the current frame
+    pub fn rect(&self) -> Rect {
+        let left = self.frame as f32 * FIRE_FRAME_SIZE;
+        Rect::new(left, 0.0, left + FIRE_FRAME_SIZE, FIRE_FRAME_SIZE)
+    }
+}
+
+fn animate_fire_sprites(
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut fires: Query<(&mut FireAnimation, &mut Sprite)>,
+) {
+    let delta = time.delta_secs() * game_speed.0;
+
+    for (mut animation, mut sprite) in &mut fires {
+        animation.timer += delta;
+        if animation.timer < FIRE_FRAME_DURATION {
+            continue;
+        }
+
+        animation.timer -= FIRE_FRAME_DURATION;
+        animation.frame = (animation.frame + 1) % FIRE_FRAME_COUNT;
+        sprite.rect = Some(animation.rect());
+    }
+}
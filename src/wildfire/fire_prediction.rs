@@ -0,0 +1,122 @@
+//! Predicts where the fire is likely to spread over the next few seconds, to
+//! help the player decide where to put water golems or sprinklers. Toggled
+//! with <v>.
+
+use bevy::{
+    input::common_conditions::input_just_pressed, platform::collections::HashSet, prelude::*,
+};
+
+use crate::{
+    Pause,
+    screens::Screen,
+    wildfire::{
+        CurrentWeather, DifficultySettings, WindDirection, WindGust,
+        map::{GameMap, MAP_UPDATE_INTERVAL, update_sprites},
+    },
+};
+
+/// How far ahead the fire is predicted, in game seconds
+const PREDICTION_WINDOW: f32 = 10.0;
+/// How often the prediction is recalculated, in seconds. The prediction is
+/// expensive so it isn't run every frame
+const PREDICTION_INTERVAL: f32 = 2.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<FirePredictionCache>();
+
+    app.add_systems(
+        Update,
+        toggle_fire_prediction.before(update_sprites).run_if(
+            in_state(Screen::Gameplay)
+                .and(resource_exists::<GameMap>)
+                .and(input_just_pressed(KeyCode::KeyV)),
+        ),
+    );
+    app.add_systems(
+        Update,
+        update_fire_prediction.before(update_sprites).run_if(
+            in_state(Pause(false))
+                .and(resource_exists::<GameMap>)
+                .and(resource_exists::<ShowFirePrediction>),
+        ),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), remove_fire_prediction);
+}
+
+/// When this resource exists, cells that are likely to catch fire soon are
+/// tinted orange
+#[derive(Resource, Debug, Default)]
+pub struct ShowFirePrediction;
+
+/// The cells predicted to catch fire, from the last time the prediction ran
+#[derive(Resource, Debug, Default)]
+pub struct FirePredictionCache {
+    pub predicted_fire: HashSet<IVec2>,
+    /// The time the prediction last ran, in seconds since startup
+    pub last_update: f32,
+}
+
+fn toggle_fire_prediction(
+    mut commands: Commands,
+    mut map: ResMut<GameMap>,
+    mut cache: ResMut<FirePredictionCache>,
+    maybe_prediction: Option<Res<ShowFirePrediction>>,
+) {
+    if maybe_prediction.is_some() {
+        info!("Hiding fire prediction");
+        commands.remove_resource::<ShowFirePrediction>();
+    } else {
+        info!("Showing fire prediction");
+        commands.init_resource::<ShowFirePrediction>();
+    }
+
+    // redraw the old prediction, and predict again straight away next time
+    clear_prediction(&mut map, &mut cache);
+}
+
+fn remove_fire_prediction(mut commands: Commands, mut cache: ResMut<FirePredictionCache>) {
+    commands.remove_resource::<ShowFirePrediction>();
+    *cache = FirePredictionCache::default();
+}
+
+/// Forgets the current prediction, redrawing the cells that were in it
+fn clear_prediction(map: &mut GameMap, cache: &mut FirePredictionCache) {
+    for loc in cache.predicted_fire.drain() {
+        if let Some(cell) = map.get_mut(loc) {
+            cell.mark_dirty();
+        }
+    }
+
+    cache.last_update = 0.0;
+}
+
+fn update_fire_prediction(
+    time: Res<Time>,
+    mut map: ResMut<GameMap>,
+    mut cache: ResMut<FirePredictionCache>,
+    wind: Res<WindDirection>,
+    maybe_gust: Option<Res<WindGust>>,
+    weather: Res<CurrentWeather>,
+    difficulty: Res<DifficultySettings>,
+) {
+    let now = time.elapsed_secs();
+    if cache.last_update > 0.0 && now - cache.last_update < PREDICTION_INTERVAL {
+        return;
+    }
+
+    clear_prediction(&mut map, &mut cache);
+    cache.last_update = now;
+    cache.predicted_fire = map.predict_fire(
+        &wind,
+        maybe_gust.as_deref(),
+        weather.0,
+        difficulty.0,
+        (PREDICTION_WINDOW / MAP_UPDATE_INTERVAL) as usize,
+    );
+
+    for loc in cache.predicted_fire.iter() {
+        if let Some(cell) = map.get_mut(*loc) {
+            cell.mark_dirty();
+        }
+    }
+}
@@ -1,26 +1,37 @@
 //! Tools to generate "realistic" maps using simplex/perlin noise maps
 
-use std::time::Duration;
-
 use bevy::{
-    input::common_conditions::input_just_pressed, prelude::*, time::common_conditions::on_timer,
+    color::palettes::{
+        css::BLUE,
+        tailwind::{BLUE_600, ORANGE_500, RED_600},
+    },
+    input::common_conditions::input_just_pressed,
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
 };
 use fastnoise_lite::FastNoiseLite;
-use rand::Rng;
+use rand::{Rng, SeedableRng, rngs::StdRng};
 
 use crate::{
     Pause,
     screens::{
-        BuildingMode, BuildingType, EndlessMode, OnRedrawToolbar, PlayerResources,
-        RequiresCityHall, Screen,
+        BuildingMode, BuildingType, EndlessMode, GameSpeed, OnRedrawToolbar, PlayerResources,
+        RequiresCityHall, ResearchState, Screen,
+    },
+    wildfire::{
+        Biome, BiomeConfig, ColourBlindMode, CurrentWeather, DifficultyLevel, DifficultySettings,
+        FireStats, FogOfWar, MoistureMap, OnSpawnMap, SMOLDERING_RECOVERY_UPDATES, SelectedMapSize,
+        SpawnedMap, TerrainCell, TerrainCellState, TerrainType, WeatherEvent, WindDirection,
+        WindGust,
+        fire_animation::{FIRE_FRAME_COUNT, FireAnimation, TerrainAssets},
+        fire_prediction::{FirePredictionCache, ShowFirePrediction},
     },
-    wildfire::{OnSpawnMap, SpawnedMap, TerrainCell, TerrainCellState, TerrainType, WindDirection},
 };
 
 /// the amount of cells in the neighbourhood
 const NEIGHBOURHOOD_SIZE: usize = 8;
 
-const NEIGHBOUR_COORDINATES: [IVec2; NEIGHBOURHOOD_SIZE] = [
+pub(super) const NEIGHBOUR_COORDINATES: [IVec2; NEIGHBOURHOOD_SIZE] = [
     // Left
     IVec2::new(-1, 0),
     // Top Left
@@ -58,19 +69,122 @@ const NEIGHBOUR_VECTOR: [f32; NEIGHBOURHOOD_SIZE] = [
     -std::f32::consts::FRAC_PI_2 * 1.5,
 ];
 
+/// The moisture map value at which the moisture overlay is fully blue
+const MOISTURE_OVERLAY_SATURATION: f32 = 0.2;
+
+/// The time between map updates at normal game speed, in seconds
+pub(super) const MAP_UPDATE_INTERVAL: f32 = 0.1;
+/// The most map updates run in a single frame
+const MAX_MAP_UPDATES_PER_FRAME: u32 = 5;
+
 const NOISE_REDIST_FACTOR: f32 = 1.46;
 const NOISE_SCALE: f32 = 0.5;
+const RIVER_NOISE_SCALE: f32 = 0.4;
+/// Elevation uses a lower frequency than the terrain so it forms broad hills
+const ELEVATION_NOISE_SCALE: f32 = 0.5;
+/// How close to the middle of the river noise a cell must be to become water.
+/// Smaller values give narrower rivers
+const RIVER_WIDTH: f32 = 0.035;
+/// The fewest rivers that flow downhill across the map
+const MIN_FLOW_RIVERS: usize = 3;
+/// The most rivers that flow downhill across the map
+const MAX_FLOW_RIVERS: usize = 5;
+/// The number of random cells checked when looking for high ground to start
+/// each river from
+const RIVER_SOURCE_SAMPLES: usize = 50;
+/// Rivers get one cell wider every time they flow this many cells
+const RIVER_WIDEN_INTERVAL: usize = 20;
+/// Rivers stop before their banks come within this many cells of a location
+/// that has to stay dry, such as where a story level builds its city hall
+const RIVER_CLEARANCE: i32 = 3;
+
+/// The number of bytes at the start of a compact map, holding the width,
+/// height and biome
+const COMPACT_HEADER_SIZE: usize = 5;
+/// Moisture is stored as a 4 bit bucket in compact maps, from 0 to this value
+const COMPACT_MOISTURE_BUCKETS: f32 = 15.0;
+
+/// The minimum wind strength before embers can be carried to start spot fires
+const EMBER_SPOTTING_MIN_WIND: f32 = 50.0;
+/// The chance each tick that a burning cell throws an ember, if the wind is strong enough
+const EMBER_SPOTTING_CHANCE: f64 = 0.002;
+/// The furthest distance (in cells) that an ember can be carried
+const EMBER_MAX_DISTANCE: f32 = 20.0;
+
+/// How far (in cells) upwind of a cell a stone cell shelters it from the wind
+const WIND_SHADOW_DISTANCE: f32 = 3.0;
+
+/// The fire spread multiplier when burning downhill
+const MIN_ELEVATION_SPREAD_FACTOR: f32 = 0.7;
+/// The fire spread multiplier when burning uphill
+const MAX_ELEVATION_SPREAD_FACTOR: f32 = 1.5;
+
+/// The fire spread multiplier for diagonal neighbours, which are further away
+/// than the cardinal neighbours
+const DIAGONAL_SPREAD_FACTOR: f32 = 0.7;
+
+/// The width (in cells) of the water that surrounds the map
+const MAP_BORDER_WIDTH: usize = 2;
+
+/// How often (in map updates) recovering ground is redrawn as it regrows
+const RECOVERY_REDRAW_INTERVAL: u32 = 100;
+
+/// How much the intensity of a burning cell falls each update
+const FIRE_INTENSITY_DECAY: f32 = 0.01;
+/// Fires burning more intensely than this spread to their neighbours faster
+const INTENSE_FIRE_THRESHOLD: f32 = 0.7;
+/// How much more likely an intense fire is to spread to its neighbours
+const INTENSE_FIRE_SPREAD_FACTOR: f32 = 2.0;
+/// The intensity a fire needs to scorch neighbouring stone down to bare earth
+const SCORCHING_FIRE_INTENSITY: f32 = 0.8;
+/// The fuel load a fire needs to scorch neighbouring stone down to bare earth
+const SCORCHING_FIRE_FUEL_LOAD: u8 = 8;
+/// The chance each update that a scorching fire breaks down a neighbouring
+/// stone cell
+const STONE_SCORCH_CHANCE: f64 = 0.05;
+
+/// The chance that a tree lit by a neighbouring burning tree catches fire in
+/// its canopy
+const CROWN_FIRE_CHANCE: f64 = 0.5;
+/// How much more likely a crown fire is to spread to neighbouring trees
+const CROWN_FIRE_SPREAD_FACTOR: f32 = 3.0;
+
+/// Cells less likely than this to be burning are ignored when predicting the
+/// fire, to keep the prediction cheap
+const PREDICTION_MIN_CHANCE: f64 = 0.01;
+/// How likely a cell must be to catch fire to be shown in the prediction
+const PREDICTION_THRESHOLD: f64 = 0.5;
+
+/// The conditions that fire spreads under for a single map update
+struct SpreadParams {
+    global_wind: Vec2,
+    spread_chance: f64,
+    moisture_decay_rate: f32,
+    burn_rate_modifier: f32,
+    intensity_decay: f32,
+}
+
+/// A change to the map proposed while spreading fire in parallel
+#[cfg(feature = "parallel")]
+enum SpreadDelta {
+    /// Dries out a cell next to a fire
+    Dry(IVec2),
+    /// Sets a cell on fire, and whether it burns in the tree canopy
+    Ignite(IVec2, bool),
+    /// Throws an ember from a burning cell on the wind
+    Ember(IVec2, Vec2),
+    /// Cools a burning cell
+    BurnDown(IVec2),
+    /// Breaks down a stone cell next to a scorching fire
+    Scorch(IVec2),
+}
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<GameMap>();
 
     app.add_systems(
         Update,
-        update_map.run_if(
-            on_timer(Duration::from_millis(100))
-                .and(in_state(Pause(false)))
-                .and(resource_exists::<GameMap>),
-        ),
+        update_map.run_if(in_state(Pause(false)).and(resource_exists::<GameMap>)),
     );
 
     app.add_systems(
@@ -78,6 +192,36 @@ pub(super) fn plugin(app: &mut App) {
         update_sprites.run_if(in_state(Pause(false)).and(resource_exists::<GameMap>)),
     );
 
+    app.add_systems(
+        Update,
+        toggle_fire_risk_overlay.before(update_sprites).run_if(
+            in_state(Screen::Gameplay)
+                .and(resource_exists::<GameMap>)
+                .and(input_just_pressed(KeyCode::Tab)),
+        ),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), remove_fire_risk_overlay);
+
+    app.add_systems(
+        Update,
+        toggle_heatmap.before(update_sprites).run_if(
+            in_state(Screen::Gameplay)
+                .and(resource_exists::<GameMap>)
+                .and(input_just_pressed(KeyCode::KeyO)),
+        ),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), remove_heatmap);
+
+    app.add_systems(
+        Update,
+        toggle_moisture_overlay.before(update_sprites).run_if(
+            in_state(Screen::Gameplay)
+                .and(resource_exists::<GameMap>)
+                .and(input_just_pressed(KeyCode::KeyN)),
+        ),
+    );
+    app.add_systems(OnExit(Screen::Gameplay), remove_moisture_overlay);
+
     app.add_systems(
         Update,
         redraw_map.run_if(
@@ -89,11 +233,222 @@ pub(super) fn plugin(app: &mut App) {
     );
 }
 
-fn update_map(mut map: ResMut<GameMap>, wind: Res<WindDirection>) {
-    map.update(&wind);
+/// Updates the map at a fixed interval, scaled by the game speed. At higher
+/// game speeds this may update the map more than once in a frame, up to
+/// [MAX_MAP_UPDATES_PER_FRAME].
+pub(super) fn update_map(
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut since_last_update: Local<f32>,
+    mut map: ResMut<GameMap>,
+    mut moisture: ResMut<MoistureMap>,
+    mut stats: ResMut<FireStats>,
+    wind: Res<WindDirection>,
+    maybe_gust: Option<Res<WindGust>>,
+    weather: Res<CurrentWeather>,
+    difficulty: Res<DifficultySettings>,
+) {
+    *since_last_update += time.delta_secs() * game_speed.0;
+
+    let mut updates = 0;
+    while *since_last_update >= MAP_UPDATE_INTERVAL {
+        if updates == MAX_MAP_UPDATES_PER_FRAME {
+            // after a long frame, drop the time that is left rather than
+            // catching up, which would make the next frame long as well
+            *since_last_update = 0.0;
+            break;
+        }
+
+        updates += 1;
+        *since_last_update -= MAP_UPDATE_INTERVAL;
+        map.update(
+            &wind,
+            maybe_gust.as_deref(),
+            weather.0,
+            difficulty.0,
+            &mut moisture,
+            &mut stats,
+        );
+    }
+}
+
+/// When this resource exists the map shows how likely each cell is to catch
+/// fire instead of the terrain. This is a debug / planning aid to help find
+/// where firebreaks are needed, toggled with <tab>.
+#[derive(Resource, Debug, Default)]
+pub struct FireRiskOverlayActive;
+
+fn toggle_fire_risk_overlay(
+    mut commands: Commands,
+    mut map: ResMut<GameMap>,
+    maybe_overlay: Option<Res<FireRiskOverlayActive>>,
+) {
+    if maybe_overlay.is_some() {
+        info!("Hiding fire risk overlay");
+        commands.remove_resource::<FireRiskOverlayActive>();
+    } else {
+        info!("Showing fire risk overlay");
+        commands.init_resource::<FireRiskOverlayActive>();
+    }
+
+    // force all the sprites to redraw with the new colours
+    for row in map.data.iter_mut() {
+        for cell in row.iter_mut() {
+            cell.dirty = true;
+        }
+    }
+}
+
+fn remove_fire_risk_overlay(mut commands: Commands) {
+    commands.remove_resource::<FireRiskOverlayActive>();
+}
+
+/// The colour of a cell in the fire risk overlay, from blue (low risk) to red
+/// (high risk)
+fn fire_risk_colour(cell: &TerrainCellState) -> Color {
+    if cell.fog {
+        return cell.visible_colour();
+    }
+
+    let risk = (cell.effective_burn_rate() * (1.0 + cell.wind.length() / 100.0)).clamp(0.0, 1.0);
+
+    BLUE_600.mix(&RED_600, risk).into()
+}
+
+/// When this resource exists the map is tinted by how many times each cell
+/// has burned, showing where fire has historically been most active. Toggled
+/// with <o>.
+#[derive(Resource, Debug, Default)]
+pub struct ShowHeatmap;
+
+fn toggle_heatmap(
+    mut commands: Commands,
+    mut map: ResMut<GameMap>,
+    maybe_heatmap: Option<Res<ShowHeatmap>>,
+) {
+    if maybe_heatmap.is_some() {
+        info!("Hiding heat map");
+        commands.remove_resource::<ShowHeatmap>();
+    } else {
+        info!("Showing heat map");
+        commands.init_resource::<ShowHeatmap>();
+    }
+
+    // force all the sprites to redraw with the new colours
+    for row in map.data.iter_mut() {
+        for cell in row.iter_mut() {
+            cell.dirty = true;
+        }
+    }
+}
+
+fn remove_heatmap(mut commands: Commands) {
+    commands.remove_resource::<ShowHeatmap>();
+}
+
+/// The heat map tint for the given number of burns, along with how strongly
+/// it is applied over the terrain
+const HEATMAP_BREAKPOINTS: [(u8, Color, f32); 3] = [
+    (1, Color::srgba(1.0, 0.95, 0.55, 1.0), 0.5),
+    (5, Color::srgba(0.98, 0.45, 0.09, 1.0), 0.75),
+    (10, Color::srgba(0.35, 0.02, 0.02, 1.0), 1.0),
+];
+
+/// The colour of a cell in the heat map, from untinted (never burned) through
+/// yellow and orange to red-black for cells that have burned 10 or more times
+fn heatmap_colour(cell: &TerrainCellState) -> Color {
+    let base = cell.visible_colour();
+    if cell.fog || cell.burn_count == 0 {
+        return base;
+    }
+
+    // interpolate between the two breakpoints either side of the burn count
+    for pair in HEATMAP_BREAKPOINTS.windows(2) {
+        let (from_count, from_colour, from_strength) = pair[0];
+        let (to_count, to_colour, to_strength) = pair[1];
+
+        if cell.burn_count < to_count {
+            let t = (cell.burn_count - from_count) as f32 / (to_count - from_count) as f32;
+            let strength = from_strength + (to_strength - from_strength) * t;
+            return base.mix(&from_colour.mix(&to_colour, t), strength);
+        }
+    }
+
+    let (_, colour, strength) = HEATMAP_BREAKPOINTS[HEATMAP_BREAKPOINTS.len() - 1];
+    base.mix(&colour, strength)
+}
+
+/// When this resource exists the ground is tinted blue by the water that water
+/// golems and sprinklers have added to the [MoistureMap], showing where it is
+/// building up and spreading out. Toggled with <n>.
+#[derive(Resource, Debug, Default)]
+pub struct ShowMoistureOverlay;
+
+fn toggle_moisture_overlay(
+    mut commands: Commands,
+    mut map: ResMut<GameMap>,
+    maybe_overlay: Option<Res<ShowMoistureOverlay>>,
+) {
+    if maybe_overlay.is_some() {
+        info!("Hiding moisture overlay");
+        commands.remove_resource::<ShowMoistureOverlay>();
+    } else {
+        info!("Showing moisture overlay");
+        commands.init_resource::<ShowMoistureOverlay>();
+    }
+
+    // force all the sprites to redraw with the new colours
+    for row in map.data.iter_mut() {
+        for cell in row.iter_mut() {
+            cell.dirty = true;
+        }
+    }
+}
+
+fn remove_moisture_overlay(mut commands: Commands) {
+    commands.remove_resource::<ShowMoistureOverlay>();
 }
 
-fn update_sprites(mut map: ResMut<GameMap>, mut sprites: Query<&mut Sprite, With<TerrainCell>>) {
+/// The colour of a cell in the moisture overlay, given the moisture waiting to
+/// soak into it
+fn moisture_colour(cell: &TerrainCellState, moisture: f32) -> Color {
+    let base = cell.visible_colour();
+    if cell.fog {
+        return base;
+    }
+
+    let wetness = (moisture / MOISTURE_OVERLAY_SATURATION).min(1.0);
+    match cell.terrain {
+        TerrainType::Grassland | TerrainType::Tree | TerrainType::Dirt => {
+            base.mix(&BLUE.into(), wetness * 0.7)
+        }
+        TerrainType::Building
+        | TerrainType::Fire
+        | TerrainType::Smoldering
+        | TerrainType::Stone
+        | TerrainType::Water => base,
+    }
+}
+
+pub(super) fn update_sprites(
+    mut commands: Commands,
+    mut map: ResMut<GameMap>,
+    maybe_overlay: Option<Res<FireRiskOverlayActive>>,
+    maybe_heatmap: Option<Res<ShowHeatmap>>,
+    maybe_moisture: Option<Res<ShowMoistureOverlay>>,
+    maybe_moisture_map: Option<Res<MoistureMap>>,
+    maybe_prediction: Option<Res<ShowFirePrediction>>,
+    prediction: Res<FirePredictionCache>,
+    maybe_terrain_assets: Option<Res<TerrainAssets>>,
+    colour_mode: Res<ColourBlindMode>,
+    mut sprites: Query<(&mut Sprite, Has<FireAnimation>, Has<FogOfWar>), With<TerrainCell>>,
+) {
+    let show_fire_risk = maybe_overlay.is_some();
+    let show_heatmap = maybe_heatmap.is_some();
+    let show_moisture = maybe_moisture.is_some();
+    let show_prediction = maybe_prediction.is_some();
+    let mut rng = rand::thread_rng();
+
     for y in 0..map.size_y {
         for x in 0..map.size_x {
             let cell = &mut map.data[y][x];
@@ -107,9 +462,63 @@ fn update_sprites(mut map: ResMut<GameMap>, mut sprites: Query<&mut Sprite, With
                 continue;
             };
 
-            if let Ok(mut sprite) = sprites.get_mut(entity) {
-                sprite.color = cell.colour();
+            let Ok((mut sprite, animating, fogged)) = sprites.get_mut(entity) else {
+                continue;
+            };
+
+            // the cell has been revealed since its sprite was last drawn
+            if fogged && !cell.fog {
+                commands.entity(entity).remove::<FogOfWar>();
+            }
+
+            // burning cells are animated, unless they are hidden or one of the
+            // overlays is showing. Crown fires are drawn brighter instead
+            let animate = matches!(cell.terrain, TerrainType::Fire)
+                && !cell.canopy_fire
+                && !cell.fog
+                && !show_fire_risk
+                && !show_heatmap;
+
+            if let (true, Some(terrain_assets)) = (animate, &maybe_terrain_assets) {
+                if !animating {
+                    let animation = FireAnimation::starting_at(rng.gen_range(0..FIRE_FRAME_COUNT));
+                    sprite.image = terrain_assets.fire_sheet.clone();
+                    sprite.rect = Some(animation.rect());
+                    sprite.color = Color::WHITE;
+                    commands.entity(entity).insert(animation);
+                }
+
+                continue;
+            }
+
+            if animating {
+                commands.entity(entity).remove::<FireAnimation>();
+                sprite.image = Handle::default();
+                sprite.rect = None;
             }
+
+            let colour = if show_fire_risk {
+                fire_risk_colour(cell)
+            } else if show_heatmap {
+                heatmap_colour(cell)
+            } else if show_moisture {
+                let moisture = maybe_moisture_map
+                    .as_ref()
+                    .and_then(|moisture_map| moisture_map.get(IVec2::new(x as i32, y as i32)))
+                    .copied()
+                    .unwrap_or_default();
+                moisture_colour(cell, moisture)
+            } else if show_prediction
+                && !cell.fog
+                && prediction
+                    .predicted_fire
+                    .contains(&IVec2::new(x as i32, y as i32))
+            {
+                cell.visible_colour().mix(&ORANGE_500.into(), 0.5)
+            } else {
+                cell.visible_colour()
+            };
+            sprite.color = colour_mode.correct(colour);
         }
     }
 }
@@ -118,11 +527,13 @@ fn update_sprites(mut map: ResMut<GameMap>, mut sprites: Query<&mut Sprite, With
 fn redraw_map(
     mut commands: Commands,
     mut mode: ResMut<BuildingMode>,
+    selected_size: Res<SelectedMapSize>,
     spawned_maps: Query<Entity, With<SpawnedMap>>,
     buildings: Query<Entity, With<BuildingType>>,
 ) {
     commands.init_resource::<RequiresCityHall>();
     commands.insert_resource(PlayerResources::default());
+    commands.insert_resource(ResearchState::default());
 
     for map in spawned_maps {
         commands.entity(map).despawn();
@@ -133,13 +544,15 @@ fn redraw_map(
     }
 
     let mut rng = rand::thread_rng();
-    commands.trigger(OnSpawnMap::new(rng.r#gen()));
+    commands
+        .trigger(OnSpawnMap::new(rng.r#gen(), selected_size.0).with_biome(Biome::random(&mut rng)));
     *mode = BuildingMode::PlaceCityHall;
     commands.trigger(OnRedrawToolbar);
 }
 
 pub struct NoiseMap {
     noise: FastNoiseLite,
+    seed: i32,
 }
 
 /// Seeds for mapgen that are "known good"
@@ -158,6 +571,7 @@ impl NoiseMap {
     pub fn new(seed: i32) -> Self {
         Self {
             noise: FastNoiseLite::with_seed(seed),
+            seed,
         }
     }
 
@@ -175,8 +589,59 @@ impl NoiseMap {
         self.noise(x + MOISTURE_OFFSET, y + MOISTURE_OFFSET)
     }
 
+    /// Returns an elevation from 0-1. Uses a separate pass with a constant
+    /// offset so hills don't line up with the terrain or moisture
+    #[inline]
+    pub fn elevation(&self, x: f32, y: f32) -> f32 {
+        const ELEVATION_OFFSET: f32 = 293.7;
+        self.noise(
+            ELEVATION_NOISE_SCALE * x + ELEVATION_OFFSET,
+            ELEVATION_NOISE_SCALE * y + ELEVATION_OFFSET,
+        )
+    }
+
+    /// Returns a river value from 0-1, where values close to 0 are in the
+    /// middle of a river. Uses a separate fractal pass with a constant offset
+    /// so that rivers wind independently of the rest of the terrain
+    #[inline]
+    pub fn river(&self, x: f32, y: f32) -> f32 {
+        const RIVER_OFFSET: f32 = 731.3;
+        let x = RIVER_NOISE_SCALE * x + RIVER_OFFSET;
+        let y = RIVER_NOISE_SCALE * y + RIVER_OFFSET;
+
+        let noise = 1.0 * self.noise(x, y) + 0.5 * self.noise(2.0 * x, 2.0 * y);
+        let noise = noise / (1.0 + 0.5);
+
+        // fold the noise around its midpoint so we get thin ridges of low
+        // values that wind across the map rather than big blobs
+        (2.0 * noise - 1.0).abs()
+    }
+
+    /// Samples the noise map and returns the starting state of the cell at
+    /// the given tile coordinates
+    pub fn sample_cell(&self, x: usize, y: usize, config: &BiomeConfig) -> TerrainCellState {
+        let (terrain, fuel) = self.sample(x, y, config);
+        let mut cell = TerrainCellState {
+            terrain,
+            fuel_load: fuel,
+            elevation: self.elevation(x as f32, y as f32),
+            ..default()
+        };
+
+        match terrain {
+            TerrainType::Grassland | TerrainType::Tree => {
+                // the noise is centred on 0.5, so shift it to the biome's average
+                cell.moisture = (self.moisture(x as f32, y as f32) - 0.5 + config.base_moisture)
+                    .clamp(0.0, 1.0);
+            }
+            _ => {}
+        }
+
+        cell
+    }
+
     /// Samples the noise map and returns a terrain type and fuel load
-    pub fn sample(&self, x: usize, y: usize) -> (TerrainType, u8) {
+    pub fn sample(&self, x: usize, y: usize, biome: &BiomeConfig) -> (TerrainType, u8) {
         let x = x as f32;
         let y = y as f32;
 
@@ -186,26 +651,162 @@ impl NoiseMap {
             + 0.25 * self.noise(NOISE_SCALE * 4.0 * x, NOISE_SCALE * 4.0 * y);
         let noise = (noise / (1.0 + 0.5 + 0.25)).powf(NOISE_REDIST_FACTOR);
 
-        const DIRT: f32 = 0.01;
-        const GRASS: f32 = 0.5;
-        const TREE: f32 = 0.75;
+        let dirt = biome.dirt_threshold;
+        let grass = biome.grass_threshold;
+        let tree = biome.tree_threshold;
+
+        // rivers cut through everything except the high stone areas
+        if noise < tree && self.river(x, y) < RIVER_WIDTH {
+            return (TerrainType::Water, 0);
+        }
 
         // note trees are placed in a separate pass
-        if noise < DIRT {
+        if noise < dirt {
             (TerrainType::Dirt, 0)
-        } else if noise < GRASS {
-            let fuel_load = (12.0 * (noise - DIRT) / (GRASS - DIRT)).clamp(1.0, 12.0) as u8;
+        } else if noise < grass {
+            let fuel_load = (12.0 * (noise - dirt) / (grass - dirt)).clamp(1.0, 12.0) as u8;
             (TerrainType::Grassland, fuel_load)
-        } else if noise < TREE {
-            let fuel_load = (24.0 * (noise - GRASS) / (TREE - GRASS)).clamp(1.0, 24.0) as u8;
+        } else if noise < tree {
+            let fuel_load = (24.0 * (noise - grass) / (tree - grass)).clamp(1.0, 24.0) as u8;
             (TerrainType::Tree, fuel_load)
         } else {
-            let rock_and_stone = 10.0 * (noise - TREE) / (1.0 - TREE);
+            let rock_and_stone = 10.0 * (noise - tree) / (1.0 - tree);
             (TerrainType::Stone, rock_and_stone.clamp(1.0, 10.0) as u8)
         }
     }
+
+    /// Carves rivers that start on high ground and flow downhill, always
+    /// moving to the lowest neighbouring cell they haven't already flowed
+    /// through. Rivers widen as they flow, and stop when they join another
+    /// river or come close to one of the `keep_clear` locations. The rivers
+    /// are seeded from the map seed so that the same map is always generated
+    pub fn generate_rivers(
+        &self,
+        data: &mut [Vec<TerrainCellState>],
+        size_x: usize,
+        size_y: usize,
+        keep_clear: &[IVec2],
+    ) {
+        if size_x == 0 || size_y == 0 {
+            return;
+        }
+
+        let mut rng = StdRng::seed_from_u64(self.seed as u64);
+        // which river (if any) has flowed through each cell
+        let mut owners = vec![vec![None; size_x]; size_y];
+        let max_length = 2 * (size_x + size_y);
+
+        for river in 0..rng.gen_range(MIN_FLOW_RIVERS..=MAX_FLOW_RIVERS) {
+            let Some(mut current) = (0..RIVER_SOURCE_SAMPLES)
+                .map(|_| {
+                    IVec2::new(
+                        rng.gen_range(0..size_x) as i32,
+                        rng.gen_range(0..size_y) as i32,
+                    )
+                })
+                .max_by(|a, b| {
+                    let a = data[a.y as usize][a.x as usize].elevation;
+                    let b = data[b.y as usize][b.x as usize].elevation;
+                    a.total_cmp(&b)
+                })
+            else {
+                continue;
+            };
+
+            let mut course = HashSet::default();
+            for length in 0..max_length {
+                let (x, y) = (current.x as usize, current.y as usize);
+                // stop once this river joins another one
+                if matches!(owners[y][x], Some(other) if other != river) {
+                    break;
+                }
+
+                let width = (length / RIVER_WIDEN_INTERVAL) as i32;
+                // stop before the banks reach somewhere that has to stay dry
+                if keep_clear.iter().any(|loc| {
+                    let distance = (*loc - current).abs();
+                    distance.max_element() <= width + RIVER_CLEARANCE
+                }) {
+                    break;
+                }
+
+                for dy in -width..=width {
+                    for dx in -width..=width {
+                        let (wx, wy) = (current.x + dx, current.y + dy);
+                        if wx < 0 || wy < 0 || wx as usize >= size_x || wy as usize >= size_y {
+                            continue;
+                        }
+
+                        let cell = &mut data[wy as usize][wx as usize];
+                        cell.terrain = TerrainType::Water;
+                        cell.fuel_load = 0;
+                        owners[wy as usize][wx as usize].get_or_insert(river);
+                    }
+                }
+                owners[y][x] = Some(river);
+                course.insert(current);
+
+                let next = NEIGHBOUR_COORDINATES
+                    .iter()
+                    .map(|offset| current + *offset)
+                    .filter(|n| {
+                        n.x >= 0 && n.y >= 0 && (n.x as usize) < size_x && (n.y as usize) < size_y
+                    })
+                    // a river can flow through its own banks as it widens, but
+                    // never back up its own course
+                    .filter(|n| !course.contains(n))
+                    .min_by(|a, b| {
+                        let a = data[a.y as usize][a.x as usize].elevation;
+                        let b = data[b.y as usize][b.x as usize].elevation;
+                        a.total_cmp(&b)
+                    });
+
+                let Some(next) = next else {
+                    break;
+                };
+                current = next;
+            }
+        }
+    }
+}
+
+/// Gets the tiles on a line between `start` and `end` (inclusive) using
+/// Bresenham's line algorithm
+pub fn line_between(start: IVec2, end: IVec2) -> Vec<IVec2> {
+    let dx = (end.x - start.x).abs();
+    let dy = -(end.y - start.y).abs();
+    let step_x = if start.x < end.x { 1 } else { -1 };
+    let step_y = if start.y < end.y { 1 } else { -1 };
+
+    let mut tiles = Vec::new();
+    let mut current = start;
+    let mut error = dx + dy;
+
+    loop {
+        tiles.push(current);
+        if current == end {
+            break;
+        }
+
+        let e2 = 2 * error;
+        if e2 >= dy {
+            error += dy;
+            current.x += step_x;
+        }
+        if e2 <= dx {
+            error += dx;
+            current.y += step_y;
+        }
+    }
+
+    tiles
 }
 
+/// Terrain loaded from a saved game. When this resource exists, the next
+/// [OnSpawnMap] uses this data instead of generating the terrain from noise.
+#[derive(Resource, Debug, Default)]
+pub struct LoadedMapData(pub Vec<Vec<TerrainCellState>>);
+
 /// Contains information about the map that the game is being played on.
 /// This is stored in a 2d Vec in the `data` field
 #[derive(Resource, Reflect, Debug)]
@@ -215,24 +816,63 @@ pub struct GameMap {
     pub size_y: usize,
     pub sprite_size: f32,
     pub data: Vec<Vec<TerrainCellState>>,
+    /// The biome the map was generated with
+    pub biome: Biome,
+    /// The seed the map was generated from. This also names the map regions,
+    /// so it is kept with saved games
+    pub seed: i32,
+    /// The coordinates of every cell that is on fire, so that the fire spread
+    /// only has to look at the fire front rather than the whole map
+    #[reflect(ignore)]
+    fire_cells: HashSet<IVec2>,
+    /// The coordinates of every burnt cell that is still regrowing, so that
+    /// recovery doesn't have to scan the whole map
+    #[reflect(ignore)]
+    smoldering_cells: HashSet<IVec2>,
+    /// Embers thrown by the fire since they were last collected, as (origin,
+    /// wind). These are shown being carried across the map before they land
+    #[reflect(ignore)]
+    thrown_embers: Vec<(IVec2, Vec2)>,
+    /// The water cells covered by river ferries. Water next to these can be
+    /// built on and walked across. Not saved, as ferries add them again when
+    /// they are restored
+    #[reflect(ignore)]
+    water_crossings: HashSet<IVec2>,
 }
 
 impl GameMap {
-    pub fn new(seed: i32, sprite_size: f32, size_x: usize, size_y: usize) -> Self {
+    /// Generates a new map from the seed. Rivers are kept away from the
+    /// `keep_clear` locations
+    pub fn new(
+        seed: i32,
+        sprite_size: f32,
+        size_x: usize,
+        size_y: usize,
+        biome: Biome,
+        keep_clear: &[IVec2],
+    ) -> Self {
         let noise_map = NoiseMap::new(seed);
+        let config = biome.config();
         let mut data = vec![vec![TerrainCellState::default(); size_x]; size_y];
 
         for (y, row) in data.iter_mut().enumerate().take(size_y) {
             for (x, cell) in row.iter_mut().enumerate().take(size_y) {
-                let (terrain, fuel) = noise_map.sample(x, y);
-                cell.terrain = terrain;
-                cell.fuel_load = fuel;
-
-                match terrain {
-                    TerrainType::Grassland | TerrainType::Tree => {
-                        cell.moisture = noise_map.moisture(x as f32, y as f32);
-                    }
-                    _ => {}
+                *cell = noise_map.sample_cell(x, y, &config);
+            }
+        }
+        noise_map.generate_rivers(&mut data, size_x, size_y, keep_clear);
+
+        // surround the map with water so the fire burns out at the edge
+        // rather than running off it
+        for (y, row) in data.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                if x < MAP_BORDER_WIDTH
+                    || y < MAP_BORDER_WIDTH
+                    || x + MAP_BORDER_WIDTH >= size_x
+                    || y + MAP_BORDER_WIDTH >= size_y
+                {
+                    cell.terrain = TerrainType::Water;
+                    cell.fuel_load = 0;
                 }
             }
         }
@@ -242,9 +882,135 @@ impl GameMap {
             size_x,
             size_y,
             sprite_size,
+            biome,
+            seed,
+            fire_cells: HashSet::default(),
+            smoldering_cells: HashSet::default(),
+            thrown_embers: Vec::new(),
+            water_crossings: HashSet::default(),
         }
     }
 
+    /// Creates a map from existing terrain data, e.g. from a saved game
+    pub fn from_data(
+        seed: i32,
+        sprite_size: f32,
+        data: Vec<Vec<TerrainCellState>>,
+        biome: Biome,
+    ) -> Self {
+        let size_y = data.len();
+        let size_x = data.first().map(|row| row.len()).unwrap_or_default();
+
+        // saved games can be loaded mid-fire
+        let cells_of_type = |terrain: TerrainType| {
+            data.iter()
+                .enumerate()
+                .flat_map(|(y, row)| {
+                    row.iter().enumerate().filter_map(move |(x, cell)| {
+                        (cell.terrain == terrain).then_some(IVec2::new(x as i32, y as i32))
+                    })
+                })
+                .collect::<HashSet<_>>()
+        };
+        let fire_cells = cells_of_type(TerrainType::Fire);
+        let smoldering_cells = cells_of_type(TerrainType::Smoldering);
+
+        Self {
+            data,
+            size_x,
+            size_y,
+            sprite_size,
+            biome,
+            seed,
+            fire_cells,
+            smoldering_cells,
+            thrown_embers: Vec::new(),
+            water_crossings: HashSet::default(),
+        }
+    }
+
+    /// Encodes the terrain and moisture of each cell into a compact byte
+    /// array for sharing. The width and height are written first as little
+    /// endian u16s and then the biome, followed by a byte per cell holding
+    /// the terrain type in the high 4 bits and a moisture bucket in the low
+    /// 4 bits
+    pub fn to_compact_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(COMPACT_HEADER_SIZE + self.size_x * self.size_y);
+        bytes.extend_from_slice(&(self.size_x as u16).to_le_bytes());
+        bytes.extend_from_slice(&(self.size_y as u16).to_le_bytes());
+        bytes.push(self.biome.compact_id());
+
+        for cell in self.data.iter().flatten() {
+            // the buildings themselves aren't shared, so don't leave their
+            // footprints behind
+            let terrain = if cell.terrain == TerrainType::Building {
+                TerrainType::Dirt
+            } else {
+                cell.terrain
+            };
+            let moisture = (cell.moisture.clamp(0.0, 1.0) * COMPACT_MOISTURE_BUCKETS).round() as u8;
+            bytes.push((terrain.compact_id() << 4) | moisture);
+        }
+
+        bytes
+    }
+
+    /// Reads the width and height from the start of a compact map
+    pub fn compact_dimensions(data: &[u8]) -> Option<UVec2> {
+        let header = data.get(..COMPACT_HEADER_SIZE)?;
+        Some(UVec2::new(
+            u16::from_le_bytes([header[0], header[1]]) as u32,
+            u16::from_le_bytes([header[2], header[3]]) as u32,
+        ))
+    }
+
+    /// Decodes a map written by [GameMap::to_compact_bytes]. The elevation
+    /// and fuel load aren't stored, so these are sampled from noise with the
+    /// given seed. Returns None if the data isn't a valid compact map
+    pub fn from_compact_bytes(data: &[u8], seed: i32, sprite_size: f32) -> Option<Self> {
+        let size = Self::compact_dimensions(data)?;
+        let (size_x, size_y) = (size.x as usize, size.y as usize);
+        let cells = &data[COMPACT_HEADER_SIZE..];
+        if size_x == 0 || size_y == 0 || cells.len() != size_x * size_y {
+            return None;
+        }
+
+        let noise_map = NoiseMap::new(seed);
+        let biome = Biome::from_compact_id(data[COMPACT_HEADER_SIZE - 1])?;
+        let config = biome.config();
+
+        let mut rows = Vec::with_capacity(size_y);
+        for (y, row) in cells.chunks(size_x).enumerate() {
+            let mut cells = Vec::with_capacity(size_x);
+            for (x, byte) in row.iter().enumerate() {
+                let terrain = TerrainType::from_compact_id(byte >> 4)?;
+                let sampled = noise_map.sample_cell(x, y, &config);
+
+                cells.push(TerrainCellState {
+                    terrain,
+                    moisture: (byte & 0x0F) as f32 / COMPACT_MOISTURE_BUCKETS,
+                    fuel_load: if sampled.terrain == terrain {
+                        sampled.fuel_load
+                    } else {
+                        terrain.typical_fuel_load()
+                    },
+                    // intensity isn't shared, so shared fires burn at a typical
+                    // intensity
+                    fire_intensity: if terrain == TerrainType::Fire {
+                        0.5
+                    } else {
+                        0.0
+                    },
+                    elevation: sampled.elevation,
+                    ..default()
+                });
+            }
+            rows.push(cells);
+        }
+
+        Some(Self::from_data(seed, sprite_size, rows, biome))
+    }
+
     /// Gets coordinates of valid cells within a given range of a point
     pub fn cells_within_range(&self, center: IVec2, range: i32) -> impl Iterator<Item = IVec2> {
         ((center.y - range).max(0)..=(center.y + range).max(0)).flat_map(move |y| {
@@ -265,12 +1031,86 @@ impl GameMap {
 
     /// Returns true if any of the cells in the map is on fire
     pub fn any_on_fire(&self) -> bool {
-        self.data.iter().any(|row| {
-            row.iter()
-                .any(|cell| matches!(cell.terrain, TerrainType::Fire))
+        // cells can be put out outside of the map update, so check the terrain
+        // rather than relying on the cache being empty
+        self.fire_cells.iter().any(|loc| {
+            self.get(*loc)
+                .is_some_and(|cell| matches!(cell.terrain, TerrainType::Fire))
         })
     }
 
+    /// Returns the squared distance (in tiles) from the given location to the
+    /// closest cell that is on fire, or None if nothing is burning
+    pub fn distance_squared_to_fire(&self, loc: IVec2) -> Option<i32> {
+        self.fire_cells
+            .iter()
+            .filter(|fire| {
+                self.get(**fire)
+                    .is_some_and(|cell| matches!(cell.terrain, TerrainType::Fire))
+            })
+            .map(|fire| fire.distance_squared(loc))
+            .min()
+    }
+
+    /// Records that a river ferry covers the given water cell
+    pub fn add_water_crossing(&mut self, loc: IVec2) {
+        self.water_crossings.insert(loc);
+    }
+
+    /// Removes a water cell that was covered by a river ferry
+    pub fn remove_water_crossing(&mut self, loc: IVec2) {
+        self.water_crossings.remove(&loc);
+    }
+
+    /// Whether the given cell is covered by, or right next to, a river ferry
+    /// so that it can be built on and walked across even if it is water
+    pub fn is_crossable(&self, loc: IVec2) -> bool {
+        (-1..=1)
+            .any(|dy| (-1..=1).any(|dx| self.water_crossings.contains(&(loc + IVec2::new(dx, dy)))))
+    }
+
+    /// Returns the average location (in tiles) of all the cells that are on
+    /// fire, or None if nothing is burning
+    pub fn fire_centroid(&self) -> Option<Vec2> {
+        let (sum, count) = self
+            .fire_cells
+            .iter()
+            .filter(|loc| {
+                self.get(**loc)
+                    .is_some_and(|cell| matches!(cell.terrain, TerrainType::Fire))
+            })
+            .fold((Vec2::ZERO, 0), |(sum, count), loc| {
+                (sum + loc.as_vec2(), count + 1)
+            });
+
+        (count > 0).then(|| sum / count as f32)
+    }
+
+    /// Returns the number of cells in the map that are currently on fire
+    pub fn fire_count(&self) -> usize {
+        self.fire_cells
+            .iter()
+            .filter(|loc| {
+                self.get(**loc)
+                    .is_some_and(|cell| matches!(cell.terrain, TerrainType::Fire))
+            })
+            .count()
+    }
+
+    /// Records that the cell at the given location has been set on fire. This
+    /// must be called whenever a cell's terrain is changed to [TerrainType::Fire]
+    /// so that the fire spreads from it.
+    pub fn mark_on_fire(&mut self, loc: IVec2) {
+        self.fire_cells.insert(loc);
+    }
+
+    /// Records that the cell at the given location has been turned to burnt
+    /// ground outside of the fire spread, e.g. by painting terrain, so that it
+    /// regrows
+    pub fn mark_smoldering(&mut self, loc: IVec2) {
+        self.smoldering_cells.insert(loc);
+    }
+
     /// Checks whether the cell at the given tile coords is on fire
     pub fn check_on_fire(&self, locs: &[IVec2]) -> bool {
         locs.iter().any(|loc| {
@@ -354,7 +1194,7 @@ impl GameMap {
     /// Returns the neighbours of this cell in a regular pattern as defined by
     /// [NEIGHBOUR_COORDINATES]. If the cell coordinate of a neighbour is
     /// invalid (i.e. off the grid) then `None` will be returned.
-    fn neighbours(&mut self, x: i32, y: i32) -> impl Iterator<Item = Option<IVec2>> {
+    fn neighbours(&self, x: i32, y: i32) -> impl Iterator<Item = Option<IVec2>> {
         let sx = self.size_x as i32;
         let sy = self.size_y as i32;
 
@@ -367,102 +1207,707 @@ impl GameMap {
         })
     }
 
-    /// Updates the map, spreading fire etc
-    pub fn update(&mut self, global_wind: &WindDirection) {
-        const BURN_DECAY_RATE: f64 = 0.15;
-        const FIRE_SPREAD_CHANCE: f64 = 0.35;
-        const MOISTURE_DECAY_RATE: f32 = 0.02;
+    /// Whether any of the cells around the given location have the given
+    /// terrain
+    pub fn has_neighbour(&self, loc: IVec2, terrain: TerrainType) -> bool {
+        NEIGHBOUR_COORDINATES.iter().any(|offset| {
+            self.get(loc + *offset)
+                .is_some_and(|cell| cell.terrain == terrain)
+        })
+    }
 
-        let mut rng = rand::thread_rng();
-        let global_wind_vec = global_wind.as_vec();
-
-        for y in 0..self.size_y {
-            for x in 0..self.size_x {
-                let self_terrain = self.data[y][x].terrain;
-
-                match self_terrain {
-                    TerrainType::Fire => {
-                        if rng.gen_bool(BURN_DECAY_RATE) {
-                            let new_fuel_load = self.data[y][x].fuel_load.saturating_sub(1);
-                            self.data[y][x].fuel_load = new_fuel_load;
-
-                            if self.data[y][x].fuel_load == 0 {
-                                self.data[y][x].terrain = TerrainType::Smoldering;
-                                self.data[y][x].dirty = true;
-                            }
-                        }
+    /// Takes the embers thrown by the fire since this was last called
+    pub fn take_thrown_embers(&mut self) -> Vec<(IVec2, Vec2)> {
+        std::mem::take(&mut self.thrown_embers)
+    }
+
+    /// Immediately carries an ember from a burning cell downwind and attempts
+    /// to start a spot fire where it lands, without showing it in flight
+    pub fn spot_ember(&mut self, origin: IVec2, wind: Vec2, rng: &mut impl Rng) {
+        // embers mostly travel with the wind, and travel furthest when they
+        // are blown directly downwind
+        let wind_angle = rng.gen_range(-std::f32::consts::FRAC_PI_2..std::f32::consts::FRAC_PI_2);
+        let distance = (EMBER_MAX_DISTANCE * wind_angle.cos() * rng.r#gen::<f32>()).ceil();
+        let offset = Vec2::from_angle(wind.to_angle() + wind_angle) * distance;
+        let target = origin + offset.round().as_ivec2();
+
+        self.land_ember(target, rng);
+    }
+
+    /// Attempts to start a spot fire where an ember lands
+    pub fn land_ember(&mut self, target: IVec2, rng: &mut impl Rng) {
+        let Some(cell) = self.get_mut(target) else {
+            return;
+        };
+
+        match cell.terrain {
+            TerrainType::Grassland | TerrainType::Tree | TerrainType::Building => {
+                let guard_factor = 1. - cell.ember_resistance.clamp(0.0, 1.0);
+                let burn_chance = (cell.effective_burn_rate()
+                    * self.biome.config().burn_rate_modifier
+                    * guard_factor)
+                    .clamp(0.0, 1.0) as f64;
+
+                if rng.gen_bool(burn_chance) {
+                    cell.ignite();
+                    self.fire_cells.insert(target);
+                }
+            }
+            TerrainType::Dirt
+            | TerrainType::Stone
+            | TerrainType::Fire
+            | TerrainType::Smoldering
+            | TerrainType::Water => {
+                // nop
+            }
+        }
+    }
+
+    /// Rain falls on random cells across the map, making them wetter
+    fn rain(&mut self, rng: &mut impl Rng) {
+        const RAIN_CELLS_PER_UPDATE: usize = 100;
+        const RAIN_MOISTURE_INCREASE: f32 = 0.02;
+
+        for _ in 0..RAIN_CELLS_PER_UPDATE {
+            let x = rng.gen_range(0..self.size_x);
+            let y = rng.gen_range(0..self.size_y);
+            let cell = &mut self.data[y][x];
+
+            match cell.terrain {
+                TerrainType::Grassland | TerrainType::Tree => {
+                    cell.moisture = (cell.moisture + RAIN_MOISTURE_INCREASE).min(1.0);
+                    cell.dirty = true;
+                }
+                TerrainType::Building
+                | TerrainType::Fire
+                | TerrainType::Dirt
+                | TerrainType::Stone
+                | TerrainType::Smoldering
+                | TerrainType::Water => {}
+            }
+        }
+    }
+
+    /// The whole map slowly dries out, faster during a drought. This only
+    /// visits a small random sample of cells each update so the change is
+    /// gradual and easy to miss
+    fn dry_out(&mut self, drought_factor: f32, rng: &mut impl Rng) {
+        const DRY_OUT_FRACTION: usize = 200;
+        const DRY_OUT_MOISTURE_DECREASE: f32 = 0.001;
+
+        let samples = self.size_x * self.size_y / DRY_OUT_FRACTION;
+        for _ in 0..samples {
+            let x = rng.gen_range(0..self.size_x);
+            let y = rng.gen_range(0..self.size_y);
+            let cell = &mut self.data[y][x];
+
+            match cell.terrain {
+                TerrainType::Grassland | TerrainType::Tree => {
+                    cell.moisture =
+                        (cell.moisture - DRY_OUT_MOISTURE_DECREASE * drought_factor).max(0.0);
+                    cell.dirty = true;
+                }
+                TerrainType::Building
+                | TerrainType::Fire
+                | TerrainType::Dirt
+                | TerrainType::Stone
+                | TerrainType::Smoldering
+                | TerrainType::Water => {}
+            }
+        }
+    }
+
+    /// The average moisture of all grass and tree cells, from 0-1
+    pub fn average_moisture(&self) -> f32 {
+        let (total, count) = self
+            .data
+            .iter()
+            .flatten()
+            .filter(|cell| matches!(cell.terrain, TerrainType::Grassland | TerrainType::Tree))
+            .fold((0.0, 0), |(total, count), cell| {
+                (total + cell.moisture, count + 1)
+            });
+
+        if count == 0 {
+            0.0
+        } else {
+            total / count as f32
+        }
+    }
+
+    /// The wind blowing over the given cell, which is the local wind (i.e. from
+    /// a storm mage) if one is set or the global wind otherwise
+    fn wind_at(&self, loc: IVec2, global_wind: Vec2) -> Vec2 {
+        let local_wind = self.data[loc.y as usize][loc.x as usize].wind;
+        if local_wind.length_squared() > 1.0 {
+            local_wind
+        } else {
+            global_wind
+        }
+    }
+
+    /// Whether a stone cell lies just upwind of `loc`, sheltering it from the
+    /// wind. Neighbouring cells have nothing in between them, so rather than
+    /// looking between the burning cell and its neighbour this looks along
+    /// the line the wind takes to reach the neighbour
+    fn in_wind_shadow(&self, loc: IVec2, wind: Vec2) -> bool {
+        let Some(direction) = wind.try_normalize() else {
+            return false;
+        };
+
+        let upwind = loc - (direction * WIND_SHADOW_DISTANCE).round().as_ivec2();
+        line_between(loc, upwind).into_iter().skip(1).any(|coord| {
+            self.get(coord)
+                .is_some_and(|cell| matches!(cell.terrain, TerrainType::Stone))
+        })
+    }
+
+    /// The chance that the burning cell at `loc` sets fire to its neighbour `n`,
+    /// where `idx` is the index of the neighbour in [NEIGHBOUR_COORDINATES]
+    fn burn_chance(&self, loc: IVec2, n: IVec2, idx: usize, spread: &SpreadParams) -> f64 {
+        let (x, y) = (loc.x as usize, loc.y as usize);
+        let (nx, ny) = (n.x as usize, n.y as usize);
+
+        // the direction from the neighbour back towards the burning cell
+        let towards_fire = (idx + NEIGHBOURHOOD_SIZE / 2) % NEIGHBOURHOOD_SIZE;
+
+        // crown fires race through the canopy, no matter how damp the ground is
+        let target = &self.data[ny][nx];
+        let base_probability =
+            if self.data[y][x].canopy_fire && matches!(target.terrain, TerrainType::Tree) {
+                target.terrain.burn_rate() * CROWN_FIRE_SPREAD_FACTOR
+            } else {
+                target.effective_burn_rate()
+            } * spread.burn_rate_modifier;
+
+        // if local wind is set, use that and a slightly different relationship.
+        // TODO: could tidy this up to use f32/f32 or Vec2 for both.
+        // This is a hangover of an earlier implementation where I wanted to add the vecs
+        let wind_factor = if self.data[ny][nx].wind.length_squared() > 1.0 {
+            // here we check how close the wind and neighbour angles are.
+            // if the angles are close that means the wind factor shoudl be high
+            // if the angles are close to 180 degrees, then the fire is pushing
+            // directly into the wind and the wind factor should be basically 0.
+            let wind_angle = self.data[ny][nx].wind;
+            let delta_angle = (wind_angle - NEIGHBOUR_VECTOR[towards_fire])
+                .to_angle()
+                .abs();
+            let ratio = delta_angle / std::f32::consts::FRAC_PI_2;
+
+            // sharp drop-off but make sure its 0 chance of spreading from about right angles
+            // to the wind onwards
+            let factor = (-5.0 * ratio).exp();
+            if factor < 0.1 { 0. } else { factor }
+        } else if self.in_wind_shadow(n, spread.global_wind) {
+            // stone formations act as windbreaks, so the fire spreads into
+            // sheltered cells as if there were no wind at all
+            1.0
+        } else {
+            // here we use the formula from the paper
+            let total_wind = spread.global_wind;
+            let delta_angle = (total_wind - NEIGHBOUR_VECTOR[towards_fire]).to_angle();
+            let wind_strength = total_wind.length();
+            1.0 + (wind_strength * 0.021 * (delta_angle.cos() - 1.0))
+                * (0.005 * wind_strength).exp()
+        };
+
+        // real fires spread by radiant heat and flame contact, which fall away
+        // with distance, and diagonal cells are ~1.4 cells away. Without this
+        // the fire front grows as a square rather than something rounder
+        let direction_factor = if idx % 2 == 1 {
+            DIAGONAL_SPREAD_FACTOR
+        } else {
+            1.0
+        };
+
+        // fire races uphill and creeps downhill. Here the neighbour is the
+        // cell that might catch and `loc` is already burning
+        let elev_delta = self.data[ny][nx].elevation - self.data[y][x].elevation;
+        let elevation_factor = (1.0 + elev_delta * 2.0)
+            .clamp(MIN_ELEVATION_SPREAD_FACTOR, MAX_ELEVATION_SPREAD_FACTOR);
+
+        let intensity_factor = if self.data[y][x].fire_intensity > INTENSE_FIRE_THRESHOLD {
+            INTENSE_FIRE_SPREAD_FACTOR
+        } else {
+            1.0
+        };
+
+        (base_probability * wind_factor * direction_factor * elevation_factor * intensity_factor)
+            .clamp(0.0, 1.0) as f64
+    }
+
+    /// Whether fire spreading from a burning tree at `loc` to the tree at `n`
+    /// climbs into its canopy
+    fn spreads_to_canopy(&self, loc: IVec2, n: IVec2, rng: &mut impl Rng) -> bool {
+        self.data[loc.y as usize][loc.x as usize].burning_tree
+            && matches!(
+                self.data[n.y as usize][n.x as usize].terrain,
+                TerrainType::Tree
+            )
+            && rng.gen_bool(CROWN_FIRE_CHANCE)
+    }
+
+    /// Whether the fire at `loc` is hot enough to scorch the stone at `n`
+    fn scorches_stone(&self, loc: IVec2, n: IVec2) -> bool {
+        let cell = &self.data[loc.y as usize][loc.x as usize];
+        cell.fuel_load > SCORCHING_FIRE_FUEL_LOAD
+            && cell.fire_intensity > SCORCHING_FIRE_INTENSITY
+            && matches!(
+                self.data[n.y as usize][n.x as usize].terrain,
+                TerrainType::Stone
+            )
+    }
+
+    /// Breaks a stone cell down into bare earth
+    fn scorch(&mut self, loc: IVec2) {
+        let cell = &mut self.data[loc.y as usize][loc.x as usize];
+        cell.terrain = TerrainType::Dirt;
+        cell.fuel_load = 0;
+        cell.dirty = true;
+    }
+
+    /// Cools a burning cell, which burns out once its intensity reaches 0
+    fn burn_down(&mut self, loc: IVec2, decay: f32, stats: &mut FireStats) {
+        let cell = &mut self.data[loc.y as usize][loc.x as usize];
+        let previous_colour = cell.colour();
+
+        // the canopy burns out twice as fast as the ground
+        let decay = if cell.canopy_fire { 2.0 * decay } else { decay };
+        cell.fire_intensity = (cell.fire_intensity - decay).max(0.0);
+        if cell.colour() != previous_colour {
+            cell.dirty = true;
+        }
+
+        if cell.fire_intensity <= 0.0 {
+            cell.terrain = TerrainType::Smoldering;
+            cell.recovery_timer = 0.0;
+            cell.dirty = true;
+            stats.cells_burned += 1;
+            self.fire_cells.remove(&loc);
+            self.smoldering_cells.insert(loc);
+        }
+    }
+
+    /// Spreads fire from each of the burning cells in turn
+    #[cfg(not(feature = "parallel"))]
+    fn spread_fire(
+        &mut self,
+        burning: Vec<IVec2>,
+        spread: &SpreadParams,
+        stats: &mut FireStats,
+        rng: &mut impl Rng,
+    ) {
+        for loc in burning {
+            // strong winds can carry burning embers well ahead of the
+            // fire front, jumping over any firebreaks
+            let wind = self.wind_at(loc, spread.global_wind);
+            if wind.length() > EMBER_SPOTTING_MIN_WIND && rng.gen_bool(EMBER_SPOTTING_CHANCE) {
+                self.thrown_embers.push((loc, wind));
+            }
+
+            let neighbours = self.neighbours(loc.x, loc.y).collect::<Vec<_>>();
+            for (idx, n) in neighbours.iter().enumerate() {
+                let Some(n) = n else {
+                    continue;
+                };
+
+                if self.scorches_stone(loc, *n) && rng.gen_bool(STONE_SCORCH_CHANCE) {
+                    self.scorch(*n);
+                    continue;
+                }
+
+                let (nx, ny) = (n.x as usize, n.y as usize);
+                if !matches!(
+                    self.data[ny][nx].terrain,
+                    TerrainType::Grassland | TerrainType::Tree | TerrainType::Building
+                ) {
+                    continue;
+                }
+
+                // reduce moisture of the neighbour for each neighouring fire cell
+                self.data[ny][nx].moisture =
+                    (self.data[ny][nx].moisture - spread.moisture_decay_rate).max(0.0);
+
+                // on some percentage, spread the fire
+                if !rng.gen_bool(spread.spread_chance) {
+                    continue;
+                }
+
+                let burn_chance = self.burn_chance(loc, *n, idx, spread);
+
+                // check if we "roll" less than burn_chance, modified by a random
+                // amount to create some noise in the burning
+                let rng_factor = rng.r#gen::<f64>();
+                if rng.gen_bool(burn_chance * rng_factor) {
+                    let crown = self.spreads_to_canopy(loc, *n, rng);
+                    self.data[ny][nx].ignite();
+                    self.data[ny][nx].canopy_fire = crown;
+                    self.fire_cells.insert(*n);
+                }
+            }
+
+            self.burn_down(loc, spread.intensity_decay, stats);
+        }
+    }
+
+    /// Spreads fire from the burning cells in parallel. Each burning cell reads
+    /// the map as it was at the start of the update and proposes changes. The
+    /// changes are then applied in a single pass once every cell is done.
+    #[cfg(feature = "parallel")]
+    fn spread_fire_parallel(
+        &mut self,
+        burning: Vec<IVec2>,
+        spread: &SpreadParams,
+        stats: &mut FireStats,
+    ) {
+        use rayon::prelude::*;
+
+        let map = &*self;
+        let deltas = burning
+            .par_iter()
+            .map_init(rand::thread_rng, |rng, &loc| {
+                let mut deltas = Vec::new();
+
+                // strong winds can carry burning embers well ahead of the
+                // fire front, jumping over any firebreaks
+                let wind = map.wind_at(loc, spread.global_wind);
+                if wind.length() > EMBER_SPOTTING_MIN_WIND && rng.gen_bool(EMBER_SPOTTING_CHANCE) {
+                    deltas.push(SpreadDelta::Ember(loc, wind));
+                }
+
+                for (idx, n) in map.neighbours(loc.x, loc.y).enumerate() {
+                    let Some(n) = n else {
+                        continue;
+                    };
+
+                    if map.scorches_stone(loc, n) && rng.gen_bool(STONE_SCORCH_CHANCE) {
+                        deltas.push(SpreadDelta::Scorch(n));
+                        continue;
                     }
-                    TerrainType::Grassland | TerrainType::Tree | TerrainType::Building => {
-                        let neighbours = self.neighbours(x as i32, y as i32).collect::<Vec<_>>();
-                        for (idx, n) in neighbours.iter().enumerate() {
-                            let Some(n) = n else {
-                                continue;
-                            };
-
-                            let neighbour = self.data[n.y as usize][n.x as usize].terrain;
-
-                            // each neighbouring fire has a chance to set this on fire
-                            if matches!(neighbour, TerrainType::Fire) {
-                                // reduce moisture of `self` for each neighouring fire cell
-                                self.data[y][x].moisture =
-                                    (self.data[y][x].moisture - MOISTURE_DECAY_RATE).max(0.0);
-
-                                // on some percentage, spread the fire
-                                if rng.gen_bool(FIRE_SPREAD_CHANCE) {
-                                    let base_probability = self.data[y][x].terrain.burn_rate();
-
-                                    // if local wind is set, use that and a slightly different relationship.
-                                    // TODO: could tidy this up to use f32/f32 or Vec2 for both.
-                                    // This is a hangover of an earlier implementation where I wanted to add the vecs
-                                    let wind_factor = if self.data[y][x].wind.length_squared() > 1.0
-                                    {
-                                        // here we check how close the wind and neighbour angles are.
-                                        // if the angles are close that means the wind factor shoudl be high
-                                        // if the angles are close to 180 degrees, then the fire is pushing
-                                        // directly into the wind and the wind factor should be basically 0.
-                                        let wind_angle = self.data[x][y].wind;
-                                        let delta_angle =
-                                            (wind_angle - NEIGHBOUR_VECTOR[idx]).to_angle().abs();
-                                        let ratio = delta_angle / std::f32::consts::FRAC_PI_2;
-
-                                        // sharp drop-off but make sure its 0 chance of spreading from about right angles
-                                        // to the wind onwards
-                                        let factor = (-5.0 * ratio).exp();
-                                        if factor < 0.1 { 0. } else { factor }
-                                    } else {
-                                        // here we use the formula from the paper
-                                        let total_wind = global_wind_vec;
-                                        let delta_angle =
-                                            (total_wind - NEIGHBOUR_VECTOR[idx]).to_angle();
-                                        let wind_strength = total_wind.length();
-                                        1.0 + (wind_strength * 0.021 * (delta_angle.cos() - 1.0))
-                                            * (0.005 * wind_strength).exp()
-                                    };
-
-                                    let moisture_factor = 1. - self.data[y][x].moisture;
-
-                                    let burn_chance =
-                                        (base_probability * moisture_factor * wind_factor)
-                                            .clamp(0.0, 1.0)
-                                            as f64;
-
-                                    // check if we "roll" less than burn_chance, modified by a random
-                                    // amount to create some noise in the burning
-                                    let rng_factor = rng.r#gen::<f64>();
-                                    if rng.gen_bool(burn_chance * rng_factor) {
-                                        self.data[y][x].terrain = TerrainType::Fire;
-                                        self.data[y][x].dirty = true;
-                                        break; // no need to set it on fire any other way, lets take a break
-                                    }
-                                }
-                            }
-                        }
+
+                    if !matches!(
+                        map.data[n.y as usize][n.x as usize].terrain,
+                        TerrainType::Grassland | TerrainType::Tree | TerrainType::Building
+                    ) {
+                        continue;
+                    }
+
+                    deltas.push(SpreadDelta::Dry(n));
+
+                    if !rng.gen_bool(spread.spread_chance) {
+                        continue;
+                    }
+
+                    let burn_chance = map.burn_chance(loc, n, idx, spread);
+                    let rng_factor = rng.r#gen::<f64>();
+                    if rng.gen_bool(burn_chance * rng_factor) {
+                        deltas.push(SpreadDelta::Ignite(n, map.spreads_to_canopy(loc, n, rng)));
                     }
-                    TerrainType::Dirt | TerrainType::Stone | TerrainType::Smoldering => {
-                        //nop
+                }
+
+                deltas.push(SpreadDelta::BurnDown(loc));
+
+                deltas
+            })
+            .flatten_iter()
+            .collect::<Vec<_>>();
+
+        for delta in deltas {
+            match delta {
+                SpreadDelta::Dry(loc) => {
+                    let cell = &mut self.data[loc.y as usize][loc.x as usize];
+                    cell.moisture = (cell.moisture - spread.moisture_decay_rate).max(0.0);
+                }
+                SpreadDelta::Ignite(loc, crown) => {
+                    let cell = &mut self.data[loc.y as usize][loc.x as usize];
+                    // several burning cells may have lit the same neighbour
+                    if matches!(
+                        cell.terrain,
+                        TerrainType::Grassland | TerrainType::Tree | TerrainType::Building
+                    ) {
+                        cell.ignite();
+                        cell.canopy_fire = crown;
+                        self.fire_cells.insert(loc);
+                    }
+                }
+                SpreadDelta::Ember(loc, wind) => self.thrown_embers.push((loc, wind)),
+                SpreadDelta::BurnDown(loc) => {
+                    self.burn_down(loc, spread.intensity_decay, stats);
+                }
+                SpreadDelta::Scorch(loc) => {
+                    // several scorching fires may have broken down the same stone
+                    if matches!(
+                        self.data[loc.y as usize][loc.x as usize].terrain,
+                        TerrainType::Stone
+                    ) {
+                        self.scorch(loc);
                     }
                 }
             }
         }
     }
+
+    /// The conditions that fire spreads under for the current wind, weather
+    /// and difficulty
+    fn spread_params(
+        &self,
+        global_wind: &WindDirection,
+        gust: Option<&WindGust>,
+        weather: WeatherEvent,
+        difficulty: DifficultyLevel,
+    ) -> SpreadParams {
+        const FIRE_SPREAD_CHANCE: f64 = 0.35;
+        const MOISTURE_DECAY_RATE: f32 = 0.02;
+
+        SpreadParams {
+            global_wind: global_wind.as_vec() + gust.map(|gust| gust.direction).unwrap_or_default(),
+            spread_chance: (FIRE_SPREAD_CHANCE
+                * weather.spread_chance_multiplier()
+                * difficulty.spread_multiplier())
+            .clamp(0.0, 1.0),
+            moisture_decay_rate: MOISTURE_DECAY_RATE
+                * weather.moisture_decay_multiplier()
+                * difficulty.moisture_decay_multiplier(),
+            burn_rate_modifier: self.biome.config().burn_rate_modifier,
+            intensity_decay: FIRE_INTENSITY_DECAY * difficulty.burn_decay_multiplier() as f32,
+        }
+    }
+
+    /// Estimates which cells will probably catch fire over the given number of
+    /// map updates, without changing the map. Rather than rolling the dice,
+    /// this tracks the chance that each cell is burning after each update and
+    /// returns the cells that are more likely than not to have caught fire.
+    pub fn predict_fire(
+        &self,
+        global_wind: &WindDirection,
+        gust: Option<&WindGust>,
+        weather: WeatherEvent,
+        difficulty: DifficultyLevel,
+        updates: usize,
+    ) -> HashSet<IVec2> {
+        let spread = self.spread_params(global_wind, gust, weather, difficulty);
+
+        // the chance that each cell is burning, and that each unburnt cell
+        // has caught fire at some point
+        let mut burning = self
+            .fire_cells
+            .iter()
+            .filter(|loc| self.check_on_fire(&[**loc]))
+            .map(|loc| (*loc, 1.0))
+            .collect::<HashMap<IVec2, f64>>();
+        let mut ignited = HashMap::<IVec2, f64>::default();
+
+        for _ in 0..updates {
+            // the chance that each cell is *not* lit by any burning neighbour
+            let mut unlit = HashMap::<IVec2, f64>::default();
+
+            for (loc, chance) in burning.iter() {
+                if *chance < PREDICTION_MIN_CHANCE {
+                    continue;
+                }
+
+                for (idx, n) in self.neighbours(loc.x, loc.y).enumerate() {
+                    let Some(n) = n else {
+                        continue;
+                    };
+
+                    if !matches!(
+                        self.data[n.y as usize][n.x as usize].terrain,
+                        TerrainType::Grassland | TerrainType::Tree | TerrainType::Building
+                    ) {
+                        continue;
+                    }
+
+                    // the burn roll is scaled by a random factor, which
+                    // halves it on average
+                    let lit = spread.spread_chance * self.burn_chance(*loc, n, idx, &spread) * 0.5;
+                    *unlit.entry(n).or_insert(1.0) *= 1.0 - chance * lit;
+                }
+            }
+
+            // cells burn out once their intensity has fallen to 0. Cells that
+            // haven't caught fire yet are assumed to burn as they would now
+            for (loc, chance) in burning.iter_mut() {
+                let cell = &self.data[loc.y as usize][loc.x as usize];
+                let intensity = if matches!(cell.terrain, TerrainType::Fire) {
+                    cell.fire_intensity
+                } else {
+                    cell.ignition_intensity()
+                };
+                let updates_left = (intensity / spread.intensity_decay).max(1.0);
+                *chance *= 1.0 - 1.0 / updates_left as f64;
+            }
+
+            for (n, unlit) in unlit {
+                let previous = ignited.get(&n).copied().unwrap_or_default();
+                let newly_lit = (1.0 - previous) * (1.0 - unlit);
+                ignited.insert(n, previous + newly_lit);
+                *burning.entry(n).or_default() += newly_lit;
+            }
+        }
+
+        ignited
+            .into_iter()
+            .filter(|(_, chance)| *chance > PREDICTION_THRESHOLD)
+            .map(|(loc, _)| loc)
+            .collect()
+    }
+
+    /// Updates the map, spreading fire etc
+    pub fn update(
+        &mut self,
+        global_wind: &WindDirection,
+        gust: Option<&WindGust>,
+        weather: WeatherEvent,
+        difficulty: DifficultyLevel,
+        moisture: &mut MoistureMap,
+        stats: &mut FireStats,
+    ) {
+        let spread = self.spread_params(global_wind, gust, weather, difficulty);
+
+        let mut rng = rand::thread_rng();
+        if weather == WeatherEvent::Rain {
+            self.rain(&mut rng);
+        }
+        self.dry_out(weather.moisture_decay_multiplier(), &mut rng);
+        moisture.update(&mut self.data);
+
+        // anything that was put out since the last update (e.g. by rain clouds
+        // or water golems) no longer needs to spread, but burnt ground still
+        // has to regrow
+        let data = &self.data;
+        let smoldering_cells = &mut self.smoldering_cells;
+        self.fire_cells
+            .retain(|loc| match data[loc.y as usize][loc.x as usize].terrain {
+                TerrainType::Fire => true,
+                TerrainType::Smoldering => {
+                    smoldering_cells.insert(*loc);
+                    false
+                }
+                TerrainType::Dirt
+                | TerrainType::Building
+                | TerrainType::Grassland
+                | TerrainType::Tree
+                | TerrainType::Stone
+                | TerrainType::Water => false,
+            });
+
+        // only the cells burning at the start of the update spread fire, so
+        // newly lit cells wait for the next update
+        let burning = self.fire_cells.iter().copied().collect::<Vec<_>>();
+        let fire_size = burning.len() as u32;
+
+        #[cfg(not(feature = "parallel"))]
+        self.spread_fire(burning, &spread, stats, &mut rng);
+        #[cfg(feature = "parallel")]
+        self.spread_fire_parallel(burning, &spread, stats);
+
+        // burnt ground slowly regrows. Cells that were built over or changed
+        // since they burnt out are dropped
+        let data = &mut self.data;
+        self.smoldering_cells.retain(|loc| {
+            let cell = &mut data[loc.y as usize][loc.x as usize];
+            if !matches!(cell.terrain, TerrainType::Smoldering) {
+                return false;
+            }
+
+            cell.recovery_timer += 1.0;
+
+            if cell.recovery_timer >= SMOLDERING_RECOVERY_UPDATES {
+                cell.terrain = TerrainType::Grassland;
+                cell.fuel_load = 1;
+                cell.moisture = 0.3;
+                cell.recovery_timer = 0.0;
+                cell.dirty = true;
+                false
+            } else {
+                // redraw every so often to show the ground slowly regrowing
+                if cell.recovery_timer as u32 % RECOVERY_REDRAW_INTERVAL == 0 {
+                    cell.dirty = true;
+                }
+                true
+            }
+        });
+
+        stats.peak_fire_size = stats.peak_fire_size.max(fire_size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a small generated map to encode
+    fn test_map() -> GameMap {
+        GameMap::new(GOOD_SEEDS[0], 16.0, 24, 24, Biome::default(), &[])
+    }
+
+    #[test]
+    fn compact_bytes_round_trip() {
+        let map = test_map();
+        let bytes = map.to_compact_bytes();
+
+        let decoded = GameMap::from_compact_bytes(&bytes, map.seed, map.sprite_size)
+            .expect("the encoded map is valid");
+
+        assert_eq!((decoded.size_x, decoded.size_y), (map.size_x, map.size_y));
+        assert_eq!(decoded.biome, map.biome);
+        for (original, decoded) in map.data.iter().flatten().zip(decoded.data.iter().flatten()) {
+            assert_eq!(decoded.terrain, original.terrain);
+            // moisture is rounded to the nearest bucket
+            assert!(
+                (decoded.moisture - original.moisture.clamp(0.0, 1.0)).abs()
+                    <= 0.5 / COMPACT_MOISTURE_BUCKETS + f32::EPSILON
+            );
+        }
+        assert_eq!(decoded.to_compact_bytes(), bytes);
+    }
+
+    #[test]
+    fn compact_bytes_leave_out_buildings() {
+        let mut map = test_map();
+        let loc = IVec2::new(10, 10);
+        map.get_mut(loc).unwrap().terrain = TerrainType::Building;
+
+        let decoded =
+            GameMap::from_compact_bytes(&map.to_compact_bytes(), map.seed, map.sprite_size)
+                .expect("the encoded map is valid");
+
+        assert_eq!(decoded.get(loc).unwrap().terrain, TerrainType::Dirt);
+    }
+
+    #[test]
+    fn compact_bytes_keep_biome() {
+        let map = GameMap::new(GOOD_SEEDS[0], 16.0, 24, 24, Biome::Boreal, &[]);
+
+        let decoded =
+            GameMap::from_compact_bytes(&map.to_compact_bytes(), map.seed, map.sprite_size)
+                .expect("the encoded map is valid");
+
+        assert_eq!(decoded.biome, Biome::Boreal);
+    }
+
+    #[test]
+    fn malformed_compact_bytes_return_none() {
+        let bytes = test_map().to_compact_bytes();
+        let decode = |data: &[u8]| GameMap::from_compact_bytes(data, GOOD_SEEDS[0], 16.0);
+
+        // missing or partial header
+        assert!(decode(&[]).is_none());
+        assert!(decode(&bytes[..COMPACT_HEADER_SIZE - 1]).is_none());
+        // no cells, or an empty map
+        assert!(decode(&bytes[..COMPACT_HEADER_SIZE]).is_none());
+        assert!(decode(&[0, 0, 0, 0, 0]).is_none());
+        // too few or too many cells for the size in the header
+        assert!(decode(&bytes[..bytes.len() - 1]).is_none());
+        assert!(decode(&[bytes.as_slice(), &[0]].concat()).is_none());
+        // a biome that doesn't exist
+        let mut unknown_biome = bytes.clone();
+        unknown_biome[COMPACT_HEADER_SIZE - 1] = 0xFF;
+        assert!(decode(&unknown_biome).is_none());
+        // a terrain type that doesn't exist
+        let mut unknown_terrain = bytes;
+        unknown_terrain[COMPACT_HEADER_SIZE] = 0xF0;
+        assert!(decode(&unknown_terrain).is_none());
+    }
 }
@@ -11,7 +11,10 @@ use crate::{
     Pause,
     asset_tracking::LoadResource,
     audio::sound_effect,
-    screens::Screen,
+    screens::{
+        BEACON_TOWER_MAX_REDIRECTS, BEACON_TOWER_RANGE, BeaconTower, BuildingLocation, Screen,
+        ShakeScreen, WearOutBeaconTower,
+    },
     wildfire::{TerrainType, map::GameMap},
 };
 
@@ -56,6 +59,7 @@ fn handle_meteor_strike(
     mut commands: Commands,
     meteor_assets: Res<MeteorAssets>,
     map: ResMut<GameMap>,
+    mut beacons: Query<(Entity, &BuildingLocation, &mut BeaconTower)>,
 ) {
     let loc = trigger.event().0;
     let Some(cell) = map.get(loc) else {
@@ -65,15 +69,42 @@ fn handle_meteor_strike(
 
     match cell.terrain {
         TerrainType::Grassland | TerrainType::Tree => {
-            info!("Spawning meteor strike at {loc}");
+            // any nearby beacon towers draw the meteor towards themselves
+            let beacon = beacons
+                .iter_mut()
+                .filter(|(_, beacon_loc, beacon)| {
+                    beacon.is_ready()
+                        && beacon_loc.0.distance_squared(loc)
+                            <= BEACON_TOWER_RANGE * BEACON_TOWER_RANGE
+                })
+                .min_by_key(|(_, beacon_loc, _)| beacon_loc.0.distance_squared(loc));
+
+            let target = if let Some((beacon_entity, beacon_loc, mut beacon)) = beacon {
+                info!(
+                    "Beacon tower at {} redirected meteor strike from {loc}",
+                    beacon_loc.0
+                );
+                beacon.last_redirect = 0.0;
+                beacon.redirects += 1;
+
+                if beacon.redirects >= BEACON_TOWER_MAX_REDIRECTS {
+                    commands.queue(WearOutBeaconTower(beacon_entity));
+                }
+
+                beacon_loc.0
+            } else {
+                loc
+            };
+
+            info!("Spawning meteor strike at {target}");
 
             commands.spawn(sound_effect(meteor_assets.boom_one.clone()));
 
-            let world_pos = map.world_coords(loc);
+            let world_pos = map.world_coords(target);
             commands.spawn((
                 Transform::from_xyz(-1000.0, 100.0, 1.0),
                 Meteor {
-                    target_coord: loc,
+                    target_coord: target,
                     target_world_pos: world_pos,
                     speed: (Vec2::new(-1000.0, 100.0) - world_pos).length() / METEOR_FLIGHT_TIME,
                 },
@@ -87,7 +118,8 @@ fn handle_meteor_strike(
         | TerrainType::Fire
         | TerrainType::Dirt
         | TerrainType::Stone
-        | TerrainType::Smoldering => {}
+        | TerrainType::Smoldering
+        | TerrainType::Water => {}
     }
 }
 
@@ -96,6 +128,8 @@ const METEOR_FLIGHT_TIME: f32 = 0.9;
 
 /// RANDOM I GUESS
 const FIREBALL_SPEED: f32 = 70.0;
+/// How hard the screen shakes when a meteor lands
+const METEOR_SHAKE_INTENSITY: f32 = 2.0;
 fn handle_fireball_impacts(
     mut commands: Commands,
     time: Res<Time>,
@@ -129,6 +163,7 @@ fn handle_meteor_impacts(
         if (meteor.target_world_pos - tx.translation.truncate()).length_squared() < 100.0 {
             // we hit
             commands.entity(entity).despawn();
+            commands.queue(ShakeScreen(METEOR_SHAKE_INTENSITY));
 
             // find some random thingos around the impact point and start fires
             let points = map
@@ -145,13 +180,18 @@ fn handle_meteor_impacts(
                 })
                 .collect::<Vec<_>>();
 
+            // meteors landing in water or on stone have nothing to set alight
+            if points.is_empty() {
+                continue;
+            }
+
             let mut rng = rand::thread_rng();
             for _ in 0..rng.gen_range(2..=4) {
                 let idx = rng.gen_range(0..points.len());
                 let coords = points[idx];
                 if let Some(cell) = map.get_mut(coords) {
-                    cell.dirty = true;
-                    cell.terrain = TerrainType::Fire;
+                    cell.ignite();
+                    map.mark_on_fire(coords);
                 }
             }
         }
@@ -0,0 +1,219 @@
+//! A minimap plugin that shows the whole map in the corner of the screen.
+//! Each pixel of the minimap represents a 2x2 block of cells, and clicking
+//! the minimap moves the camera to that location.
+
+use bevy::{
+    asset::RenderAssetUsages,
+    color::palettes::css::WHITE,
+    image::ImageSampler,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    window::PrimaryWindow,
+};
+
+use crate::{
+    MainCamera, Pause,
+    screens::Screen,
+    theme::node_builder::NodeBuilder,
+    wildfire::{
+        ColourBlindMode, OnSpawnMap,
+        map::{GameMap, update_sprites},
+    },
+};
+
+/// The number of cells (in each direction) represented by a single minimap pixel
+const CELLS_PER_PIXEL: u32 = 2;
+/// The distance from the edge of the screen to the minimap
+const MINIMAP_MARGIN: f32 = 10.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Minimap>();
+    app.register_type::<MinimapUi>();
+    app.register_type::<MinimapViewport>();
+
+    app.add_observer(spawn_minimap);
+
+    app.add_systems(
+        Update,
+        (
+            update_minimap.before(update_sprites),
+            update_minimap_viewport,
+        )
+            .run_if(
+                in_state(Screen::Gameplay)
+                    .or(in_state(Screen::LevelEditor))
+                    .and(in_state(Pause(false)))
+                    .and(resource_exists::<GameMap>)
+                    .and(resource_exists::<Minimap>),
+            ),
+    );
+}
+
+/// Holds the image that the minimap is drawn into
+#[derive(Resource, Reflect, Debug)]
+#[reflect(Resource)]
+pub struct Minimap {
+    image: Handle<Image>,
+    /// The size of the minimap image in pixels
+    size: UVec2,
+}
+
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct MinimapUi;
+
+/// The rectangle on the minimap showing what the camera can currently see
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct MinimapViewport;
+
+fn spawn_minimap(
+    trigger: Trigger<OnSpawnMap>,
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    previous_minimaps: Query<Entity, With<MinimapUi>>,
+) {
+    for previous in &previous_minimaps {
+        commands.entity(previous).despawn();
+    }
+
+    let size = trigger.event().size / CELLS_PER_PIXEL;
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    // Use `nearest` image sampling to preserve pixel art style.
+    image.sampler = ImageSampler::nearest();
+    let image = images.add(image);
+
+    commands
+        .spawn((
+            Name::new("Minimap"),
+            MinimapUi,
+            GlobalZIndex(4),
+            StateScoped(Screen::Gameplay),
+            NodeBuilder::new()
+                .position(PositionType::Absolute)
+                .right(MINIMAP_MARGIN)
+                .bottom(MINIMAP_MARGIN)
+                .width(Val::Px(size.x as f32))
+                .height(Val::Px(size.y as f32))
+                .build(),
+            ImageNode::new(image.clone()),
+            children![(
+                Name::new("Minimap Viewport"),
+                MinimapViewport,
+                Node {
+                    position_type: PositionType::Absolute,
+                    border: UiRect::all(Val::Px(1.0)),
+                    ..default()
+                },
+                BorderColor(WHITE.into()),
+                Pickable::IGNORE,
+            )],
+        ))
+        .observe(move_camera_to_minimap_click);
+
+    commands.insert_resource(Minimap { image, size });
+}
+
+/// Copies any dirty cells from the map into the minimap. This has to run
+/// before the sprites are updated as that clears the dirty flag
+fn update_minimap(
+    map: Res<GameMap>,
+    minimap: Res<Minimap>,
+    colour_mode: Res<ColourBlindMode>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if !map.data.iter().any(|row| row.iter().any(|cell| cell.dirty)) {
+        return;
+    }
+
+    let Some(image) = images.get_mut(&minimap.image) else {
+        warn!("Unable to find minimap image, skipping update");
+        return;
+    };
+
+    for (y, row) in map.data.iter().enumerate() {
+        for (x, cell) in row.iter().enumerate() {
+            if !cell.dirty {
+                continue;
+            }
+
+            // images go top to bottom but the map goes bottom to top
+            let pixel_x = x as u32 / CELLS_PER_PIXEL;
+            let pixel_y = minimap.size.y - 1 - y as u32 / CELLS_PER_PIXEL;
+
+            if let Err(e) =
+                image.set_color_at(pixel_x, pixel_y, colour_mode.correct(cell.visible_colour()))
+            {
+                warn!("Unable to update minimap pixel at {pixel_x},{pixel_y}: {e:?}");
+            }
+        }
+    }
+}
+
+/// Moves the viewport rectangle on the minimap to show what the camera can see
+fn update_minimap_viewport(
+    map: Res<GameMap>,
+    minimap: Res<Minimap>,
+    camera: Single<(&Camera, &Transform, &Projection), With<MainCamera>>,
+    mut viewport: Single<&mut Node, With<MinimapViewport>>,
+) {
+    let (camera, tx, projection) = *camera;
+    let Projection::Orthographic(proj) = projection else {
+        return;
+    };
+    let Some(viewport_size) = camera.logical_viewport_size() else {
+        return;
+    };
+
+    let half_size = viewport_size * proj.scale * 0.5;
+    let centre = tx.translation.truncate();
+    let map_size = Vec2::new(map.size_x as f32, map.size_y as f32);
+    let ui_size = minimap.size.as_vec2();
+
+    let min = (map.tile_coords(centre - half_size).as_vec2() / map_size)
+        .clamp(Vec2::ZERO, Vec2::ONE)
+        * ui_size;
+    let max = (map.tile_coords(centre + half_size).as_vec2() / map_size)
+        .clamp(Vec2::ZERO, Vec2::ONE)
+        * ui_size;
+
+    viewport.left = Val::Px(min.x);
+    viewport.top = Val::Px(ui_size.y - max.y);
+    viewport.width = Val::Px(max.x - min.x);
+    viewport.height = Val::Px(max.y - min.y);
+}
+
+fn move_camera_to_minimap_click(
+    trigger: Trigger<Pointer<Click>>,
+    maybe_map: Option<Res<GameMap>>,
+    maybe_minimap: Option<Res<Minimap>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut camera: Single<&mut Transform, With<MainCamera>>,
+) {
+    let (Some(map), Some(minimap)) = (maybe_map, maybe_minimap) else {
+        return;
+    };
+
+    // the minimap is anchored to the bottom right of the window
+    let ui_size = minimap.size.as_vec2();
+    let top_left =
+        Vec2::new(window.width(), window.height()) - ui_size - Vec2::splat(MINIMAP_MARGIN);
+    let relative = (trigger.event().pointer_location.position - top_left) / ui_size;
+
+    let tile_coords = IVec2::new(
+        (relative.x * map.size_x as f32) as i32,
+        ((1.0 - relative.y) * map.size_y as f32) as i32,
+    );
+
+    camera.translation = map.world_coords(tile_coords).extend(camera.translation.z);
+}
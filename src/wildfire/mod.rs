@@ -6,47 +6,192 @@ use bevy::{
     color::palettes::{
         css::{BLACK, WHITE},
         tailwind::{
-            AMBER_700, AMBER_900, GREEN_900, LIME_500, ORANGE_600, ORANGE_700, PINK_600, SLATE_700,
-            STONE_500, YELLOW_400, YELLOW_500, YELLOW_600,
+            AMBER_700, AMBER_900, GREEN_900, LIME_500, ORANGE_600, ORANGE_700, PINK_600, SKY_700,
+            SLATE_700, STONE_500, YELLOW_100, YELLOW_400, YELLOW_500, YELLOW_600,
         },
     },
     prelude::*,
 };
 
+mod accessibility;
+mod biome;
+mod controlled_burn;
+mod difficulty;
+mod embers;
+mod enemies;
+mod fire_animation;
+mod fire_prediction;
 mod map;
 mod meteor;
+mod minimap;
+mod moisture_conduction;
+mod moisture_map;
+mod rain_cloud;
+mod regions;
+mod spread_rate;
+mod stats;
+mod tile_coords;
+mod tornado;
+mod weather;
 mod wind;
+mod wind_rose;
 
-pub use map::{GOOD_SEEDS, GameMap};
+pub use accessibility::{ColourBlindMode, ShowTerrainPatterns};
+pub use biome::{Biome, BiomeConfig};
+pub use controlled_burn::{CONTROLLED_BURN_COST_MANA, OnControlledBurn};
+pub use difficulty::{DifficultyLevel, DifficultySettings};
+pub use enemies::{SpawnGoblinArsonist, SpawnGoblinShaman};
+pub use map::{GOOD_SEEDS, GameMap, LoadedMapData, NoiseMap, line_between};
 pub use meteor::{Fireball, MeteorAssets, OnMeteorStrike};
-pub use wind::WindDirection;
+pub use minimap::MinimapUi;
+pub use moisture_map::MoistureMap;
+pub use rain_cloud::{OnRainCloud, RAIN_CLOUD_COST_MANA, RAIN_CLOUD_RADIUS};
+pub use regions::MapRegions;
+pub use spread_rate::{SpreadRateAutoPause, SpreadRateTracker, fire_spread_surged};
+pub use stats::{FireStats, fire_stats_panel};
+pub use tornado::{OnTornado, Tornado};
+pub use weather::{CurrentWeather, MoistureTrend, WeatherEvent};
+pub use wind::{WindDirection, WindGust};
 
 pub fn plugin(app: &mut App) {
     app.register_type::<OnSpawnMap>();
+    app.register_type::<MapSize>();
+    app.register_type::<SelectedMapSize>();
     app.register_type::<TerrainCell>();
+    app.register_type::<FogOfWar>();
     app.register_type::<TerrainCellState>();
     app.register_type::<TerrainType>();
 
-    app.add_plugins((map::plugin, meteor::plugin, wind::plugin));
+    app.init_resource::<SelectedMapSize>();
+
+    app.add_plugins((
+        accessibility::plugin,
+        biome::plugin,
+        controlled_burn::plugin,
+        difficulty::plugin,
+        embers::plugin,
+        enemies::plugin,
+        fire_animation::plugin,
+        fire_prediction::plugin,
+    ));
+    app.add_plugins((
+        map::plugin,
+        meteor::plugin,
+        minimap::plugin,
+        moisture_conduction::plugin,
+        moisture_map::plugin,
+        rain_cloud::plugin,
+        regions::plugin,
+        spread_rate::plugin,
+        stats::plugin,
+        tile_coords::plugin,
+        tornado::plugin,
+        weather::plugin,
+        wind::plugin,
+        wind_rose::plugin,
+    ));
     app.add_observer(spawn_map);
 }
 
+/// The preset map sizes that can be chosen for endless mode
+#[derive(Debug, Reflect, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MapSize {
+    /// 128x128 cells
+    Small,
+    /// 256x256 cells, used by the story levels
+    #[default]
+    Medium,
+    /// 512x512 cells, drawn with smaller sprites to fit on screen
+    Large,
+}
+
+impl From<MapSize> for UVec2 {
+    fn from(value: MapSize) -> Self {
+        match value {
+            MapSize::Small => UVec2::splat(128),
+            MapSize::Medium => UVec2::splat(256),
+            MapSize::Large => UVec2::splat(512),
+        }
+    }
+}
+
+impl std::fmt::Display for MapSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let size = UVec2::from(*self);
+        match self {
+            MapSize::Small => write!(f, "Small ({}x{})", size.x, size.y),
+            MapSize::Medium => write!(f, "Medium ({}x{})", size.x, size.y),
+            MapSize::Large => write!(f, "Large ({}x{})", size.x, size.y),
+        }
+    }
+}
+
+impl MapSize {
+    /// The map size with the given width and height, if there is one
+    pub fn from_dimensions(size: UVec2) -> Option<Self> {
+        [MapSize::Small, MapSize::Medium, MapSize::Large]
+            .into_iter()
+            .find(|map_size| UVec2::from(*map_size) == size)
+    }
+
+    /// The size (in pixels) of each cell sprite
+    pub fn sprite_size(&self) -> f32 {
+        match self {
+            MapSize::Small | MapSize::Medium => 4.0,
+            MapSize::Large => 2.0,
+        }
+    }
+}
+
+/// The map size chosen for endless mode
+#[derive(Resource, Debug, Reflect, Clone, Copy, Default)]
+#[reflect(Resource)]
+pub struct SelectedMapSize(pub MapSize);
+
 // NOTE: slightly weird using this as an event and a resource but game jam
 #[derive(Event, Debug, Reflect, Clone, Copy)]
 pub struct OnSpawnMap {
     pub size: UVec2,
     pub sprite_size: f32,
     pub seed: i32,
+    /// Whether the map starts hidden until revealed by buildings
+    pub fog_of_war: bool,
+    /// The biome used to generate the map
+    pub biome: Biome,
+    /// Where the city hall will be built, if known. Rivers are kept away
+    /// from here so it is always buildable
+    pub starting_location: Option<IVec2>,
 }
 
 impl OnSpawnMap {
-    pub fn new(seed: i32) -> Self {
+    pub fn new(seed: i32, size: MapSize) -> Self {
         Self {
-            size: UVec2::splat(256),
-            sprite_size: 4.0,
+            size: size.into(),
+            sprite_size: size.sprite_size(),
             seed,
+            fog_of_war: false,
+            biome: Biome::default(),
+            starting_location: None,
         }
     }
+
+    /// Sets the biome used to generate the map
+    pub fn with_biome(mut self, biome: Biome) -> Self {
+        self.biome = biome;
+        self
+    }
+
+    /// Keeps rivers away from where the city hall will be built
+    pub fn with_starting_location(mut self, starting_location: IVec2) -> Self {
+        self.starting_location = Some(starting_location);
+        self
+    }
+
+    /// Hides the map under fog of war when it is spawned
+    pub fn with_fog_of_war(mut self) -> Self {
+        self.fog_of_war = true;
+        self
+    }
 }
 
 #[derive(Component, Debug, Clone, Copy, Reflect)]
@@ -57,17 +202,50 @@ pub struct SpawnedMap;
 #[reflect(Component)]
 pub struct TerrainCell;
 
-fn spawn_map(trigger: Trigger<OnSpawnMap>, mut commands: Commands) {
+/// Marks terrain cell sprites that are hidden under fog of war, removed when
+/// the cell is revealed
+#[derive(Component, Debug, Clone, Copy, Reflect)]
+#[reflect(Component)]
+pub struct FogOfWar;
+
+fn spawn_map(
+    trigger: Trigger<OnSpawnMap>,
+    mut commands: Commands,
+    maybe_loaded: Option<ResMut<LoadedMapData>>,
+) {
     let data = trigger.event();
     let size_x = data.size.x;
     let size_y = data.size.y;
     let sprite_size = data.sprite_size;
-    info!(
-        "Spawning {size_x}x{size_y} map with {sprite_size}px grid. Seed - {}",
-        data.seed
-    );
 
-    let mut map = GameMap::new(data.seed, sprite_size, size_x as usize, size_y as usize);
+    // maps loaded from a save skip the noise generation
+    let is_loaded = maybe_loaded.is_some();
+    let mut map = if let Some(mut loaded) = maybe_loaded {
+        info!("Spawning {size_x}x{size_y} map with {sprite_size}px grid from a saved game");
+        commands.remove_resource::<LoadedMapData>();
+        GameMap::from_data(
+            data.seed,
+            sprite_size,
+            std::mem::take(&mut loaded.0),
+            data.biome,
+        )
+    } else {
+        info!(
+            "Spawning {size_x}x{size_y} {} map with {sprite_size}px grid. Seed - {}",
+            data.biome, data.seed
+        );
+        GameMap::new(
+            data.seed,
+            sprite_size,
+            size_x as usize,
+            size_y as usize,
+            data.biome,
+            data.starting_location.as_slice(),
+        )
+    };
+
+    commands.insert_resource(MapRegions::new(data.seed, data.size));
+    commands.insert_resource(MoistureMap::new(size_x as usize, size_y as usize));
 
     commands
         .spawn((
@@ -83,24 +261,24 @@ fn spawn_map(trigger: Trigger<OnSpawnMap>, mut commands: Commands) {
         .with_children(|builder| {
             for y in 0..size_y {
                 for x in 0..size_x {
-                    let entity = builder
-                        .spawn((
-                            TerrainCell,
-                            Sprite {
-                                custom_size: Some(Vec2::splat(sprite_size)),
-                                ..Default::default()
-                            },
-                            Transform::from_xyz(
-                                sprite_size * x as f32,
-                                sprite_size * y as f32,
-                                0.0,
-                            ),
-                        ))
-                        .id();
-
                     let cell = &mut map.data[y as usize][x as usize];
-                    cell.sprite_entity = Some(entity);
+                    if !is_loaded {
+                        cell.fog = data.fog_of_war;
+                    }
                     cell.dirty = true;
+
+                    let mut cell_sprite = builder.spawn((
+                        TerrainCell,
+                        Sprite {
+                            custom_size: Some(Vec2::splat(sprite_size)),
+                            ..Default::default()
+                        },
+                        Transform::from_xyz(sprite_size * x as f32, sprite_size * y as f32, 0.0),
+                    ));
+                    if cell.fog {
+                        cell_sprite.insert(FogOfWar);
+                    }
+                    cell.sprite_entity = Some(cell_sprite.id());
                 }
             }
         });
@@ -120,6 +298,8 @@ pub enum TerrainType {
     Stone,
     Fire,
     Smoldering,
+    /// Rivers never burn and act as a natural firebreak
+    Water,
 }
 
 impl TerrainType {
@@ -128,11 +308,65 @@ impl TerrainType {
             TerrainType::Fire
             | TerrainType::Smoldering
             | TerrainType::Dirt
-            | TerrainType::Stone => 0.0,
+            | TerrainType::Stone
+            | TerrainType::Water => 0.0,
             TerrainType::Grassland | TerrainType::Building => 0.6,
             TerrainType::Tree => 0.4,
         }
     }
+
+    /// A typical fuel load for this terrain, for when the terrain is known
+    /// but the fuel load isn't, e.g. in maps shared between players
+    pub fn typical_fuel_load(&self) -> u8 {
+        match self {
+            TerrainType::Dirt | TerrainType::Smoldering | TerrainType::Water => 0,
+            TerrainType::Building | TerrainType::Grassland | TerrainType::Fire => 6,
+            TerrainType::Stone => 5,
+            TerrainType::Tree => 12,
+        }
+    }
+
+    /// The 4 bit id used to store this terrain in compact maps
+    pub fn compact_id(&self) -> u8 {
+        match self {
+            TerrainType::Dirt => 0,
+            TerrainType::Building => 1,
+            TerrainType::Grassland => 2,
+            TerrainType::Tree => 3,
+            TerrainType::Stone => 4,
+            TerrainType::Fire => 5,
+            TerrainType::Smoldering => 6,
+            TerrainType::Water => 7,
+        }
+    }
+
+    /// The terrain stored with the given id in compact maps, if it is valid
+    pub fn from_compact_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(TerrainType::Dirt),
+            1 => Some(TerrainType::Building),
+            2 => Some(TerrainType::Grassland),
+            3 => Some(TerrainType::Tree),
+            4 => Some(TerrainType::Stone),
+            5 => Some(TerrainType::Fire),
+            6 => Some(TerrainType::Smoldering),
+            7 => Some(TerrainType::Water),
+            _ => None,
+        }
+    }
+
+    /// A typical colour for this terrain, for when only the terrain type is
+    /// known and not the rest of the cell state
+    pub fn typical_colour(&self) -> Color {
+        TerrainCellState {
+            terrain: *self,
+            moisture: 0.5,
+            fuel_load: 5,
+            fire_intensity: 0.5,
+            ..default()
+        }
+        .colour()
+    }
 }
 
 impl std::fmt::Display for TerrainType {
@@ -148,6 +382,7 @@ impl std::fmt::Display for TerrainType {
                 TerrainType::Tree => "Forest",
                 TerrainType::Fire => "Fire",
                 TerrainType::Smoldering => "Burnt Ground",
+                TerrainType::Water => "River",
             }
         )
     }
@@ -157,16 +392,52 @@ impl std::fmt::Display for TerrainType {
 #[derive(Debug, Copy, Clone, PartialEq, Reflect, Default)]
 pub struct TerrainCellState {
     pub terrain: TerrainType,
+    /// Wind from nearby storm mages. Not saved as it is re-applied when the
+    /// mages are restored
+    #[reflect(skip_serializing)]
     pub wind: Vec2,
     pub moisture: f32,
     pub fuel_load: u8,
+    /// How fiercely the cell is burning, from 0-1. Set when the cell catches
+    /// fire and falls each map update until the fire burns out
+    pub fire_intensity: f32,
+    /// The height of the cell from 0-1. Fire spreads faster uphill
+    pub elevation: f32,
+    /// Reduces the chance of embers starting spot fires in this cell, 0-1.
+    /// Not saved as it is re-applied when ember guards are restored
+    #[reflect(skip_serializing)]
+    pub ember_resistance: f32,
+    /// Whether the cell is still hidden under fog of war
+    pub fog: bool,
+    /// The number of map updates this cell has spent smoldering, once this
+    /// reaches [SMOLDERING_RECOVERY_UPDATES] the cell regrows as grass
+    pub recovery_timer: f32,
+    /// The number of times this cell has caught fire, shown by the heat map.
+    /// Not saved, so the history starts again each time a map is spawned
+    #[reflect(skip_serializing)]
+    pub burn_count: u8,
+    /// Whether the fire in this cell is burning through the tree canopy.
+    /// Crown fires spread quickly between trees but burn out sooner. Not
+    /// saved, so loaded fires burn as ground fires
+    #[reflect(skip_serializing)]
+    pub canopy_fire: bool,
+    /// Whether this cell was a tree when it caught fire, so that the fire
+    /// can climb into the canopy of neighbouring trees
+    #[reflect(skip_serializing)]
+    burning_tree: bool,
 
+    #[reflect(skip_serializing)]
     pub sprite_entity: Option<Entity>,
+    #[reflect(skip_serializing)]
     dirty: bool,
 }
 
 impl std::fmt::Display for TerrainCellState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.fog {
+            return write!(f, "Unexplored");
+        }
+
         match self.terrain {
             TerrainType::Grassland | TerrainType::Tree => {
                 write!(
@@ -193,23 +464,98 @@ impl std::fmt::Display for TerrainCellState {
                     self.terrain,
                 )
             }
+            TerrainType::Smoldering if self.recovery_timer > 0.0 => {
+                write!(f, "Recovering Ground")
+            }
+            TerrainType::Fire if self.canopy_fire => write!(f, "Crown Fire"),
             TerrainType::Dirt
             | TerrainType::Building
             | TerrainType::Stone
             | TerrainType::Fire
-            | TerrainType::Smoldering => write!(f, "{}", self.terrain),
+            | TerrainType::Smoldering
+            | TerrainType::Water => write!(f, "{}", self.terrain),
+        }?;
+
+        write!(
+            f,
+            " (Elevation: {:.0}m)",
+            self.elevation * MAX_ELEVATION_METRES
+        )?;
+
+        if self.burn_count > 0 {
+            write!(
+                f,
+                " Burned {} time{}",
+                self.burn_count,
+                if self.burn_count == 1 { "" } else { "s" }
+            )?;
         }
+
+        Ok(())
     }
 }
 
 const DRY_GRASS: Color = Color::Srgba(Srgba::new(0.85, 0.8, 0.21, 1.0));
+/// The colour of cells hidden under fog of war
+const DARK_GREY: Color = Color::Srgba(Srgba::new(0.12, 0.12, 0.13, 1.0));
+
+/// The height in metres shown for the highest cells on the map
+const MAX_ELEVATION_METRES: f32 = 1000.0;
+
+/// The number of map updates (roughly 2 minutes) before burnt ground regrows
+/// as grass
+const SMOLDERING_RECOVERY_UPDATES: f32 = 1200.0;
+
+/// The burn rate of the most flammable terrain. Intensity is measured against
+/// this so that the driest grass burns at full intensity
+const MAX_BURN_RATE: f32 = 0.6;
+/// The intensity of the weakest fires, so that even damp cells (or fire
+/// painted onto bare earth) burn for a little while
+const MIN_FIRE_INTENSITY: f32 = 0.1;
 
 impl TerrainCellState {
     pub fn mark_dirty(&mut self) {
         self.dirty = true;
     }
 
-    fn colour(&self) -> Color {
+    /// Sets the cell on fire, recording it in the cell's burn history
+    pub fn ignite(&mut self) {
+        self.fire_intensity = self.ignition_intensity();
+        self.burning_tree = matches!(self.terrain, TerrainType::Tree);
+        self.canopy_fire = false;
+        self.terrain = TerrainType::Fire;
+        self.burn_count = self.burn_count.saturating_add(1);
+        self.dirty = true;
+    }
+
+    /// How intensely this cell would burn if it caught fire now. Dry cells
+    /// burn hotter and for longer
+    pub fn ignition_intensity(&self) -> f32 {
+        (self.terrain.burn_rate() / MAX_BURN_RATE * (1.0 - self.moisture))
+            .clamp(MIN_FIRE_INTENSITY, 1.0)
+    }
+
+    /// The base chance of this cell catching fire, reduced by its moisture.
+    /// Even the wettest cells keep a small chance of burning
+    pub fn effective_burn_rate(&self) -> f32 {
+        self.terrain.burn_rate() * (1.0 - self.moisture).max(0.1)
+    }
+
+    /// Removes the fog of war from this cell
+    pub fn reveal(&mut self) {
+        if self.fog {
+            self.fog = false;
+            self.dirty = true;
+        }
+    }
+
+    /// The colour to draw this cell, taking fog of war into account
+    fn visible_colour(&self) -> Color {
+        if self.fog { DARK_GREY } else { self.colour() }
+    }
+
+    /// The colour to draw this cell, ignoring fog of war
+    pub fn colour(&self) -> Color {
         match self.terrain {
             TerrainType::Building => PINK_600.into(),
             TerrainType::Dirt => Color::Srgba(Srgba {
@@ -230,13 +576,14 @@ impl TerrainCellState {
                 8..=11 => GREEN_900.mix(&WHITE, 0.05).into(),
                 _ => GREEN_900.mix(&WHITE, 0.075).into(),
             },
-            TerrainType::Fire => match self.fuel_load {
-                0 => AMBER_900.into(),
-                1 => AMBER_700.into(),
-                2 => ORANGE_700.into(),
-                3 => ORANGE_600.into(),
-                4 => YELLOW_600.into(),
-                5 => YELLOW_500.into(),
+            TerrainType::Fire if self.canopy_fire => YELLOW_100.into(),
+            TerrainType::Fire => match self.fire_intensity {
+                0.0..0.1 => AMBER_900.into(),
+                0.1..0.2 => AMBER_700.into(),
+                0.2..0.35 => ORANGE_700.into(),
+                0.35..0.5 => ORANGE_600.into(),
+                0.5..0.65 => YELLOW_600.into(),
+                0.65..0.8 => YELLOW_500.into(),
                 _ => YELLOW_400.into(),
             },
             TerrainType::Stone => match self.fuel_load {
@@ -245,7 +592,11 @@ impl TerrainCellState {
                 4 | 5 => STONE_500.mix(&WHITE, 0.05).into(),
                 _ => STONE_500.mix(&WHITE, 0.1).into(),
             },
-            TerrainType::Smoldering => SLATE_700.into(),
+            TerrainType::Smoldering => {
+                let progress = (self.recovery_timer / SMOLDERING_RECOVERY_UPDATES).clamp(0.0, 1.0);
+                SLATE_700.mix(&LIME_500, progress).into()
+            }
+            TerrainType::Water => SKY_700.into(),
         }
     }
 }
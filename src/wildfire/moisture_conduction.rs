@@ -0,0 +1,76 @@
+//! Rivers and lakes slowly soak the grass and trees that grow along their
+//! banks, so vegetation beside water is harder to burn.
+
+use bevy::prelude::*;
+
+use crate::{
+    Pause,
+    screens::{GameSpeed, Screen},
+    wildfire::{GameMap, TerrainType},
+};
+
+/// How often (in seconds) water soaks its neighbours
+const MOISTURE_CONDUCTION_INTERVAL: f32 = 5.0;
+/// How much moisture each neighbour of a water cell gains every interval
+const MOISTURE_CONDUCTION_AMOUNT: f32 = 0.01;
+
+/// The neighbours that water soaks into
+const CARDINAL_NEIGHBOURS: [IVec2; 4] = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y];
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        conduct_moisture_from_water.run_if(
+            in_state(Screen::Gameplay)
+                .and(in_state(Pause(false)))
+                .and(resource_exists::<GameMap>),
+        ),
+    );
+}
+
+fn conduct_moisture_from_water(
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut map: ResMut<GameMap>,
+    mut since_last_conduction: Local<f32>,
+) {
+    *since_last_conduction += time.delta_secs() * game_speed.0;
+    if *since_last_conduction < MOISTURE_CONDUCTION_INTERVAL {
+        return;
+    }
+    *since_last_conduction = 0.0;
+
+    let water = map
+        .data
+        .iter()
+        .enumerate()
+        .flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, cell)| cell.terrain == TerrainType::Water)
+                .map(move |(x, _)| IVec2::new(x as i32, y as i32))
+        })
+        .collect::<Vec<_>>();
+
+    // cells beside more than one water cell soak up moisture from each of them
+    for loc in water {
+        for offset in CARDINAL_NEIGHBOURS {
+            let Some(cell) = map.get_mut(loc + offset) else {
+                continue;
+            };
+
+            match cell.terrain {
+                TerrainType::Grassland | TerrainType::Tree => {
+                    cell.moisture = (cell.moisture + MOISTURE_CONDUCTION_AMOUNT).min(1.0);
+                    cell.mark_dirty();
+                }
+                TerrainType::Dirt
+                | TerrainType::Stone
+                | TerrainType::Fire
+                | TerrainType::Smoldering
+                | TerrainType::Water
+                | TerrainType::Building => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Tracks water added to the map by water golems and sprinklers. Water is
+//! added here rather than straight to the cells, then spreads out to
+//! neighbouring cells and soaks into the ground a little each map update, so
+//! nearby golems and sprinklers build on each other's work.
+
+use bevy::prelude::*;
+
+use crate::wildfire::{TerrainCellState, TerrainType, map::NEIGHBOUR_COORDINATES};
+
+/// How much of the average of its neighbourhood each cell keeps when the
+/// moisture spreads out
+const MOISTURE_DIFFUSION_FACTOR: f32 = 0.9;
+/// The fraction of each cell's moisture that soaks into the ground each
+/// update. This matches what diffusion loses, so over time each cell receives
+/// about as much water as was added to the moisture map
+const MOISTURE_ABSORB_FRACTION: f32 = 1.0 - MOISTURE_DIFFUSION_FACTOR;
+/// Moisture below this is too little to matter and is dropped
+const MIN_TRACKED_MOISTURE: f32 = 0.001;
+/// How much the moisture of a cell must change before it is redrawn in the
+/// moisture overlay
+const MOISTURE_REDRAW_THRESHOLD: f32 = 0.01;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<MoistureMap>();
+}
+
+/// Moisture waiting to soak into each cell of the [GameMap](super::GameMap),
+/// indexed by `data[y][x]` in the same way as the map
+#[derive(Resource, Reflect, Debug, Clone)]
+#[reflect(Resource)]
+pub struct MoistureMap {
+    data: Vec<Vec<f32>>,
+    /// The spread out moisture is written here, then swapped with `data`.
+    /// Every value is zero between updates
+    #[reflect(ignore)]
+    next: Vec<Vec<f32>>,
+    /// The moisture of each cell when it was last redrawn
+    #[reflect(ignore)]
+    drawn: Vec<Vec<f32>>,
+    /// The smallest area holding every wet cell, if any are wet. Only this
+    /// area (and the cells around it) are updated
+    wet_area: Option<IRect>,
+    size_x: usize,
+    size_y: usize,
+}
+
+impl MoistureMap {
+    /// Creates a dry moisture map of the given size
+    pub fn new(size_x: usize, size_y: usize) -> Self {
+        Self {
+            data: vec![vec![0.0; size_x]; size_y],
+            next: vec![vec![0.0; size_x]; size_y],
+            drawn: vec![vec![0.0; size_x]; size_y],
+            wet_area: None,
+            size_x,
+            size_y,
+        }
+    }
+
+    /// Gets the moisture at the given location, if it is on the map
+    pub fn get(&self, loc: IVec2) -> Option<&f32> {
+        if loc.x < 0 || loc.x >= self.size_x as i32 || loc.y < 0 || loc.y >= self.size_y as i32 {
+            return None;
+        }
+
+        Some(&self.data[loc.y as usize][loc.x as usize])
+    }
+
+    /// Gets a mutable ref to the moisture at the given location, if it is on
+    /// the map. The location is assumed to be wet from now on
+    pub fn get_mut(&mut self, loc: IVec2) -> Option<&mut f32> {
+        if loc.x < 0 || loc.x >= self.size_x as i32 || loc.y < 0 || loc.y >= self.size_y as i32 {
+            return None;
+        }
+
+        self.wet_area = Some(match self.wet_area {
+            Some(area) => area.union_point(loc),
+            None => IRect::from_corners(loc, loc),
+        });
+
+        Some(&mut self.data[loc.y as usize][loc.x as usize])
+    }
+
+    /// Spreads the moisture and soaks some of it into the grass and trees on
+    /// the map. Called once per map update
+    pub(super) fn update(&mut self, cells: &mut [Vec<TerrainCellState>]) {
+        // most of the time nothing is being watered
+        let Some(wet_area) = self.wet_area else {
+            return;
+        };
+
+        // moisture spreads at most one cell beyond the wet area each update
+        let area = IRect::from_corners(
+            (wet_area.min - IVec2::ONE).max(IVec2::ZERO),
+            (wet_area.max + IVec2::ONE)
+                .min(IVec2::new(self.size_x as i32 - 1, self.size_y as i32 - 1)),
+        );
+
+        self.diffuse(area);
+
+        for y in area.min.y as usize..=area.max.y as usize {
+            for x in area.min.x as usize..=area.max.x as usize {
+                let moisture = self.data[y][x];
+                let cell = &mut cells[y][x];
+
+                if moisture > 0.0 {
+                    match cell.terrain {
+                        TerrainType::Grassland | TerrainType::Tree => {
+                            let colour = cell.colour();
+                            cell.moisture =
+                                (cell.moisture + moisture * MOISTURE_ABSORB_FRACTION).min(1.0);
+                            cell.dirty |= cell.colour() != colour;
+                        }
+                        TerrainType::Dirt
+                        | TerrainType::Building
+                        | TerrainType::Stone
+                        | TerrainType::Fire
+                        | TerrainType::Smoldering
+                        | TerrainType::Water => {}
+                    }
+                }
+
+                // the moisture overlay is redrawn when the change is big enough
+                // to see, and once more when the cell dries out to clear it
+                let drawn = &mut self.drawn[y][x];
+                if (moisture - *drawn).abs() > MOISTURE_REDRAW_THRESHOLD
+                    || (moisture == 0.0 && *drawn > 0.0)
+                {
+                    *drawn = moisture;
+                    cell.dirty = true;
+                }
+            }
+        }
+    }
+
+    /// Spreads the moisture out over the given area, with each cell taking the
+    /// average of itself and its neighbours. Some moisture is lost each time
+    /// so it doesn't spread forever
+    fn diffuse(&mut self, area: IRect) {
+        let mut wet_area: Option<IRect> = None;
+
+        for y in area.min.y..=area.max.y {
+            for x in area.min.x..=area.max.x {
+                let loc = IVec2::new(x, y);
+                let (total, count) = std::iter::once(IVec2::ZERO)
+                    .chain(NEIGHBOUR_COORDINATES)
+                    .filter_map(|offset| self.get(loc + offset))
+                    .fold((0.0, 0), |(total, count), value| (total + value, count + 1));
+
+                let average = total / count as f32 * MOISTURE_DIFFUSION_FACTOR;
+                if average < MIN_TRACKED_MOISTURE {
+                    continue;
+                }
+
+                self.next[y as usize][x as usize] = average;
+                wet_area = Some(match wet_area {
+                    Some(wet_area) => wet_area.union_point(loc),
+                    None => IRect::from_corners(loc, loc),
+                });
+            }
+        }
+
+        std::mem::swap(&mut self.data, &mut self.next);
+
+        // the old moisture is only non-zero in the old wet area, so clearing
+        // that leaves the buffer ready for the next update
+        if let Some(old_area) = self.wet_area {
+            for row in &mut self.next[old_area.min.y as usize..=old_area.max.y as usize] {
+                row[old_area.min.x as usize..=old_area.max.x as usize].fill(0.0);
+            }
+        }
+
+        self.wet_area = wet_area;
+    }
+}
@@ -0,0 +1,194 @@
+//! A rain cloud soaks an area of the map, making it less likely to burn
+//! and putting out some of the fires underneath. Triggered by [OnRainCloud]
+
+use bevy::{color::palettes::tailwind::SKY_400, prelude::*};
+use bevy_vector_shapes::{prelude::ShapePainter, shapes::DiscPainter};
+use rand::Rng;
+
+use crate::{
+    Pause,
+    screens::{GameSpeed, PlayerResources, Screen},
+    wildfire::{FireStats, TerrainType, map::GameMap},
+};
+
+/// The amount of mana used each time a rain cloud is summoned
+pub const RAIN_CLOUD_COST_MANA: i32 = 40;
+/// The radius (in tiles) of a summoned rain cloud
+pub const RAIN_CLOUD_RADIUS: f32 = 8.0;
+/// How long a rain cloud persists, in seconds
+pub const RAIN_CLOUD_DURATION: f32 = 10.0;
+/// The moisture added when the rain cloud first arrives
+pub const RAIN_CLOUD_INITIAL_MOISTURE: f32 = 0.4;
+/// The moisture added each second while the rain cloud persists
+pub const RAIN_CLOUD_MOISTURE_PER_SECOND: f32 = 0.05;
+/// The chance that a fire under the cloud is put out when it arrives
+pub const RAIN_CLOUD_QUENCH_CHANCE: f64 = 0.3;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<OnRainCloud>();
+    app.register_type::<RainCloud>();
+
+    app.add_observer(handle_rain_cloud);
+    app.add_systems(
+        Update,
+        (update_rain_clouds, draw_rain_clouds).run_if(
+            in_state(Screen::Gameplay)
+                .and(in_state(Pause(false)))
+                .and(resource_exists::<GameMap>),
+        ),
+    );
+}
+
+/// Summons a rain cloud at the given tile position with the given radius (in tiles)
+#[derive(Debug, Clone, Event, Reflect)]
+pub struct OnRainCloud(pub IVec2, pub f32);
+
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct RainCloud {
+    /// The tile at the center of the cloud
+    pub center: IVec2,
+    /// The radius of the cloud in tiles
+    pub radius: f32,
+    /// The remaining time the cloud will persist for
+    pub duration: f32,
+    time_since_last_tick: f32,
+}
+
+fn handle_rain_cloud(
+    trigger: Trigger<OnRainCloud>,
+    mut commands: Commands,
+    maybe_resources: Option<ResMut<PlayerResources>>,
+    maybe_map: Option<ResMut<GameMap>>,
+) {
+    let (Some(mut resources), Some(mut map)) = (maybe_resources, maybe_map) else {
+        warn!("Skipping rain cloud as there is no map or resources yet");
+        return;
+    };
+
+    if resources.mana < RAIN_CLOUD_COST_MANA {
+        warn!("Not enough mana to summon a rain cloud");
+        return;
+    }
+
+    let OnRainCloud(center, radius) = *trigger.event();
+    if !map.is_valid_coords(center) {
+        info!("Unable to summon rain cloud outside the map at {center:?}");
+        return;
+    }
+
+    info!("Summoning rain cloud at {center}");
+    resources.mana -= RAIN_CLOUD_COST_MANA;
+
+    let mut rng = rand::thread_rng();
+    let cells = map
+        .cells_within_range(center, radius.round() as i32)
+        .collect::<Vec<_>>();
+
+    for loc in cells {
+        let Some(cell) = map.get_mut(loc) else {
+            continue;
+        };
+
+        match cell.terrain {
+            TerrainType::Grassland | TerrainType::Tree => {
+                cell.moisture = (cell.moisture + RAIN_CLOUD_INITIAL_MOISTURE).min(1.0);
+                cell.mark_dirty();
+            }
+            TerrainType::Fire => {
+                if rng.gen_bool(RAIN_CLOUD_QUENCH_CHANCE) {
+                    cell.terrain = TerrainType::Smoldering;
+                    cell.mark_dirty();
+                    stats.fires_extinguished += 1;
+                }
+            }
+            TerrainType::Building
+            | TerrainType::Dirt
+            | TerrainType::Stone
+            | TerrainType::Smoldering
+            | TerrainType::Water => {}
+        }
+    }
+
+    commands.spawn((
+        Name::new("Rain Cloud"),
+        RainCloud {
+            center,
+            radius,
+            duration: RAIN_CLOUD_DURATION,
+            time_since_last_tick: 0.0,
+        },
+        Transform::from_translation(map.world_coords(center).extend(0.5)),
+        StateScoped(Screen::Gameplay),
+    ));
+}
+
+fn update_rain_clouds(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut map: ResMut<GameMap>,
+    mut stats: ResMut<FireStats>,
+    mut clouds: Query<(Entity, &mut RainCloud)>,
+) {
+    let delta = time.delta_secs() * game_speed.0;
+
+    for (entity, mut cloud) in &mut clouds {
+        cloud.duration -= delta;
+        if cloud.duration <= 0.0 {
+            info!("Rain cloud at {} has dissipated", cloud.center);
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        cloud.time_since_last_tick += delta;
+        if cloud.time_since_last_tick < 1.0 {
+            continue;
+        }
+        cloud.time_since_last_tick -= 1.0;
+
+        let cells = map
+            .cells_within_range(cloud.center, cloud.radius.round() as i32)
+            .collect::<Vec<_>>();
+
+        for loc in cells {
+            let Some(cell) = map.get_mut(loc) else {
+                continue;
+            };
+
+            match cell.terrain {
+                TerrainType::Grassland | TerrainType::Tree => {
+                    cell.moisture = (cell.moisture + RAIN_CLOUD_MOISTURE_PER_SECOND).min(1.0);
+                    cell.mark_dirty();
+                }
+                TerrainType::Building
+                | TerrainType::Fire
+                | TerrainType::Dirt
+                | TerrainType::Stone
+                | TerrainType::Smoldering
+                | TerrainType::Water => {}
+            }
+        }
+    }
+}
+
+fn draw_rain_clouds(
+    mut painter: ShapePainter,
+    map: Res<GameMap>,
+    clouds: Query<(&Transform, &RainCloud)>,
+) {
+    let original_tx = painter.transform;
+
+    for (tx, cloud) in &clouds {
+        let mut color = SKY_400;
+        // fade the cloud out as it dissipates
+        color.alpha = 0.35 * (cloud.duration / RAIN_CLOUD_DURATION).clamp(0.2, 1.0);
+
+        painter.hollow = false;
+        painter.set_color(color);
+        painter.translate(tx.translation);
+        painter.circle(cloud.radius * map.sprite_size);
+
+        painter.transform = original_tx;
+    }
+}
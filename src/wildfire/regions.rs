@@ -0,0 +1,164 @@
+//! Divides the map into a grid of named regions, so that events can say where
+//! they happen in words rather than tile coordinates. The region map can be
+//! shown with <k>.
+
+use bevy::{
+    color::palettes::tailwind::{SLATE_500, SLATE_800},
+    input::common_conditions::input_just_pressed,
+    prelude::*,
+};
+use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+use crate::{Pause, screens::Screen, theme::node_builder::NodeBuilder};
+
+/// The number of regions along each side of the map
+const REGION_GRID_SIZE: usize = 4;
+/// The width and height of each region in the region map overlay, in pixels
+const REGION_OVERLAY_CELL_SIZE: f32 = 90.0;
+
+const REGION_ADJECTIVES: [&str; 12] = [
+    "Dry", "Stone", "Green", "Misty", "Windy", "Golden", "Shadow", "Ember", "Silver", "Quiet",
+    "Broken", "Lonely",
+];
+const REGION_NOUNS: [&str; 12] = [
+    "Hollow", "Ridge", "Vale", "Meadow", "Hills", "Crossing", "Glen", "Moor", "Woods", "Fields",
+    "Heights", "Basin",
+];
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<MapRegions>();
+    app.register_type::<RegionMapOverlay>();
+
+    app.add_systems(
+        Update,
+        toggle_region_map.run_if(
+            input_just_pressed(KeyCode::KeyK)
+                .and(in_state(Screen::Gameplay))
+                .and(in_state(Pause(false)))
+                .and(resource_exists::<MapRegions>),
+        ),
+    );
+}
+
+/// The names of each region of the current map, indexed by `names[y][x]`
+/// where y runs up the map as it does for tile coordinates
+#[derive(Resource, Reflect, Debug, Clone)]
+#[reflect(Resource)]
+pub struct MapRegions {
+    pub names: Vec<Vec<String>>,
+    /// The width and height of each region, in tiles
+    region_size: IVec2,
+}
+
+impl MapRegions {
+    /// Names the regions of a map of the given size. The same seed always
+    /// gives the same names
+    pub fn new(seed: i32, map_size: UVec2) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed as u64);
+
+        let mut combinations = REGION_ADJECTIVES
+            .iter()
+            .flat_map(|adjective| {
+                REGION_NOUNS
+                    .iter()
+                    .map(move |noun| format!("{adjective} {noun}"))
+            })
+            .collect::<Vec<_>>();
+        combinations.shuffle(&mut rng);
+
+        let names = combinations
+            .chunks(REGION_GRID_SIZE)
+            .take(REGION_GRID_SIZE)
+            .map(|row| row.to_vec())
+            .collect();
+
+        Self {
+            names,
+            region_size: (map_size.as_ivec2() / REGION_GRID_SIZE as i32).max(IVec2::ONE),
+        }
+    }
+
+    /// The name of the region containing the given tile
+    pub fn name_at(&self, loc: IVec2) -> &str {
+        let region =
+            (loc / self.region_size).clamp(IVec2::ZERO, IVec2::splat(REGION_GRID_SIZE as i32 - 1));
+        &self.names[region.y as usize][region.x as usize]
+    }
+}
+
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct RegionMapOverlay;
+
+fn toggle_region_map(
+    mut commands: Commands,
+    regions: Res<MapRegions>,
+    overlays: Query<Entity, With<RegionMapOverlay>>,
+) {
+    if !overlays.is_empty() {
+        info!("Hiding region map");
+        for overlay in &overlays {
+            commands.entity(overlay).despawn();
+        }
+        return;
+    }
+
+    info!("Showing region map");
+    let size = REGION_OVERLAY_CELL_SIZE * REGION_GRID_SIZE as f32;
+
+    commands
+        .spawn((
+            Name::new("Region Map"),
+            RegionMapOverlay,
+            GlobalZIndex(3),
+            StateScoped(Screen::Gameplay),
+            NodeBuilder::new()
+                .position(PositionType::Absolute)
+                .full()
+                .center_content()
+                .build(),
+            Pickable::IGNORE,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    NodeBuilder::new()
+                        .width(Val::Px(size))
+                        .height(Val::Px(size))
+                        .flex_direction(FlexDirection::Column)
+                        .background(SLATE_800.with_alpha(0.8))
+                        .build(),
+                    Pickable::IGNORE,
+                ))
+                .with_children(|grid| {
+                    // UI rows run down the screen, while map rows run up it
+                    for row in regions.names.iter().rev() {
+                        grid.spawn((
+                            NodeBuilder::new()
+                                .width(Val::Percent(100.0))
+                                .height(Val::Px(REGION_OVERLAY_CELL_SIZE))
+                                .build(),
+                            Pickable::IGNORE,
+                        ))
+                        .with_children(|row_node| {
+                            for name in row {
+                                row_node.spawn((
+                                    NodeBuilder::new()
+                                        .sized(Val::Px(REGION_OVERLAY_CELL_SIZE))
+                                        .center_content()
+                                        .build(),
+                                    Outline::new(Val::Px(1.0), Val::ZERO, SLATE_500.into()),
+                                    Pickable::IGNORE,
+                                    children![(
+                                        Text::new(name.clone()),
+                                        TextFont::from_font_size(12.0),
+                                        TextLayout::new_with_justify(JustifyText::Center),
+                                        Pickable::IGNORE,
+                                    )],
+                                ));
+                            }
+                        });
+                    }
+                });
+        });
+}
@@ -0,0 +1,114 @@
+//! Tracks how quickly the fire front is growing or shrinking, so the player
+//! can see at a glance whether their defences are working
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    Pause,
+    screens::GameSpeed,
+    wildfire::{
+        OnSpawnMap,
+        map::{GameMap, MAP_UPDATE_INTERVAL, update_map},
+    },
+};
+
+/// The number of map updates the spread rate is averaged over
+const SPREAD_RATE_WINDOW: usize = 10;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<SpreadRateTracker>();
+    app.register_type::<SpreadRateAutoPause>();
+
+    app.init_resource::<SpreadRateTracker>();
+    app.init_resource::<SpreadRateAutoPause>();
+
+    app.add_observer(reset_spread_rate);
+    app.add_systems(
+        Update,
+        track_spread_rate
+            .after(update_map)
+            .run_if(in_state(Pause(false)).and(resource_exists::<GameMap>)),
+    );
+}
+
+/// A rolling record of the change in the number of burning cells at each
+/// map update
+#[derive(Resource, Reflect, Debug, Clone, Default)]
+#[reflect(Resource)]
+pub struct SpreadRateTracker {
+    /// The change in burning cells for each of the last few map updates
+    pub history: VecDeque<i32>,
+    /// The number of burning cells at the last map update
+    pub last_fire_count: i32,
+}
+
+impl SpreadRateTracker {
+    /// The average change in the number of burning cells, in cells per second
+    pub fn cells_per_second(&self) -> f32 {
+        if self.history.is_empty() {
+            return 0.0;
+        }
+
+        let total: i32 = self.history.iter().sum();
+        total as f32 / self.history.len() as f32 / MAP_UPDATE_INTERVAL
+    }
+}
+
+/// The spread rate (in cells per second) above which the game pauses itself,
+/// or None to never pause. Set from the settings menu
+#[derive(Resource, Reflect, Debug, Clone, Copy, Default)]
+#[reflect(Resource)]
+pub struct SpreadRateAutoPause(pub Option<f32>);
+
+fn reset_spread_rate(_trigger: Trigger<OnSpawnMap>, mut tracker: ResMut<SpreadRateTracker>) {
+    *tracker = SpreadRateTracker::default();
+}
+
+/// Samples the number of burning cells at the same rate the map updates
+fn track_spread_rate(
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    map: Res<GameMap>,
+    mut tracker: ResMut<SpreadRateTracker>,
+    mut since_last_sample: Local<f32>,
+) {
+    *since_last_sample += time.delta_secs() * game_speed.0;
+    if *since_last_sample < MAP_UPDATE_INTERVAL {
+        return;
+    }
+    *since_last_sample = 0.0;
+
+    let fire_count = map.fire_count() as i32;
+    let change = fire_count - tracker.last_fire_count;
+    tracker.last_fire_count = fire_count;
+
+    if tracker.history.len() >= SPREAD_RATE_WINDOW {
+        tracker.history.pop_front();
+    }
+    tracker.history.push_back(change);
+}
+
+/// A run condition that is true on the first update that the spread rate
+/// goes above the auto pause threshold
+pub fn fire_spread_surged(
+    auto_pause: Res<SpreadRateAutoPause>,
+    tracker: Res<SpreadRateTracker>,
+    mut was_above: Local<bool>,
+) -> bool {
+    let Some(threshold) = auto_pause.0 else {
+        *was_above = false;
+        return false;
+    };
+
+    let above = tracker.cells_per_second() > threshold;
+    let surged = above && !*was_above;
+    *was_above = above;
+
+    if surged {
+        info!("Fire spread rate passed {threshold} cells/sec, pausing");
+    }
+
+    surged
+}
@@ -0,0 +1,94 @@
+//! Tracks statistics about the wildfire so they can be shown to the player
+//! when the game ends
+
+use bevy::prelude::*;
+
+use crate::{
+    Pause,
+    screens::{BuildingType, GameSpeed, Screen},
+    theme::widget,
+    wildfire::OnSpawnMap,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<FireStats>();
+    app.register_type::<FireStatsPanel>();
+
+    app.init_resource::<FireStats>();
+    app.add_observer(reset_fire_stats);
+
+    app.add_systems(
+        Update,
+        track_fire_stats.run_if(in_state(Screen::Gameplay).and(in_state(Pause(false)))),
+    );
+}
+
+/// Statistics about the current game
+#[derive(Resource, Reflect, Debug, Clone, Default)]
+#[reflect(Resource)]
+pub struct FireStats {
+    /// The number of cells that have burned out
+    pub cells_burned: u32,
+    /// The most cells that have been on fire at the same time
+    pub peak_fire_size: u32,
+    /// The time the level has been played for in seconds
+    pub duration: f32,
+    /// The number of buildings that have been destroyed
+    pub buildings_lost: u32,
+    /// The number of buildings still standing
+    pub buildings_surviving: u32,
+    /// The number of buildings that have been placed
+    pub buildings_built: u32,
+    /// The number of burning cells put out by the player's buildings or rain
+    pub fires_extinguished: u32,
+}
+
+/// The UI panel showing [FireStats] on the game over and victory screens
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct FireStatsPanel;
+
+fn reset_fire_stats(_trigger: Trigger<OnSpawnMap>, mut stats: ResMut<FireStats>) {
+    *stats = FireStats::default();
+}
+
+fn track_fire_stats(
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut stats: ResMut<FireStats>,
+    buildings: Query<(), With<BuildingType>>,
+    new_buildings: Query<(), Added<BuildingType>>,
+) {
+    stats.duration += time.delta_secs() * game_speed.0;
+    stats.buildings_surviving = buildings.iter().count() as u32;
+    stats.buildings_built += new_buildings.iter().count() as u32;
+}
+
+/// Builds a panel showing the fire statistics in a grid
+pub fn fire_stats_panel(stats: &FireStats) -> impl Bundle {
+    let duration = stats.duration as u32;
+
+    (
+        Name::new("Fire Stats Panel"),
+        FireStatsPanel,
+        widget::grid(vec![
+            ["Cells burned".to_string(), stats.cells_burned.to_string()],
+            [
+                "Largest fire".to_string(),
+                format!("{} cells", stats.peak_fire_size),
+            ],
+            [
+                "Time survived".to_string(),
+                format!("{}:{:02}", duration / 60, duration % 60),
+            ],
+            [
+                "Buildings lost".to_string(),
+                stats.buildings_lost.to_string(),
+            ],
+            [
+                "Buildings standing".to_string(),
+                stats.buildings_surviving.to_string(),
+            ],
+        ]),
+    )
+}
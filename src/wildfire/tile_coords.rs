@@ -0,0 +1,72 @@
+//! An overlay that labels cells with their tile coordinates, which is useful
+//! when authoring levels as story events are placed in tile space. Toggled
+//! with <l>.
+
+use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+
+use crate::{
+    screens::Screen,
+    wildfire::{OnSpawnMap, map::GameMap},
+};
+
+/// Every Nth cell in each direction is labelled
+const TILE_COORD_SPACING: usize = 16;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<TileCoordLabel>();
+
+    app.add_observer(hide_tile_coords_on_new_map);
+    app.add_systems(
+        Update,
+        toggle_tile_coords.run_if(
+            in_state(Screen::Gameplay)
+                .and(resource_exists::<GameMap>)
+                .and(input_just_pressed(KeyCode::KeyL)),
+        ),
+    );
+}
+
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct TileCoordLabel;
+
+fn toggle_tile_coords(
+    mut commands: Commands,
+    map: Res<GameMap>,
+    labels: Query<Entity, With<TileCoordLabel>>,
+) {
+    if !labels.is_empty() {
+        info!("Hiding tile coordinates");
+        for label in &labels {
+            commands.entity(label).despawn();
+        }
+        return;
+    }
+
+    info!("Showing tile coordinates");
+    for y in (0..map.size_y).step_by(TILE_COORD_SPACING) {
+        for x in (0..map.size_x).step_by(TILE_COORD_SPACING) {
+            let coords = IVec2::new(x as i32, y as i32);
+
+            commands.spawn((
+                Name::new("Tile Coordinate Label"),
+                TileCoordLabel,
+                Text2d::new(format!("{x},{y}")),
+                TextFont::from_font_size(6.0),
+                Transform::from_translation(map.world_coords(coords).extend(0.7)),
+                StateScoped(Screen::Gameplay),
+            ));
+        }
+    }
+}
+
+/// The labels are for the old map, so remove them when a new one is spawned
+fn hide_tile_coords_on_new_map(
+    _trigger: Trigger<OnSpawnMap>,
+    mut commands: Commands,
+    labels: Query<Entity, With<TileCoordLabel>>,
+) {
+    for label in &labels {
+        commands.entity(label).despawn();
+    }
+}
@@ -0,0 +1,213 @@
+//! A fire tornado is a spinning column of flame that wanders across the map,
+//! setting light to everything it passes over. Triggered by [OnTornado]
+
+use std::f32::consts::PI;
+
+use bevy::{color::palettes::tailwind::ORANGE_600, prelude::*};
+use bevy_vector_shapes::{prelude::ShapePainter, shapes::DiscPainter};
+use rand::Rng;
+
+use crate::{
+    Pause,
+    screens::{EndlessMode, EventLog, GameSpeed, Screen, log_event},
+    wildfire::{FireStats, TerrainType, WindDirection, map::GameMap},
+};
+
+/// How long a tornado lasts before it blows itself out, in seconds
+pub const TORNADO_LIFETIME: f32 = 20.0;
+/// How fast a tornado moves, in tiles per second
+const TORNADO_SPEED: f32 = 3.0;
+/// The most a tornado can turn each second, in radians
+const TORNADO_WANDER: f32 = PI;
+/// How often (in seconds) endless mode rolls for a tornado
+const TORNADO_CHECK_INTERVAL: f32 = 60.0;
+/// The chance of a tornado each time endless mode rolls for one
+const TORNADO_CHANCE: f64 = 0.001;
+/// Tornados only form in endless mode when the wind is at least this strong
+const TORNADO_MIN_WIND_STRENGTH: f32 = 60.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<OnTornado>();
+    app.register_type::<Tornado>();
+
+    app.add_observer(handle_tornado);
+    app.add_systems(
+        Update,
+        (update_tornadoes, draw_tornadoes).run_if(
+            in_state(Screen::Gameplay)
+                .and(in_state(Pause(false)))
+                .and(resource_exists::<GameMap>),
+        ),
+    );
+    app.add_systems(
+        Update,
+        spawn_endless_tornadoes.run_if(
+            in_state(Screen::Gameplay)
+                .and(in_state(Pause(false)))
+                .and(resource_exists::<GameMap>)
+                .and(resource_exists::<EndlessMode>),
+        ),
+    );
+}
+
+/// Starts a tornado at the given tile, travelling in the given direction
+#[derive(Debug, Clone, Event, Reflect)]
+pub struct OnTornado(pub IVec2, pub Vec2);
+
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct Tornado {
+    /// The current world position of the tornado
+    pub position: Vec2,
+    /// The (normalised) direction the tornado is travelling in
+    pub direction: Vec2,
+    /// How fast the tornado moves, in world units per second
+    pub speed: f32,
+    /// The remaining time the tornado will persist for
+    pub lifetime: f32,
+}
+
+fn handle_tornado(
+    trigger: Trigger<OnTornado>,
+    mut commands: Commands,
+    maybe_map: Option<Res<GameMap>>,
+    mut log: ResMut<EventLog>,
+    stats: Res<FireStats>,
+) {
+    let Some(map) = maybe_map else {
+        warn!("Skipping tornado as there is no map yet");
+        return;
+    };
+
+    let OnTornado(start, direction) = *trigger.event();
+    if !map.is_valid_coords(start) {
+        info!("Unable to start tornado outside the map at {start:?}");
+        return;
+    }
+
+    log_event(
+        &mut log,
+        stats.duration,
+        format!("TORNADO! A fire tornado touched down at {start}"),
+    );
+
+    commands.spawn((
+        Name::new("Fire Tornado"),
+        Tornado {
+            position: map.world_coords(start),
+            direction: direction.normalize_or(Vec2::X),
+            speed: TORNADO_SPEED * map.sprite_size,
+            lifetime: TORNADO_LIFETIME,
+        },
+        StateScoped(Screen::Gameplay),
+    ));
+}
+
+/// Every so often in strong winds, a tornado forms somewhere on the map and
+/// travels with the wind
+fn spawn_endless_tornadoes(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    map: Res<GameMap>,
+    wind: Res<WindDirection>,
+    mut since_last_check: Local<f32>,
+) {
+    *since_last_check += time.delta_secs() * game_speed.0;
+    if *since_last_check < TORNADO_CHECK_INTERVAL {
+        return;
+    }
+    *since_last_check = 0.0;
+
+    let mut rng = rand::thread_rng();
+    if wind.strength() < TORNADO_MIN_WIND_STRENGTH || !rng.gen_bool(TORNADO_CHANCE) {
+        return;
+    }
+
+    let start = IVec2::new(
+        rng.gen_range(0..map.size_x as i32),
+        rng.gen_range(0..map.size_y as i32),
+    );
+    commands.trigger(OnTornado(start, wind.get_wind_vec()));
+}
+
+fn update_tornadoes(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut map: ResMut<GameMap>,
+    mut tornadoes: Query<(Entity, &mut Tornado)>,
+) {
+    let delta = time.delta_secs() * game_speed.0;
+    let mut rng = rand::thread_rng();
+
+    for (entity, mut tornado) in &mut tornadoes {
+        tornado.lifetime -= delta;
+
+        // wobble around rather than travelling in a straight line
+        let turn = rng.gen_range(-TORNADO_WANDER..TORNADO_WANDER) * delta;
+        tornado.direction = Vec2::from_angle(turn).rotate(tornado.direction);
+        let step = tornado.direction * tornado.speed * delta;
+        tornado.position += step;
+
+        let coords = map.tile_coords(tornado.position);
+        if tornado.lifetime <= 0.0 || !map.is_valid_coords(coords) {
+            info!("Fire tornado has blown itself out at {coords}");
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        for x in -1..=1 {
+            for y in -1..=1 {
+                let loc = coords + IVec2::new(x, y);
+                let Some(cell) = map.get_mut(loc) else {
+                    continue;
+                };
+
+                match cell.terrain {
+                    TerrainType::Grassland | TerrainType::Tree => {
+                        cell.ignite();
+                        map.mark_on_fire(loc);
+                    }
+                    TerrainType::Building
+                    | TerrainType::Fire
+                    | TerrainType::Dirt
+                    | TerrainType::Stone
+                    | TerrainType::Smoldering
+                    | TerrainType::Water => {}
+                }
+            }
+        }
+    }
+}
+
+fn draw_tornadoes(
+    mut painter: ShapePainter,
+    time: Res<Time>,
+    map: Res<GameMap>,
+    tornadoes: Query<&Tornado>,
+) {
+    let original_tx = painter.transform;
+    let elapsed = time.elapsed_secs();
+
+    for tornado in &tornadoes {
+        // the column pulses as it spins
+        let radius = (1.5 + 0.5 * (elapsed * 6.0).sin()) * map.sprite_size;
+        let spin = elapsed * 10.0;
+
+        let mut color = ORANGE_600;
+        color.alpha = 0.5;
+
+        painter.set_color(color);
+        painter.translate(tornado.position.extend(0.5));
+
+        painter.hollow = false;
+        painter.circle(radius);
+
+        painter.hollow = true;
+        painter.arc(radius * 1.5, spin, spin + PI * 0.75);
+        painter.arc(radius * 1.5, spin + PI, spin + PI * 1.75);
+
+        painter.transform = original_tx;
+    }
+}
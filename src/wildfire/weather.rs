@@ -0,0 +1,185 @@
+//! Weather conditions that change how quickly fire spreads. In endless mode
+//! the weather changes randomly, in story mode it is fixed for each level.
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    Pause,
+    screens::{EndlessMode, GameSpeed, Screen},
+    wildfire::{DifficultySettings, GameMap, OnSpawnMap},
+};
+
+/// The shortest time a weather condition lasts in endless mode, in seconds
+const MIN_WEATHER_DURATION: f32 = 60.0;
+/// The longest time a weather condition lasts in endless mode, in seconds
+const MAX_WEATHER_DURATION: f32 = 120.0;
+
+/// How often (in seconds) the average map moisture is sampled
+const MOISTURE_TREND_INTERVAL: f32 = 5.0;
+/// The smallest change in average moisture between samples that is shown as
+/// the map getting wetter or drier
+const MOISTURE_TREND_THRESHOLD: f32 = 0.0001;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<WeatherEvent>();
+    app.register_type::<CurrentWeather>();
+    app.register_type::<MoistureTrend>();
+
+    app.init_resource::<CurrentWeather>();
+    app.init_resource::<MoistureTrend>();
+    app.add_observer(reset_weather);
+    app.add_observer(reset_moisture_trend);
+
+    app.add_systems(
+        Update,
+        change_weather.run_if(
+            in_state(Screen::Gameplay)
+                .and(in_state(Pause(false)))
+                .and(resource_exists::<EndlessMode>),
+        ),
+    );
+
+    app.add_systems(
+        Update,
+        sample_moisture_trend.run_if(
+            in_state(Screen::Gameplay)
+                .and(in_state(Pause(false)))
+                .and(resource_exists::<GameMap>),
+        ),
+    );
+}
+
+#[derive(Reflect, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WeatherEvent {
+    #[default]
+    Normal,
+    Drought,
+    Rain,
+}
+
+impl std::fmt::Display for WeatherEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                WeatherEvent::Normal => "Clear",
+                WeatherEvent::Drought => "Drought",
+                WeatherEvent::Rain => "Rain",
+            }
+        )
+    }
+}
+
+impl WeatherEvent {
+    /// How much faster cells dry out next to a fire
+    pub fn moisture_decay_multiplier(&self) -> f32 {
+        match self {
+            WeatherEvent::Normal | WeatherEvent::Rain => 1.0,
+            WeatherEvent::Drought => 3.0,
+        }
+    }
+
+    /// How much more likely fire is to spread to a neighbouring cell
+    pub fn spread_chance_multiplier(&self) -> f64 {
+        match self {
+            WeatherEvent::Normal => 1.0,
+            WeatherEvent::Drought => 1.5,
+            WeatherEvent::Rain => 0.5,
+        }
+    }
+}
+
+/// The weather currently affecting the map
+#[derive(Resource, Reflect, Debug, Clone, Copy, Default)]
+#[reflect(Resource)]
+pub struct CurrentWeather(pub WeatherEvent);
+
+/// How the average moisture of the map is changing
+#[derive(Resource, Reflect, Debug, Clone, Copy, Default)]
+#[reflect(Resource)]
+pub struct MoistureTrend {
+    /// The most recent average moisture of grass and trees, from 0-1
+    pub average: Option<f32>,
+    /// The change in average moisture since the previous sample
+    pub change: f32,
+}
+
+impl std::fmt::Display for MoistureTrend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some(average) = self.average else {
+            return write!(f, "-");
+        };
+
+        let trend = if self.change < -MOISTURE_TREND_THRESHOLD {
+            "drying"
+        } else if self.change > MOISTURE_TREND_THRESHOLD {
+            "wetter"
+        } else {
+            "steady"
+        };
+        write!(f, "{:.0}% ({trend})", average * 100.0)
+    }
+}
+
+fn reset_weather(_trigger: Trigger<OnSpawnMap>, mut weather: ResMut<CurrentWeather>) {
+    *weather = CurrentWeather::default();
+}
+
+/// Randomly transitions to a new weather condition every so often
+fn change_weather(
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut weather: ResMut<CurrentWeather>,
+    difficulty: Res<DifficultySettings>,
+    mut time_until_change: Local<Option<f32>>,
+) {
+    let mut rng = rand::thread_rng();
+    // harder difficulties change the weather more often, so droughts arrive
+    // sooner
+    let interval = difficulty.0.event_interval_multiplier();
+    let remaining = time_until_change.get_or_insert_with(|| {
+        rng.gen_range(MIN_WEATHER_DURATION..MAX_WEATHER_DURATION) * interval
+    });
+
+    *remaining -= time.delta_secs() * game_speed.0;
+    if *remaining > 0.0 {
+        return;
+    }
+
+    *remaining = rng.gen_range(MIN_WEATHER_DURATION..MAX_WEATHER_DURATION) * interval;
+    let next = match (weather.0, rng.gen_bool(0.5)) {
+        (WeatherEvent::Normal, true) | (WeatherEvent::Rain, true) => WeatherEvent::Drought,
+        (WeatherEvent::Normal, false) | (WeatherEvent::Drought, true) => WeatherEvent::Rain,
+        (WeatherEvent::Drought, false) | (WeatherEvent::Rain, false) => WeatherEvent::Normal,
+    };
+
+    info!("Weather changing from {} to {next}", weather.0);
+    weather.0 = next;
+}
+
+fn reset_moisture_trend(_trigger: Trigger<OnSpawnMap>, mut trend: ResMut<MoistureTrend>) {
+    *trend = MoistureTrend::default();
+}
+
+/// Periodically samples the average moisture so the player can see if the
+/// map is drying out
+fn sample_moisture_trend(
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    map: Res<GameMap>,
+    mut trend: ResMut<MoistureTrend>,
+    mut since_last_sample: Local<Option<f32>>,
+) {
+    let elapsed = since_last_sample.get_or_insert(MOISTURE_TREND_INTERVAL);
+    *elapsed += time.delta_secs() * game_speed.0;
+    if *elapsed < MOISTURE_TREND_INTERVAL {
+        return;
+    }
+
+    *elapsed = 0.0;
+    let average = map.average_moisture();
+    trend.change = trend.average.map(|prev| average - prev).unwrap_or_default();
+    trend.average = Some(average);
+}
@@ -1,18 +1,60 @@
 //! Wind module for generating and updating wind
 
-use bevy::{math::CompassOctant, prelude::*};
+use bevy::{
+    color::palettes::tailwind::{GREEN_500, RED_500, YELLOW_400},
+    input::common_conditions::input_just_pressed,
+    math::CompassOctant,
+    prelude::*,
+};
+use bevy_vector_shapes::{prelude::ShapePainter, shapes::LinePainter};
 use rand::Rng;
 
-use crate::{Pause, screens::EndlessMode};
+use crate::{
+    Pause,
+    screens::{EndlessMode, GameSpeed, Screen},
+    wildfire::{DifficultySettings, GameMap},
+};
+
+/// The number of cells between each arrow in the wind overlay
+const WIND_OVERLAY_SPACING: usize = 8;
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<WindDirection>();
+    app.register_type::<WindGust>();
     app.init_resource::<WindDirection>();
 
     app.add_systems(
         Update,
         wandery_wind.run_if(in_state(Pause(false)).and(resource_exists::<EndlessMode>)),
     );
+
+    app.add_systems(
+        Update,
+        (
+            toggle_wind_overlay.run_if(input_just_pressed(KeyCode::KeyW)),
+            draw_wind_vectors.run_if(resource_exists::<ShowWindOverlay>),
+        )
+            .run_if(
+                in_state(Screen::Gameplay)
+                    .and(in_state(Pause(false)))
+                    .and(resource_exists::<GameMap>),
+            ),
+    );
+    app.add_systems(
+        Update,
+        update_wind_gust.run_if(
+            in_state(Screen::Gameplay)
+                .and(in_state(Pause(false)))
+                .and(resource_exists::<WindGust>),
+        ),
+    );
+    // wind keeps wandering on the game over screen, so clear out any gust
+    // that started there as well as one still blowing when gameplay ends
+    app.add_systems(OnEnter(Screen::Gameplay), remove_wind_gust);
+    app.add_systems(
+        OnExit(Screen::Gameplay),
+        (remove_wind_overlay, remove_wind_gust),
+    );
 }
 
 #[derive(Resource, Debug, Clone, Copy, Reflect)]
@@ -32,7 +74,7 @@ impl Default for WindDirection {
 
         Self {
             angle,
-            strength: 10.0,
+            strength: INITIAL_WIND_STRENGTH,
             variance: std::f32::consts::FRAC_PI_2.to_degrees(),
             target,
         }
@@ -83,19 +125,83 @@ impl WindDirection {
         }
     }
 
+    /// The angle (in degrees) the prevailing wind is blowing towards
+    pub fn angle(&self) -> f32 {
+        self.angle
+    }
+
+    /// The current strength of the prevailing wind
+    pub fn strength(&self) -> f32 {
+        self.strength
+    }
+
+    /// Puts the wind back to its starting strength, scaled by the difficulty
+    pub fn reset_strength(&mut self, multiplier: f32) {
+        self.strength = INITIAL_WIND_STRENGTH * multiplier;
+    }
+
     pub fn r#override(&mut self, angle: f32, strength: f32) {
         self.angle = angle;
         self.strength = strength;
     }
 }
 
+/// A short burst of extreme wind that is added on top of the global wind
+/// while it lasts
+#[derive(Resource, Debug, Clone, Copy, Reflect)]
+#[reflect(Resource)]
+pub struct WindGust {
+    /// The extra wind added by the gust
+    pub direction: Vec2,
+    /// How long the gust lasts, in seconds
+    pub duration: f32,
+    /// How long the gust has been blowing
+    pub elapsed: f32,
+}
+
+impl WindGust {
+    /// Creates a gust blowing at the given angle (in degrees) and strength,
+    /// using the same convention as [WindDirection]
+    pub fn new(angle: f32, strength: f32, duration: f32) -> Self {
+        Self {
+            direction: (Quat::from_axis_angle(Vec3::Z, angle.to_radians()) * Vec3::X).truncate()
+                * strength,
+            duration,
+            elapsed: 0.0,
+        }
+    }
+}
+
 /// the speed the wind changes in degrees per second
+/// The strength of the wind at the start of an endless game, before the
+/// difficulty is applied
+const INITIAL_WIND_STRENGTH: f32 = 10.0;
 const WIND_CHANGE_SPEED: f32 = 5.0;
 const WIND_STRENGTH_VARIANCE: f32 = 1.0;
 const MIN_WIND_SPEED: f32 = 10.0;
 const MAX_WIND_SPEED: f32 = 100.0;
 
-fn wandery_wind(time: Res<Time>, mut wind: ResMut<WindDirection>) {
+/// The range of time (in seconds) between gusts in endless mode
+const MIN_GUST_INTERVAL: f32 = 30.0;
+const MAX_GUST_INTERVAL: f32 = 90.0;
+/// The range of time (in seconds) that a gust lasts
+const MIN_GUST_DURATION: f32 = 3.0;
+const MAX_GUST_DURATION: f32 = 8.0;
+/// The range of extra wind strength a gust adds
+const MIN_GUST_STRENGTH: f32 = 40.0;
+const MAX_GUST_STRENGTH: f32 = 80.0;
+/// How far (in degrees) a gust can blow from the prevailing wind
+const GUST_ANGLE_VARIANCE: f32 = 45.0;
+
+fn wandery_wind(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut wind: ResMut<WindDirection>,
+    maybe_gust: Option<Res<WindGust>>,
+    difficulty: Res<DifficultySettings>,
+    mut until_next_gust: Local<Option<f32>>,
+) {
     // find out which rotation direction is faster
     // probably a much neater way to do this but whatever
     let raw_delta = wind.target - wind.angle;
@@ -117,7 +223,7 @@ fn wandery_wind(time: Res<Time>, mut wind: ResMut<WindDirection>) {
     };
 
     // move the wind towards its target angle
-    let wind_delta = time.delta_secs() * WIND_CHANGE_SPEED;
+    let wind_delta = time.delta_secs() * game_speed.0 * WIND_CHANGE_SPEED;
     wind.angle += wind_delta * direction_sign;
 
     // if it has reached it, find a new target angle within range
@@ -136,4 +242,126 @@ fn wandery_wind(time: Res<Time>, mut wind: ResMut<WindDirection>) {
     wind.strength = (wind.strength
         + rng.gen_range(-WIND_STRENGTH_VARIANCE..WIND_STRENGTH_VARIANCE))
     .clamp(MIN_WIND_SPEED, MAX_WIND_SPEED);
+
+    // every so often a short, violent gust blows through. The timer only
+    // counts down between gusts
+    if maybe_gust.is_some() {
+        return;
+    }
+
+    let until_next = until_next_gust.get_or_insert_with(|| {
+        rng.gen_range(MIN_GUST_INTERVAL..MAX_GUST_INTERVAL)
+            * difficulty.0.event_interval_multiplier()
+    });
+    *until_next -= time.delta_secs() * game_speed.0;
+
+    if *until_next <= 0.0 {
+        let gust = WindGust::new(
+            wind.angle + rng.gen_range(-GUST_ANGLE_VARIANCE..GUST_ANGLE_VARIANCE),
+            rng.gen_range(MIN_GUST_STRENGTH..MAX_GUST_STRENGTH),
+            rng.gen_range(MIN_GUST_DURATION..MAX_GUST_DURATION),
+        );
+        info!("Wind gust for {:.1}s", gust.duration);
+
+        commands.insert_resource(gust);
+        *until_next_gust = None;
+    }
+}
+
+/// Ticks the current gust and removes it when it has blown itself out
+fn update_wind_gust(
+    mut commands: Commands,
+    time: Res<Time>,
+    game_speed: Res<GameSpeed>,
+    mut gust: ResMut<WindGust>,
+) {
+    gust.elapsed += time.delta_secs() * game_speed.0;
+
+    if gust.elapsed >= gust.duration {
+        info!("Wind gust has passed");
+        commands.remove_resource::<WindGust>();
+    }
+}
+
+fn remove_wind_gust(mut commands: Commands) {
+    commands.remove_resource::<WindGust>();
+}
+
+/// When this resource exists, arrows are drawn across the map showing the
+/// combined global and storm mage wind. Toggled with <w>.
+#[derive(Resource, Debug, Default)]
+pub struct ShowWindOverlay;
+
+fn toggle_wind_overlay(mut commands: Commands, maybe_overlay: Option<Res<ShowWindOverlay>>) {
+    if maybe_overlay.is_some() {
+        info!("Hiding wind overlay");
+        commands.remove_resource::<ShowWindOverlay>();
+    } else {
+        info!("Showing wind overlay");
+        commands.init_resource::<ShowWindOverlay>();
+    }
+}
+
+fn remove_wind_overlay(mut commands: Commands) {
+    commands.remove_resource::<ShowWindOverlay>();
+}
+
+/// The colour of a wind arrow, from green (weak) to red (strong)
+fn wind_colour(strength: f32) -> Srgba {
+    if strength < 30.0 {
+        GREEN_500
+    } else if strength < 70.0 {
+        YELLOW_400
+    } else {
+        RED_500
+    }
+}
+
+fn draw_wind_vectors(
+    mut painter: ShapePainter,
+    map: Res<GameMap>,
+    wind: Res<WindDirection>,
+    maybe_gust: Option<Res<WindGust>>,
+) {
+    const ARROWHEAD_ANGLE: f32 = 2.5;
+
+    let original_tx = painter.transform;
+    let global_wind = wind.as_vec() + maybe_gust.map(|gust| gust.direction).unwrap_or_default();
+
+    // the longest arrow fills the gap between arrows
+    let max_length = WIND_OVERLAY_SPACING as f32 * map.sprite_size;
+    painter.thickness = 0.5;
+
+    for y in (0..map.size_y).step_by(WIND_OVERLAY_SPACING) {
+        for x in (0..map.size_x).step_by(WIND_OVERLAY_SPACING) {
+            let cell = &map.data[y][x];
+            if cell.fog {
+                continue;
+            }
+
+            let cell_wind = cell.wind + global_wind;
+            let strength = cell_wind.length();
+            let Ok(direction) = Dir2::new(cell_wind) else {
+                continue;
+            };
+
+            let length = (strength / MAX_WIND_SPEED).clamp(0.2, 1.0) * max_length;
+            let start = map.world_coords(IVec2::new(x as i32, y as i32));
+            let end = start + direction * length;
+
+            // the arrowhead is two short lines angled back from the tip
+            let head_length = length * 0.3;
+            let head_left =
+                end + Vec2::from_angle(ARROWHEAD_ANGLE).rotate(*direction) * head_length;
+            let head_right =
+                end + Vec2::from_angle(-ARROWHEAD_ANGLE).rotate(*direction) * head_length;
+
+            painter.set_color(wind_colour(strength));
+            painter.line(start.extend(0.6), end.extend(0.6));
+            painter.line(end.extend(0.6), head_left.extend(0.6));
+            painter.line(end.extend(0.6), head_right.extend(0.6));
+        }
+    }
+
+    painter.transform = original_tx;
 }
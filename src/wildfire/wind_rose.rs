@@ -0,0 +1,184 @@
+//! A wind rose in the corner of the screen showing which directions the wind
+//! has blown from recently, to help the player guess where the fire will go
+//! next. Toggled with <q>.
+
+use std::{collections::VecDeque, f32::consts::FRAC_PI_4, time::Duration};
+
+use bevy::{
+    color::palettes::tailwind::{SKY_300, SLATE_400, SLATE_800},
+    input::common_conditions::input_just_pressed,
+    prelude::*,
+    time::common_conditions::on_timer,
+};
+
+use crate::{
+    Pause,
+    screens::Screen,
+    theme::node_builder::NodeBuilder,
+    wildfire::{GameMap, WindDirection},
+};
+
+/// How often the wind is sampled and the rose redrawn, in seconds
+const WIND_SAMPLE_INTERVAL: f32 = 2.0;
+/// The number of samples kept, which covers the last two minutes
+const MAX_WIND_SAMPLES: usize = 60;
+/// The width and height of the rose, in pixels
+const WIND_ROSE_SIZE: f32 = 50.0;
+/// The thickness of each spoke of the rose, in pixels
+const WIND_ROSE_SPOKE_WIDTH: f32 = 2.0;
+/// The distance from the top of the screen, leaving room for the toolbar
+const WIND_ROSE_TOP: f32 = 45.0;
+/// The distance from the right of the screen
+const WIND_ROSE_MARGIN: f32 = 10.0;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<WindHistory>();
+    app.register_type::<WindRose>();
+    app.register_type::<WindRoseSpoke>();
+
+    app.init_resource::<WindHistory>();
+
+    app.add_systems(OnEnter(Screen::Gameplay), clear_wind_history);
+    app.add_systems(
+        Update,
+        (
+            toggle_wind_rose.run_if(input_just_pressed(KeyCode::KeyQ)),
+            (record_wind_history, update_wind_rose)
+                .chain()
+                .run_if(on_timer(Duration::from_secs_f32(WIND_SAMPLE_INTERVAL))),
+        )
+            .run_if(
+                in_state(Screen::Gameplay)
+                    .and(in_state(Pause(false)))
+                    .and(resource_exists::<GameMap>),
+            ),
+    );
+}
+
+/// The recent wind, sampled every [WIND_SAMPLE_INTERVAL] seconds
+#[derive(Resource, Reflect, Debug, Default)]
+#[reflect(Resource)]
+pub struct WindHistory {
+    pub samples: VecDeque<Vec2>,
+}
+
+impl WindHistory {
+    /// How often the wind blew in each of the eight compass directions,
+    /// anticlockwise from east, as a fraction of the most common direction
+    fn frequencies(&self) -> [f32; 8] {
+        let mut counts = [0u32; 8];
+        for sample in &self.samples {
+            if *sample == Vec2::ZERO {
+                continue;
+            }
+
+            let octant = (sample.to_angle() / FRAC_PI_4).round() as i32;
+            counts[octant.rem_euclid(8) as usize] += 1;
+        }
+
+        let most = counts.iter().copied().max().unwrap_or_default().max(1);
+        counts.map(|count| count as f32 / most as f32)
+    }
+}
+
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct WindRose;
+
+/// One of the spokes of the rose, with the index of its compass direction
+/// anticlockwise from east
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component)]
+pub struct WindRoseSpoke(usize);
+
+fn clear_wind_history(mut history: ResMut<WindHistory>) {
+    history.samples.clear();
+}
+
+fn record_wind_history(wind: Res<WindDirection>, mut history: ResMut<WindHistory>) {
+    history.samples.push_back(wind.as_vec());
+    while history.samples.len() > MAX_WIND_SAMPLES {
+        history.samples.pop_front();
+    }
+}
+
+fn toggle_wind_rose(
+    mut commands: Commands,
+    history: Res<WindHistory>,
+    roses: Query<Entity, With<WindRose>>,
+) {
+    if !roses.is_empty() {
+        info!("Hiding wind rose");
+        for rose in &roses {
+            commands.entity(rose).despawn();
+        }
+        return;
+    }
+
+    info!("Showing wind rose");
+    let frequencies = history.frequencies();
+
+    commands
+        .spawn((
+            Name::new("Wind Rose"),
+            WindRose,
+            GlobalZIndex(3),
+            StateScoped(Screen::Gameplay),
+            NodeBuilder::new()
+                .position(PositionType::Absolute)
+                .top(WIND_ROSE_TOP)
+                .right(WIND_ROSE_MARGIN)
+                .width(Val::Px(WIND_ROSE_SIZE))
+                .height(Val::Px(WIND_ROSE_SIZE))
+                .background(SLATE_800.with_alpha(0.8))
+                .build(),
+            BorderRadius::MAX,
+            Pickable::IGNORE,
+        ))
+        .with_children(|parent| {
+            for (idx, frequency) in frequencies.iter().enumerate() {
+                parent.spawn((
+                    Name::new("Wind Rose Spoke"),
+                    WindRoseSpoke(idx),
+                    spoke_node(idx, *frequency),
+                    spoke_rotation(idx),
+                    BackgroundColor(if idx % 2 == 0 { SKY_300 } else { SLATE_400 }.into()),
+                    Pickable::IGNORE,
+                ));
+            }
+        });
+}
+
+fn update_wind_rose(history: Res<WindHistory>, mut spokes: Query<(&WindRoseSpoke, &mut Node)>) {
+    if spokes.is_empty() {
+        return;
+    }
+
+    let frequencies = history.frequencies();
+    for (spoke, mut node) in &mut spokes {
+        *node = spoke_node(spoke.0, frequencies[spoke.0]);
+    }
+}
+
+/// Lays out a spoke so that, once rotated about its middle, it runs from the
+/// centre of the rose out towards its compass direction
+fn spoke_node(idx: usize, frequency: f32) -> Node {
+    let centre = WIND_ROSE_SIZE / 2.0;
+    let length = frequency * centre;
+    let middle = Vec2::from_angle(idx as f32 * FRAC_PI_4) * length / 2.0;
+
+    Node {
+        position_type: PositionType::Absolute,
+        // UI coordinates run downwards, so the y offset is flipped
+        left: Val::Px(centre + middle.x - length / 2.0),
+        top: Val::Px(centre - middle.y - WIND_ROSE_SPOKE_WIDTH / 2.0),
+        width: Val::Px(length),
+        height: Val::Px(WIND_ROSE_SPOKE_WIDTH),
+        ..default()
+    }
+}
+
+fn spoke_rotation(idx: usize) -> Transform {
+    // rotations are clockwise on screen as UI coordinates run downwards
+    Transform::from_rotation(Quat::from_rotation_z(-(idx as f32) * FRAC_PI_4))
+}